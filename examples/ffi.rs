@@ -0,0 +1,3 @@
+// The functions of `src/ffi.rs` as a shared library: the library itself
+// cannot be one, since it has no panic handler when built without `std`
+pub use tictactoe::ffi::*;
@@ -0,0 +1,20 @@
+// Positions as typed in `tictactoe solve` or sent over the network, such as
+// `X.O/.X./...`: any text is either refused or gives a position that can be
+// written back, solved and encoded
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tictactoe::analysis::solve;
+use tictactoe::notation::{parse_coordinates, parse_position, position};
+
+fuzz_target!(|input: &str| {
+    let _ = parse_coordinates(input);
+    let Ok(grid) = parse_position(input) else {
+        return;
+    };
+    let again = parse_position(&position(&grid)).unwrap();
+    assert_eq!(again.matrix(), grid.matrix());
+    let _ = solve(&grid);
+    let _ = grid.heatmap();
+    let _ = grid.to_features();
+});
@@ -0,0 +1,25 @@
+// Games as read from log files and the server: any text is either refused or
+// gives games that can be replayed, analyzed and written back the same way
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tictactoe::analysis::Annotations;
+use tictactoe::code;
+use tictactoe::record::{parse_log, GameRecord};
+use tictactoe::stats::Stats;
+
+fuzz_target!(|input: &str| {
+    let _ = code::decode(input);
+    let Ok(records) = parse_log(input) else {
+        return;
+    };
+    for record in &records {
+        let _ = record.replay();
+        if let Some(annotations) = Annotations::read(record) {
+            let _ = annotations.accuracy(record.first_player);
+        }
+        let again = GameRecord::parse(&record.to_notation()).unwrap();
+        assert_eq!(&again, record);
+    }
+    let _ = Stats::of("X", &records);
+});
@@ -0,0 +1,121 @@
+#ifndef TICTACTOE_H
+#define TICTACTOE_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * What `ttt_set` returns
+ */
+#define TTT_OK 0
+
+#define TTT_OCCUPIED 1
+
+#define TTT_OUT_OF_RANGE 2
+
+#define TTT_GAME_OVER 3
+
+#define TTT_NULL -1
+
+/**
+ * The number of values written by `ttt_features`
+ */
+#define TTT_FEATURES 27
+
+typedef enum TttPlayer {
+  TTT_PLAYER_EMPTY = 0,
+  TTT_PLAYER_X = 1,
+  TTT_PLAYER_O = 2,
+} TttPlayer;
+
+typedef enum TttStatus {
+  TTT_STATUS_IN_PROGRESS = 0,
+  TTT_STATUS_X_WINS = 1,
+  TTT_STATUS_O_WINS = 2,
+  TTT_STATUS_DRAW = 3,
+} TttStatus;
+
+/**
+ * A game, only handled through pointers
+ */
+typedef struct TttGame TttGame;
+
+typedef struct TttState {
+  /**
+   * Row by row, `board[3 * y + x]`
+   */
+  enum TttPlayer board[9];
+  /**
+   * The player to move, `Empty` once the game is over
+   */
+  enum TttPlayer turn;
+  enum TttStatus status;
+} TttState;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * A new game, where X moves first
+ */
+struct TttGame *ttt_new(void);
+
+/**
+ * Play the square for the player to move: `TTT_OK`, or why it cannot be played
+ *
+ * # Safety
+ *
+ * `game` must come from `ttt_new` and not have been freed.
+ */
+int32_t ttt_set(struct TttGame *game, uint32_t x, uint32_t y);
+
+/**
+ * Write the best move of the player to move to `x` and `y`, false once the
+ * game is over
+ *
+ * # Safety
+ *
+ * `game` must come from `ttt_new` and not have been freed, and `x` and `y`
+ * must be valid pointers.
+ */
+bool ttt_best_play(const struct TttGame *game, uint32_t *x, uint32_t *y);
+
+/**
+ * The board, the player to move and whether the game is over
+ *
+ * # Safety
+ *
+ * `game` must come from `ttt_new` and not have been freed.
+ */
+struct TttState ttt_state(const struct TttGame *game);
+
+/**
+ * Write the position as numbers for a model to `features`, `TTT_FEATURES`
+ * values laid out as `Grid::to_features` describes, false if a pointer is null
+ *
+ * # Safety
+ *
+ * `game` must come from `ttt_new` and not have been freed, and `features`
+ * must point to room for `TTT_FEATURES` floats.
+ */
+bool ttt_features(const struct TttGame *game, float *features);
+
+/**
+ * Free a game; a null pointer is ignored
+ *
+ * # Safety
+ *
+ * `game` must come from `ttt_new` and not have been freed already.
+ */
+void ttt_free(struct TttGame *game);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TICTACTOE_H */
@@ -0,0 +1,854 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+#[cfg(feature = "async")]
+use crate::engine::spawn_search;
+use crate::engine::{legal_moves, Cache, SearchInfo};
+use crate::game::negamax_counted;
+use crate::notation::{parse_coordinates, position};
+use crate::record::GameRecord;
+use crate::session::{GameSession, Move};
+use crate::{coordinates, minimax, Grid, Player, LINES};
+
+// The game-theoretic value of a position for the player about to move, with
+// the number of moves left until the end of the game with perfect play
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Value {
+    Win(i32),
+    Draw,
+    Loss(i32),
+}
+
+impl Value {
+    // From a score given by `minimax`, for `player` when `turns` moves have
+    // been played
+    pub fn from_score(score: i32, player: Player, turns: i32) -> Value {
+        // The scores are given from O's point of view
+        let score = if player == Player::X { -score } else { score };
+
+        if score > 0 {
+            Value::Win(10 - score - turns)
+        } else if score < 0 {
+            Value::Loss(10 + score - turns)
+        } else {
+            Value::Draw
+        }
+    }
+
+    fn rank(self) -> i32 {
+        match self {
+            Value::Win(_) => 1,
+            Value::Draw => 0,
+            Value::Loss(_) => -1,
+        }
+    }
+}
+
+// The score of every legal move, from the point of view of the player to move
+// (the higher, the better)
+pub fn move_scores(grid: &Grid) -> Vec<((usize, usize), i32)> {
+    legal_moves(grid)
+        .into_iter()
+        .map(|m| (m, move_score(grid, m)))
+        .collect()
+}
+
+pub fn move_score(grid: &Grid, (x, y): (usize, usize)) -> i32 {
+    let sign = if grid.player_turn == Player::X { -1 } else { 1 };
+    let mut g = grid.clone();
+    let _ = g.set(x, y);
+
+    sign * minimax(g)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Solution {
+    pub player: Player,
+    pub value: Value,
+    pub best_moves: Vec<(usize, usize)>,
+}
+
+// The value of a position and the moves that keep it, or `None` if the game is
+// over
+pub fn solve(grid: &Grid) -> Option<Solution> {
+    solve_unless(grid, &AtomicBool::new(false))
+}
+
+// `solve`, giving up once `cancelled` is set
+fn solve_unless(grid: &Grid, cancelled: &AtomicBool) -> Option<Solution> {
+    if grid.has_winner() || grid.is_full() {
+        return None;
+    }
+
+    let mut scores = Vec::new();
+    for m in legal_moves(grid) {
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        scores.push((m, move_score(grid, m)));
+    }
+    let best = scores.iter().map(|&(_, s)| s).max()?;
+    let sign = if grid.player_turn == Player::X { -1 } else { 1 };
+
+    Some(Solution {
+        player: grid.player_turn,
+        value: Value::from_score(sign * best, grid.player_turn, grid.number_of_turns),
+        best_moves: scores
+            .into_iter()
+            .filter(|&(_, s)| s == best)
+            .map(|(m, _)| m)
+            .collect(),
+    })
+}
+
+// The value of every legal move for the player to move, from the best to the
+// worst
+pub fn move_values(grid: &Grid) -> Vec<((usize, usize), Value)> {
+    if grid.has_winner() {
+        return Vec::new();
+    }
+    let sign = if grid.player_turn == Player::X { -1 } else { 1 };
+    let mut scores = move_scores(grid);
+    scores.sort_by_key(|&(_, score)| -score);
+
+    scores
+        .into_iter()
+        .map(|(m, score)| {
+            let value = Value::from_score(sign * score, grid.player_turn, grid.number_of_turns);
+            (m, value)
+        })
+        .collect()
+}
+
+// `solve`, with what the search went through: the positions searched and the
+// best play of both sides to the end of the game
+pub fn solve_with_info(grid: &Grid) -> Option<(Solution, SearchInfo)> {
+    solve_searched(grid, |next, info| negamax_counted(next, &mut info.nodes))
+}
+
+// `solve_with_info`, looking up the positions of `cache` rather than search
+// them again, and adding those searched to it
+pub fn solve_cached(grid: &Grid, cache: &mut Cache) -> Option<(Solution, SearchInfo)> {
+    solve_searched(grid, |next, info| cache.score(next, info))
+}
+
+// `solve` searched with `engine::spawn_search`, for a task following a game
+// to ask about each position as it comes. Dropping the future stops the
+// search.
+#[cfg(feature = "async")]
+pub async fn solve_async(grid: Grid) -> Option<Solution> {
+    spawn_search(move |cancelled| solve_unless(&grid, cancelled)).await
+}
+
+// `score` gives the value of the position after each move, for the player to
+// move there
+fn solve_searched(
+    grid: &Grid,
+    mut score: impl FnMut(&Grid, &mut SearchInfo) -> i32,
+) -> Option<(Solution, SearchInfo)> {
+    if grid.has_winner() || grid.is_full() {
+        return None;
+    }
+
+    let start = Instant::now();
+    let mut info = SearchInfo {
+        depth: u32::try_from(9 - grid.number_of_turns).unwrap_or(0),
+        ..SearchInfo::default()
+    };
+    let mut scores = Vec::new();
+    for m in legal_moves(grid) {
+        let mut next = grid.clone();
+        let _ = next.set(m.0, m.1);
+        scores.push((m, -score(&next, &mut info)));
+    }
+    let best = scores.iter().map(|&(_, s)| s).max()?;
+    let sign = if grid.player_turn == Player::X { -1 } else { 1 };
+    let best_moves: Vec<(usize, usize)> = scores
+        .into_iter()
+        .filter(|&(_, s)| s == best)
+        .map(|(m, _)| m)
+        .collect();
+    info.elapsed = start.elapsed();
+    info.pv = principal_variation(grid)
+        .iter()
+        .map(|m| (m.x, m.y))
+        .collect();
+
+    let solution = Solution {
+        player: grid.player_turn,
+        value: Value::from_score(sign * best, grid.player_turn, grid.number_of_turns),
+        best_moves,
+    };
+    Some((solution, info))
+}
+
+// The moves from `grid` to the end of the game when both players play their
+// best, the first of the best moves each time
+pub fn principal_variation(grid: &Grid) -> Vec<Move> {
+    let mut grid = grid.clone();
+    let mut moves = Vec::new();
+    while let Some(solution) = solve(&grid) {
+        let (x, y) = solution.best_moves[0];
+        moves.push(Move {
+            player: grid.player_turn,
+            x,
+            y,
+        });
+        let _ = grid.set(x, y);
+    }
+
+    moves
+}
+
+// The columns of `csv_row`
+pub const CSV_HEADER: &str = "position,player,value,plies,best_move,best_moves";
+
+// The solution of `grid` as a line of CSV: the player to move, `win`, `draw`
+// or `loss` for them with the number of moves left, the first of the best
+// moves and all of them. A finished game only has its position.
+pub fn csv_row(grid: &Grid) -> String {
+    let Some(solution) = solve(grid) else {
+        return format!("{},,,,,", position(grid));
+    };
+
+    let (value, plies) = match solution.value {
+        Value::Win(n) => ("win", n),
+        Value::Draw => ("draw", 9 - grid.number_of_turns),
+        Value::Loss(n) => ("loss", n),
+    };
+    let best: Vec<String> = solution
+        .best_moves
+        .iter()
+        .map(|&(x, y)| coordinates(x, y))
+        .collect();
+    format!(
+        "{},{},{value},{plies},{},{}",
+        position(grid),
+        solution.player,
+        best[0],
+        best.join(" ")
+    )
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Verdict {
+    // One of the best moves
+    Best,
+    // Keeps the outcome, but wins slower or loses faster
+    Inaccuracy,
+    // Changes the outcome of the game
+    Mistake,
+}
+
+// How a move of a game compares to the best ones
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Review {
+    // The number of moves played before this one
+    pub ply: usize,
+    pub played: Move,
+    // The value of the position before the move, and with the move played,
+    // both for the player who moved
+    pub before: Value,
+    pub after: Value,
+    pub best_moves: Vec<(usize, usize)>,
+}
+
+impl Review {
+    pub fn verdict(&self) -> Verdict {
+        if self.best_moves.contains(&(self.played.x, self.played.y)) {
+            Verdict::Best
+        } else if self.before.rank() == self.after.rank() {
+            Verdict::Inaccuracy
+        } else {
+            Verdict::Mistake
+        }
+    }
+}
+
+// Review the move played at `ply` in `grid`
+pub fn review_move(grid: &Grid, ply: usize, played: Move) -> Option<Review> {
+    let solution = solve(grid)?;
+
+    let mut after = grid.clone();
+    after.set(played.x, played.y).ok()?;
+    let after = Value::from_score(minimax(after), played.player, grid.number_of_turns);
+
+    Some(Review {
+        ply,
+        played,
+        before: solution.value,
+        after,
+        best_moves: solution.best_moves,
+    })
+}
+
+// Every move of a game, compared to the best ones
+pub fn review(session: &GameSession) -> Vec<Review> {
+    session
+        .history()
+        .iter()
+        .enumerate()
+        .filter_map(|(ply, &m)| review_move(&session.position_at(ply), ply, m))
+        .collect()
+}
+
+// The moves of a game that were not among the best, as `tictactoe analyze
+// --archive` keeps them in the `Annotations` tag of its record: `a1?! c2?` for
+// an inaccuracy on a1 and a mistake on c2, the way chess marks them
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Annotations {
+    pub flaws: Vec<(Move, Verdict)>,
+    // The number of moves of X and of O
+    moves: [usize; 2],
+}
+
+impl Annotations {
+    // Review every move of a game
+    pub fn of(record: &GameRecord) -> Annotations {
+        let session = record.replay();
+        let flaws = review(&session)
+            .into_iter()
+            .map(|r| (r.played, r.verdict()))
+            .filter(|&(_, verdict)| verdict != Verdict::Best)
+            .collect();
+
+        Annotations {
+            flaws,
+            moves: count_moves(session.history()),
+        }
+    }
+
+    // The annotations of a game that has been analyzed, unless they do not
+    // match its moves
+    pub fn read(record: &GameRecord) -> Option<Annotations> {
+        let session = record.replay();
+        let history = session.history();
+        let mut flaws = Vec::new();
+        for token in record.tag("Annotations")?.split_whitespace() {
+            let (square, verdict) = match token.strip_suffix("?!") {
+                Some(square) => (square, Verdict::Inaccuracy),
+                None => (token.strip_suffix('?')?, Verdict::Mistake),
+            };
+            let square = parse_coordinates(square).ok()?;
+            let &played = history.iter().find(|m| (m.x, m.y) == square)?;
+            if flaws.iter().any(|&(m, _)| m == played) {
+                return None;
+            }
+            flaws.push((played, verdict));
+        }
+
+        Some(Annotations {
+            flaws,
+            moves: count_moves(history),
+        })
+    }
+
+    pub fn write(&self, record: &mut GameRecord) {
+        let marks: Vec<String> = self
+            .flaws
+            .iter()
+            .map(|(m, verdict)| {
+                let mark = match verdict {
+                    Verdict::Inaccuracy => "?!",
+                    _ => "?",
+                };
+                format!("{}{mark}", coordinates(m.x, m.y))
+            })
+            .collect();
+        record.set_tag("Annotations", &marks.join(" "));
+    }
+
+    pub fn moves(&self, player: Player) -> usize {
+        match player {
+            Player::X => self.moves[0],
+            Player::O => self.moves[1],
+            Player::Empty => 0,
+        }
+    }
+
+    // The moves of `player` with this verdict, other than `Best`
+    pub fn count(&self, player: Player, verdict: Verdict) -> usize {
+        self.flaws
+            .iter()
+            .filter(|(m, v)| m.player == player && *v == verdict)
+            .count()
+    }
+
+    // The share of the moves of `player` that were among the best, in percent
+    pub fn accuracy(&self, player: Player) -> Option<f64> {
+        let moves = self.moves(player);
+        let flaws = self.flaws.iter().filter(|(m, _)| m.player == player);
+        (moves > 0).then(|| 100.0 * (moves - flaws.count()) as f64 / moves as f64)
+    }
+}
+
+fn count_moves(history: &[Move]) -> [usize; 2] {
+    let x = history.iter().filter(|m| m.player == Player::X).count();
+    [x, history.len() - x]
+}
+
+// The line of three marks of the winner, if any
+pub fn winning_line(grid: &Grid) -> Option<[(usize, usize); 3]> {
+    grid.lines()
+        .find(|&[(_, a), (_, b), (_, c)]| a != Player::Empty && a == b && b == c)
+        .map(|line| line.map(|(square, _)| square))
+}
+
+// The empty squares where `player` would complete a line
+pub fn threats(grid: &Grid, player: Player) -> Vec<(usize, usize)> {
+    grid.winning_moves(player).collect()
+}
+
+// The empty squares where `player` would make two threats at once
+pub fn forks(grid: &Grid, player: Player) -> Vec<(usize, usize)> {
+    legal_moves(grid)
+        .into_iter()
+        .filter(|&(x, y)| threats(&grid.with_mark(x, y, player), player).len() >= 2)
+        .collect()
+}
+
+// A row, a column or a diagonal, as named in explanations
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+    // From a1 to c3
+    Diagonal,
+    // From a3 to c1
+    AntiDiagonal,
+}
+
+impl Line {
+    // The name of `LINES[i]`
+    fn of(i: usize) -> Line {
+        match i {
+            0..=2 => Line::Row(i),
+            3..=5 => Line::Column(i - 3),
+            6 => Line::Diagonal,
+            _ => Line::AntiDiagonal,
+        }
+    }
+}
+
+// One thing that a move does, as told by `explain`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Reason {
+    // Completes the line
+    Wins(Line),
+    // Takes the square where the opponent would complete the line
+    Blocks(Line),
+    // Makes two threats at once, on these squares
+    Forks((usize, usize), (usize, usize)),
+    // Threatens to win on this square
+    Threatens((usize, usize)),
+    // Takes a square where the opponent could make a fork
+    PreventsFork,
+    // The opponent can now win on this square
+    AllowsWin((usize, usize)),
+    // The opponent can now make a fork on this square
+    AllowsFork((usize, usize)),
+    // When nothing else can be said
+    TakesCenter,
+    TakesCorner,
+    TakesSide,
+}
+
+// Why a move is played, in terms of the threats and forks on the board: a
+// winning move only wins, otherwise the good and the bad of the move are told
+// in that order. Empty if the square is taken or the game is over.
+pub fn explain(grid: &Grid, (x, y): (usize, usize)) -> Vec<Reason> {
+    if grid.has_winner() || grid.matrix()[y][x] != Player::Empty {
+        return Vec::new();
+    }
+    let player = grid.player_turn;
+    let opponent = player.opponent();
+    let mut after = grid.clone();
+    let _ = after.set(x, y);
+
+    let count = |line: &[(usize, usize); 3], p| {
+        line.iter()
+            .filter(|&&(x, y)| grid.matrix()[y][x] == p)
+            .count()
+    };
+    let lines = LINES
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.contains(&(x, y)));
+    let wins: Vec<_> = lines
+        .clone()
+        .filter(|(_, line)| count(line, player) == 2)
+        .map(|(i, _)| Reason::Wins(Line::of(i)))
+        .collect();
+    if !wins.is_empty() {
+        return wins;
+    }
+
+    let mut reasons: Vec<_> = lines
+        .filter(|(_, line)| count(line, opponent) == 2)
+        .map(|(i, _)| Reason::Blocks(Line::of(i)))
+        .collect();
+    let before = threats(grid, player);
+    let made: Vec<_> = threats(&after, player)
+        .into_iter()
+        .filter(|s| !before.contains(s))
+        .collect();
+    match made[..] {
+        [] => {}
+        [s] => reasons.push(Reason::Threatens(s)),
+        [a, b, ..] => reasons.push(Reason::Forks(a, b)),
+    }
+
+    // A fork does not matter when the opponent can win anyway
+    let allowed_win = threats(&after, opponent).first().copied();
+    if allowed_win.is_none() && forks(grid, opponent).contains(&(x, y)) {
+        reasons.push(Reason::PreventsFork);
+    }
+
+    // The opponent has to answer a threat first, so they can only fork on the
+    // square where they block it
+    let opponent_forks = forks(&after, opponent);
+    let allowed_fork = match made[..] {
+        [] => opponent_forks.first().copied(),
+        [s] => opponent_forks.contains(&s).then_some(s),
+        _ => None,
+    };
+    if let Some(s) = allowed_win {
+        reasons.push(Reason::AllowsWin(s));
+    } else if let Some(s) = allowed_fork {
+        reasons.push(Reason::AllowsFork(s));
+    }
+
+    if reasons.is_empty() {
+        reasons.push(match (x, y) {
+            (1, 1) => Reason::TakesCenter,
+            (1, _) | (_, 1) => Reason::TakesSide,
+            _ => Reason::TakesCorner,
+        });
+    }
+
+    reasons
+}
+
+// What is wrong with a move that is not one of the best
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Flaw {
+    // The opponent can now win on this square
+    AllowsWin((usize, usize)),
+    // The opponent can now make a fork on this square
+    AllowsFork((usize, usize)),
+    // The player could have won on this square
+    MissedWin((usize, usize)),
+    // Another reason, see the verdict of the review
+    Other,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Advice {
+    pub flaw: Flaw,
+    pub review: Review,
+    // Why the first of the best moves is better
+    pub explanation: Vec<Reason>,
+}
+
+// Feedback on a move played in `grid`, or `None` if it was one of the best
+pub fn coach(grid: &Grid, played: Move) -> Option<Advice> {
+    let review = review_move(grid, grid.number_of_turns as usize, played)?;
+    if review.verdict() == Verdict::Best {
+        return None;
+    }
+
+    let mut after = grid.clone();
+    let _ = after.set(played.x, played.y);
+    let opponent = played.player.opponent();
+
+    let flaw = if let Some(&square) = threats(grid, played.player).first() {
+        Flaw::MissedWin(square)
+    } else if let Some(&square) = threats(&after, opponent).first() {
+        Flaw::AllowsWin(square)
+    } else if let Some(&square) = forks(&after, opponent).first() {
+        Flaw::AllowsFork(square)
+    } else {
+        Flaw::Other
+    };
+
+    let explanation = explain(grid, review.best_moves[0]);
+
+    Some(Advice {
+        flaw,
+        review,
+        explanation,
+    })
+}
+
+// The same number for the eight rotations and reflections of a grid
+pub fn canonical(grid: &Grid) -> u32 {
+    let mut m = *grid.matrix();
+    let mut smallest = u32::MAX;
+    for _ in 0..4 {
+        m = [0, 1, 2].map(|y| [0, 1, 2].map(|x| m[2 - x][y]));
+        let mirror = m.map(|mut row| {
+            row.reverse();
+            row
+        });
+        smallest = smallest.min(number(&m)).min(number(&mirror));
+    }
+
+    smallest
+}
+
+fn number(matrix: &[[Player; 3]; 3]) -> u32 {
+    matrix.iter().flatten().fold(0, |n, &square| {
+        3 * n
+            + match square {
+                Player::Empty => 0,
+                Player::X => 1,
+                Player::O => 2,
+            }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::parse_position;
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn solved_async() {
+        let grid = parse_position("XX./OO./...").unwrap();
+        assert_eq!(solve_async(grid.clone()).await, solve(&grid));
+        assert_eq!(
+            solve_async(parse_position("XXX/OO./...").unwrap()).await,
+            None
+        );
+        // Given up once cancelled, as when the future is dropped
+        assert_eq!(solve_unless(&grid, &AtomicBool::new(true)), None);
+    }
+
+    #[test]
+    fn position_analysis() {
+        let grid = parse_position("X.O/.X./...").unwrap();
+        let values = move_values(&grid);
+        assert_eq!(values.len(), 6);
+        assert_eq!(values[0], ((2, 2), Value::Draw));
+        assert!(values[1..]
+            .iter()
+            .all(|&(_, v)| matches!(v, Value::Loss(_))));
+
+        let pv = principal_variation(&grid);
+        assert_eq!(pv.len(), 6);
+        assert_eq!((pv[0].player, pv[0].x, pv[0].y), (Player::O, 2, 2));
+
+        let grid = parse_position("XX./OO./...").unwrap();
+        assert_eq!(move_values(&grid)[0], ((2, 0), Value::Win(1)));
+        assert_eq!(principal_variation(&grid).len(), 1);
+    }
+
+    #[test]
+    fn csv() {
+        let row = |p: &str| csv_row(&parse_position(p).unwrap());
+        assert_eq!(row("XX./OO./..."), "XX./OO./...,X,win,1,a3,a3");
+        assert_eq!(row("X.O/.X./..."), "X.O/.X./...,O,draw,6,c3,c3");
+        assert_eq!(row("XXX/OO./..."), "XXX/OO./...,,,,,");
+    }
+
+    #[test]
+    fn solve_win() {
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::Empty],
+            [Player::O, Player::O, Player::Empty],
+            [Player::Empty, Player::Empty, Player::Empty],
+        ]);
+
+        let solution = solve(&grid).unwrap();
+        assert_eq!(solution.player, Player::X);
+        assert_eq!(solution.value, Value::Win(1));
+        assert_eq!(solution.best_moves, vec![(2, 0)]);
+    }
+
+    #[test]
+    fn solve_loss() {
+        // O can only stop one of X's two lines
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::Empty],
+            [Player::X, Player::O, Player::Empty],
+            [Player::Empty, Player::Empty, Player::O],
+        ]);
+
+        let solution = solve(&grid).unwrap();
+        assert_eq!(solution.player, Player::O);
+        assert_eq!(solution.value, Value::Loss(2));
+        assert_eq!(solution.best_moves.len(), 4);
+    }
+
+    #[test]
+    fn solve_with_search_info() {
+        let grid = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        let (solution, info) = solve_with_info(&grid).unwrap();
+        assert_eq!(Some(solution), solve(&grid));
+        assert_eq!(info.pv, [(2, 0)]);
+        assert_eq!(info.depth, 5);
+        assert!(info.nodes > 5);
+
+        let (solution, info) = solve_with_info(&Grid::new()).unwrap();
+        assert_eq!(solution.value, Value::Draw);
+        assert_eq!(info.pv.len(), 9);
+        assert_eq!(info.pv[0], solution.best_moves[0]);
+        assert!(solve_with_info(&Grid::try_from(&["XXX", "OO.", "..."]).unwrap()).is_none());
+
+        let mut cache = Cache::new();
+        let (cached, first) = solve_cached(&Grid::new(), &mut cache).unwrap();
+        assert_eq!(cached, solution);
+        // Positions reached in more than one way are searched once
+        assert!(first.nodes < info.nodes);
+        let (_, again) = solve_cached(&Grid::new(), &mut cache).unwrap();
+        assert_eq!(again.cache_hits, 9);
+    }
+
+    #[test]
+    fn solve_over() {
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::X],
+            [Player::O, Player::O, Player::Empty],
+            [Player::Empty, Player::Empty, Player::Empty],
+        ]);
+
+        assert_eq!(solve(&grid), None);
+    }
+
+    #[test]
+    fn review_game() {
+        let mut session = GameSession::new();
+        // X takes the center, O answers on an edge and loses
+        for (x, y) in [(1, 1), (1, 0), (0, 0), (2, 2)] {
+            session.play(x, y).unwrap();
+        }
+
+        let reviews = review(&session);
+        assert_eq!(reviews.len(), 4);
+        assert_eq!(reviews[0].verdict(), Verdict::Best);
+        assert_eq!(reviews[0].before, Value::Draw);
+
+        assert_eq!(reviews[1].verdict(), Verdict::Mistake);
+        assert_eq!(reviews[1].after, Value::Loss(6));
+        assert_eq!(reviews[1].best_moves, vec![(0, 0), (2, 0), (0, 2), (2, 2)]);
+
+        assert_eq!(reviews[2].before, Value::Win(5));
+        assert_eq!(reviews[3].played.player, Player::O);
+    }
+
+    #[test]
+    fn annotations() {
+        let mut record = GameRecord::parse(
+            "[Date \"2026-10-15\"]\n[Time \"20:00:00\"]\n[X \"alice\"]\n[O \"bob\"]\n\
+             [First \"X\"]\n[Result \"1-0\"]\n[Termination \"resignation\"]\n\n\
+             b2 a2 a1 c3 1-0\n",
+        )
+        .unwrap();
+        assert_eq!(Annotations::read(&record), None);
+
+        let annotations = Annotations::of(&record);
+        annotations.write(&mut record);
+        assert_eq!(record.tag("Annotations"), Some("a2?"));
+        assert_eq!(Annotations::read(&record), Some(annotations.clone()));
+
+        assert_eq!(annotations.count(Player::O, Verdict::Mistake), 1);
+        assert_eq!(annotations.accuracy(Player::O), Some(50.0));
+        assert_eq!(annotations.accuracy(Player::X), Some(100.0));
+
+        record.set_tag("Annotations", "b3?");
+        assert_eq!(Annotations::read(&record), None);
+        // A move marked twice would count more flaws than moves
+        record.set_tag("Annotations", "a2? a2?! a2?");
+        assert_eq!(Annotations::read(&record), None);
+    }
+
+    #[test]
+    fn threats_and_forks() {
+        let grid = Grid::from([
+            [Player::X, Player::Empty, Player::Empty],
+            [Player::Empty, Player::O, Player::Empty],
+            [Player::Empty, Player::Empty, Player::X],
+        ]);
+
+        assert_eq!(threats(&grid, Player::X), vec![]);
+        assert_eq!(forks(&grid, Player::X), vec![(2, 0), (0, 2)]);
+
+        let grid = grid.with_mark(2, 0, Player::X);
+        assert_eq!(threats(&grid, Player::X), vec![(1, 0), (2, 1)]);
+        assert_eq!(winning_line(&grid), None);
+
+        let grid = grid.with_mark(1, 0, Player::X);
+        assert_eq!(winning_line(&grid), Some([(0, 0), (1, 0), (2, 0)]));
+    }
+
+    #[test]
+    fn explanations() {
+        let (e, x, o) = (Player::Empty, Player::X, Player::O);
+        let grid = Grid::from([[x, x, e], [o, o, e], [e, e, e]]);
+        assert_eq!(explain(&grid, (2, 0)), [Reason::Wins(Line::Row(0))]);
+        assert_eq!(explain(&grid, (0, 0)), []);
+
+        let grid = Grid::from([[x, x, e], [o, e, e], [e, e, e]]);
+        assert_eq!(
+            explain(&grid, (2, 0)),
+            [Reason::Blocks(Line::Row(0)), Reason::AllowsFork((1, 1))]
+        );
+
+        // a3 was a fork for X, but X must answer the threat of O on c1, which
+        // makes another one
+        let grid = Grid::from([[x, e, e], [e, o, e], [e, e, x]]);
+        assert_eq!(
+            explain(&grid, (2, 0)),
+            [
+                Reason::Threatens((0, 2)),
+                Reason::PreventsFork,
+                Reason::AllowsFork((0, 2))
+            ]
+        );
+        assert_eq!(explain(&grid, (1, 0)), [Reason::Threatens((1, 2))]);
+
+        assert_eq!(explain(&Grid::new(), (1, 1)), [Reason::TakesCenter]);
+        assert_eq!(explain(&Grid::new(), (2, 2)), [Reason::TakesCorner]);
+    }
+
+    #[test]
+    fn coaching() {
+        let mut grid = Grid::new();
+        for (x, y) in [(0, 0), (1, 1), (2, 2)] {
+            grid.set(x, y).unwrap();
+        }
+
+        // O must take an edge, a corner lets X fork
+        let corner = Move {
+            player: Player::O,
+            x: 2,
+            y: 0,
+        };
+        let advice = coach(&grid, corner).unwrap();
+        assert_eq!(advice.flaw, Flaw::AllowsFork((0, 2)));
+        assert_eq!(
+            advice.review.best_moves,
+            vec![(1, 0), (0, 1), (2, 1), (1, 2)]
+        );
+
+        let edge = Move { x: 1, ..corner };
+        assert_eq!(coach(&grid, edge), None);
+    }
+
+    #[test]
+    fn missed_win() {
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::Empty],
+            [Player::O, Player::O, Player::Empty],
+            [Player::Empty, Player::Empty, Player::Empty],
+        ]);
+        let played = Move {
+            player: Player::X,
+            x: 2,
+            y: 1,
+        };
+
+        assert_eq!(coach(&grid, played).unwrap().flaw, Flaw::MissedWin((2, 0)));
+    }
+}
@@ -0,0 +1,193 @@
+use macroquad::prelude::*;
+
+use crate::analysis::winning_line;
+use crate::engine::{Bot, Difficulty};
+use crate::i18n::{Lang, Msg};
+use crate::session::GameSession;
+use crate::Player;
+
+// The window of the `tictactoe-arcade` binary: the same game as the desktop
+// interface, but drawn every frame, with the marks growing into place and the
+// winning line flashing. The session is only changed by clicks and by the
+// engine once its delay is over, and each frame draws what it holds. The
+// engine is seeded with `seed`.
+pub async fn run(lang: Lang, seed: u64) {
+    let mut arcade = Arcade::new(seed);
+    loop {
+        arcade.update(get_time());
+        arcade.draw(lang, get_time());
+        next_frame().await;
+    }
+}
+
+const CELL: f32 = 100.0;
+const TOP: f32 = 60.0;
+// How long a mark takes to appear, in seconds
+const APPEAR: f64 = 0.25;
+// How long the engine waits before answering, to let the mark of the player
+// appear first
+const ENGINE_DELAY: f64 = 0.5;
+
+const BACKGROUND: Color = Color::new(0.1, 0.1, 0.15, 1.0);
+const X_COLOR: Color = Color::new(0.9, 0.4, 0.3, 1.0);
+const O_COLOR: Color = Color::new(0.3, 0.7, 0.9, 1.0);
+
+struct Arcade {
+    session: GameSession,
+    bot: Bot,
+    // When each move of the history was played
+    played_at: Vec<f64>,
+    // When the engine plays its next move
+    engine_at: Option<f64>,
+}
+
+impl Arcade {
+    fn new(seed: u64) -> Arcade {
+        Arcade {
+            session: GameSession::new(),
+            bot: Bot::new(Difficulty::Medium, seed),
+            played_at: Vec::new(),
+            engine_at: None,
+        }
+    }
+
+    fn restart(&mut self) {
+        self.session = GameSession::new();
+        self.played_at.clear();
+        self.engine_at = None;
+    }
+
+    fn update(&mut self, now: f64) {
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let (mx, my) = mouse_position();
+            if restart_button().contains(vec2(mx, my)) {
+                self.restart();
+            } else if let Some((x, y)) = square_at(mx, my) {
+                self.click(x, y, now);
+            }
+        }
+
+        if self.engine_at.is_some_and(|at| now >= at) {
+            self.engine_at = None;
+            if self.session.play_with(&mut self.bot).is_some() {
+                self.played_at.push(now);
+            }
+        }
+    }
+
+    fn click(&mut self, x: usize, y: usize, now: f64) {
+        if self.engine_at.is_some() || self.session.grid().player_turn != Player::X {
+            return;
+        }
+        if self.session.play(x, y).is_ok() {
+            self.played_at.push(now);
+            if !self.session.is_over() {
+                self.engine_at = Some(now + ENGINE_DELAY);
+            }
+        }
+    }
+
+    fn draw(&self, lang: Lang, now: f64) {
+        clear_background(BACKGROUND);
+        let left = board_left();
+
+        for i in 1..3 {
+            let offset = i as f32 * CELL;
+            let (top, bottom) = (
+                vec2(left + offset, TOP),
+                vec2(left + offset, TOP + 3.0 * CELL),
+            );
+            stroke(top, bottom, 4.0, GRAY);
+            let (start, end) = (
+                vec2(left, TOP + offset),
+                vec2(left + 3.0 * CELL, TOP + offset),
+            );
+            stroke(start, end, 4.0, GRAY);
+        }
+
+        for (m, &at) in self.session.history().iter().zip(&self.played_at) {
+            let progress = ((now - at) / APPEAR).min(1.0) as f32;
+            draw_mark(m.player, center((m.x, m.y)), progress);
+        }
+
+        // Flash the winning line once the last mark is in place
+        let settled = self.played_at.last().is_some_and(|&at| now - at >= APPEAR);
+        if let Some(line) = winning_line(self.session.grid()).filter(|_| settled) {
+            let alpha = if (now * 8.0).sin() > 0.0 { 1.0 } else { 0.3 };
+            let (start, end) = (center(line[0]), center(line[2]));
+            stroke(start, end, 10.0, Color::new(1.0, 0.9, 0.2, alpha));
+        }
+
+        let status = match self.session.result() {
+            Some(result) => lang.text(Msg::from(result)),
+            None => lang.text(Msg::GuiYourTurn),
+        };
+        draw_text(&status, left, TOP - 20.0, 28.0, WHITE);
+
+        let button = restart_button();
+        draw_rectangle(button.x, button.y, button.w, button.h, DARKGRAY);
+        let text = lang.text(Msg::GuiNewGame);
+        let size = measure_text(&text, None, 28, 1.0);
+        let x = button.x + (button.w - size.width) / 2.0;
+        let y = button.y + (button.h + size.height) / 2.0;
+        draw_text(&text, x, y, 28.0, WHITE);
+    }
+}
+
+fn board_left() -> f32 {
+    (screen_width() - 3.0 * CELL) / 2.0
+}
+
+fn center((x, y): (usize, usize)) -> Vec2 {
+    vec2(
+        board_left() + (x as f32 + 0.5) * CELL,
+        TOP + (y as f32 + 0.5) * CELL,
+    )
+}
+
+fn square_at(mx: f32, my: f32) -> Option<(usize, usize)> {
+    let (x, y) = (
+        ((mx - board_left()) / CELL).floor(),
+        ((my - TOP) / CELL).floor(),
+    );
+    ((0.0..3.0).contains(&x) && (0.0..3.0).contains(&y)).then_some((x as usize, y as usize))
+}
+
+fn restart_button() -> Rect {
+    Rect::new(
+        board_left() + 0.5 * CELL,
+        TOP + 3.0 * CELL + 30.0,
+        2.0 * CELL,
+        50.0,
+    )
+}
+
+// A mark `progress` of the way into place: the strokes of an X are drawn one
+// after the other, and an O grows from its center
+fn draw_mark(player: Player, center: Vec2, progress: f32) {
+    let size = CELL * 0.3;
+    match player {
+        Player::X => {
+            let first = (progress * 2.0).min(1.0);
+            let second = (progress * 2.0 - 1.0).max(0.0);
+            let from = center - vec2(size, size);
+            let to = from + vec2(2.0 * size, 2.0 * size) * first;
+            stroke(from, to, 8.0, X_COLOR);
+            if second > 0.0 {
+                let from = center + vec2(size, -size);
+                let to = from + vec2(-2.0 * size, 2.0 * size) * second;
+                stroke(from, to, 8.0, X_COLOR);
+            }
+        }
+        Player::O => {
+            // Ease out, so that it slows down as it settles
+            let scale = 1.0 - (1.0 - progress).powi(3);
+            draw_circle_lines(center.x, center.y, size * scale, 8.0, O_COLOR);
+        }
+        Player::Empty => (),
+    }
+}
+
+fn stroke(from: Vec2, to: Vec2, thickness: f32, color: Color) {
+    draw_line(from.x, from.y, to.x, to.y, thickness, color);
+}
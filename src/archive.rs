@@ -0,0 +1,346 @@
+use std::io;
+use std::path::Path;
+
+use rusqlite::{params, params_from_iter, Connection, Transaction};
+
+use crate::notation::parse_coordinates;
+use crate::record::{parse_result, result_tag, termination_tag, GameRecord};
+use crate::{coordinates, Player};
+
+// The games of a SQLite database, for players who keep too many games for a
+// log file: they can be queried by player, date or tag without reading them
+// all. A log file named `*.db` or `*.sqlite` is kept this way.
+pub struct Archive {
+    connection: Connection,
+}
+
+// Which games to get from an archive: every field left to `None` matches all
+// the games
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Query {
+    // Games where this player had either side
+    pub player: Option<String>,
+    // Games won by this player
+    pub winner: Option<String>,
+    // Dates as `YYYY-MM-DD`, both included
+    pub since: Option<String>,
+    pub until: Option<String>,
+    // Games with this tag, such as `Difficulty` and `hard`
+    pub tag: Option<(String, String)>,
+    // Only the last games played
+    pub limit: Option<usize>,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS games (
+        id INTEGER PRIMARY KEY,
+        date TEXT NOT NULL,
+        time TEXT NOT NULL,
+        x TEXT NOT NULL,
+        o TEXT NOT NULL,
+        first TEXT NOT NULL,
+        moves TEXT NOT NULL,
+        result TEXT NOT NULL,
+        termination TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS tags (
+        game INTEGER NOT NULL REFERENCES games (id),
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (game, position)
+    );
+    CREATE INDEX IF NOT EXISTS games_x ON games (x);
+    CREATE INDEX IF NOT EXISTS games_o ON games (o);
+    CREATE INDEX IF NOT EXISTS games_date ON games (date, time);
+";
+
+// Whether a log file is to be kept in an archive, from its extension
+pub fn is_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "db" || e == "sqlite")
+}
+
+impl Archive {
+    // Open the archive at `path`, creating it if needed
+    pub fn open(path: &Path) -> io::Result<Archive> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        Archive::new(Connection::open(path).map_err(error)?)
+    }
+
+    // An archive that is lost when dropped
+    pub fn in_memory() -> io::Result<Archive> {
+        Archive::new(Connection::open_in_memory().map_err(error)?)
+    }
+
+    fn new(connection: Connection) -> io::Result<Archive> {
+        connection.execute_batch(SCHEMA).map_err(error)?;
+        Ok(Archive { connection })
+    }
+
+    pub fn add(&mut self, record: &GameRecord) -> io::Result<()> {
+        self.add_all(std::slice::from_ref(record))
+    }
+
+    // Add games in a single transaction, much faster than one by one
+    pub fn add_all(&mut self, records: &[GameRecord]) -> io::Result<()> {
+        let transaction = self.connection.transaction().map_err(error)?;
+        insert(&transaction, records)?;
+        transaction.commit().map_err(error)
+    }
+
+    // Put these games in place of all those of the archive, at once so that
+    // none are lost if it fails
+    pub fn replace_all(&mut self, records: &[GameRecord]) -> io::Result<()> {
+        let transaction = self.connection.transaction().map_err(error)?;
+        transaction
+            .execute_batch("DELETE FROM tags; DELETE FROM games;")
+            .map_err(error)?;
+        insert(&transaction, records)?;
+        transaction.commit().map_err(error)
+    }
+
+    pub fn len(&self) -> io::Result<usize> {
+        self.connection
+            .query_row("SELECT COUNT(*) FROM games", [], |row| row.get::<_, i64>(0))
+            .map(|n| n as usize)
+            .map_err(error)
+    }
+
+    pub fn is_empty(&self) -> io::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    // The games matching `query`, in the order they were added
+    pub fn games(&self, query: &Query) -> io::Result<Vec<GameRecord>> {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if let Some(player) = &query.player {
+            conditions.push("(x = ? OR o = ?)");
+            values.extend([player.clone(), player.clone()]);
+        }
+        if let Some(winner) = &query.winner {
+            conditions.push("((x = ? AND result = '1-0') OR (o = ? AND result = '0-1'))");
+            values.extend([winner.clone(), winner.clone()]);
+        }
+        if let Some(since) = &query.since {
+            conditions.push("date >= ?");
+            values.push(since.clone());
+        }
+        if let Some(until) = &query.until {
+            conditions.push("date <= ?");
+            values.push(until.clone());
+        }
+        if let Some((name, value)) = &query.tag {
+            conditions.push("id IN (SELECT game FROM tags WHERE name = ? AND value = ?)");
+            values.extend([name.clone(), value.clone()]);
+        }
+
+        let mut sql =
+            "SELECT id, date, time, x, o, first, moves, result, termination FROM games".to_string();
+        if !conditions.is_empty() {
+            sql += " WHERE ";
+            sql += &conditions.join(" AND ");
+        }
+        // The last games are taken first, then put back in order
+        sql += " ORDER BY id DESC";
+        if let Some(limit) = query.limit {
+            sql += &format!(" LIMIT {limit}");
+        }
+
+        let mut statement = self.connection.prepare(&sql).map_err(error)?;
+        let rows = statement
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    [
+                        row.get::<_, String>(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                        row.get(7)?,
+                        row.get(8)?,
+                    ],
+                ))
+            })
+            .map_err(error)?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let (id, columns) = row.map_err(error)?;
+            records.push(self.record(id, columns)?);
+        }
+        records.reverse();
+
+        Ok(records)
+    }
+
+    fn record(&self, id: i64, columns: [String; 8]) -> io::Result<GameRecord> {
+        let [date, time, x, o, first, moves, result, termination] = columns;
+        let invalid = |message: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("game {id}: {message}"))
+        };
+
+        let first_player = match first.as_str() {
+            "X" => Player::X,
+            "O" => Player::O,
+            _ => return Err(invalid(format!("invalid first player {first}"))),
+        };
+        let moves = moves
+            .split_whitespace()
+            .map(|m| parse_coordinates(m).map_err(|e| invalid(format!("{m}: {e}"))))
+            .collect::<io::Result<_>>()?;
+        let result = parse_result(&result, &termination)
+            .ok_or_else(|| invalid(format!("invalid result {result} ({termination})")))?;
+
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT name, value FROM tags WHERE game = ?1 ORDER BY position")
+            .map_err(error)?;
+        let tags = statement
+            .query_map([id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(error)?
+            .collect::<Result<_, _>>()
+            .map_err(error)?;
+
+        Ok(GameRecord {
+            date,
+            time,
+            x,
+            o,
+            first_player,
+            moves,
+            result,
+            tags,
+        })
+    }
+}
+
+fn insert(transaction: &Transaction, records: &[GameRecord]) -> io::Result<()> {
+    for record in records {
+        let moves: Vec<String> = record
+            .moves
+            .iter()
+            .map(|&(x, y)| coordinates(x, y))
+            .collect();
+        transaction
+            .execute(
+                "INSERT INTO games (date, time, x, o, first, moves, result, termination)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    record.date,
+                    record.time,
+                    record.x,
+                    record.o,
+                    record.first_player.to_string(),
+                    moves.join(" "),
+                    result_tag(record.result),
+                    termination_tag(record.result),
+                ],
+            )
+            .map_err(error)?;
+        let game = transaction.last_insert_rowid();
+        for (i, (name, value)) in record.tags.iter().enumerate() {
+            transaction
+                .execute(
+                    "INSERT INTO tags (game, position, name, value) VALUES (?1, ?2, ?3, ?4)",
+                    params![game, i as i64, name, value],
+                )
+                .map_err(error)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::test_game;
+    use crate::session::GameResult;
+
+    fn game(date: &str, x: &str, o: &str, result: GameResult) -> GameRecord {
+        let mut record = test_game(x, o, &[(1, 1), (0, 0), (2, 2)], result);
+        record.date = date.to_string();
+        record.set_tag("Difficulty", "hard");
+        record
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut archive = Archive::in_memory().unwrap();
+        assert!(archive.is_empty().unwrap());
+
+        let records = [
+            game(
+                "2026-10-13",
+                "alice",
+                "Bot",
+                GameResult::Resigned(Player::O),
+            ),
+            game("2026-10-14", "Bot", "bob", GameResult::DrawAgreed),
+        ];
+        archive.add_all(&records).unwrap();
+        assert_eq!(archive.len().unwrap(), 2);
+        assert_eq!(archive.games(&Query::default()).unwrap(), records);
+
+        archive.replace_all(&records[1..]).unwrap();
+        assert_eq!(archive.games(&Query::default()).unwrap(), records[1..]);
+    }
+
+    #[test]
+    fn queries() {
+        let mut archive = Archive::in_memory().unwrap();
+        let mut records = vec![
+            game("2026-10-13", "alice", "Bot", GameResult::Win(Player::X)),
+            game("2026-10-14", "Bot", "alice", GameResult::Win(Player::X)),
+            game("2026-10-14", "bob", "alice", GameResult::Draw),
+            game("2026-10-15", "alice", "bob", GameResult::Timeout(Player::O)),
+        ];
+        records[2].set_tag("Difficulty", "easy");
+        archive.add_all(&records).unwrap();
+
+        let dates = |query: Query| -> Vec<String> {
+            let games = archive.games(&query).unwrap();
+            games.into_iter().map(|g| g.date).collect()
+        };
+        let alice = Some("alice".to_string());
+        assert_eq!(
+            dates(Query {
+                winner: alice.clone(),
+                ..Query::default()
+            }),
+            ["2026-10-13", "2026-10-15"]
+        );
+        assert_eq!(
+            dates(Query {
+                player: Some("bob".to_string()),
+                since: Some("2026-10-15".to_string()),
+                ..Query::default()
+            }),
+            ["2026-10-15"]
+        );
+        assert_eq!(
+            dates(Query {
+                tag: Some(("Difficulty".to_string(), "easy".to_string())),
+                ..Query::default()
+            }),
+            ["2026-10-14"]
+        );
+        assert_eq!(
+            dates(Query {
+                player: alice,
+                limit: Some(2),
+                ..Query::default()
+            }),
+            ["2026-10-14", "2026-10-15"]
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::engine::{legal_moves, Bot, Difficulty, OpponentModel};
+use crate::eval::{search, Policy, Rollouts, Weights};
+use crate::record::GameRecord;
+use crate::rng::{RandomSource, Rng};
+use crate::session::{GameResult, GameSession};
+use crate::{Grid, Player};
+
+// The engines that `tictactoe match` plays against each other, by name
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Engine {
+    // Perfect play, choosing randomly between the best moves
+    Minimax,
+    // The bot at its lower difficulties
+    Easy,
+    Medium,
+    // Perfect play that sets traps for an opponent blundering 30% of the time
+    Exploit,
+    // A search two moves ahead with the hand-picked `eval::Weights`, or those
+    // given to `Contestant::with_weights`
+    Heuristic,
+    // A search two moves ahead valuing positions with 100 heuristic
+    // `eval::Rollouts`
+    Rollouts,
+    // Any legal move
+    Random,
+}
+
+impl Engine {
+    pub const ALL: [Engine; 7] = [
+        Engine::Minimax,
+        Engine::Easy,
+        Engine::Medium,
+        Engine::Exploit,
+        Engine::Heuristic,
+        Engine::Rollouts,
+        Engine::Random,
+    ];
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Minimax => "minimax",
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Exploit => "exploit",
+            Self::Heuristic => "heuristic",
+            Self::Rollouts => "rollouts",
+            Self::Random => "random",
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for Engine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_lowercase();
+        Engine::ALL
+            .into_iter()
+            .find(|e| e.to_string() == s)
+            .ok_or_else(|| format!("Unknown engine: {s}"))
+    }
+}
+
+// An engine playing a game, with the random state of its choices
+#[derive(Debug, Clone)]
+pub struct Contestant {
+    pub engine: Engine,
+    bot: Bot,
+    rng: Rng,
+    seed: u64,
+}
+
+impl Contestant {
+    pub fn new(engine: Engine, seed: u64) -> Contestant {
+        let bot = match engine {
+            Engine::Easy => Bot::new(Difficulty::Easy, seed),
+            Engine::Medium => Bot::new(Difficulty::Medium, seed),
+            Engine::Exploit => Bot::new(Difficulty::Hard, seed).exploiting(OpponentModel::new(0.3)),
+            Engine::Heuristic => Bot::new(Difficulty::Hard, seed).with_weights(Weights::default()),
+            _ => Bot::new(Difficulty::Hard, seed),
+        };
+
+        Contestant {
+            engine,
+            bot,
+            rng: Rng::new(seed),
+            seed,
+        }
+    }
+
+    // The heuristic engine values positions with `weights` rather than the
+    // hand-picked ones, such as those of `tictactoe tune`
+    pub fn with_weights(mut self, weights: Weights) -> Contestant {
+        if self.engine == Engine::Heuristic {
+            self.bot = self.bot.with_weights(weights);
+        }
+        self
+    }
+
+    pub fn choose(&mut self, grid: &Grid) -> Option<(usize, usize)> {
+        match self.engine {
+            Engine::Rollouts => {
+                let rollouts = Rollouts {
+                    policy: Policy::Heuristic,
+                    seed: self.seed,
+                    ..Rollouts::default()
+                };
+                search(grid, 2, &rollouts).map(|(m, _)| m)
+            }
+            Engine::Random => self.rng.choose(&legal_moves(grid)).copied(),
+            _ => self.bot.choose(grid),
+        }
+    }
+}
+
+// The results of a match from the side of X, who starts every game
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Results {
+    pub x_wins: u32,
+    pub draws: u32,
+    pub o_wins: u32,
+}
+
+// The usual 95% confidence
+const Z: f64 = 1.96;
+
+impl Results {
+    pub fn add(&mut self, result: GameResult) {
+        match result.winner() {
+            Some(Player::X) => self.x_wins += 1,
+            Some(_) => self.o_wins += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.x_wins + self.draws + self.o_wins
+    }
+
+    // The share of the points of X, 1 for a win and 1/2 for a draw, with the
+    // margin of its 95% confidence interval
+    pub fn score(&self) -> (f64, f64) {
+        let n = f64::from(self.games());
+        if n == 0.0 {
+            return (0.5, 0.5);
+        }
+        let score = (f64::from(self.x_wins) + f64::from(self.draws) / 2.0) / n;
+        let variance = (f64::from(self.x_wins) * (1.0 - score).powi(2)
+            + f64::from(self.draws) * (0.5 - score).powi(2)
+            + f64::from(self.o_wins) * score.powi(2))
+            / n;
+
+        (score, Z * (variance / n).sqrt())
+    }
+
+    // The 95% Wilson interval of the rate of `count` games out of all of
+    // them, which stays within 0 and 1 even when the rate is near either
+    pub fn interval(&self, count: u32) -> (f64, f64) {
+        let n = f64::from(self.games());
+        if n == 0.0 {
+            return (0.0, 1.0);
+        }
+        let p = f64::from(count) / n;
+        let center = (p + Z * Z / (2.0 * n)) / (1.0 + Z * Z / n);
+        let margin = Z / (1.0 + Z * Z / n) * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt();
+
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+}
+
+// A game between two contestants, X starting
+pub fn play_game(x: &mut Contestant, o: &mut Contestant) -> GameSession {
+    let mut session = GameSession::new();
+    while !session.is_over() {
+        let contestant = match session.grid().player_turn {
+            Player::X => &mut *x,
+            _ => &mut *o,
+        };
+        let Some((mx, my)) = contestant.choose(session.grid()) else {
+            break;
+        };
+        if session.play(mx, my).is_err() {
+            break;
+        }
+    }
+
+    session
+}
+
+// `games` games between `x` and `o`, each recorded and given to `on_game` as
+// it ends. Each game gets new seeds from `seed`, so the same seed plays the
+// same match. The heuristic engine values positions with `weights`.
+pub fn play_match(
+    (x, o): (Engine, Engine),
+    weights: Weights,
+    games: u32,
+    seed: u64,
+    mut on_game: impl FnMut(&GameRecord),
+) -> Results {
+    let mut rng = Rng::new(seed);
+    let mut results = Results::default();
+    for round in 1..=games {
+        let mut white = Contestant::new(x, rng.next_u64()).with_weights(weights);
+        let mut black = Contestant::new(o, rng.next_u64()).with_weights(weights);
+        let session = play_game(&mut white, &mut black);
+        let Some(mut record) = GameRecord::from_session(&session, &x.to_string(), &o.to_string())
+        else {
+            continue;
+        };
+        record.set_tag("Event", "engine match");
+        record.set_tag("Round", &round.to_string());
+        results.add(record.result);
+        on_game(&record);
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn engines() {
+        assert_eq!("Minimax".parse(), Ok(Engine::Minimax));
+        assert!("mcts".parse::<Engine>().is_err());
+        assert!(Engine::ALL.iter().all(|e| e.to_string().parse() == Ok(*e)));
+    }
+
+    #[test]
+    fn matches() {
+        let mut rounds = Vec::new();
+        let results = play_match(
+            (Engine::Minimax, Engine::Random),
+            Weights::default(),
+            10,
+            3,
+            |r| rounds.push(r.tag("Round").unwrap().to_string()),
+        );
+        assert_eq!(rounds.len(), 10);
+        assert_eq!(rounds[9], "10");
+        // Perfect play never loses
+        assert_eq!(results.o_wins, 0);
+        assert!(results.x_wins > 0);
+
+        let again = play_match(
+            (Engine::Minimax, Engine::Random),
+            Weights::default(),
+            10,
+            3,
+            |_| {},
+        );
+        assert_eq!(again, results);
+    }
+
+    #[test]
+    fn intervals() {
+        let results = Results {
+            x_wins: 50,
+            draws: 0,
+            o_wins: 50,
+        };
+        let (score, margin) = results.score();
+        assert_eq!(score, 0.5);
+        assert!((margin - 0.098).abs() < 0.001);
+        let (low, high) = results.interval(50);
+        assert!(low < 0.5 && high > 0.5);
+        // Never a negative rate, even with no win at all
+        assert_eq!(results.interval(0).0, 0.0);
+        assert!(results.interval(0).1 > 0.0);
+
+        // All draws leave no doubt about the score
+        let draws = Results {
+            draws: 20,
+            ..Results::default()
+        };
+        assert_eq!(draws.score(), (0.5, 0.0));
+    }
+}
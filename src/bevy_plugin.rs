@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+
+use crate::engine::{Bot, Difficulty};
+use crate::session::{GameResult, GameSession, Move};
+use crate::Player;
+
+// Tic-tac-toe for Bevy games. Each game is an entity with a `Board`, and an
+// `Engine` if the computer plays one of its sides. The game asks for moves
+// with `MoveRequest`s, and learns what happened from `MovePlayed` and
+// `GameOver`:
+//
+//     app.add_plugins(TicTacToePlugin);
+//     let board = commands.spawn((Board::default(), Engine::new(Player::O, Difficulty::Hard, 0)));
+//     requests.write(MoveRequest { board, x: 1, y: 1 });
+//
+// Drawing the board and turning clicks into requests is left to the game.
+pub struct TicTacToePlugin;
+
+impl Plugin for TicTacToePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<MoveRequest>()
+            .add_message::<MovePlayed>()
+            .add_message::<GameOver>()
+            .add_systems(Update, (play_moves, engine_turns).chain());
+    }
+}
+
+// A game, whose squares are `(x, y)` as in `Grid::set`
+#[derive(Component, Debug, Clone, Default)]
+pub struct Board {
+    pub session: GameSession,
+}
+
+// The computer plays `player` on the board of the same entity
+#[derive(Component, Debug, Clone)]
+pub struct Engine {
+    pub player: Player,
+    pub bot: Bot,
+}
+
+impl Engine {
+    pub fn new(player: Player, difficulty: Difficulty, seed: u64) -> Engine {
+        Engine {
+            player,
+            bot: Bot::new(difficulty, seed),
+        }
+    }
+}
+
+// Play a square for the player to move on `board`. Requests that cannot be
+// played are ignored.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveRequest {
+    pub board: Entity,
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MovePlayed {
+    pub board: Entity,
+    pub m: Move,
+}
+
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameOver {
+    pub board: Entity,
+    pub result: GameResult,
+}
+
+fn play_moves(
+    mut requests: MessageReader<MoveRequest>,
+    mut boards: Query<&mut Board>,
+    mut played: MessageWriter<MovePlayed>,
+    mut over: MessageWriter<GameOver>,
+) {
+    for request in requests.read() {
+        if let Ok(mut board) = boards.get_mut(request.board) {
+            let square = (request.x, request.y);
+            play(request.board, &mut board, square, &mut played, &mut over);
+        }
+    }
+}
+
+// Play the moves of the engines whose turn it is, after the requests so that
+// they answer in the same update
+fn engine_turns(
+    mut boards: Query<(Entity, &mut Board, &mut Engine)>,
+    mut played: MessageWriter<MovePlayed>,
+    mut over: MessageWriter<GameOver>,
+) {
+    for (entity, mut board, mut engine) in &mut boards {
+        if board.session.is_over() || board.session.grid().player_turn != engine.player {
+            continue;
+        }
+        if let Some(square) = engine.bot.choose(board.session.grid()) {
+            play(entity, &mut board, square, &mut played, &mut over);
+        }
+    }
+}
+
+fn play(
+    entity: Entity,
+    board: &mut Board,
+    (x, y): (usize, usize),
+    played: &mut MessageWriter<MovePlayed>,
+    over: &mut MessageWriter<GameOver>,
+) {
+    let Ok(m) = board.session.play(x, y) else {
+        return;
+    };
+    played.write(MovePlayed { board: entity, m });
+    if let Some(result) = board.session.result() {
+        over.write(GameOver {
+            board: entity,
+            result,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugin() {
+        let mut app = App::new();
+        app.add_plugins(TicTacToePlugin);
+        let board = app
+            .world_mut()
+            .spawn((
+                Board::default(),
+                Engine::new(Player::O, Difficulty::Hard, 0),
+            ))
+            .id();
+
+        // The engine answers in the same update
+        app.world_mut()
+            .write_message(MoveRequest { board, x: 1, y: 1 });
+        app.update();
+        let session = &app.world().get::<Board>(board).unwrap().session;
+        assert_eq!(session.history().len(), 2);
+        assert_eq!(session.history()[1].player, Player::O);
+
+        // An occupied square is ignored
+        app.world_mut()
+            .write_message(MoveRequest { board, x: 1, y: 1 });
+        app.update();
+        let session = &app.world().get::<Board>(board).unwrap().session;
+        assert_eq!(session.history().len(), 2);
+
+        let played = app.world().resource::<Messages<MovePlayed>>();
+        assert_eq!(played.iter_current_update_messages().count(), 0);
+    }
+}
@@ -0,0 +1,10 @@
+use tictactoe::arcade;
+use tictactoe::i18n::Lang;
+use tictactoe::rng;
+
+#[macroquad::main("tictactoe")]
+async fn main() {
+    #[cfg(feature = "tracing")]
+    tictactoe::init_tracing();
+    arcade::run(Lang::from_env(), rng::random_seed()).await;
+}
@@ -0,0 +1,14 @@
+use std::process;
+
+use tictactoe::gui;
+use tictactoe::i18n::Lang;
+use tictactoe::rng;
+
+fn main() {
+    #[cfg(feature = "tracing")]
+    tictactoe::init_tracing();
+    if let Err(e) = gui::run(Lang::from_env(), rng::random_seed()) {
+        eprintln!("{e}");
+        process::exit(1);
+    }
+}
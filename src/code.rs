@@ -0,0 +1,210 @@
+use std::fmt;
+
+use crate::notation::{keypad, parse_keypad};
+use crate::session::{GameResult, GameSession};
+use crate::{Grid, Player};
+
+// A game written as a short code, for correspondence play: the player who
+// started, the squares played so far (numbered 1-9 row by row, as on the
+// keypad), how the game ended if the moves do not say it (`R` and the player
+// who resigned, `T` and the player who ran out of time, or `D` for a draw by
+// agreement), then a checksum letter, so that a mistyped code is noticed:
+//
+//     X519-X
+//     X519RO-X
+//
+// Codes are read in any case and spaces are ignored.
+pub fn encode(session: &GameSession) -> String {
+    let mut body = session.first_player().to_string();
+    for m in session.history() {
+        body.push_str(&keypad(m.x, m.y).to_string());
+    }
+    match session.result() {
+        Some(GameResult::Resigned(player)) => body.push_str(&format!("R{player}")),
+        Some(GameResult::Timeout(player)) => body.push_str(&format!("T{player}")),
+        Some(GameResult::DrawAgreed) => body.push('D'),
+        // Wins and draws follow from the moves
+        Some(GameResult::Win(_) | GameResult::Draw) | None => (),
+    }
+
+    format!("{body}-{}", checksum(&body))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CodeError {
+    // Not made of a player, squares and a checksum
+    Malformed,
+    // The checksum does not match, the code was mistyped
+    Checksum,
+    // The nth move cannot be played
+    IllegalMove(usize),
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "not a game code, such as X519-X"),
+            Self::Checksum => write!(f, "the code has a typo, its checksum does not match"),
+            Self::IllegalMove(n) => write!(f, "move {n} of the code cannot be played"),
+        }
+    }
+}
+
+// Replay the game written as `code` by `encode`
+pub fn decode(code: &str) -> Result<GameSession, CodeError> {
+    let code: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+    let (body, check) = code.rsplit_once('-').ok_or(CodeError::Malformed)?;
+
+    let mut chars = body.chars();
+    let first = match chars.next() {
+        Some('X') => Player::X,
+        Some('O') => Player::O,
+        _ => return Err(CodeError::Malformed),
+    };
+    let rest = chars.as_str();
+    let (squares, ending) = rest.split_at(
+        rest.find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len()),
+    );
+    let squares = squares
+        .chars()
+        .map(|c| parse_keypad(&c.to_string()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or(CodeError::Malformed)?;
+
+    let mut check = check.chars();
+    if check.next() != Some(checksum(body)) || check.next().is_some() {
+        return Err(CodeError::Checksum);
+    }
+
+    let mut session = GameSession::from(Grid::starting_with(first));
+    for (i, &(x, y)) in squares.iter().enumerate() {
+        session
+            .play(x, y)
+            .map_err(|_| CodeError::IllegalMove(i + 1))?;
+    }
+    // A lone `R`, from older codes, is the resignation of the player to move
+    let to_move = session.grid().player_turn;
+    let ended = match ending {
+        "" => Ok(()),
+        "R" => session.resign(to_move),
+        "RX" => session.resign(Player::X),
+        "RO" => session.resign(Player::O),
+        "TX" => session.time_out(Player::X),
+        "TO" => session.time_out(Player::O),
+        "D" => session.agree_draw(),
+        _ => return Err(CodeError::Malformed),
+    };
+    ended.map_err(|_| CodeError::IllegalMove(squares.len() + 1))?;
+
+    Ok(session)
+}
+
+// A letter depending on every character and its place, so that swapping two
+// squares changes it too
+fn checksum(body: &str) -> char {
+    let sum = body
+        .bytes()
+        .enumerate()
+        .fold(0, |sum, (i, byte)| sum + (i + 1) * byte as usize);
+
+    (b'A' + (sum % 26) as u8) as char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut session = GameSession::from(Grid::starting_with(Player::O));
+        for (x, y) in [(1, 1), (0, 0), (2, 2)] {
+            session.play(x, y).unwrap();
+        }
+
+        let code = encode(&session);
+        assert!(code.starts_with("O519-"), "{code}");
+        let decoded = decode(&code.to_lowercase()).unwrap();
+        assert_eq!(decoded.history(), session.history());
+        assert_eq!(decoded.first_player(), Player::O);
+
+        session.resign(Player::X).unwrap();
+        let decoded = decode(&encode(&session)).unwrap();
+        assert_eq!(decoded.result(), Some(GameResult::Resigned(Player::X)));
+    }
+
+    #[test]
+    fn results() {
+        let game = |moves: &[(usize, usize)]| {
+            let mut session = GameSession::new();
+            for &(x, y) in moves {
+                session.play(x, y).unwrap();
+            }
+            session
+        };
+        let mut sessions = Vec::new();
+        // Resigned by either player, whoever is to move
+        for player in [Player::X, Player::O] {
+            let mut session = game(&[(1, 1), (0, 0)]);
+            session.resign(player).unwrap();
+            sessions.push(session);
+
+            let mut session = game(&[(1, 1)]);
+            session.time_out(player).unwrap();
+            sessions.push(session);
+        }
+        let mut session = game(&[(1, 1), (0, 0)]);
+        session.agree_draw().unwrap();
+        sessions.push(session);
+        sessions.push(game(&[(0, 0), (1, 0), (1, 1), (2, 0), (2, 2)]));
+        sessions.push(game(&[
+            (1, 1),
+            (0, 0),
+            (2, 2),
+            (2, 0),
+            (1, 0),
+            (1, 2),
+            (0, 1),
+            (2, 1),
+            (0, 2),
+        ]));
+
+        for session in sessions {
+            let code = encode(&session);
+            let decoded = decode(&code).unwrap();
+            assert!(session.result().is_some(), "{code}");
+            assert_eq!(decoded.result(), session.result(), "{code}");
+            assert_eq!(decoded.history(), session.history(), "{code}");
+        }
+
+        // Older codes resigned for the player to move
+        let code = format!("X5R-{}", checksum("X5R"));
+        let decoded = decode(&code).unwrap();
+        assert_eq!(decoded.result(), Some(GameResult::Resigned(Player::O)));
+        let code = format!("X5RZ-{}", checksum("X5RZ"));
+        assert_eq!(decode(&code).unwrap_err(), CodeError::Malformed);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(decode("X519-X").is_ok());
+        let code = encode(&GameSession::new());
+        assert_eq!(decode(&code).unwrap().history(), &[]);
+
+        assert_eq!(decode("519").unwrap_err(), CodeError::Malformed);
+        assert_eq!(decode("Z5-A").unwrap_err(), CodeError::Malformed);
+        assert_eq!(decode("X50-A").unwrap_err(), CodeError::Malformed);
+
+        // Swapping two moves is noticed
+        let body = "X519";
+        let code = format!("X591-{}", checksum(body));
+        assert_eq!(decode(&code).unwrap_err(), CodeError::Checksum);
+
+        let code = format!("X55-{}", checksum("X55"));
+        assert_eq!(decode(&code).unwrap_err(), CodeError::IllegalMove(2));
+    }
+}
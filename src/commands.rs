@@ -0,0 +1,870 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Instant;
+use tictactoe::analysis::{self, Annotations, Verdict};
+use tictactoe::arena::{self, Engine};
+use tictactoe::code;
+use tictactoe::dataset::{self, Format as DatasetFormat};
+use tictactoe::elo::Ratings;
+use tictactoe::engine::{Bot, Difficulty};
+use tictactoe::eval::Weights;
+use tictactoe::i18n::{Lang, Msg};
+use tictactoe::notation::parse_position;
+use tictactoe::notation::Command;
+use tictactoe::openings;
+use tictactoe::puzzle::{self, Grade, Puzzle};
+use tictactoe::record;
+use tictactoe::render::{Layout, Palette};
+use tictactoe::report::{Format as ReportFormat, Report};
+use tictactoe::retrograde;
+use tictactoe::rng::{RandomSource, Rng};
+use tictactoe::session::{GameResult, GameSession, PlayError};
+use tictactoe::stats::{Crosstable, Stats, Summary};
+use tictactoe::tournament::{self, Entrant, Format};
+use tictactoe::tree::SearchTree;
+use tictactoe::tuning::{self, Tuning};
+use tictactoe::*;
+
+use crate::{
+    bot_turn, end_game, human_turn, load_cache, log_games, main_loop, peek, player_turn,
+    save_cache, tournament_game, update_profile, Turn, Ui,
+};
+
+// The subcommands other than `play`
+
+pub fn stats(lang: Lang, name: &str, log_file: Option<PathBuf>) {
+    let Some(path) = log_file else {
+        eprintln!("{}", lang.text(Msg::NoDataDir));
+        process::exit(1);
+    };
+    let records = match record::load_log(&path) {
+        Ok(records) => records,
+        Err(e) => {
+            let e = format!("{}: {e}", path.display());
+            eprintln!("{}", lang.text(Msg::LoadLogFailed(&e)));
+            process::exit(1);
+        }
+    };
+
+    let stats = Stats::of(name, &records);
+    if stats.overall.games() == 0 {
+        println!("{}", lang.text(Msg::NoGames(name)));
+        return;
+    }
+
+    println!(
+        "{}",
+        lang.text(Msg::StatsTitle(name, stats.overall.games()))
+    );
+    println!("{}", lang.text(Msg::StatsOverall(stats.overall)));
+    for &(difficulty, tally) in stats.by_difficulty.iter() {
+        println!("{}", lang.text(Msg::StatsDifficulty(difficulty, tally)));
+    }
+    for (side, tally) in [(Player::X, stats.as_x), (Player::O, stats.as_o)] {
+        if tally.games() > 0 {
+            println!("{}", lang.text(Msg::StatsSide(side, tally)));
+        }
+    }
+    for (opening, tally) in stats.by_opening.iter() {
+        println!("{}", lang.text(Msg::StatsOpening(opening, *tally)));
+    }
+    println!("{}", lang.text(Msg::AverageLength(stats.average_length())));
+    if stats.analyzed > 0 {
+        println!("{}", lang.text(Msg::StatsAccuracy(&stats)));
+    }
+}
+
+// The first moves, each followed by the replies to it
+pub fn openings(lang: Lang, log_file: Option<PathBuf>) {
+    let records = match log_file.as_deref().map(record::load_log) {
+        Some(Ok(records)) => records,
+        Some(Err(e)) => {
+            let e = e.to_string();
+            eprintln!("{}", lang.text(Msg::LoadLogFailed(&e)));
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+
+    let replies = openings::explore(&records, 2);
+    for first in openings::explore(&records, 1) {
+        println!("{}", lang.text(Msg::OpeningLine(&first)));
+        for reply in replies.iter().filter(|o| o.moves[0] == first.moves[0]) {
+            println!("{}", lang.text(Msg::OpeningLine(reply)));
+        }
+    }
+}
+
+pub fn solve(lang: Lang, position: &str, layout: Layout, palette: Palette) {
+    let grid = match parse_position(position) {
+        Ok(grid) => grid,
+        Err(e) => {
+            eprintln!("{position}: {}", lang.text(Msg::Position(e)));
+            process::exit(2);
+        }
+    };
+
+    display_heatmap(&grid, layout, palette);
+    match analysis::solve(&grid) {
+        Some(solution) => println!("{}", lang.text(Msg::Solution(&solution))),
+        None => println!("{}", lang.text(Msg::FinishedPosition)),
+    }
+}
+
+// Solve the positions of a file, one per line, and print the solutions as
+// CSV. Lines that are not positions are reported and skipped.
+pub fn solve_file(lang: Lang, path: &Path) {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            let e = format!("{}: {e}", path.display());
+            eprintln!("{}", lang.text(Msg::LoadPositionsFailed(&e)));
+            process::exit(1);
+        }
+    };
+
+    let mut out = io::stdout().lock();
+    let mut failed = false;
+    let _ = writeln!(out, "{}", analysis::CSV_HEADER);
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_position(line) {
+            Ok(grid) => {
+                if writeln!(out, "{}", analysis::csv_row(&grid)).is_err() {
+                    // Nobody is reading anymore
+                    return;
+                }
+            }
+            Err(e) => {
+                let e = lang.text(Msg::Position(e));
+                eprintln!("{}:{}: {e}", path.display(), i + 1);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+// The report of the logged games, on stdout
+pub fn report(lang: Lang, format: ReportFormat, log_file: Option<PathBuf>) {
+    let Some(path) = log_file else {
+        eprintln!("{}", lang.text(Msg::NoDataDir));
+        process::exit(1);
+    };
+    let records = match record::load_log(&path) {
+        Ok(records) => records,
+        Err(e) => {
+            let e = format!("{}: {e}", path.display());
+            eprintln!("{}", lang.text(Msg::LoadLogFailed(&e)));
+            process::exit(1);
+        }
+    };
+
+    print!("{}", Report::new(&records).render(lang, format));
+}
+
+// Every position with its solution, on stdout
+pub fn dataset(lang: Lang, format: DatasetFormat) {
+    let mut out = io::stdout().lock();
+    let lines = match format {
+        DatasetFormat::Csv => {
+            let rows = dataset::examples().into_iter().map(|e| e.to_csv());
+            Some(dataset::CSV_HEADER.to_string())
+                .into_iter()
+                .chain(rows)
+                .collect()
+        }
+        DatasetFormat::Jsonl => jsonl_examples(lang),
+    };
+    for line in lines {
+        if writeln!(out, "{line}").is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+fn jsonl_examples(_: Lang) -> Vec<String> {
+    dataset::examples()
+        .iter()
+        .filter_map(|e| serde_json::to_string(e).ok())
+        .collect()
+}
+
+#[cfg(not(feature = "json"))]
+fn jsonl_examples(lang: Lang) -> Vec<String> {
+    eprintln!("{}", lang.text(Msg::JsonUnavailable));
+    process::exit(2);
+}
+
+pub fn analyze(lang: Lang, path: &Path, verbose: bool, cache: Option<&Path>) {
+    // A position rather than a log file
+    if !path.exists() {
+        if let Some(Ok(grid)) = path.to_str().map(parse_position) {
+            analyze_position(lang, &grid, verbose, cache);
+            return;
+        }
+    }
+
+    // `load_log` takes a missing file for an empty log
+    let records = fs::metadata(path).and_then(|_| record::load_log(path));
+    let records = match records {
+        Ok(records) => records,
+        Err(e) => {
+            let e = format!("{}: {e}", path.display());
+            eprintln!("{}", lang.text(Msg::LoadLogFailed(&e)));
+            process::exit(1);
+        }
+    };
+
+    for (i, record) in records.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}", lang.text(Msg::GameHeader(i + 1, record)));
+
+        let reviews = analysis::review(&record.replay());
+        let mistakes: Vec<_> = reviews
+            .iter()
+            .filter(|r| r.verdict() != Verdict::Best)
+            .collect();
+        if mistakes.is_empty() {
+            println!("{}", lang.text(Msg::NoMistakes));
+        }
+        for review in mistakes {
+            println!("{}", lang.text(Msg::MoveReview(review)));
+        }
+    }
+}
+
+// Review every move of every game of a log file or an archive, and write the
+// flaws found back in the tags of the games
+pub fn annotate(lang: Lang, path: &Path) {
+    let records = fs::metadata(path).and_then(|_| record::load_log(path));
+    let mut records = match records {
+        Ok(records) => records,
+        Err(e) => {
+            let e = format!("{}: {e}", path.display());
+            eprintln!("{}", lang.text(Msg::LoadLogFailed(&e)));
+            process::exit(1);
+        }
+    };
+
+    let (mut inaccuracies, mut mistakes) = (0, 0);
+    for record in records.iter_mut() {
+        let annotations = Annotations::of(record);
+        for (_, verdict) in annotations.flaws.iter() {
+            match verdict {
+                Verdict::Inaccuracy => inaccuracies += 1,
+                _ => mistakes += 1,
+            }
+        }
+        annotations.write(record);
+    }
+
+    if let Err(e) = record::save_log(path, &records) {
+        let e = format!("{}: {e}", path.display());
+        eprintln!("{}", lang.text(Msg::SaveAnnotationsFailed(&e)));
+        process::exit(1);
+    }
+    let games = records.len();
+    println!(
+        "{}",
+        lang.text(Msg::ArchiveAnalyzed(games, inaccuracies, mistakes))
+    );
+}
+
+// Everything about a single position: its value, that of every move, the
+// threats of both players and how the game goes on with the best play
+fn analyze_position(lang: Lang, grid: &Grid, verbose: bool, cache: Option<&Path>) {
+    display(grid);
+    let solved = match cache {
+        Some(path) => {
+            let mut cache = load_cache(lang, path);
+            let cached = cache.len();
+            let solved = analysis::solve_cached(grid, &mut cache);
+            if cache.len() > cached {
+                save_cache(lang, path, &cache);
+            }
+            solved
+        }
+        None => analysis::solve_with_info(grid),
+    };
+    let Some((solution, info)) = solved else {
+        println!("{}", lang.text(Msg::FinishedPosition));
+        return;
+    };
+    println!("{}", lang.text(Msg::Solution(&solution)));
+    if verbose {
+        println!("{}", lang.text(Msg::SearchInfo(&info)));
+    }
+
+    println!("{}", lang.text(Msg::MoveValuesTitle));
+    for (m, value) in analysis::move_values(grid) {
+        let reasons = analysis::explain(grid, m);
+        println!("{}", lang.text(Msg::MoveValue(m, value, &reasons)));
+    }
+    for player in [Player::X, Player::O] {
+        let threats = analysis::threats(grid, player);
+        let forks = analysis::forks(grid, player);
+        println!("{}", lang.text(Msg::Threats(player, &threats, &forks)));
+    }
+    let moves = analysis::principal_variation(grid);
+    println!("{}", lang.text(Msg::PrincipalVariation(&moves)));
+}
+
+pub fn bench(lang: Lang, count: u32) {
+    let start = Instant::now();
+    for _ in 0..count {
+        analysis::solve(&Grid::new());
+    }
+
+    println!("{}", lang.text(Msg::Bench(count, start.elapsed())));
+}
+
+// Check the search against the retrograde analysis on every position
+pub fn verify(lang: Lang) {
+    let start = Instant::now();
+    let (checked, disagreements) = retrograde::verify(&retrograde::Table::build());
+    for disagreement in disagreements.iter() {
+        println!("{}", lang.text(Msg::Disagreement(disagreement)));
+    }
+    if !disagreements.is_empty() {
+        process::exit(1);
+    }
+
+    println!("{}", lang.text(Msg::Verified(checked, start.elapsed())));
+}
+
+// Evolve the weights of the static evaluation, showing the best set of each
+// generation, and save the last one
+// Write the tree of an alpha-beta search from `position` as a Graphviz graph
+pub fn search_tree(lang: Lang, position: &str, depth: Option<u32>, output: Option<PathBuf>) {
+    let grid = match parse_position(position) {
+        Ok(grid) => grid,
+        Err(e) => {
+            eprintln!("{position}: {}", lang.text(Msg::Position(e)));
+            process::exit(2);
+        }
+    };
+    let output = output.unwrap_or_else(|| PathBuf::from("tree.dot"));
+    let tree = SearchTree::explore(&grid);
+
+    let name = output.display().to_string();
+    match fs::write(&output, tree.to_dot(depth)) {
+        Ok(()) => println!("{}", lang.text(Msg::TreeSaved(&name, &tree))),
+        Err(e) => {
+            let e = format!("{name}: {e}");
+            eprintln!("{}", lang.text(Msg::SaveTreeFailed(&e)));
+            process::exit(1);
+        }
+    }
+}
+
+pub fn tune(lang: Lang, generations: u32, seed: u64, output: Option<PathBuf>) {
+    let output = output.unwrap_or_else(|| PathBuf::from("weights.txt"));
+    let tuning = Tuning {
+        generations,
+        ..Tuning::default()
+    };
+    // The seed is printed so that the tuning can be run again with `--seed`
+    println!("{}", lang.text(Msg::Tuning(generations, seed)));
+    let weights = tuning::evolve(&tuning, seed, |generation| {
+        println!("{}", lang.text(Msg::Generation(generation)));
+    });
+
+    let name = output.display().to_string();
+    match weights.save(&output) {
+        Ok(()) => println!("{}", lang.text(Msg::WeightsSaved(&name))),
+        Err(e) => {
+            let e = format!("{name}: {e}");
+            eprintln!("{}", lang.text(Msg::SaveWeightsFailed(&e)));
+            process::exit(1);
+        }
+    }
+}
+
+// Play `games` games between two engines, writing each one to `log_file` or
+// else to the standard output, and sum up the results
+pub fn engine_match(
+    lang: Lang,
+    (x, o): (Engine, Engine),
+    weights: Weights,
+    games: u32,
+    seed: u64,
+    log_file: Option<PathBuf>,
+) {
+    // The seed is printed so that the match can be played again with `--seed`
+    eprintln!("{}", lang.text(Msg::EngineMatch(x, o, games, seed)));
+    // Once a game cannot be written, the others are not either
+    let mut failed = false;
+    let mut out = io::stdout().lock();
+    let results = arena::play_match((x, o), weights, games, seed, |record| {
+        if failed {
+            return;
+        }
+        match &log_file {
+            Some(path) => {
+                if let Err(e) = record::append_to_log(path, record) {
+                    let e = format!("{}: {e}", path.display());
+                    eprintln!("{}", lang.text(Msg::SaveLogFailed(&e)));
+                    failed = true;
+                }
+            }
+            None => failed = writeln!(out, "{}", record.to_notation()).is_err(),
+        }
+    });
+    drop(out);
+
+    let summary = lang.text(Msg::EngineMatchResults(x, o, &results));
+    if log_file.is_some() {
+        println!("{summary}");
+    } else {
+        // Kept apart from the games, which may be piped elsewhere
+        eprintln!("{summary}");
+    }
+}
+
+// How many of the most played openings are shown after a tournament
+const TOURNAMENT_OPENINGS: usize = 5;
+
+pub fn tournament(
+    ui: &Ui,
+    entrants: &[Entrant],
+    format: Format,
+    games: u32,
+    seed: u64,
+    k: f64,
+    log_file: Option<PathBuf>,
+) {
+    let lang = ui.lang;
+    let play = |x: &Entrant, o: &Entrant, seed| tournament_game(ui, x, o, seed);
+    // The seed is printed so that the tournament can be replayed with `--seed`
+    let records = match format {
+        Format::RoundRobin => {
+            let title = Msg::Tournament(entrants.len(), games, seed);
+            println!("{}", lang.text(title));
+            let records = tournament::round_robin(entrants, games, seed, play);
+            let names: Vec<String> = entrants.iter().map(|e| e.name.clone()).collect();
+            let table = Crosstable::new(&names, &records);
+            println!("{}", lang.text(Msg::Crosstable(&table)));
+            records
+        }
+        Format::Swiss(rounds) => {
+            let title = Msg::SwissTournament(entrants.len(), rounds, games, seed);
+            println!("{}", lang.text(title));
+            let swiss = tournament::swiss(entrants, rounds, games, seed, play);
+            println!("{}", lang.text(Msg::SwissStandings(&swiss)));
+            swiss.records
+        }
+        Format::Knockout(best_of) => {
+            let title = Msg::KnockoutTournament(entrants.len(), best_of, seed);
+            println!("{}", lang.text(title));
+            let Some(bracket) = tournament::knockout(entrants, best_of, seed, play) else {
+                return;
+            };
+            println!("{}", lang.text(Msg::Bracket(&bracket)));
+            bracket.records
+        }
+    };
+    if let Some(path) = &log_file {
+        log_games(lang, path, &records);
+    }
+
+    let summary = Summary::of(&records);
+    for (opening, score) in summary.by_opening.iter().take(TOURNAMENT_OPENINGS) {
+        println!("{}", lang.text(Msg::OpeningResults(opening, *score)));
+    }
+    println!(
+        "{}",
+        lang.text(Msg::AverageLength(summary.average_length()))
+    );
+    let ratings = Ratings::from_records(&records, k);
+    println!("{}", lang.text(Msg::Ratings(&ratings)));
+}
+
+pub fn daily(ui: &Ui, profile: Option<&str>) {
+    let lang = ui.lang;
+    let (date, _) = record::utc_now();
+    let puzzle = puzzle::daily(&date);
+
+    // The attempt counts as soon as it starts, so that quitting does not
+    // give another one
+    if let Some(name) = profile {
+        let mut first = true;
+        update_profile(lang, name, |profile| {
+            first = profile.record_daily(&date, false);
+            if !first {
+                println!("{}", lang.text(Msg::DailyAlreadyPlayed));
+                println!("{}", lang.text(Msg::DailyRecord(profile)));
+            }
+        });
+        if !first {
+            return;
+        }
+    }
+    println!("{}", lang.text(Msg::DailyPuzzle(&date, puzzle.moves)));
+
+    // The same defence for everyone
+    let mut bot = Bot::new(Difficulty::Hard, 0);
+    let Some(session) = main_loop(ui, &mut bot, GameSession::from(puzzle.grid.clone())) else {
+        return;
+    };
+    let result = end_game(ui, &session);
+
+    let moves = session
+        .history()
+        .iter()
+        .filter(|m| m.player == Player::X)
+        .count();
+    let solved = result == GameResult::Win(Player::X) && moves as i32 <= puzzle.moves;
+    if solved {
+        println!("{}", lang.text(Msg::PuzzleSolved));
+    } else {
+        println!("{}", lang.text(Msg::PuzzleFailed));
+    }
+
+    if let Some(name) = profile {
+        update_profile(lang, name, |profile| {
+            if solved {
+                profile.solve_daily(&date);
+            }
+            println!("{}", lang.text(Msg::DailyRecord(profile)));
+        });
+    }
+}
+
+// Random puzzles of `grade`, or of every grade, until the player stops
+pub fn puzzles(ui: &Ui, grade: Option<Grade>, seed: u64, profile: Option<&str>) {
+    let lang = ui.lang;
+    let puzzles: Vec<Puzzle> = puzzle::puzzle_set()
+        .into_iter()
+        .filter(|p| grade.is_none_or(|g| p.grade == g))
+        .collect();
+    let mut rng = Rng::new(seed);
+    let mut bot = Bot::new(Difficulty::Hard, seed);
+
+    while let Some(puzzle) = rng.choose(&puzzles) {
+        println!("{}", lang.text(Msg::PuzzleStart(puzzle)));
+        let Some(solved) = solve_puzzle(ui, &mut bot, puzzle) else {
+            return;
+        };
+        if solved {
+            println!("{}", lang.text(Msg::PuzzleSolved));
+        } else {
+            println!("{}", lang.text(Msg::PuzzleAnswer(puzzle)));
+        }
+
+        if let Some(name) = profile {
+            update_profile(lang, name, |profile| {
+                profile.record_puzzle(solved);
+                println!("{}", lang.text(Msg::PuzzleStreak(profile)));
+            });
+        }
+
+        println!("{}", lang.text(Msg::NextPuzzle));
+        if !lang.is_yes(&ui.input.read_line()) {
+            return;
+        }
+    }
+}
+
+// Whether the player keeps the fastest win of `puzzle` at every move against
+// the best defence, or `None` if they leave. The first move that does not is
+// explained.
+fn solve_puzzle(ui: &Ui, bot: &mut Bot, puzzle: &Puzzle) -> Option<bool> {
+    let me = puzzle.grid.player_turn;
+    let mut session = GameSession::from(puzzle.grid.clone());
+    while !session.is_over() {
+        if session.grid().player_turn != me {
+            let (x, y) = bot_turn(ui, bot, &session)?;
+            match session.play(x, y) {
+                Ok(m) => ui.announce(m),
+                Err(_) => break,
+            }
+            continue;
+        }
+
+        let before = session.grid().clone();
+        let played = session.history().len();
+        human_turn(ui, &mut session, true)?;
+        let Some(m) = session.history().get(played) else {
+            // Resigned
+            return Some(false);
+        };
+        if let Some(mistake) = puzzle::mistake(&before, (m.x, m.y)) {
+            ui.reveal_board(session.grid());
+            println!("{}", ui.lang.text(Msg::PuzzleMistake(mistake)));
+            return Some(false);
+        }
+    }
+
+    end_game(ui, &session);
+    Some(session.result() == Some(GameResult::Win(me)))
+}
+
+// Play one move of the game given by `code`, or the first move of a new game,
+// and give the code to send back to the opponent
+pub fn correspond(ui: &Ui, code: Option<&str>) {
+    let lang = ui.lang;
+    let mut session = match code.map(code::decode) {
+        Some(Ok(session)) => session,
+        Some(Err(e)) => {
+            eprintln!("{}: {}", code.unwrap_or_default(), lang.text(Msg::Code(e)));
+            process::exit(2);
+        }
+        None => GameSession::new(),
+    };
+    if session.is_over() {
+        end_game(ui, &session);
+        return;
+    }
+
+    let me = session.grid().player_turn;
+    println!("{}", lang.text(Msg::CorrespondenceTurn(me)));
+    ui.show_board(session.grid());
+    loop {
+        match player_turn(ui, &session) {
+            Turn::Move(x, y) => match session.play(x, y) {
+                Ok(m) => {
+                    ui.announce(m);
+                    break;
+                }
+                Err(PlayError::Occupied(p)) => println!("{}", lang.text(Msg::Occupied(p))),
+                Err(PlayError::GameOver) => break,
+            },
+            Turn::Command(Command::Resign) => {
+                let _ = session.resign(me);
+                break;
+            }
+            Turn::Command(Command::Peek) => peek(ui, &mut session),
+            Turn::Command(Command::Quit) | Turn::Timeout => return,
+            Turn::Chat(_) => println!("{}", lang.text(Msg::NoOneToChat)),
+            Turn::Command(Command::Draw) => println!("{}", lang.text(Msg::NoDrawOffers)),
+        }
+    }
+
+    if session.is_over() {
+        end_game(ui, &session);
+    } else {
+        ui.show_board(session.grid());
+    }
+    println!("{}", lang.text(Msg::SendCode(&code::encode(&session))));
+}
+
+#[cfg(feature = "server")]
+pub fn serve(
+    lang: Lang,
+    port: u16,
+    auth_file: Option<PathBuf>,
+    storage: Option<PathBuf>,
+    ui: bool,
+    seed: Option<u64>,
+) {
+    use tictactoe::server::{self, Auth, Settings};
+
+    let auth = auth_file.map(|path| match Auth::load(&path) {
+        Ok(auth) => auth,
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            process::exit(1);
+        }
+    });
+    println!("{}", lang.text(Msg::Serving(port)));
+    let settings = Settings {
+        auth,
+        storage,
+        ui,
+        seed,
+    };
+    if let Err(e) = server::serve(([0, 0, 0, 0], port).into(), settings) {
+        eprintln!("{}", lang.text(Msg::NetworkError(&e.to_string())));
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "server"))]
+pub fn serve(lang: Lang, _: u16, _: Option<PathBuf>, _: Option<PathBuf>, _: bool, _: Option<u64>) {
+    eprintln!("{}", lang.text(Msg::ServeUnavailable));
+    process::exit(2);
+}
+
+// Follow a game of a server, drawing the board after each move. With
+// `kibitz`, the engine solves each position on the side and says what it
+// thinks as soon as it is done, unless the next move came first.
+#[cfg(feature = "server")]
+pub fn watch(ui: &Ui, name: &str, address: &str, game: u64, kibitz: bool) {
+    use tictactoe::server::Spectator;
+    use tokio::task::JoinHandle;
+
+    let lang = ui.lang;
+    let watched = async {
+        let mut spectator = Spectator::connect(address, name, game).await?;
+        println!("{}", lang.text(Msg::Watching(game, address)));
+        let mut thinking: Option<JoinHandle<Option<analysis::Solution>>> = None;
+        loop {
+            tokio::select! {
+                watched = spectator.next() => {
+                    let Some(watched) = watched? else {
+                        return Ok(());
+                    };
+                    ui.reveal_board(&watched.grid);
+                    if let Some(result) = watched.result {
+                        println!("{}", lang.text(Msg::from(result)));
+                        return Ok(());
+                    }
+                    if kibitz {
+                        let solving = tokio::spawn(analysis::solve_async(watched.grid));
+                        // The position it was solving is gone, and aborting
+                        // the task stops its search
+                        if let Some(outdated) = thinking.replace(solving) {
+                            outdated.abort();
+                        }
+                    }
+                }
+                solved = async { thinking.as_mut().unwrap().await }, if thinking.is_some() => {
+                    thinking = None;
+                    if let Ok(Some(solution)) = solved {
+                        println!("{}", lang.text(Msg::Kibitz(&solution)));
+                    }
+                }
+            }
+        }
+    };
+
+    let result = tokio::runtime::Runtime::new()
+        .map_err(|e| e.to_string())
+        .and_then(|runtime| runtime.block_on(watched));
+    if let Err(e) = result {
+        eprintln!("{}", lang.text(Msg::NetworkError(&e)));
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "server"))]
+pub fn watch(ui: &Ui, _: &str, _: &str, _: u64, _: bool) {
+    eprintln!("{}", ui.lang.text(Msg::ServeUnavailable));
+    process::exit(2);
+}
+
+// Draw a position, or every game of a log file, each in a file numbered after
+// `output`: a GIF output animates the games, move by move, and a PNG one shows
+// their final board
+#[cfg(feature = "image")]
+pub fn image(
+    lang: Lang,
+    target: &str,
+    output: Option<PathBuf>,
+    size: Option<u32>,
+    theme: Option<&str>,
+) {
+    use std::fs::File;
+    use std::io::BufWriter;
+    use tictactoe::image::{self, Theme, DEFAULT_SIZE};
+
+    let theme = match theme.map(str::parse::<Theme>).transpose() {
+        Ok(theme) => theme.unwrap_or_default(),
+        Err(_) => {
+            let theme = theme.unwrap_or_default();
+            eprintln!("{}", lang.text(Msg::InvalidValue("--theme", theme)));
+            process::exit(2);
+        }
+    };
+    let output = output.unwrap_or_else(|| PathBuf::from("board.png"));
+    let animated = output.extension().is_some_and(|e| e == "gif");
+    let path = Path::new(target);
+
+    let boards = match parse_position(target) {
+        Ok(grid) if !path.exists() => vec![(output, vec![grid])],
+        _ => {
+            let records = fs::metadata(path).and_then(|_| record::load_log(path));
+            let records = match records {
+                Ok(records) => records,
+                Err(e) => {
+                    let e = format!("{}: {e}", path.display());
+                    eprintln!("{}", lang.text(Msg::LoadLogFailed(&e)));
+                    process::exit(1);
+                }
+            };
+            let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+            let extension = if animated { "gif" } else { "png" };
+            records
+                .iter()
+                .enumerate()
+                .map(|(i, r)| {
+                    let file = output.with_file_name(format!("{stem}-{}.{extension}", i + 1));
+                    let frames = if animated {
+                        image::frames(r)
+                    } else {
+                        vec![r.replay().grid().clone()]
+                    };
+                    (file, frames)
+                })
+                .collect()
+        }
+    };
+
+    let size = size.unwrap_or(DEFAULT_SIZE);
+    for (file, frames) in boards {
+        let written = File::create(&file).and_then(|f| {
+            let out = BufWriter::new(f);
+            if animated {
+                image::write_gif(out, &frames, size, &theme)
+            } else {
+                image::write_png(out, &frames[frames.len() - 1], size, &theme)
+            }
+        });
+        let name = file.display().to_string();
+        match written {
+            Ok(()) => println!("{}", lang.text(Msg::ImageSaved(&name))),
+            Err(e) => {
+                let e = format!("{name}: {e}");
+                eprintln!("{}", lang.text(Msg::SaveImageFailed(&e)));
+                process::exit(1);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "image"))]
+pub fn image(lang: Lang, _: &str, _: Option<PathBuf>, _: Option<u32>, _: Option<&str>) {
+    eprintln!("{}", lang.text(Msg::ImageUnavailable));
+    process::exit(2);
+}
+
+// Print the JSON Schema named `name`, or all of them as an object
+#[cfg(feature = "schema")]
+pub fn schema(lang: Lang, name: Option<&str>) {
+    use tictactoe::schema;
+
+    let json = match name {
+        Some(name) => match schema::schema(name) {
+            Some(schema) => serde_json::to_string_pretty(&schema),
+            None => {
+                eprintln!("{}", lang.text(Msg::UnknownSchema(name)));
+                process::exit(2);
+            }
+        },
+        None => {
+            let schemas: serde_json::Map<_, _> = schema::schemas()
+                .into_iter()
+                .map(|(name, schema)| (name.to_string(), schema.to_value()))
+                .collect();
+            serde_json::to_string_pretty(&schemas)
+        }
+    };
+    println!("{}", json.expect("schemas are valid JSON"));
+}
+
+#[cfg(not(feature = "schema"))]
+pub fn schema(lang: Lang, _: Option<&str>) {
+    eprintln!("{}", lang.text(Msg::SchemaUnavailable));
+    process::exit(2);
+}
@@ -0,0 +1,224 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::engine::Difficulty;
+use crate::i18n::Lang;
+use crate::notation::InputScheme;
+use crate::render::Palette;
+
+// Default settings, read from a TOML file such as:
+//
+//     difficulty = "medium"
+//     notation = "keypad"
+//     lang = "fr"
+//     accessible = false
+//     clock = 30
+//     profile = "alice"
+//     log = true
+//     log_file = "/home/alice/games.txt"
+//     k_factor = 32
+//     palette = "colorblind"
+//
+// Only flat `key = value` pairs are supported, which is all the settings need.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Config {
+    pub difficulty: Option<Difficulty>,
+    pub notation: Option<InputScheme>,
+    pub lang: Option<Lang>,
+    pub accessible: Option<bool>,
+    // Seconds per player
+    pub clock: Option<u64>,
+    pub profile: Option<String>,
+    // Append the finished games to a log file
+    pub log: Option<bool>,
+    pub log_file: Option<PathBuf>,
+    // How much a game can change an Elo rating
+    pub k_factor: Option<u32>,
+    pub palette: Option<Palette>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+enum Value {
+    String(String),
+    Integer(u64),
+    Boolean(bool),
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Config::parse(&content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(ConfigError::Io(e)),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+
+        for (i, line) in content.lines().enumerate() {
+            let error = |message: String| ConfigError::Parse {
+                line: i + 1,
+                message,
+            };
+
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `key = value`".to_string()))?;
+            let key = key.trim();
+            let value = parse_value(value.trim()).ok_or_else(|| error("invalid value".into()))?;
+
+            match (key, value) {
+                ("difficulty", Value::String(s)) => {
+                    config.difficulty = Some(s.parse().map_err(error)?)
+                }
+                ("notation", Value::String(s)) => config.notation = Some(s.parse().map_err(error)?),
+                ("lang", Value::String(s)) => config.lang = Some(s.parse().map_err(error)?),
+                ("accessible", Value::Boolean(b)) => config.accessible = Some(b),
+                ("clock", Value::Integer(n)) if n > 0 => config.clock = Some(n),
+                ("profile", Value::String(s)) => config.profile = Some(s),
+                ("log", Value::Boolean(b)) => config.log = Some(b),
+                ("log_file", Value::String(s)) => config.log_file = Some(PathBuf::from(s)),
+                ("k_factor", Value::Integer(n)) if n > 0 => {
+                    config.k_factor = Some(n.try_into().map_err(|_| error("too large".into()))?)
+                }
+                ("palette", Value::String(s)) => config.palette = Some(s.parse().map_err(error)?),
+                (
+                    "difficulty" | "notation" | "lang" | "accessible" | "clock" | "profile" | "log"
+                    | "log_file" | "k_factor" | "palette",
+                    _,
+                ) => return Err(error(format!("invalid value for `{key}`"))),
+                _ => return Err(error(format!("unknown setting `{key}`"))),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+// Remove a `#` comment, unless it is inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => (),
+        }
+    }
+
+    line
+}
+
+fn parse_value(value: &str) -> Option<Value> {
+    if let Some(s) = value.strip_prefix('"') {
+        let s = s.strip_suffix('"')?;
+        if s.contains('"') || s.contains('\\') {
+            // Escapes are not supported
+            return None;
+        }
+        return Some(Value::String(s.to_string()));
+    }
+
+    match value {
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        _ => value.replace('_', "").parse().ok().map(Value::Integer),
+    }
+}
+
+// Where the configuration is read from, following the XDG base directories
+pub fn config_file() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME").filter(|h| !h.is_empty())?).join(".config"),
+    };
+
+    Some(dir.join("tictactoe").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let config = Config::parse(
+            "# My settings\n\
+             difficulty = \"medium\"\n\
+             notation = \"keypad\" # faster\n\
+             \n\
+             lang = \"fr\"\n\
+             accessible = true\n\
+             clock = 30\n\
+             profile = \"alice # bob\"\n\
+             log = true\n\
+             log_file = \"games.txt\"\n\
+             k_factor = 24\n\
+             palette = \"high-contrast\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                difficulty: Some(Difficulty::Medium),
+                notation: Some(InputScheme::Keypad),
+                lang: Some(Lang::Fr),
+                accessible: Some(true),
+                clock: Some(30),
+                profile: Some("alice # bob".to_string()),
+                log: Some(true),
+                log_file: Some(PathBuf::from("games.txt")),
+                k_factor: Some(24),
+                palette: Some(Palette::HighContrast),
+            }
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn errors() {
+        let line = |content| match Config::parse(content) {
+            Err(ConfigError::Parse { line, .. }) => Some(line),
+            _ => None,
+        };
+
+        assert_eq!(line("difficulty = \"impossible\""), Some(1));
+        assert_eq!(line("\ncolour = \"red\""), Some(2));
+        assert_eq!(line("clock = \"30\""), Some(1));
+        assert_eq!(line("clock = 0"), Some(1));
+        assert_eq!(line("accessible"), Some(1));
+        assert_eq!(line("profile = \"alice"), Some(1));
+        assert_eq!(line("log = \"yes\""), Some(1));
+        assert_eq!(line("k_factor = 0"), Some(1));
+    }
+}
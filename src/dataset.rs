@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::analysis::{solve, Value};
+use crate::engine::legal_moves;
+use crate::notation::{parse_position, position};
+use crate::{coordinates, Grid, Player, FEATURES};
+
+// A position with its value under perfect play, for training and testing
+// models against the exact answer
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Example {
+    // As read by `tictactoe solve`, such as `X.O/.X./...`
+    pub position: String,
+    // The squares row by row: 1 for X, -1 for O and 0 when empty
+    pub cells: [i8; 9],
+    pub player: Player,
+    // 1 if the player to move wins, 0 for a draw and -1 if they lose
+    pub value: i8,
+    // The moves left until the end of the game with the best play
+    pub plies: i32,
+    // Every move that keeps the value, such as `b2`
+    pub best_moves: Vec<String>,
+}
+
+// How `tictactoe dataset` writes the examples
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    #[default]
+    Csv,
+    // A JSON object per line
+    Jsonl,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" => Ok(Self::Jsonl),
+            _ => Err(format!("Unknown dataset format: {s}")),
+        }
+    }
+}
+
+// The columns of `Example::to_csv`
+pub const CSV_HEADER: &str = "position,a1,a2,a3,b1,b2,b3,c1,c2,c3,player,value,plies,best_moves";
+
+impl Example {
+    pub fn new(grid: &Grid) -> Option<Example> {
+        let solution = solve(grid)?;
+        let (value, plies) = match solution.value {
+            Value::Win(n) => (1, n),
+            Value::Draw => (0, 9 - grid.number_of_turns),
+            Value::Loss(n) => (-1, n),
+        };
+        let mut cells = [0; 9];
+        for (cell, &square) in cells.iter_mut().zip(grid.matrix().iter().flatten()) {
+            *cell = match square {
+                Player::X => 1,
+                Player::O => -1,
+                Player::Empty => 0,
+            };
+        }
+
+        Some(Example {
+            position: position(grid),
+            cells,
+            player: solution.player,
+            value,
+            plies,
+            best_moves: solution
+                .best_moves
+                .iter()
+                .map(|&(x, y)| coordinates(x, y))
+                .collect(),
+        })
+    }
+
+    // The best moves are separated by spaces
+    pub fn to_csv(&self) -> String {
+        let cells: Vec<String> = self.cells.iter().map(|c| c.to_string()).collect();
+        format!(
+            "{},{},{},{},{},{}",
+            self.position,
+            cells.join(","),
+            self.player,
+            self.value,
+            self.plies,
+            self.best_moves.join(" ")
+        )
+    }
+}
+
+// Every position of a game started by X where the game goes on, by number of
+// moves played and then in the order they are first reached
+pub fn examples() -> Vec<Example> {
+    let mut seen = HashSet::new();
+    let mut examples = Vec::new();
+    let mut positions = vec![Grid::new()];
+
+    while !positions.is_empty() {
+        let mut next = Vec::new();
+        for grid in positions {
+            let Some(example) = Example::new(&grid) else {
+                continue;
+            };
+            for (x, y) in legal_moves(&grid) {
+                let mut g = grid.clone();
+                let _ = g.set(x, y);
+                if seen.insert(position(&g)) {
+                    next.push(g);
+                }
+            }
+            examples.push(example);
+        }
+        positions = next;
+    }
+
+    examples
+}
+
+// Positions as a single batch for a model: the `Grid::to_features` of each one
+// after the other, a tensor of shape [grids.len(), FEATURES] read row by row
+pub fn features(grids: &[Grid]) -> Vec<f32> {
+    let mut batch = Vec::with_capacity(grids.len() * FEATURES);
+    for grid in grids {
+        batch.extend(grid.to_features());
+    }
+
+    batch
+}
+
+// Examples as inputs and targets to train a model on: the features of their
+// positions, as given by `features`, and their values
+pub fn batch(examples: &[Example]) -> (Vec<f32>, Vec<f32>) {
+    let (grids, values): (Vec<Grid>, Vec<f32>) = examples
+        .iter()
+        .filter_map(|e| Some((parse_position(&e.position).ok()?, f32::from(e.value))))
+        .unzip();
+
+    (features(&grids), values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let example = Example::new(&parse_position("XX./OO./...").unwrap()).unwrap();
+        assert_eq!(example.cells, [1, 1, 0, -1, -1, 0, 0, 0, 0]);
+        assert_eq!(example.to_csv(), "XX./OO./...,1,1,0,-1,-1,0,0,0,0,X,1,1,a3");
+
+        assert_eq!(Example::new(&parse_position("XXX/OO./...").unwrap()), None);
+    }
+
+    #[test]
+    fn every_position() {
+        let examples = examples();
+        // The well-known count of the positions where a move is still to play
+        assert_eq!(examples.len(), 4520);
+        assert_eq!(examples[0].position, ".../.../...");
+        assert_eq!(examples[0].best_moves.len(), 9);
+        assert!(examples.iter().all(|e| !e.best_moves.is_empty()));
+        assert_eq!(CSV_HEADER.split(',').count(), 14);
+    }
+
+    #[test]
+    fn batches() {
+        let examples = &examples()[..3];
+        let (inputs, targets) = batch(examples);
+        assert_eq!(inputs.len(), 3 * FEATURES);
+        assert_eq!(targets, [0.0, 0.0, 0.0]);
+        // The second position, X on a1 seen by O, starts its second plane
+        assert_eq!(
+            inputs[FEATURES..2 * FEATURES]
+                .iter()
+                .position(|&f| f == 1.0),
+            Some(9)
+        );
+    }
+}
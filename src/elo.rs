@@ -0,0 +1,138 @@
+use crate::engine::Difficulty;
+use crate::record::GameRecord;
+use crate::session::GameResult;
+use crate::Player;
+
+// Elo ratings, as in chess: the difference between the ratings of two players
+// gives the score expected from their games, and each game moves the ratings
+// towards what actually happened, by at most the K-factor
+
+// The rating of a new player
+pub const INITIAL_RATING: f64 = 1500.0;
+
+// How much a single game can change a rating, unless set otherwise
+pub const DEFAULT_K: f64 = 32.0;
+
+// The score that a player rated `rating` is expected to make against one rated
+// `opponent`, from 0 to 1
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+// The rating after a game scoring `score` against `opponent`
+pub fn update(rating: f64, opponent: f64, score: f64, k: f64) -> f64 {
+    rating + k * (score - expected_score(rating, opponent))
+}
+
+// A point for a win and half a point for a draw
+pub fn score(result: GameResult, side: Player) -> f64 {
+    match result.winner() {
+        Some(p) if p == side => 1.0,
+        Some(_) => 0.0,
+        None => 0.5,
+    }
+}
+
+// The bot does not learn, so it keeps a fixed rating at each difficulty, which
+// anchors the ratings of the players who play against it
+pub fn bot_rating(difficulty: Difficulty) -> f64 {
+    match difficulty {
+        Difficulty::Easy => 1000.0,
+        Difficulty::Medium => 1400.0,
+        Difficulty::Hard => 1800.0,
+    }
+}
+
+// The ratings of the players of some games, all starting at `INITIAL_RATING`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ratings {
+    pub k: f64,
+    ratings: Vec<(String, f64)>,
+}
+
+impl Ratings {
+    pub fn new(k: f64) -> Ratings {
+        Ratings {
+            k,
+            ratings: Vec::new(),
+        }
+    }
+
+    // The ratings after `records`, played in that order
+    pub fn from_records(records: &[GameRecord], k: f64) -> Ratings {
+        let mut ratings = Ratings::new(k);
+        for record in records {
+            ratings.record(record);
+        }
+
+        ratings
+    }
+
+    pub fn get(&self, name: &str) -> f64 {
+        self.ratings
+            .iter()
+            .find(|(n, _)| n == name)
+            .map_or(INITIAL_RATING, |&(_, rating)| rating)
+    }
+
+    pub fn set(&mut self, name: &str, rating: f64) {
+        match self.ratings.iter_mut().find(|(n, _)| n == name) {
+            Some(entry) => entry.1 = rating,
+            None => self.ratings.push((name.to_string(), rating)),
+        }
+    }
+
+    // Update the ratings of both players of a game
+    pub fn record(&mut self, record: &GameRecord) {
+        let (x, o) = (self.get(&record.x), self.get(&record.o));
+        let k = self.k;
+        self.set(&record.x, update(x, o, score(record.result, Player::X), k));
+        self.set(&record.o, update(o, x, score(record.result, Player::O), k));
+    }
+
+    // The players from the highest rating to the lowest
+    pub fn ranking(&self) -> Vec<(&str, f64)> {
+        let mut ranking: Vec<_> = self.ratings.iter().map(|(n, r)| (n.as_str(), *r)).collect();
+        ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranking
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::test_game;
+
+    #[test]
+    fn update_ratings() {
+        assert_eq!(expected_score(1500.0, 1500.0), 0.5);
+        assert!((expected_score(1900.0, 1500.0) - 0.909).abs() < 0.001);
+
+        assert_eq!(update(1500.0, 1500.0, 1.0, 32.0), 1516.0);
+        assert_eq!(update(1500.0, 1500.0, 0.5, 32.0), 1500.0);
+        assert_eq!(update(1500.0, 1500.0, 0.0, 10.0), 1495.0);
+        // Drawing a stronger player gains points
+        assert!(update(1500.0, bot_rating(Difficulty::Hard), 0.5, 32.0) > 1500.0);
+    }
+
+    #[test]
+    fn ratings() {
+        let game = |x: &str, o: &str, result| test_game(x, o, &[], result);
+        let ratings = Ratings::from_records(
+            &[
+                game("hard", "easy", GameResult::Win(Player::X)),
+                game("easy", "hard", GameResult::Resigned(Player::X)),
+                game("hard", "medium", GameResult::Draw),
+            ],
+            DEFAULT_K,
+        );
+
+        assert_eq!(ratings.get("nobody"), INITIAL_RATING);
+        let ranking = ratings.ranking();
+        let names: Vec<&str> = ranking.iter().map(|&(n, _)| n).collect();
+        assert_eq!(names, ["hard", "medium", "easy"]);
+        // Points are only exchanged
+        let total: f64 = ranking.iter().map(|&(_, r)| r).sum();
+        assert!((total - 3.0 * INITIAL_RATING).abs() < 1e-9);
+    }
+}
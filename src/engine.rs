@@ -0,0 +1,1028 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::eval::{self, Weights};
+use crate::game::{negamax_counted, Game};
+use crate::rng::{RandomSource, Rng};
+use crate::{Grid, Player, NODES};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    // Chance of playing a random move instead of one of the best
+    fn blunder_rate(self) -> f64 {
+        match self {
+            Self::Easy => 0.7,
+            Self::Medium => 0.3,
+            Self::Hard => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            _ => Err(format!("Unknown difficulty: {s}")),
+        }
+    }
+}
+
+// The computer opponent. Even at the hardest difficulty it picks randomly
+// between equally good moves, so it needs a seed to be reproducible.
+#[derive(Debug, Clone)]
+pub struct Bot<R = Rng> {
+    pub difficulty: Difficulty,
+    // Set with `exploiting`, to play for the mistakes of the opponent
+    opponent: Option<OpponentModel>,
+    // Set with `with_cache`, to remember the positions searched
+    cache: Option<Cache>,
+    // Set with `with_weights`, to look two moves ahead instead of solving
+    weights: Option<Weights>,
+    rng: R,
+}
+
+impl Bot {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Bot {
+        Bot::with_source(difficulty, Rng::new(seed))
+    }
+}
+
+impl<R: RandomSource> Bot<R> {
+    // A bot taking its random numbers from `source` rather than from a seed
+    pub fn with_source(difficulty: Difficulty, source: R) -> Bot<R> {
+        Bot {
+            difficulty,
+            opponent: None,
+            cache: None,
+            weights: None,
+            rng: source,
+        }
+    }
+
+    // Play the moves with the best expected value against an opponent who
+    // plays as `model` says, rather than assume that they never blunder.
+    // The bot still plays random moves as often as its difficulty says.
+    pub fn exploiting(mut self, model: OpponentModel) -> Bot<R> {
+        self.opponent = Some(model);
+        self
+    }
+
+    // Look up the positions of `cache` rather than search them again, and add
+    // those searched to it. A bot exploiting its opponent does not use it.
+    pub fn with_cache(mut self, cache: Cache) -> Bot<R> {
+        self.cache = Some(cache);
+        self
+    }
+
+    // Search two moves ahead, valuing the positions there with `weights`,
+    // rather than solve the game. This takes over from `exploiting`.
+    pub fn with_weights(mut self, weights: Weights) -> Bot<R> {
+        self.weights = Some(weights);
+        self
+    }
+
+    // The cache given with `with_cache` and filled since, to be saved
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    pub fn choose(&mut self, grid: &Grid) -> Option<(usize, usize)> {
+        let (m, _) = self.choose_unless(grid, &AtomicBool::new(false))?;
+        Some(m)
+    }
+
+    // The move chosen by `choose`, with what the search went through to
+    // choose it and the best play from there
+    pub fn choose_with_info(&mut self, grid: &Grid) -> Option<((usize, usize), SearchInfo)> {
+        self.choose_with_info_unless(grid, &AtomicBool::new(false))
+    }
+
+    fn choose_with_info_unless(
+        &mut self,
+        grid: &Grid,
+        cancelled: &AtomicBool,
+    ) -> Option<((usize, usize), SearchInfo)> {
+        let (m, mut info) = self.choose_unless(grid, cancelled)?;
+        info.pv = principal_variation(grid, m);
+        Some((m, info))
+    }
+
+    // Stops early, without choosing anything, once `cancelled` is set. The
+    // principal variation is left out, as it takes another search.
+    fn choose_unless(
+        &mut self,
+        grid: &Grid,
+        cancelled: &AtomicBool,
+    ) -> Option<((usize, usize), SearchInfo)> {
+        let start = Instant::now();
+        let mut info = SearchInfo::default();
+        // A won game has no move to choose, even with empty squares left
+        if grid.value().is_some() {
+            return None;
+        }
+        let moves = legal_moves(grid);
+
+        if self.rng.chance(self.difficulty.blunder_rate()) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(difficulty = %self.difficulty, "random move");
+            let m = *self.rng.choose(&moves)?;
+            return Some((m, info));
+        }
+
+        if let Some(weights) = &self.weights {
+            let (m, _) = eval::search(grid, 2, weights)?;
+            info.elapsed = start.elapsed();
+            return Some((m, info));
+        }
+
+        let moves = match self.opponent {
+            Some(model) => model.best_moves(grid, cancelled, &mut info)?,
+            None => best_moves(grid, cancelled, self.cache.as_mut(), &mut info)?,
+        };
+        let m = *self.rng.choose(&moves)?;
+        info.elapsed = start.elapsed();
+        Some((m, info))
+    }
+}
+
+// What a search went through to choose its move, to see how the engine
+// behaves: `Bot::choose_with_info` gives it, and `tictactoe --verbose` shows
+// it after each move of the bot
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct SearchInfo {
+    // The positions searched, none for a random move
+    pub nodes: u64,
+    // How many moves ahead the search looked
+    pub depth: u32,
+    pub elapsed: Duration,
+    // The positions whose value was remembered rather than searched again,
+    // for the searches that remember them
+    pub cache_hits: u64,
+    // The move chosen, then the best play of both sides until the end
+    pub pv: Vec<(usize, usize)>,
+}
+
+// How many moves the search goes through to reach the end of the game
+fn moves_left(grid: &Grid) -> u32 {
+    u32::try_from(9 - grid.number_of_turns).unwrap_or(0)
+}
+
+fn principal_variation(grid: &Grid, first: (usize, usize)) -> Vec<(usize, usize)> {
+    let mut grid = grid.clone();
+    let mut pv = vec![first];
+    let _ = grid.set(first.0, first.1);
+    while let Some((x, y)) = grid.best_play() {
+        pv.push((x, y));
+        let _ = grid.set(x, y);
+    }
+
+    pv
+}
+
+// The values of the positions searched so far, as `game::negamax` gives them,
+// for later searches to look up rather than search again. `save` and `load`
+// keep them between runs in a small binary file: "TTTC" and a version byte,
+// then each position as its key on four bytes, little-endian, and its value
+// on one.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Cache {
+    scores: HashMap<u32, i32>,
+}
+
+const CACHE_MAGIC: &[u8; 4] = b"TTTC";
+const CACHE_VERSION: u8 = 1;
+
+impl Cache {
+    pub fn new() -> Cache {
+        Cache::default()
+    }
+
+    // The positions remembered
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    // The value of `grid` for the player to move, adding the positions it
+    // goes through and those found in the cache to `info`
+    pub fn score(&mut self, grid: &Grid, info: &mut SearchInfo) -> i32 {
+        let mut nodes = 0;
+        let score = self.search(grid, &mut nodes, &mut info.cache_hits);
+        NODES.fetch_add(nodes, Ordering::Relaxed);
+        info.nodes += nodes;
+
+        score
+    }
+
+    fn search(&mut self, grid: &Grid, nodes: &mut u64, hits: &mut u64) -> i32 {
+        *nodes += 1;
+        if let Some(value) = grid.value() {
+            return value;
+        }
+        if let Some(&score) = self.scores.get(&key(grid)) {
+            *hits += 1;
+            return score;
+        }
+
+        let mut best = i32::MIN;
+        for m in legal_moves(grid) {
+            let mut next = grid.clone();
+            next.play(m);
+            best = best.max(-self.search(&next, nodes, hits));
+        }
+        self.scores.insert(key(grid), best);
+        best
+    }
+
+    // The positions sorted by key, so that the same cache gives the same bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut scores: Vec<(u32, i32)> = self.scores.iter().map(|(&k, &s)| (k, s)).collect();
+        scores.sort_unstable();
+        let mut bytes = CACHE_MAGIC.to_vec();
+        bytes.push(CACHE_VERSION);
+        for (key, score) in scores {
+            bytes.extend(key.to_le_bytes());
+            // Values are at most 10 either way
+            bytes.push(score as i8 as u8);
+        }
+
+        bytes
+    }
+
+    // `None` if the bytes are not those of a cache, or one of another version
+    pub fn from_bytes(bytes: &[u8]) -> Option<Cache> {
+        let entries = bytes
+            .strip_prefix(CACHE_MAGIC)?
+            .strip_prefix(&[CACHE_VERSION])?;
+        if entries.len() % 5 != 0 {
+            return None;
+        }
+
+        let mut scores = HashMap::new();
+        for entry in entries.chunks_exact(5) {
+            let key = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let score = i32::from(entry[4] as i8);
+            // 3^9 boards, each with either player to move
+            if key >= 39366 || score.abs() > 10 {
+                return None;
+            }
+            scores.insert(key, score);
+        }
+
+        Some(Cache { scores })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Cache> {
+        let bytes = fs::read(path)?;
+        Cache::from_bytes(&bytes)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a cache of tictactoe"))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+}
+
+// How a bot `exploiting` it expects its opponent to play: one of their best
+// moves, except for a share `epsilon` of random ones, as a person who mostly
+// sees the threats but not always. Against such a player, a draw from the
+// opening is worth less than a move that keeps the draw and sets a trap.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OpponentModel {
+    pub epsilon: f64,
+}
+
+impl OpponentModel {
+    // From 0, an opponent who plays perfectly, to 1, one who only plays at
+    // random
+    pub fn new(epsilon: f64) -> OpponentModel {
+        OpponentModel {
+            epsilon: epsilon.clamp(0.0, 1.0),
+        }
+    }
+
+    // The chance for the player to move of winning, less that of losing, when
+    // they play for it and their opponent plays as the model says: 1 for a
+    // sure win, 0 for a draw, or where the chances even out
+    pub fn expected_value(self, grid: &Grid) -> f64 {
+        Expectimax::new(self).mine(grid)
+    }
+
+    // The chance of X winning, less that of O, when both players play as the
+    // model says: from -1 for a sure win of O to 1 for a sure win of X
+    pub fn balance(self, grid: &Grid) -> f64 {
+        self.balance_unless(grid, &AtomicBool::new(false))
+            .unwrap_or_default()
+    }
+
+    // `balance`, or `None` once `cancelled` is set. The moves are valued one
+    // at a time to look at the flag between them, then remembered for the
+    // position itself.
+    pub(crate) fn balance_unless(self, grid: &Grid, cancelled: &AtomicBool) -> Option<f64> {
+        let mut search = Expectimax::new(self);
+        if grid.value().is_none() {
+            for m in grid.moves() {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let mut next = grid.clone();
+                next.play(m);
+                search.both(&next);
+            }
+        }
+        let value = search.both(grid);
+        Some(if grid.player_turn == Player::O {
+            -value
+        } else {
+            value
+        })
+    }
+
+    // The moves with the best expected value, or `None` if cancelled
+    fn best_moves(
+        self,
+        grid: &Grid,
+        cancelled: &AtomicBool,
+        info: &mut SearchInfo,
+    ) -> Option<Vec<(usize, usize)>> {
+        let mut search = Expectimax::new(self);
+        let mut values = Vec::new();
+        for m in legal_moves(grid) {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let mut next = grid.clone();
+            next.play(m);
+            values.push((m, search.theirs(&next)));
+        }
+        NODES.fetch_add(search.nodes, Ordering::Relaxed);
+        (info.nodes, info.cache_hits) = (search.nodes, search.hits);
+        info.depth = moves_left(grid);
+        let best = values.iter().map(|&(_, v)| v).fold(f64::MIN, f64::max);
+
+        // Rounding apart, equal values are ties
+        Some(
+            values
+                .into_iter()
+                .filter(|&(_, v)| best - v < 1e-9)
+                .map(|(m, _)| m)
+                .collect(),
+        )
+    }
+}
+
+// The search behind `OpponentModel`, which remembers the positions it has
+// valued: the same ones come up again and again in different orders
+struct Expectimax {
+    model: OpponentModel,
+    // The values of the player choosing their move, when they or their
+    // opponent are to move, by `key`
+    mine: HashMap<u32, f64>,
+    theirs: HashMap<u32, f64>,
+    // The values of the player to move when both play as the model says
+    both: HashMap<u32, f64>,
+    // Their `Grid::score`, to tell which moves the opponent sees as best
+    scores: HashMap<u32, i32>,
+    // The positions looked at, and those found among the remembered ones
+    nodes: u64,
+    hits: u64,
+}
+
+impl Expectimax {
+    fn new(model: OpponentModel) -> Expectimax {
+        Expectimax {
+            model,
+            mine: HashMap::new(),
+            theirs: HashMap::new(),
+            both: HashMap::new(),
+            scores: HashMap::new(),
+            nodes: 0,
+            hits: 0,
+        }
+    }
+
+    // The player choosing is to move, and plays the move worth the most
+    fn mine(&mut self, grid: &Grid) -> f64 {
+        self.nodes += 1;
+        if let Some(value) = grid.value() {
+            return f64::from(value.signum());
+        }
+        if let Some(&value) = self.mine.get(&key(grid)) {
+            self.hits += 1;
+            return value;
+        }
+
+        let mut best = f64::MIN;
+        for m in grid.moves() {
+            let mut next = grid.clone();
+            next.play(m);
+            best = best.max(self.theirs(&next));
+        }
+        self.mine.insert(key(grid), best);
+        best
+    }
+
+    // The opponent is to move: one of their best moves, or any at random
+    fn theirs(&mut self, grid: &Grid) -> f64 {
+        self.nodes += 1;
+        if let Some(value) = grid.value() {
+            return -f64::from(value.signum());
+        }
+        if let Some(&value) = self.theirs.get(&key(grid)) {
+            self.hits += 1;
+            return value;
+        }
+
+        let mut children = Vec::new();
+        for m in grid.moves() {
+            let mut next = grid.clone();
+            next.play(m);
+            children.push((-self.score(&next), self.mine(&next)));
+        }
+        let value = self.expected(&children);
+        self.theirs.insert(key(grid), value);
+        value
+    }
+
+    // Whoever is to move plays as the model says
+    fn both(&mut self, grid: &Grid) -> f64 {
+        self.nodes += 1;
+        if let Some(value) = grid.value() {
+            return f64::from(value.signum());
+        }
+        if let Some(&value) = self.both.get(&key(grid)) {
+            self.hits += 1;
+            return value;
+        }
+
+        let mut children = Vec::new();
+        for m in grid.moves() {
+            let mut next = grid.clone();
+            next.play(m);
+            children.push((-self.score(&next), -self.both(&next)));
+        }
+        let value = self.expected(&children);
+        self.both.insert(key(grid), value);
+        value
+    }
+
+    // The value of a move chosen as the model says, from the score and the
+    // value of each: one of the best by score, or any at random
+    fn expected(&self, children: &[(i32, f64)]) -> f64 {
+        let best = children.iter().map(|&(score, _)| score).max().unwrap_or(0);
+        let mean = |values: Vec<f64>| values.iter().sum::<f64>() / values.len() as f64;
+        let best_value = mean(
+            children
+                .iter()
+                .filter(|&&(score, _)| score == best)
+                .map(|&(_, v)| v)
+                .collect(),
+        );
+        let any_value = mean(children.iter().map(|&(_, v)| v).collect());
+
+        let epsilon = self.model.epsilon;
+        (1.0 - epsilon) * best_value + epsilon * any_value
+    }
+
+    // `Grid::score`, remembered
+    fn score(&mut self, grid: &Grid) -> i32 {
+        self.nodes += 1;
+        if let Some(value) = grid.value() {
+            return value;
+        }
+        if let Some(&score) = self.scores.get(&key(grid)) {
+            self.hits += 1;
+            return score;
+        }
+
+        let mut best = i32::MIN;
+        for m in grid.moves() {
+            let mut next = grid.clone();
+            next.play(m);
+            best = best.max(-self.score(&next));
+        }
+        self.scores.insert(key(grid), best);
+        best
+    }
+}
+
+// The squares in base 3, and who is to move
+fn key(grid: &Grid) -> u32 {
+    let squares = grid.matrix().iter().flatten().fold(0, |key, &square| {
+        key * 3
+            + match square {
+                Player::Empty => 0,
+                Player::X => 1,
+                Player::O => 2,
+            }
+    });
+
+    squares * 2 + u32::from(grid.player_turn == Player::X)
+}
+
+impl<R: RandomSource + Clone + Send + 'static> Bot<R> {
+    // Choose a move on another thread, so that the caller can show that the
+    // bot is thinking meanwhile
+    pub fn think(&self, grid: &Grid) -> Search<Thought<R>> {
+        let mut bot = self.clone();
+        let grid = grid.clone();
+        Search::spawn(move |cancelled| {
+            let (choice, info) = match bot.choose_with_info_unless(&grid, cancelled) {
+                Some((m, info)) => (Some(m), info),
+                None if cancelled.load(Ordering::Relaxed) => return None,
+                None => (None, SearchInfo::default()),
+            };
+            Some(Thought { bot, choice, info })
+        })
+    }
+
+    // `think` for a Tokio task, such as those of the server: the search runs
+    // on the blocking threads and stops when the future is dropped. `None` if
+    // the game is over.
+    #[cfg(feature = "async")]
+    pub async fn think_async(&self, grid: &Grid) -> Option<Thought<R>> {
+        let mut bot = self.clone();
+        let grid = grid.clone();
+        spawn_search(move |cancelled| {
+            let (choice, info) = bot.choose_with_info_unless(&grid, cancelled)?;
+            Some(Thought {
+                bot,
+                choice: Some(choice),
+                info,
+            })
+        })
+        .await
+    }
+}
+
+// The outcome of a search: the move chosen, and the bot whose random state has
+// moved on while choosing it
+#[derive(Debug)]
+pub struct Thought<R = Rng> {
+    pub bot: Bot<R>,
+    pub choice: Option<(usize, usize)>,
+    pub info: SearchInfo,
+}
+
+// Why `Search::wait` has no outcome to give
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WaitError {
+    // The search is still going
+    Timeout,
+    // The search has panicked, and will never end
+    Died,
+}
+
+// A search running on another thread, which stops when dropped
+#[derive(Debug)]
+pub struct Search<T = Thought> {
+    result: mpsc::Receiver<T>,
+    cancelled: Arc<AtomicBool>,
+    started: Instant,
+}
+
+impl<T: Send + 'static> Search<T> {
+    // Run `search` on another thread. The flag it is given is raised when the
+    // `Search` is cancelled or dropped, for it to give up by returning `None`.
+    pub fn spawn(search: impl FnOnce(&AtomicBool) -> Option<T> + Send + 'static) -> Search<T> {
+        let (sender, result) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let flag = Arc::clone(&cancelled);
+        thread::spawn(move || {
+            if let Some(outcome) = search(&flag) {
+                let _ = sender.send(outcome);
+            }
+        });
+
+        Search {
+            result,
+            cancelled,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl<T> Search<T> {
+    // The outcome of the search, if it ends within `timeout`
+    pub fn wait(&self, timeout: Duration) -> Result<T, WaitError> {
+        self.result.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => WaitError::Timeout,
+            RecvTimeoutError::Disconnected => WaitError::Died,
+        })
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    // Stop the search, whose outcome is lost
+    pub fn cancel(self) {
+        drop(self);
+    }
+}
+
+impl<T> Drop for Search<T> {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn legal_moves(grid: &Grid) -> Vec<(usize, usize)> {
+    let mut moves = Vec::new();
+    for (y, row) in grid.matrix().iter().enumerate() {
+        for (x, &square) in row.iter().enumerate() {
+            if square == Player::Empty {
+                moves.push((x, y));
+            }
+        }
+    }
+
+    moves
+}
+
+// Stops a search when dropped, whether the search is over or not
+#[cfg(feature = "async")]
+struct Cancel(Arc<AtomicBool>);
+
+#[cfg(feature = "async")]
+impl Drop for Cancel {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+// Run `search` on the blocking threads of Tokio so that the executor goes on
+// meanwhile. The flag it is given is raised when the future is dropped, for
+// it to give up. `None` if it gives up or panics.
+#[cfg(feature = "async")]
+pub async fn spawn_search<T: Send + 'static>(
+    search: impl FnOnce(&AtomicBool) -> Option<T> + Send + 'static,
+) -> Option<T> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _cancel = Cancel(Arc::clone(&cancelled));
+
+    tokio::task::spawn_blocking(move || search(&cancelled))
+        .await
+        .ok()?
+}
+
+// The best move of the player to move, as `Grid::best_play` gives it, searched
+// with `spawn_search`. `None` once the game is over.
+#[cfg(feature = "async")]
+pub async fn best_play_async(grid: Grid) -> Option<(usize, usize)> {
+    if grid.has_winner() {
+        return None;
+    }
+    spawn_search(move |cancelled| {
+        let mut info = SearchInfo::default();
+        best_moves(&grid, cancelled, None, &mut info)?
+            .first()
+            .copied()
+    })
+    .await
+}
+
+// All the moves tied for the best score, or `None` if cancelled. The scores
+// are looked up in `cache`, if any, and added to it.
+fn best_moves(
+    grid: &Grid,
+    cancelled: &AtomicBool,
+    mut cache: Option<&mut Cache>,
+    info: &mut SearchInfo,
+) -> Option<Vec<(usize, usize)>> {
+    // The depth is the number of moves left, as every search goes to the end
+    info.depth = moves_left(grid);
+    #[cfg(feature = "tracing")]
+    let (_span, start) = (
+        tracing::debug_span!("search", depth = info.depth).entered(),
+        Instant::now(),
+    );
+
+    let mut scores = Vec::new();
+    for m in legal_moves(grid) {
+        if cancelled.load(Ordering::Relaxed) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(nodes = info.nodes, "cancelled");
+            return None;
+        }
+        let mut next = grid.clone();
+        next.play(m);
+        if let Some(cache) = cache.as_deref_mut() {
+            scores.push((m, -cache.score(&next, info)));
+            continue;
+        }
+        let mut nodes = 0;
+        scores.push((m, -negamax_counted(&next, &mut nodes)));
+        NODES.fetch_add(nodes, Ordering::Relaxed);
+        info.nodes += nodes;
+    }
+    let best = scores.iter().map(|&(_, score)| score).max().unwrap_or(0);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        nodes = info.nodes,
+        score = best,
+        elapsed = ?start.elapsed(),
+        "searched"
+    );
+
+    Some(
+        scores
+            .into_iter()
+            .filter(|&(_, score)| score == best)
+            .map(|(m, _)| m)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hard_wins_immediately() {
+        let grid = Grid::from([
+            [Player::Empty, Player::O, Player::Empty],
+            [Player::X, Player::O, Player::X],
+            [Player::Empty, Player::Empty, Player::X],
+        ]);
+
+        for seed in 0..20 {
+            assert_eq!(Bot::new(Difficulty::Hard, seed).choose(&grid), Some((1, 2)));
+        }
+    }
+
+    // Always the lowest number: a random move, the first of them
+    struct Zeros;
+
+    impl RandomSource for Zeros {
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn other_sources() {
+        let mut bot = Bot::with_source(Difficulty::Easy, Zeros);
+        assert_eq!(bot.choose(&Grid::new()), Some((0, 0)));
+    }
+
+    #[test]
+    fn same_seed_same_moves() {
+        let grid = Grid::new();
+        let mut a = Bot::new(Difficulty::Easy, 1234);
+        let mut b = Bot::new(Difficulty::Easy, 1234);
+
+        for _ in 0..10 {
+            assert_eq!(a.choose(&grid), b.choose(&grid));
+        }
+    }
+
+    #[test]
+    fn think_in_background() {
+        let grid = Grid::new();
+        let mut bot = Bot::new(Difficulty::Medium, 42);
+        let search = bot.think(&grid);
+        let expected = bot.choose(&grid);
+
+        let mut thought = search.wait(Duration::from_secs(60)).unwrap();
+        assert_eq!(thought.choice, expected);
+        assert_eq!(thought.bot.rng.next_u64(), bot.rng.next_u64());
+
+        // Cancelling does not wait for the search
+        Bot::new(Difficulty::Hard, 0).think(&grid).cancel();
+
+        // A search that panics is told apart from one still going
+        let died = Search::spawn(|_| -> Option<()> { panic!("lost") });
+        assert_eq!(
+            died.wait(Duration::from_secs(60)).unwrap_err(),
+            WaitError::Died
+        );
+
+        // Dropping a search stops it
+        let stopped = Arc::new(AtomicBool::new(false));
+        let seen = Arc::clone(&stopped);
+        drop(Search::spawn(move |cancelled| {
+            while !cancelled.load(Ordering::Relaxed) {
+                thread::yield_now();
+            }
+            seen.store(true, Ordering::Relaxed);
+            None::<()>
+        }));
+        let start = Instant::now();
+        while !stopped.load(Ordering::Relaxed) {
+            assert!(start.elapsed() < Duration::from_secs(60));
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn awaited() {
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::Empty],
+            [Player::O, Player::O, Player::Empty],
+            [Player::Empty; 3],
+        ]);
+        assert_eq!(best_play_async(grid.clone()).await, grid.best_play());
+        assert_eq!(best_play_async(Grid::new()).await, Grid::new().best_play());
+
+        // Given up as soon as the task is aborted
+        let search = tokio::spawn(best_play_async(Grid::new()));
+        search.abort();
+        assert!(search.await.unwrap_err().is_cancelled());
+
+        // The bot thinks the same, awaited or not
+        let mut bot = Bot::new(Difficulty::Medium, 42);
+        let thought = bot.think_async(&Grid::new()).await.unwrap();
+        assert_eq!(thought.choice, bot.choose(&Grid::new()));
+        let full = Grid::try_from(&["XOX", "XOO", "OXX"]).unwrap();
+        assert!(bot.think_async(&full).await.is_none());
+    }
+
+    #[test]
+    fn sets_traps() {
+        // Against a perfect opponent, nothing beats a draw
+        assert_eq!(OpponentModel::new(0.0).expected_value(&Grid::new()), 0.0);
+        let model = OpponentModel::new(0.3);
+        assert!(model.expected_value(&Grid::new()) > 0.0);
+
+        // Every opening draws, but the center gives O the most ways to go
+        // wrong, then the corners
+        let after = |m| {
+            let mut grid = Grid::new();
+            grid.play(m);
+            Expectimax::new(model).theirs(&grid)
+        };
+        assert!(after((1, 1)) > after((0, 0)));
+        assert!(after((0, 0)) > after((1, 0)));
+        let mut bot = Bot::new(Difficulty::Hard, 7).exploiting(model);
+        assert_eq!(bot.choose(&Grid::new()), Some((1, 1)));
+
+        // A win on the board is still taken at once
+        let grid = Grid::try_from(&[".O.", "XOX", "..X"]).unwrap();
+        assert_eq!(bot.choose(&grid), Some((1, 2)));
+        assert_eq!(model.expected_value(&grid), 1.0);
+
+        // Between two such players, the one who starts has the edge, and the
+        // balance is the same whoever is to move
+        assert_eq!(OpponentModel::new(0.0).balance(&Grid::new()), 0.0);
+        assert!(model.balance(&Grid::new()) > 0.0);
+        let o_to_win = Grid::try_from(&["XX.", "OO.", "X.."]).unwrap();
+        assert!(model.balance(&o_to_win) < -0.5);
+        let o_to_lose = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        assert!(model.balance(&o_to_lose) > 0.5);
+    }
+
+    #[test]
+    fn search_info() {
+        let grid = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        let (m, info) = Bot::new(Difficulty::Hard, 0)
+            .choose_with_info(&grid)
+            .unwrap();
+        assert_eq!(m, (2, 0));
+        assert_eq!(info.pv, [(2, 0)]);
+        assert_eq!(info.depth, 5);
+        // One position per move that ends the game, more for the others
+        assert!(info.nodes > 5);
+        assert_eq!(info.cache_hits, 0);
+
+        // The opening leads to a draw, nine moves in all
+        let mut bot = Bot::new(Difficulty::Hard, 0).exploiting(OpponentModel::new(0.3));
+        let (_, info) = bot.choose_with_info(&Grid::new()).unwrap();
+        assert_eq!(info.pv.len(), 9);
+        assert!(info.cache_hits > 0);
+
+        // A random move has nothing to show but itself and what follows
+        let mut bot = Bot::with_source(Difficulty::Easy, Zeros);
+        let (m, info) = bot.choose_with_info(&Grid::new()).unwrap();
+        assert_eq!((m, info.nodes), ((0, 0), 0));
+        assert_eq!(info.pv[0], (0, 0));
+    }
+
+    #[test]
+    fn cache() {
+        let mut bot = Bot::new(Difficulty::Hard, 0).with_cache(Cache::new());
+        let (m, first) = bot.choose_with_info(&Grid::new()).unwrap();
+        let cache = bot.cache().unwrap().clone();
+        assert!(!cache.is_empty());
+        // The same search again only looks up the moves from the empty board
+        let (_, again) = bot.choose_with_info(&Grid::new()).unwrap();
+        assert_eq!(again.cache_hits, 9);
+        assert!(again.nodes < first.nodes);
+        // The values are those of the search without a cache
+        let mut plain = Bot::new(Difficulty::Hard, 0);
+        assert_eq!(plain.choose(&Grid::new()), Some(m));
+
+        let path = std::env::temp_dir().join(format!("tictactoe-cache-{}", std::process::id()));
+        cache.save(&path).unwrap();
+        let loaded = Cache::load(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.unwrap(), cache);
+        assert_eq!(cache.to_bytes().len(), 5 + 5 * cache.len());
+        assert_eq!(Cache::from_bytes(b"TTTC\x02"), None);
+        assert_eq!(Cache::from_bytes(b"TTTC\x01\x00"), None);
+        assert_eq!(Cache::from_bytes(b"TTTC\x01"), Some(Cache::new()));
+    }
+
+    #[test]
+    fn weights() {
+        let weights = Weights::from_array([0.5, 4.0, -1.0, 2.25]);
+        let grid = Grid::from([
+            [Player::X, Player::Empty, Player::Empty],
+            [Player::Empty, Player::O, Player::Empty],
+            [Player::Empty, Player::Empty, Player::Empty],
+        ]);
+        for grid in [Grid::new(), grid] {
+            let mut bot = Bot::new(Difficulty::Hard, 0).with_weights(weights);
+            let expected = eval::search(&grid, 2, &weights).map(|(m, _)| m);
+            assert_eq!(bot.choose(&grid), expected);
+        }
+
+        // Two moves ahead is enough to see a win
+        let grid = Grid::from([
+            [Player::Empty, Player::O, Player::Empty],
+            [Player::X, Player::O, Player::X],
+            [Player::Empty, Player::Empty, Player::X],
+        ]);
+        let mut bot = Bot::new(Difficulty::Hard, 0).with_weights(Weights::default());
+        assert_eq!(bot.choose(&grid), Some((1, 2)));
+    }
+
+    #[test]
+    fn full_grid() {
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::O],
+            [Player::O, Player::O, Player::X],
+            [Player::X, Player::O, Player::X],
+        ]);
+
+        assert_eq!(Bot::new(Difficulty::Medium, 0).choose(&grid), None);
+    }
+
+    #[test]
+    fn won_grid() {
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::X],
+            [Player::O, Player::O, Player::Empty],
+            [Player::Empty; 3],
+        ]);
+
+        for difficulty in [Difficulty::Easy, Difficulty::Hard] {
+            assert_eq!(Bot::new(difficulty, 0).choose(&grid), None);
+        }
+        let exploiting = Bot::new(Difficulty::Hard, 0).exploiting(OpponentModel::new(0.3));
+        assert_eq!(
+            exploiting
+                .think(&grid)
+                .wait(Duration::from_secs(60))
+                .unwrap()
+                .choice,
+            None
+        );
+    }
+
+    #[test]
+    fn parse_difficulty() {
+        assert_eq!("Hard".parse(), Ok(Difficulty::Hard));
+        assert_eq!(Difficulty::Medium.to_string(), "medium");
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+}
@@ -0,0 +1,432 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+#[cfg(feature = "tracing")]
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use crate::config::ConfigError;
+use crate::engine::legal_moves;
+use crate::rng::{RandomSource, Rng};
+use crate::{Grid, Player, NODES};
+
+// The positions left to the evaluator at the horizon, for tracing
+#[cfg(feature = "tracing")]
+static CUTOFFS: AtomicU64 = AtomicU64::new(0);
+
+// A static evaluation of positions, for searches that stop before the end of
+// the game, such as a model trained on `tictactoe dataset`
+pub trait Evaluator {
+    // How good the position is for the player to move, from -1 for a loss to
+    // 1 for a win
+    fn evaluate(&self, grid: &Grid) -> f32;
+}
+
+// The lines still open to the player to move against those open to their
+// opponent, each counting more for every mark already on it: a baseline for
+// the models to beat
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OpenLines;
+
+impl Evaluator for OpenLines {
+    fn evaluate(&self, grid: &Grid) -> f32 {
+        let player = grid.player_turn;
+        let mut score = 0;
+        for line in grid.lines() {
+            let marks = line.map(|(_, p)| p);
+            let mine = marks.iter().filter(|&&p| p == player).count() as i32;
+            let theirs = marks.iter().filter(|&&p| p == player.opponent()).count() as i32;
+            match (mine, theirs) {
+                (n, 0) => score += n,
+                (0, n) => score -= n,
+                _ => {}
+            }
+        }
+
+        // Never as sure as a finished game
+        score as f32 / 17.0
+    }
+}
+
+// The features of `OpenLines` and more, each counted for the player to move
+// and against their opponent, with weights to tune with the `tuning` module.
+// They are saved as flat `key = value` pairs:
+//
+//     one = 1
+//     two = 3
+//     center = 1
+//     fork = 2
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Weights {
+    // An open line holding a single mark
+    pub one: f32,
+    // An open line holding two marks, a win if the player is to move
+    pub two: f32,
+    // The center square, on four lines
+    pub center: f32,
+    // An empty square that would make two lines of two at once
+    pub fork: f32,
+}
+
+// Picked by hand, for the tuning to start from
+impl Default for Weights {
+    fn default() -> Weights {
+        Weights {
+            one: 1.0,
+            two: 3.0,
+            center: 1.0,
+            fork: 2.0,
+        }
+    }
+}
+
+const WEIGHTS: [&str; 4] = ["one", "two", "center", "fork"];
+
+impl Weights {
+    pub fn to_array(self) -> [f32; 4] {
+        [self.one, self.two, self.center, self.fork]
+    }
+
+    pub fn from_array([one, two, center, fork]: [f32; 4]) -> Weights {
+        Weights {
+            one,
+            two,
+            center,
+            fork,
+        }
+    }
+
+    // Every weight is needed, once
+    pub fn parse(content: &str) -> Result<Weights, ConfigError> {
+        let mut weights = [None; 4];
+        for (i, line) in content.lines().enumerate() {
+            let error = |message: String| ConfigError::Parse {
+                line: i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `key = value`".to_string()))?;
+            let key = key.trim();
+            let index = WEIGHTS
+                .iter()
+                .position(|&k| k == key)
+                .ok_or_else(|| error(format!("unknown weight `{key}`")))?;
+            let value: f32 = value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|v: &f32| v.is_finite())
+                .ok_or_else(|| error(format!("invalid value for `{key}`")))?;
+            if weights[index].replace(value).is_some() {
+                return Err(error(format!("`{key}` is set twice")));
+            }
+        }
+
+        let mut array = [0.0; 4];
+        for (i, weight) in weights.into_iter().enumerate() {
+            array[i] = weight.ok_or_else(|| ConfigError::Parse {
+                line: content.lines().count(),
+                message: format!("`{}` is missing", WEIGHTS[i]),
+            })?;
+        }
+        Ok(Weights::from_array(array))
+    }
+
+    pub fn load(path: &Path) -> Result<Weights, ConfigError> {
+        let content = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        Weights::parse(&content)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    // The features of `player`, as many as there are weights
+    fn features(grid: &Grid, player: Player) -> [f32; 4] {
+        let mut features = [0.0; 4];
+        // How many lines of a single mark of the player each square is on
+        let mut forks = [[0; 3]; 3];
+        for line in grid.lines() {
+            let mine = line.iter().filter(|&&(_, p)| p == player).count();
+            let theirs = line
+                .iter()
+                .filter(|&&(_, p)| p == player.opponent())
+                .count();
+            match (mine, theirs) {
+                (1, 0) => {
+                    features[0] += 1.0;
+                    for &((x, y), _) in line.iter().filter(|&&(_, p)| p == Player::Empty) {
+                        forks[y][x] += 1;
+                    }
+                }
+                (2, 0) => features[1] += 1.0,
+                _ => {}
+            }
+        }
+        if grid.matrix()[1][1] == player {
+            features[2] = 1.0;
+        }
+        features[3] = forks.iter().flatten().filter(|&&n| n >= 2).count() as f32;
+
+        features
+    }
+}
+
+impl fmt::Display for Weights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in WEIGHTS.iter().zip(self.to_array()) {
+            writeln!(f, "{key} = {value}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Evaluator for Weights {
+    fn evaluate(&self, grid: &Grid) -> f32 {
+        let player = grid.player_turn;
+        let mine = Weights::features(grid, player);
+        let theirs = Weights::features(grid, player.opponent());
+        let score: f32 = (0..4)
+            .map(|i| self.to_array()[i] * (mine[i] - theirs[i]))
+            .sum();
+
+        // Never as sure as a finished game, however large the weights
+        score / (1.0 + score.abs())
+    }
+}
+
+// How the moves of a rollout are chosen
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Policy {
+    // Any legal move
+    #[default]
+    Random,
+    // A win when there is one, else a block when needed, else any move, for
+    // games that look more like those of players
+    Heuristic,
+}
+
+// A Monte Carlo estimate of positions: the player to move plays `count`
+// games to the end from there with the moves of `policy`, and the value is
+// the share of the games they win less the share they lose. More rollouts
+// are slower but closer to the truth. The same position always gets the same
+// value: its rollouts take their random numbers from `seed` and the position.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rollouts {
+    pub count: u32,
+    pub policy: Policy,
+    pub seed: u64,
+}
+
+impl Default for Rollouts {
+    fn default() -> Rollouts {
+        Rollouts {
+            count: 100,
+            policy: Policy::default(),
+            seed: 0,
+        }
+    }
+}
+
+impl Rollouts {
+    // The winner of a game played from `grid`, `None` for a draw
+    fn rollout(&self, grid: &Grid, rng: &mut Rng) -> Option<Player> {
+        let mut grid = grid.clone();
+        loop {
+            if let Some(winner) = grid.winner() {
+                return Some(winner);
+            }
+            let moves = legal_moves(&grid);
+            let forced = match self.policy {
+                Policy::Random => None,
+                Policy::Heuristic => {
+                    let player = grid.player_turn;
+                    grid.winning_moves(player)
+                        .next()
+                        .or(grid.must_block().next())
+                }
+            };
+            let (x, y) = forced.or_else(|| rng.choose(&moves).copied())?;
+            let _ = grid.set(x, y);
+        }
+    }
+}
+
+impl Evaluator for Rollouts {
+    fn evaluate(&self, grid: &Grid) -> f32 {
+        let squares = grid.matrix().iter().flatten();
+        let position = squares.fold(0, |key, &square| key * 3 + square as u64);
+        let mut rng = Rng::new(self.seed ^ position);
+
+        let player = grid.player_turn;
+        let mut total = 0;
+        for _ in 0..self.count {
+            match self.rollout(grid, &mut rng) {
+                Some(winner) if winner == player => total += 1,
+                Some(_) => total -= 1,
+                None => {}
+            }
+        }
+
+        total as f32 / self.count.max(1) as f32
+    }
+}
+
+// The best move of a position looking `depth` moves ahead, with its value for
+// the player to move: finished games are valued exactly, the sooner the
+// better, and the positions left at the horizon by `evaluator`. `None` if the
+// game is over.
+pub fn search(
+    grid: &Grid,
+    depth: u32,
+    evaluator: &impl Evaluator,
+) -> Option<((usize, usize), f32)> {
+    if grid.has_winner() {
+        return None;
+    }
+    #[cfg(feature = "tracing")]
+    let (_span, nodes, cutoffs) = (
+        tracing::debug_span!("eval_search", depth).entered(),
+        crate::nodes_searched(),
+        CUTOFFS.load(Ordering::Relaxed),
+    );
+
+    let mut best: Option<((usize, usize), f32)> = None;
+    for m in legal_moves(grid) {
+        let value = -negamax(&after(grid, m), depth.saturating_sub(1), evaluator);
+        if best.is_none_or(|(_, v)| value > v) {
+            best = Some((m, value));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        nodes = crate::nodes_searched() - nodes,
+        cutoffs = CUTOFFS.load(Ordering::Relaxed) - cutoffs,
+        value = best.map(|(_, v)| v),
+        "searched"
+    );
+    best
+}
+
+fn negamax(grid: &Grid, depth: u32, evaluator: &impl Evaluator) -> f32 {
+    NODES.fetch_add(1, Ordering::Relaxed);
+    if grid.has_winner() {
+        // The player to move has just lost
+        return -1.0 - depth as f32;
+    }
+    if grid.is_full() {
+        return 0.0;
+    }
+    if depth == 0 {
+        #[cfg(feature = "tracing")]
+        CUTOFFS.fetch_add(1, Ordering::Relaxed);
+        return evaluator.evaluate(grid).clamp(-1.0, 1.0);
+    }
+
+    legal_moves(grid)
+        .into_iter()
+        .map(|m| -negamax(&after(grid, m), depth - 1, evaluator))
+        .fold(f32::NEG_INFINITY, f32::max)
+}
+
+fn after(grid: &Grid, (x, y): (usize, usize)) -> Grid {
+    let mut g = grid.clone();
+    let _ = g.set(x, y);
+    g
+}
+
+// The squares row by row, as a model reads them: 1 for the marks of the player
+// to move, -1 for those of their opponent and 0 when empty. This folds the
+// first two planes of `Grid::to_features` into one.
+pub fn encode(grid: &Grid) -> [f32; 9] {
+    let features = grid.to_features();
+    std::array::from_fn(|i| features[i] - features[9 + i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::parse_position;
+    use crate::Player;
+
+    #[test]
+    fn depth_limited() {
+        // O has to block at a3 or lose
+        let grid = parse_position("XX./.O./...").unwrap();
+        assert_eq!(grid.player_turn, Player::O);
+        let (m, value) = search(&grid, 1, &OpenLines).unwrap();
+        assert_eq!(m, (2, 0));
+        assert!(value.abs() < 1.0);
+
+        // A win on the spot is worth more than any evaluation
+        let grid = parse_position("XX./OO./...").unwrap();
+        let (m, value) = search(&grid, 3, &OpenLines).unwrap();
+        assert_eq!(m, (2, 0));
+        assert!(value > 1.0);
+
+        assert_eq!(
+            search(&parse_position("XXX/OO./...").unwrap(), 2, &OpenLines),
+            None
+        );
+    }
+
+    #[test]
+    fn weights() {
+        // X has two lines of two and the center, O a line of one
+        let grid = parse_position("XX./.X./O.O").unwrap();
+        let x = Weights::features(&grid, Player::X);
+        assert_eq!(x[1..], [2.0, 1.0, 0.0]);
+        // Playing a3 would make O two lines of two
+        let grid = parse_position("X../.../O.O").unwrap();
+        assert_eq!(Weights::features(&grid, Player::O)[3], 1.0);
+        assert!(Weights::default().evaluate(&grid) < 0.0);
+
+        let weights = Weights::from_array([0.5, 4.0, -1.0, 2.25]);
+        assert_eq!(Weights::parse(&weights.to_string()).unwrap(), weights);
+        let missing = Weights::parse("one = 1\ntwo = 2\n# no center\nfork = 1");
+        assert!(missing
+            .unwrap_err()
+            .to_string()
+            .contains("`center` is missing"));
+        assert!(Weights::parse("one = 1\none = 2").is_err());
+        assert!(Weights::parse("corner = 1").is_err());
+    }
+
+    #[test]
+    fn rollouts() {
+        // X to move has a win on a3, which the heuristic rollouts always take
+        let grid = parse_position("XX./OO./...").unwrap();
+        let heuristic = Rollouts {
+            policy: Policy::Heuristic,
+            ..Rollouts::default()
+        };
+        assert_eq!(heuristic.evaluate(&grid), 1.0);
+        // Random ones miss it so often that O wins about as many
+        let random = Rollouts::default();
+        let value = random.evaluate(&grid);
+        assert!(value.abs() < 0.5);
+        assert_eq!(random.evaluate(&grid), value);
+
+        // The first player wins most random games
+        assert!(random.evaluate(&Grid::new()) > 0.0);
+        // O has to block a3, which the rollouts see one move ahead
+        let grid = parse_position("XX./.O./...").unwrap();
+        assert_eq!(search(&grid, 1, &heuristic).unwrap().0, (2, 0));
+    }
+
+    #[test]
+    fn encoding() {
+        let grid = parse_position("X.O/.X./...").unwrap();
+        assert_eq!(
+            encode(&grid),
+            [-1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0]
+        );
+    }
+}
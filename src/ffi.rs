@@ -0,0 +1,228 @@
+// The engine for C, C++ or Swift programs, through the header
+// `include/tictactoe.h`. A game is created with `ttt_new` and freed with
+// `ttt_free`; squares are given by their column `x` and row `y`, from 0 to 2.
+use crate::{Grid, Player, FEATURES};
+
+/// A game, only handled through pointers
+pub struct TttGame {
+    grid: Grid,
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TttPlayer {
+    Empty = 0,
+    X = 1,
+    O = 2,
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TttStatus {
+    InProgress = 0,
+    XWins = 1,
+    OWins = 2,
+    Draw = 3,
+}
+
+/// What `ttt_set` returns
+pub const TTT_OK: i32 = 0;
+pub const TTT_OCCUPIED: i32 = 1;
+pub const TTT_OUT_OF_RANGE: i32 = 2;
+pub const TTT_GAME_OVER: i32 = 3;
+pub const TTT_NULL: i32 = -1;
+
+/// The number of values written by `ttt_features`
+pub const TTT_FEATURES: usize = 27;
+
+// A literal, for cbindgen to write it to the header
+const _: () = assert!(TTT_FEATURES == FEATURES);
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TttState {
+    /// Row by row, `board[3 * y + x]`
+    pub board: [TttPlayer; 9],
+    /// The player to move, `Empty` once the game is over
+    pub turn: TttPlayer,
+    pub status: TttStatus,
+}
+
+impl From<Player> for TttPlayer {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::X => TttPlayer::X,
+            Player::O => TttPlayer::O,
+            Player::Empty => TttPlayer::Empty,
+        }
+    }
+}
+
+fn status(grid: &Grid) -> TttStatus {
+    if grid.has_winner() {
+        match grid.player_turn.opponent() {
+            Player::O => TttStatus::OWins,
+            _ => TttStatus::XWins,
+        }
+    } else if grid.is_full() {
+        TttStatus::Draw
+    } else {
+        TttStatus::InProgress
+    }
+}
+
+/// A new game, where X moves first
+#[no_mangle]
+pub extern "C" fn ttt_new() -> *mut TttGame {
+    Box::into_raw(Box::new(TttGame { grid: Grid::new() }))
+}
+
+/// Play the square for the player to move: `TTT_OK`, or why it cannot be played
+///
+/// # Safety
+///
+/// `game` must come from `ttt_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_set(game: *mut TttGame, x: u32, y: u32) -> i32 {
+    let Some(game) = game.as_mut() else {
+        return TTT_NULL;
+    };
+    if x > 2 || y > 2 {
+        return TTT_OUT_OF_RANGE;
+    }
+    if status(&game.grid) != TttStatus::InProgress {
+        return TTT_GAME_OVER;
+    }
+    match game.grid.set(x as usize, y as usize) {
+        Ok(()) => TTT_OK,
+        Err(_) => TTT_OCCUPIED,
+    }
+}
+
+/// Write the best move of the player to move to `x` and `y`, false once the
+/// game is over
+///
+/// # Safety
+///
+/// `game` must come from `ttt_new` and not have been freed, and `x` and `y`
+/// must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_best_play(game: *const TttGame, x: *mut u32, y: *mut u32) -> bool {
+    let Some(game) = game.as_ref() else {
+        return false;
+    };
+    if x.is_null() || y.is_null() || status(&game.grid) != TttStatus::InProgress {
+        return false;
+    }
+    match game.grid.best_play() {
+        Some((bx, by)) => {
+            *x = bx as u32;
+            *y = by as u32;
+            true
+        }
+        None => false,
+    }
+}
+
+/// The board, the player to move and whether the game is over
+///
+/// # Safety
+///
+/// `game` must come from `ttt_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_state(game: *const TttGame) -> TttState {
+    let mut state = TttState {
+        board: [TttPlayer::Empty; 9],
+        turn: TttPlayer::Empty,
+        status: TttStatus::InProgress,
+    };
+    let Some(game) = game.as_ref() else {
+        return state;
+    };
+
+    for (i, &square) in game.grid.matrix().iter().flatten().enumerate() {
+        state.board[i] = square.into();
+    }
+    state.status = status(&game.grid);
+    if state.status == TttStatus::InProgress {
+        state.turn = game.grid.player_turn.into();
+    }
+
+    state
+}
+
+/// Write the position as numbers for a model to `features`, `TTT_FEATURES`
+/// values laid out as `Grid::to_features` describes, false if a pointer is null
+///
+/// # Safety
+///
+/// `game` must come from `ttt_new` and not have been freed, and `features`
+/// must point to room for `TTT_FEATURES` floats.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_features(game: *const TttGame, features: *mut f32) -> bool {
+    let Some(game) = game.as_ref() else {
+        return false;
+    };
+    if features.is_null() {
+        return false;
+    }
+    let values = game.grid.to_features();
+    std::ptr::copy_nonoverlapping(values.as_ptr(), features, FEATURES);
+
+    true
+}
+
+/// Free a game; a null pointer is ignored
+///
+/// # Safety
+///
+/// `game` must come from `ttt_new` and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_free(game: *mut TttGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn game() {
+        unsafe {
+            let game = ttt_new();
+            assert_eq!(ttt_set(game, 1, 1), TTT_OK);
+            assert_eq!(ttt_set(game, 1, 1), TTT_OCCUPIED);
+            assert_eq!(ttt_set(game, 3, 0), TTT_OUT_OF_RANGE);
+
+            let (mut x, mut y) = (9, 9);
+            assert!(ttt_best_play(game, &mut x, &mut y));
+            assert_eq!(ttt_set(game, x, y), TTT_OK);
+
+            let state = ttt_state(game);
+            assert_eq!(state.board[4], TttPlayer::X);
+            assert_eq!(state.board[(3 * y + x) as usize], TttPlayer::O);
+            assert_eq!(state.turn, TttPlayer::X);
+            assert_eq!(state.status, TttStatus::InProgress);
+
+            let mut features = [0.0; TTT_FEATURES];
+            assert!(ttt_features(game, features.as_mut_ptr()));
+            assert_eq!(features[4], 1.0);
+            assert!(!ttt_features(game, ptr::null_mut()));
+            ttt_free(game);
+
+            assert_eq!(ttt_set(ptr::null_mut(), 0, 0), TTT_NULL);
+            ttt_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn header() {
+        // Regenerated with cbindgen, see the README
+        let header = include_str!("../include/tictactoe.h");
+        assert!(header.contains(&format!("#define TTT_FEATURES {TTT_FEATURES}\n")));
+    }
+}
@@ -0,0 +1,597 @@
+#[cfg(feature = "std")]
+use core::sync::atomic::Ordering;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[cfg(feature = "std")]
+use crate::NODES;
+use crate::{Grid, Player};
+
+// A two-player game where the players take turns, which the search can play:
+// tic-tac-toe's `Grid`, but also any other small game
+pub trait Game: Clone {
+    type Move: Copy;
+
+    // The moves of the player to move, only asked while the game goes on
+    fn moves(&self) -> impl Iterator<Item = Self::Move>;
+
+    // Play a move given by `moves`
+    fn play(&mut self, m: Self::Move);
+
+    // Once the game is over, its value for the player to move: positive if they
+    // have won, negative if they have lost, 0 for a draw. `None` while the game
+    // goes on.
+    fn value(&self) -> Option<i32>;
+
+    // Whether a move wins on the spot or stops the opponent from doing so, for
+    // `AlphaBeta` to try it first. Games that cannot tell keep the default.
+    fn is_forcing(&self, _m: Self::Move) -> bool {
+        false
+    }
+}
+
+// The value of `game` for the player to move when both players play their
+// best, as given by `Game::value` for the game this leads to
+pub fn negamax<G: Game>(game: &G) -> i32 {
+    let mut nodes = 0;
+    let value = negamax_counted(game, &mut nodes);
+    #[cfg(feature = "std")]
+    NODES.fetch_add(nodes, Ordering::Relaxed);
+
+    value
+}
+
+// `negamax`, adding the positions it goes through to `nodes`
+pub fn negamax_counted<G: Game>(game: &G, nodes: &mut u64) -> i32 {
+    *nodes += 1;
+    if let Some(value) = game.value() {
+        return value;
+    }
+
+    let mut best = None;
+    for m in game.moves() {
+        let mut next = game.clone();
+        next.play(m);
+        let value = -negamax_counted(&next, nodes);
+        best = best.max(Some(value));
+    }
+
+    best.unwrap_or(0)
+}
+
+// The first of the best moves of the player to move, `None` once the game is
+// over
+pub fn best_move<G: Game>(game: &G) -> Option<G::Move> {
+    if game.value().is_some() {
+        return None;
+    }
+
+    let mut best = None;
+    for m in game.moves() {
+        let mut next = game.clone();
+        next.play(m);
+        let value = -negamax(&next);
+        if best.is_none_or(|(_, best)| value > best) {
+            best = Some((m, value));
+        }
+    }
+
+    best.map(|(m, _)| m)
+}
+
+// Every move tied for the best value, in the order of `Game::moves`: the
+// first one is `best_move`. Empty once the game is over.
+#[cfg(feature = "std")]
+pub fn best_moves<G: Game>(game: &G) -> Vec<G::Move> {
+    if game.value().is_some() {
+        return Vec::new();
+    }
+
+    let scored: Vec<_> = game
+        .moves()
+        .map(|m| {
+            let mut next = game.clone();
+            next.play(m);
+            (m, -negamax(&next))
+        })
+        .collect();
+    let best = scored.iter().map(|&(_, value)| value).max();
+    scored
+        .into_iter()
+        .filter(|&(_, value)| Some(value) == best)
+        .map(|(m, _)| m)
+        .collect()
+}
+
+// An alpha-beta search for games too large for `negamax` to go through every
+// position, such as larger boards. It cuts off the moves that cannot change
+// the value, which works best when the best moves come first, so it tries the
+// forcing moves of `Game::is_forcing` first, then the killer moves (the last
+// two to cut off the search at the same depth of the tree), then the moves
+// that have cut it off the most so far, by the history of the search. Keep the
+// same `AlphaBeta` to search related positions, such as the moves of a game
+// one after the other, so that it remembers what worked.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct AlphaBeta<G: Game> {
+    // By distance from the root of the search
+    killers: Vec<[Option<G::Move>; 2]>,
+    // The moves with the sum of the squares of the depths they cut off at,
+    // so that cutoffs near the root count more
+    history: Vec<(G::Move, u64)>,
+    // The positions searched so far
+    pub nodes: u64,
+    // When `deepen` has to stop, and whether it has
+    deadline: Option<Instant>,
+    stopped: bool,
+    // The positions valued by the evaluation function so far: none in an
+    // iteration of `deepen` means that it has gone through the whole game
+    evaluated: u64,
+}
+
+// The last iteration `AlphaBeta::deepen` went through
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Iteration<M> {
+    pub best: M,
+    pub value: i32,
+    pub depth: u32,
+}
+
+#[cfg(feature = "std")]
+impl<G: Game> Default for AlphaBeta<G> {
+    fn default() -> AlphaBeta<G> {
+        AlphaBeta {
+            killers: Vec::new(),
+            history: Vec::new(),
+            nodes: 0,
+            deadline: None,
+            stopped: false,
+            evaluated: 0,
+        }
+    }
+}
+
+// Beyond any value, and still so once negated
+#[cfg(feature = "std")]
+pub const INFINITY: i32 = i32::MAX;
+
+// Told what `AlphaBeta::visit` goes through, such as `tree::SearchTree` to
+// draw it. `()` is told nothing.
+#[cfg(feature = "std")]
+pub trait Visitor<G: Game> {
+    // The search enters `game`, reached with `m` (none for the root)
+    fn enter(&mut self, _game: &G, _m: Option<G::Move>) {}
+
+    // ... and leaves it with its value for the player to move, searched
+    // within `window`
+    fn leave(&mut self, _value: i32, _window: (i32, i32)) {}
+
+    // The move `m` from `game` was left unsearched by a cutoff
+    fn prune(&mut self, _game: &G, _m: G::Move) {}
+}
+
+#[cfg(feature = "std")]
+impl<G: Game> Visitor<G> for () {}
+
+#[cfg(feature = "std")]
+impl<G: Game> AlphaBeta<G>
+where
+    G::Move: PartialEq,
+{
+    pub fn new() -> AlphaBeta<G> {
+        AlphaBeta::default()
+    }
+
+    // The value of `game` for the player to move, looking `depth` moves ahead
+    // and valuing the positions left there with `evaluate`, which should stay
+    // within the values of finished games. Given a depth reaching the end of
+    // the game, it is the value of `negamax`. Only values between `alpha` and
+    // `beta` are exact: below, the value is at most the one given, and above,
+    // at least. `(-INFINITY, INFINITY)` always gives the exact value.
+    pub fn value(
+        &mut self,
+        game: &G,
+        depth: u32,
+        (alpha, beta): (i32, i32),
+        evaluate: &impl Fn(&G) -> i32,
+    ) -> i32 {
+        self.search(game, depth, 0, (alpha, beta), evaluate, &mut ())
+    }
+
+    // `value` with the full window, telling `visitor` each position searched
+    // and each move cut off
+    pub fn visit(
+        &mut self,
+        game: &G,
+        depth: u32,
+        evaluate: &impl Fn(&G) -> i32,
+        visitor: &mut impl Visitor<G>,
+    ) -> i32 {
+        let window = (-INFINITY, INFINITY);
+        visitor.enter(game, None);
+        let value = self.search(game, depth, 0, window, evaluate, visitor);
+        visitor.leave(value, window);
+
+        value
+    }
+
+    // The first of the best moves found looking `depth` moves ahead, with its
+    // exact value, or `None` once the game is over
+    pub fn best_move(
+        &mut self,
+        game: &G,
+        depth: u32,
+        evaluate: &impl Fn(&G) -> i32,
+    ) -> Option<(G::Move, i32)> {
+        self.root(game, depth, (-INFINITY, INFINITY), None, evaluate)
+    }
+
+    // Search one move deeper at a time, up to `max_depth` moves ahead or until
+    // `deadline`, for time controls: the iteration cut short by the deadline
+    // is dropped and the last complete one is given, the first one always
+    // being complete. Each iteration starts with the best move of the one
+    // before, and only looks for values within `window` of its value, which
+    // cuts off more; if the value turns out to be outside, the iteration is
+    // searched again without the window. The search stops early once it has
+    // gone through the whole game. `None` once the game is over.
+    pub fn deepen(
+        &mut self,
+        game: &G,
+        max_depth: u32,
+        window: i32,
+        deadline: Option<Instant>,
+        evaluate: &impl Fn(&G) -> i32,
+    ) -> Option<Iteration<G::Move>> {
+        let (best, value) = self.root(game, 1, (-INFINITY, INFINITY), None, evaluate)?;
+        let mut last = Iteration {
+            best,
+            value,
+            depth: 1,
+        };
+        (self.deadline, self.stopped) = (deadline, false);
+
+        for depth in 2..=max_depth {
+            let evaluated = self.evaluated;
+            let aspiration = (
+                last.value.saturating_sub(window),
+                last.value.saturating_add(window),
+            );
+            let mut found = self.root(game, depth, aspiration, Some(last.best), evaluate);
+            if found.is_some_and(|(_, v)| v <= aspiration.0 || v >= aspiration.1) {
+                found = self.root(
+                    game,
+                    depth,
+                    (-INFINITY, INFINITY),
+                    Some(last.best),
+                    evaluate,
+                );
+            }
+            if self.stopped {
+                break;
+            }
+            let Some((best, value)) = found else {
+                break;
+            };
+            last = Iteration { best, value, depth };
+            if self.evaluated == evaluated {
+                break;
+            }
+        }
+
+        self.deadline = None;
+        Some(last)
+    }
+
+    // The best move and its value, the first one within `(alpha, beta)`. With
+    // a value at most `alpha`, all the moves are at most worth that, and with
+    // one at least `beta`, the move is worth at least that. `first` is tried
+    // before the others.
+    fn root(
+        &mut self,
+        game: &G,
+        depth: u32,
+        (mut alpha, beta): (i32, i32),
+        first: Option<G::Move>,
+        evaluate: &impl Fn(&G) -> i32,
+    ) -> Option<(G::Move, i32)> {
+        if game.value().is_some() {
+            return None;
+        }
+
+        let mut moves = self.ordered(game, 0);
+        if let Some(i) = moves.iter().position(|&m| Some(m) == first) {
+            moves[..=i].rotate_right(1);
+        }
+        let mut best: Option<(G::Move, i32)> = None;
+        for m in moves {
+            let mut next = game.clone();
+            next.play(m);
+            let value = -self.search(
+                &next,
+                depth.saturating_sub(1),
+                1,
+                (-beta, -alpha),
+                evaluate,
+                &mut (),
+            );
+            if best.is_none_or(|(_, best)| value > best) {
+                best = Some((m, value));
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best
+    }
+
+    fn search(
+        &mut self,
+        game: &G,
+        depth: u32,
+        ply: usize,
+        (mut alpha, beta): (i32, i32),
+        evaluate: &impl Fn(&G) -> i32,
+        visitor: &mut impl Visitor<G>,
+    ) -> i32 {
+        self.nodes += 1;
+        NODES.fetch_add(1, Ordering::Relaxed);
+        // Looking at the time now and then only, which is costly
+        if self.nodes.is_multiple_of(1024) && self.deadline.is_some_and(|d| Instant::now() >= d) {
+            self.stopped = true;
+        }
+        if self.stopped {
+            // Whatever the value, it is dropped
+            return 0;
+        }
+        if let Some(value) = game.value() {
+            return value;
+        }
+        if depth == 0 {
+            self.evaluated += 1;
+            return evaluate(game);
+        }
+
+        let mut best = -INFINITY;
+        let moves = self.ordered(game, ply);
+        for (i, &m) in moves.iter().enumerate() {
+            let mut next = game.clone();
+            next.play(m);
+            let window = (-beta, -alpha);
+            visitor.enter(&next, Some(m));
+            let value = self.search(&next, depth - 1, ply + 1, window, evaluate, visitor);
+            visitor.leave(value, window);
+            let value = -value;
+            best = best.max(value);
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                self.cut_off(m, depth, ply);
+                for &m in &moves[i + 1..] {
+                    visitor.prune(game, m);
+                }
+                break;
+            }
+        }
+
+        best
+    }
+
+    // The moves of the player to move, the most promising first and otherwise
+    // in the order of `Game::moves`
+    fn ordered(&self, game: &G, ply: usize) -> Vec<G::Move> {
+        let killers = self.killers.get(ply).copied().unwrap_or([None; 2]);
+        let mut moves: Vec<(G::Move, (bool, usize, u64))> = game
+            .moves()
+            .map(|m| {
+                let killer = killers.iter().rev().position(|&k| k == Some(m));
+                let history = self.history.iter().find(|&&(h, _)| h == m);
+                let rank = (
+                    game.is_forcing(m),
+                    killer.map_or(0, |i| i + 1),
+                    history.map_or(0, |&(_, n)| n),
+                );
+                (m, rank)
+            })
+            .collect();
+        // Stable, so ties keep their order
+        moves.sort_by_key(|&(_, rank)| core::cmp::Reverse(rank));
+
+        moves.into_iter().map(|(m, _)| m).collect()
+    }
+
+    fn cut_off(&mut self, m: G::Move, depth: u32, ply: usize) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(m) {
+            killers[1] = killers[0];
+            killers[0] = Some(m);
+        }
+
+        let bonus = u64::from(depth) * u64::from(depth);
+        match self.history.iter_mut().find(|(h, _)| *h == m) {
+            Some((_, n)) => *n += bonus,
+            None => self.history.push((m, bonus)),
+        }
+    }
+}
+
+// Squares are `(x, y)`, row by row. Wins are worth more the sooner they come,
+// so the search goes for the quickest win and the slowest loss.
+impl Game for Grid {
+    type Move = (usize, usize);
+
+    fn moves(&self) -> impl Iterator<Item = (usize, usize)> {
+        let matrix = *self.matrix();
+        (0..3)
+            .flat_map(|y| (0..3).map(move |x| (x, y)))
+            .filter(move |&(x, y)| matrix[y][x] == Player::Empty)
+    }
+
+    fn play(&mut self, (x, y): (usize, usize)) {
+        let _ = self.set(x, y);
+    }
+
+    fn value(&self) -> Option<i32> {
+        if self.has_winner() {
+            // The player to move is the one who did not make the line
+            Some(self.number_of_turns - 10)
+        } else if self.is_full() {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    fn is_forcing(&self, (x, y): (usize, usize)) -> bool {
+        let player = self.player_turn;
+        self.makes_line(x, y, player) || self.makes_line(x, y, player.opponent())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Players take 1 to 3 matches in turn, and the one taking the last wins
+    #[derive(Clone)]
+    struct Nim(u32);
+
+    impl Game for Nim {
+        type Move = u32;
+
+        fn moves(&self) -> impl Iterator<Item = u32> {
+            1..=self.0.min(3)
+        }
+
+        fn play(&mut self, m: u32) {
+            self.0 -= m;
+        }
+
+        fn value(&self) -> Option<i32> {
+            // The other player took the last match
+            (self.0 == 0).then_some(-1)
+        }
+    }
+
+    #[test]
+    fn other_games() {
+        // Multiples of 4 are lost for the player to move
+        assert_eq!(negamax(&Nim(8)), -1);
+        assert_eq!(negamax(&Nim(7)), 1);
+        assert_eq!(best_move(&Nim(7)), Some(3));
+        assert_eq!(best_move(&Nim(0)), None);
+
+        // The 3 matches, then the 2, 1 and 0 left after each move and so on
+        let mut nodes = 0;
+        assert_eq!(negamax_counted(&Nim(3), &mut nodes), 1);
+        assert_eq!(nodes, 1 + 4 + 2 + 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn tied_moves() {
+        // Any move loses
+        assert_eq!(best_moves(&Nim(8)), [1, 2, 3]);
+        assert_eq!(best_moves(&Nim(6)), [2]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn alpha_beta() {
+        let mut search = AlphaBeta::new();
+        assert_eq!(search.value(&Nim(8), 8, (-INFINITY, INFINITY), &|_| 0), -1);
+        assert_eq!(search.best_move(&Nim(7), 7, &|_| 0), Some((3, 1)));
+
+        // The same values as without cutoffs, for a fraction of the positions
+        let mut search = AlphaBeta::new();
+        for position in [
+            ["...", "...", "..."],
+            ["XX.", "OO.", "..."],
+            ["X..", ".O.", "..X"],
+        ] {
+            let grid = Grid::try_from(&position).unwrap();
+            let value = search.value(&grid, 9, (-INFINITY, INFINITY), &|_| 0);
+            assert_eq!(value, negamax(&grid));
+        }
+        // `negamax` goes through more than half a million from the empty board
+        assert!(search.nodes < 5_000);
+
+        // The win is tried first and the block right after, so both cut off
+        // the others at once
+        let grid = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        let mut search = AlphaBeta::new();
+        assert_eq!(search.best_move(&grid, 9, &|_| 0), Some(((2, 0), 5)));
+        assert!(grid.is_forcing((2, 1)));
+        assert!(!grid.is_forcing((0, 2)));
+
+        // Values outside of the window are bounds
+        let mut search = AlphaBeta::new();
+        assert!(search.value(&grid, 9, (-1, 1), &|_| 0) >= 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn iterative_deepening() {
+        // The lines still open to the player to move, less those open to their
+        // opponent
+        let open_lines = |grid: &Grid| {
+            let open = |p: Player| {
+                grid.lines()
+                    .filter(|l| l.iter().all(|&(_, q)| q != p))
+                    .count()
+            };
+            open(grid.player_turn.opponent()) as i32 - open(grid.player_turn) as i32
+        };
+        let mut search = AlphaBeta::new();
+        let iteration = search
+            .deepen(&Grid::new(), 20, 1, None, &open_lines)
+            .unwrap();
+        // No need to go on once the whole game has been searched
+        assert!(iteration.depth <= 9);
+        assert_eq!(iteration.value, 0);
+        assert!(Grid::new().best_plays().contains(&iteration.best));
+
+        // The window is missed when a win comes in sight, then searched again
+        let grid = Grid::try_from(&["X..", ".O.", "..."]).unwrap();
+        let mut search = AlphaBeta::new();
+        let iteration = search.deepen(&grid, 9, 1, None, &open_lines).unwrap();
+        assert_eq!(iteration.value, negamax(&grid));
+        let grid = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        let iteration = search.deepen(&grid, 9, 1, None, &open_lines).unwrap();
+        assert_eq!((iteration.best, iteration.value), ((2, 0), 5));
+
+        // Out of time, the first iteration is still there
+        let past = Instant::now();
+        let mut search = AlphaBeta::new();
+        let iteration = search.deepen(&Grid::new(), 9, 1, Some(past), &open_lines);
+        assert!(iteration.is_some_and(|i| i.depth >= 1));
+        assert_eq!(
+            search.deepen(
+                &Grid::try_from(&["XXX", "OO.", "..."]).unwrap(),
+                9,
+                1,
+                None,
+                &open_lines
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn grid() {
+        assert_eq!(negamax(&Grid::new()), 0);
+
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::Empty],
+            [Player::O, Player::O, Player::Empty],
+            [Player::Empty, Player::Empty, Player::Empty],
+        ]);
+        assert_eq!(best_move(&grid), Some((2, 0)));
+        // X wins on the 5th move
+        assert_eq!(negamax(&grid), 5);
+    }
+}
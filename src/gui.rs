@@ -0,0 +1,138 @@
+use eframe::egui::{self, Button, Color32, ComboBox, RichText, Vec2};
+
+use crate::analysis::explain;
+use crate::engine::{Bot, Difficulty};
+use crate::i18n::{Lang, Msg};
+use crate::session::GameSession;
+use crate::Player;
+
+// The window of the `tictactoe-gui` binary, where the player plays X against
+// the engine by clicking the squares. The engine is seeded with `seed`.
+pub fn run(lang: Lang, seed: u64) -> eframe::Result {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([320.0, 420.0]),
+        ..Default::default()
+    };
+    eframe::run_native(
+        "tictactoe",
+        options,
+        Box::new(move |_| Ok(Box::new(App::new(lang, seed)))),
+    )
+}
+
+const SQUARE: f32 = 80.0;
+
+struct App {
+    lang: Lang,
+    session: GameSession,
+    bot: Bot,
+    // The square suggested by the last hint, until a move is played
+    hint: Option<(usize, usize)>,
+}
+
+impl App {
+    fn new(lang: Lang, seed: u64) -> App {
+        App {
+            lang,
+            session: GameSession::new(),
+            bot: Bot::new(Difficulty::Medium, seed),
+            hint: None,
+        }
+    }
+
+    fn play(&mut self, x: usize, y: usize) {
+        if self.session.play(x, y).is_err() {
+            return;
+        }
+        self.hint = None;
+        if !self.session.is_over() {
+            self.session.play_with(&mut self.bot);
+        }
+    }
+
+    // Take back the last move of the player, with the answer of the engine
+    fn undo(&mut self) {
+        self.hint = None;
+        while let Some(m) = self.session.undo() {
+            if m.player == Player::X {
+                break;
+            }
+        }
+    }
+
+    fn controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ComboBox::from_id_salt("difficulty")
+                .selected_text(self.bot.difficulty.to_string())
+                .show_ui(ui, |ui| {
+                    for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+                        let text = difficulty.to_string();
+                        ui.selectable_value(&mut self.bot.difficulty, difficulty, text);
+                    }
+                });
+            if ui.button(self.lang.text(Msg::GuiNewGame)).clicked() {
+                self.session = GameSession::new();
+                self.hint = None;
+            }
+            let playing = !self.session.is_over();
+            if ui
+                .add_enabled(playing, Button::new(self.lang.text(Msg::GuiHint)))
+                .clicked()
+            {
+                self.hint = self.session.grid().best_play();
+            }
+            let undoable = !self.session.history().is_empty();
+            if ui
+                .add_enabled(undoable, Button::new(self.lang.text(Msg::GuiUndo)))
+                .clicked()
+            {
+                self.undo();
+            }
+        });
+    }
+
+    fn board(&mut self, ui: &mut egui::Ui) {
+        let matrix = *self.session.grid().matrix();
+        let playing = !self.session.is_over();
+        egui::Grid::new("board").spacing([4.0, 4.0]).show(ui, |ui| {
+            for (y, row) in matrix.iter().enumerate() {
+                for (x, &square) in row.iter().enumerate() {
+                    let mark = match square {
+                        Player::Empty => String::new(),
+                        p => p.to_string(),
+                    };
+                    let mut button =
+                        Button::new(RichText::new(mark).size(48.0)).min_size(Vec2::splat(SQUARE));
+                    if self.hint == Some((x, y)) {
+                        button = button.fill(Color32::from_rgb(60, 120, 60));
+                    }
+                    let free = playing && square == Player::Empty;
+                    if ui.add_enabled(free, button).clicked() {
+                        self.play(x, y);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    }
+}
+
+impl eframe::App for App {
+    fn ui(&mut self, ui: &mut egui::Ui, _: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ui, |ui| {
+            self.controls(ui);
+            ui.add_space(8.0);
+            self.board(ui);
+            ui.add_space(8.0);
+            let status = match self.session.result() {
+                Some(result) => self.lang.text(Msg::from(result)),
+                None => self.lang.text(Msg::GuiYourTurn),
+            };
+            ui.label(status);
+            if let Some(hint) = self.hint {
+                let reasons = explain(self.session.grid(), hint);
+                ui.label(self.lang.text(Msg::Explanation(hint, &reasons)));
+            }
+        });
+    }
+}
@@ -0,0 +1,2045 @@
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::analysis::{Advice, Flaw, Line, Reason, Review, Solution, Value, Verdict};
+use crate::arena::{Engine, Results};
+use crate::code::CodeError;
+use crate::elo::Ratings;
+use crate::engine::{Difficulty, SearchInfo};
+use crate::eval::Weights;
+use crate::net::Host;
+use crate::notation::{position, Command, ParseError, PositionError};
+use crate::openings::Opening;
+use crate::profile::Profile;
+use crate::puzzle::{Grade, Mistake, Puzzle};
+use crate::record::GameRecord;
+use crate::report::{Column, Highlight, Notable, Section};
+use crate::retrograde::Disagreement;
+use crate::session::{GameResult, Match, Move, Scoreboard};
+use crate::stats::{Crosstable, Stats, Tally};
+use crate::tournament::{Bracket, EntrantError, Heat, Swiss};
+use crate::tree::SearchTree;
+use crate::tuning::Generation;
+use crate::{coordinates, Grid, Player};
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Lang {
+    #[default]
+    En,
+    Fr,
+}
+
+impl Lang {
+    // The language of a locale such as `fr_FR.UTF-8`
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        let code = locale.split(['_', '.', '@', '-']).next()?;
+        code.parse().ok()
+    }
+
+    // The language of the user's locale, English if it is not supported
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| Lang::from_locale(&locale))
+            .unwrap_or_default()
+    }
+
+    pub fn text(self, msg: Msg) -> String {
+        match self {
+            Self::En => english(msg),
+            Self::Fr => french(msg),
+        }
+    }
+
+    // The board in words, one row per line, for screen readers
+    pub fn describe_board(self, grid: &Grid) -> String {
+        let rows: Vec<String> = grid
+            .matrix()
+            .iter()
+            .enumerate()
+            .map(|(y, &row)| self.text(Msg::BoardRow(y, row)))
+            .collect();
+
+        rows.join("\n")
+    }
+
+    // A command typed by the player, in English or in this language
+    pub fn command(self, input: &str) -> Option<Command> {
+        let input = input.trim().to_lowercase();
+        match input.as_str() {
+            "resign" => return Some(Command::Resign),
+            "quit" | "exit" => return Some(Command::Quit),
+            "peek" => return Some(Command::Peek),
+            "draw" => return Some(Command::Draw),
+            _ => (),
+        }
+
+        match (self, input.as_str()) {
+            (Self::Fr, "abandon" | "abandonner") => Some(Command::Resign),
+            (Self::Fr, "quitter") => Some(Command::Quit),
+            (Self::Fr, "regarder") => Some(Command::Peek),
+            (Self::Fr, "nulle") => Some(Command::Draw),
+            _ => None,
+        }
+    }
+
+    // What the player says to their opponent, when the line starts with `say`
+    // (or its translation)
+    pub fn chat(self, input: &str) -> Option<&str> {
+        let input = input.trim();
+        let (word, text) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let word = word.to_lowercase();
+        match (self, word.as_str()) {
+            (_, "say") | (Self::Fr, "dire") => Some(text.trim_start()),
+            _ => None,
+        }
+    }
+
+    // Whether the answer to a yes/no question is yes
+    pub fn is_yes(self, answer: &str) -> bool {
+        let answer = answer.trim().to_lowercase();
+        match self {
+            Self::En => matches!(answer.as_str(), "y" | "yes"),
+            Self::Fr => matches!(answer.as_str(), "o" | "oui"),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::En => "en",
+            Self::Fr => "fr",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "en" | "english" => Ok(Self::En),
+            "fr" | "french" | "francais" | "français" => Ok(Self::Fr),
+            _ => Err(format!("Unsupported language: {s}")),
+        }
+    }
+}
+
+// What a command or an option is missing, for `Msg::Needs`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Wanted {
+    Path,
+    File,
+    Directory,
+    Name,
+    Number,
+    Seconds,
+    Games,
+    Rounds,
+    Runs,
+    Generations,
+    Moves,
+    Pixels,
+    // Between 0 and 1
+    Rate,
+    Port,
+    Level,
+    Engine,
+    Palette,
+    Style,
+    Theme,
+    Language,
+    Scheme,
+    Entrants,
+    Position,
+    PositionOrLog,
+    PositionOrFile,
+    LogPositionOrArchive,
+    AddressAndGame,
+}
+
+// Every message shown to the player
+#[derive(Debug, Clone, Copy)]
+pub enum Msg<'a> {
+    EnterCoordinates,
+    EnterKeypad,
+    InvalidMove(ParseError),
+    Occupied(Player),
+    Won(Player),
+    Draw,
+    // A draw called before the board is full
+    DeadDraw,
+    Timeout(Player),
+    Resigned(Player),
+    DrawAgreed,
+    CommandsHelp,
+    BlindfoldHelp(u64),
+    Peeked(u64),
+    Score(&'a Scoreboard),
+    PlayAgain,
+    // An unfinished game saved this long ago
+    ResumeGame(Duration),
+    MatchGame(&'a Match),
+    MatchScore(&'a Match),
+    MatchWon(Player),
+    MatchTied,
+    BotSettings(Difficulty, u64),
+    AskProfileName,
+    InvalidProfileName,
+    ProfileRecord(&'a Profile),
+    NoDataDir,
+    LoadProfilesFailed(&'a str),
+    SaveProfilesFailed(&'a str),
+    AutosaveFailed(&'a str),
+    SaveLogFailed(&'a str),
+    LoadLogFailed(&'a str),
+    LoadCacheFailed(&'a str),
+    SaveCacheFailed(&'a str),
+    LoadPositionsFailed(&'a str),
+    TuiUnavailable,
+    JsonUnavailable,
+    // Statistics
+    StatsTitle(&'a str, u32),
+    NoGames(&'a str),
+    StatsOverall(Tally),
+    StatsDifficulty(Difficulty, Tally),
+    StatsSide(Player, Tally),
+    AverageLength(f64),
+    StatsAccuracy(&'a Stats),
+    StatsOpening(&'a str, Tally),
+    OpeningLine(&'a Opening),
+    OpeningResults(&'a str, Scoreboard),
+    ReportTitle,
+    ReportPeriod(usize, &'a str, &'a str),
+    ReportSection(Section),
+    ReportColumn(Column),
+    ReportNotable(&'a Notable),
+    // Tools
+    Solution(&'a Solution),
+    FinishedPosition,
+    MoveValuesTitle,
+    MoveValue((usize, usize), Value, &'a [Reason]),
+    Explanation((usize, usize), &'a [Reason]),
+    Threats(Player, &'a [(usize, usize)], &'a [(usize, usize)]),
+    PrincipalVariation(&'a [Move]),
+    GameHeader(usize, &'a GameRecord),
+    MoveReview(&'a Review),
+    OfferAnalysis,
+    ReportMove(&'a Review),
+    NoMistakes,
+    // The number of games, inaccuracies and mistakes
+    ArchiveAnalyzed(usize, usize, usize),
+    SaveAnnotationsFailed(&'a str),
+    Advice(&'a Advice),
+    DailyPuzzle(&'a str, i32),
+    PuzzleSolved,
+    PuzzleFailed,
+    DailyAlreadyPlayed,
+    DailyRecord(&'a Profile),
+    PuzzleStart(&'a Puzzle),
+    PuzzleMistake(Mistake),
+    PuzzleAnswer(&'a Puzzle),
+    PuzzleStreak(&'a Profile),
+    NextPuzzle,
+    Bench(u32, Duration),
+    Verified(usize, Duration),
+    Disagreement(&'a Disagreement),
+    Tuning(u32, u64),
+    Generation(&'a Generation),
+    WeightsSaved(&'a str),
+    SaveWeightsFailed(&'a str),
+    LoadWeightsFailed(&'a str),
+    TreeSaved(&'a str, &'a SearchTree),
+    SaveTreeFailed(&'a str),
+    Tournament(usize, u32, u64),
+    Crosstable(&'a Crosstable),
+    SwissTournament(usize, u32, u32, u64),
+    SwissStandings(&'a Swiss),
+    KnockoutTournament(usize, u32, u64),
+    Bracket(&'a Bracket),
+    TournamentGame(&'a str, &'a str),
+    EngineMatch(Engine, Engine, u32, u64),
+    EngineMatchResults(Engine, Engine, &'a Results),
+    TournamentTurn(&'a str),
+    Ratings(&'a Ratings),
+    Thinking(Player, Duration),
+    // The search of the bot has panicked
+    SearchFailed,
+    SearchInfo(&'a SearchInfo),
+    ServeUnavailable,
+    SchemaUnavailable,
+    ImageUnavailable,
+    ImageSaved(&'a str),
+    SaveImageFailed(&'a str),
+    UnknownSchema(&'a str),
+    UnknownCommand(&'a str),
+    UnknownArgument(&'a str),
+    // A profile name given on the command line or in the configuration
+    NotAProfileName(&'a str),
+    // The commands and options of `tictactoe help`
+    Usage,
+    // A command or an option, and what it is missing
+    Needs(&'a str, Wanted),
+    // A command or an option, and the value it does not take
+    InvalidValue(&'a str, &'a str),
+    Entrants(&'a EntrantError),
+    Position(PositionError),
+    Code(CodeError),
+    // The time left to X and O
+    Clocks(Duration, Duration),
+    Serving(u16),
+    // A game of a server and its address
+    Watching(u64, &'a str),
+    // What the local engine thinks of a watched game
+    Kibitz(&'a Solution),
+    Hosting(u16),
+    Opponent(&'a str, Player),
+    Searching,
+    NetworkUnavailable,
+    NoHosts,
+    Hosts(&'a [Host]),
+    ChooseHost(usize),
+    WaitingFor(&'a str),
+    OpponentLeft(&'a str),
+    NetworkError(&'a str),
+    ChatHelp,
+    Chat(&'a str, &'a str),
+    DrawOffered(&'a str),
+    DrawOfferSent,
+    DrawDeclined,
+    NoDrawOffers,
+    NoOneToChat,
+    CorrespondenceTurn(Player),
+    SendCode(&'a str),
+    // Screen reader announcements
+    BoardRow(usize, [Player; 3]),
+    Plays(Move),
+    // Full-screen interface
+    EvaluationTitle,
+    Evaluating,
+    HistoryTitle,
+    WinsIn(Player, i32),
+    DrawnPosition,
+    SquareTaken(Player),
+    Replaying(usize),
+    TuiHelp,
+    TuiGameOverHelp,
+    // Desktop interface
+    GuiNewGame,
+    GuiHint,
+    GuiUndo,
+    GuiYourTurn,
+}
+
+impl From<GameResult> for Msg<'_> {
+    fn from(result: GameResult) -> Self {
+        match result {
+            GameResult::Win(p) => Msg::Won(p),
+            GameResult::Draw => Msg::Draw,
+            GameResult::Timeout(p) => Msg::Timeout(p),
+            GameResult::Resigned(p) => Msg::Resigned(p),
+            GameResult::DrawAgreed => Msg::DrawAgreed,
+        }
+    }
+}
+
+// The text of `tictactoe help`
+const USAGE_EN: &str = "\
+Usage: tictactoe [command] [options]
+
+Commands:
+  play               Play against the bot (the default)
+  stats              Summarize the logged games
+  openings           Browse the first two moves, with their value and how often
+                     they were played in the logged games
+  solve <position>   Give the value and best moves of a position, e.g. X.O/.X./...
+  solve --file <f>   Solve the positions of a file, one per line, as CSV
+  dataset [format]   Write every position with its value and best moves, as csv
+                     (the default) or jsonl
+  report [format]    Sum up the logged games as a page to share, in html (the
+                     default) or md
+  image <position>   Draw a position as a PNG picture, or each game of a log file,
+                     animated when the output is a .gif (needs the image feature)
+  tree <position>    Write the tree an alpha-beta search goes through as a
+                     Graphviz graph to --output (tree.dot by default), down to
+                     --depth moves
+  analyze <file>     Point out the mistakes of the games of a log file
+  analyze <position> Give the value of every move of a position, the threats of
+                     both players and the best play from there
+  analyze --archive <f>
+                     Annotate the mistakes of every game of a log file or an
+                     archive, for stats and report to show the accuracy of
+                     each player
+  bench [count]      Time the solver on the empty board
+  verify             Check the solver against a retrograde analysis of every
+                     position
+  tune [generations] Evolve the weights of the static evaluation over matches
+                     between them, saved to --output (weights.txt by default)
+  tournament [games] Play the engines (and players) against each other
+  match              Play two engines against each other without a board, e.g.
+                     --white minimax --black random --games 1000, writing the
+                     games to --log-file or the standard output
+  daily              Solve the puzzle of the day
+  puzzle [grade]     Solve puzzles one after the other, easy, medium or hard
+  host [port]        Wait for another player to join over the network
+  join [address]     Join the game of another player, found on the local network
+                     when no address is given
+  serve [port]       Serve games over HTTP and WebSockets
+  watch <address> <game>
+                     Follow a game of a server, with what the engine thinks of
+                     each position when --kibitz is given
+  continue [code]    Play the next move of a game by correspondence, starting one
+                     when no code is given
+  schema [name]      Print the JSON Schemas of the objects read and written as JSON";
+
+const USAGE_FR: &str = "\
+Usage : tictactoe [commande] [options]
+
+Commandes :
+  play               Jouer contre le bot (par défaut)
+  stats              Résumer les parties enregistrées
+  openings           Parcourir les deux premiers coups, avec leur valeur et leur
+                     fréquence dans les parties enregistrées
+  solve <position>   Donner la valeur et les meilleurs coups d'une position,
+                     par exemple X.O/.X./...
+  solve --file <f>   Résoudre les positions d'un fichier, une par ligne, en CSV
+  dataset [format]   Écrire chaque position avec sa valeur et ses meilleurs coups,
+                     en csv (par défaut) ou en jsonl
+  report [format]    Résumer les parties enregistrées en une page à partager, en
+                     html (par défaut) ou en md
+  image <position>   Dessiner une position en image PNG, ou chaque partie d'un
+                     fichier, animée quand la sortie est un .gif (demande la
+                     fonctionnalité image)
+  tree <position>    Écrire l'arbre que parcourt une recherche alpha-bêta en
+                     graphe Graphviz dans --output (tree.dot par défaut), sur
+                     --depth coups
+  analyze <fichier>  Relever les erreurs des parties d'un fichier
+  analyze <position> Donner la valeur de chaque coup d'une position, les menaces
+                     des deux joueurs et le meilleur jeu à partir de là
+  analyze --archive <f>
+                     Annoter les erreurs de chaque partie d'un fichier ou d'une
+                     archive, pour que stats et report montrent la précision de
+                     chaque joueur
+  bench [nombre]     Chronométrer le solveur sur la grille vide
+  verify             Vérifier le solveur par une analyse rétrograde de chaque
+                     position
+  tune [générations] Faire évoluer les poids de l'évaluation statique par des
+                     matchs entre eux, enregistrés dans --output (weights.txt
+                     par défaut)
+  tournament [parties]
+                     Faire jouer les moteurs (et les joueurs) les uns contre les
+                     autres
+  match              Faire jouer deux moteurs l'un contre l'autre sans grille,
+                     par exemple --white minimax --black random --games 1000,
+                     en écrivant les parties dans --log-file ou sur la sortie
+                     standard
+  daily              Résoudre le problème du jour
+  puzzle [niveau]    Résoudre des problèmes l'un après l'autre, easy, medium ou
+                     hard
+  host [port]        Attendre qu'un autre joueur se connecte par le réseau
+  join [adresse]     Rejoindre la partie d'un autre joueur, trouvée sur le réseau
+                     local quand aucune adresse n'est donnée
+  serve [port]       Servir des parties en HTTP et en WebSockets
+  watch <adresse> <partie>
+                     Suivre une partie d'un serveur, avec l'avis du moteur sur
+                     chaque position quand --kibitz est donné
+  continue [code]    Jouer le coup suivant d'une partie par correspondance, en en
+                     commençant une quand aucun code n'est donné
+  schema [nom]       Afficher les schémas JSON des objets lus et écrits en JSON";
+
+fn english(msg: Msg) -> String {
+    match msg {
+        Msg::EnterCoordinates => "Please enter some coordinates : ".to_string(),
+        Msg::EnterKeypad => "Please enter a square (1-9) : ".to_string(),
+        Msg::InvalidMove(e) => match e {
+            ParseError::Empty => "Please enter a move".to_string(),
+            ParseError::Unexpected(c) => {
+                format!("Unexpected {c:?}, please enter a row (a-c) and a column (1-3)")
+            }
+            ParseError::InvalidRow(c) => format!("The row must be a-c, not {c}"),
+            ParseError::InvalidColumn(c) => format!("The column must be 1-3, not {c}"),
+            ParseError::MissingRow => "The row (a-c) is missing".to_string(),
+            ParseError::MissingColumn => "The column (1-3) is missing".to_string(),
+            ParseError::TooLong => "Please enter only one row and one column".to_string(),
+            ParseError::InvalidSquare => "Please enter a number between 1 and 9".to_string(),
+        },
+        Msg::Occupied(p) => format!("Theses coordinates already have an {p}."),
+        Msg::Won(p) => format!("Player {p} won!"),
+        Msg::Draw => "Draw :(".to_string(),
+        Msg::DeadDraw => "Draw: nobody can make a line anymore :(".to_string(),
+        Msg::Timeout(p) => format!("Player {p} ran out of time, {} won!", p.opponent()),
+        Msg::Resigned(p) => format!("Player {p} resigned, {} won!", p.opponent()),
+        Msg::DrawAgreed => "The players agreed to a draw".to_string(),
+        Msg::CommandsHelp => {
+            "Type `resign` to give up the game, `draw` to offer a draw or `quit` to leave."
+                .to_string()
+        }
+        Msg::BlindfoldHelp(penalty) => format!(
+            "Blindfold: the board stays hidden. Type `peek` to see it, at the cost of {penalty} s."
+        ),
+        Msg::Peeked(penalty) => format!("You peek at the board (-{penalty} s)"),
+        Msg::Score(s) => format!("Score: X {} - {} O ({} draws)", s.x_wins, s.o_wins, s.draws),
+        Msg::PlayAgain => "Play again? (y/n) : ".to_string(),
+        Msg::ResumeGame(age) => format!(
+            "Unfinished game from {} ago, continue? (y/n) : ",
+            match age.as_secs() / 60 {
+                0 => "less than a minute".to_string(),
+                m @ 1..=59 => plural_en(m as i32, "minute"),
+                m @ 60..=1439 => plural_en(m as i32 / 60, "hour"),
+                m => plural_en((m / 1440) as i32, "day"),
+            }
+        ),
+        Msg::MatchGame(m) => format!("Game {} of {}", m.next_game(), m.length),
+        Msg::MatchScore(m) => format!("Match: X {} - {} O", m.score.x_wins, m.score.o_wins),
+        Msg::MatchWon(p) => format!("Player {p} won the match!"),
+        Msg::MatchTied => "The match is tied".to_string(),
+        Msg::BotSettings(difficulty, seed) => {
+            format!("Bot difficulty: {difficulty}, seed: {seed}")
+        }
+        Msg::AskProfileName => {
+            "Enter your name to keep track of your results (leave empty to play as a guest) : "
+                .to_string()
+        }
+        Msg::InvalidProfileName => "Please enter a valid name : ".to_string(),
+        Msg::ProfileRecord(p) => format!(
+            "{}: {} wins, {} losses, {} draws, rated {:.0}",
+            p.name, p.wins, p.losses, p.draws, p.rating
+        ),
+        Msg::NoDataDir => "Could not find where to save the data".to_string(),
+        Msg::LoadProfilesFailed(e) => format!("Could not load the profiles: {e}"),
+        Msg::SaveProfilesFailed(e) => format!("Could not save the profiles: {e}"),
+        Msg::AutosaveFailed(e) => {
+            format!("Could not save the game, it cannot be resumed after a crash: {e}")
+        }
+        Msg::SaveLogFailed(e) => format!("Could not log the game: {e}"),
+        Msg::LoadLogFailed(e) => format!("Could not read the game log: {e}"),
+        Msg::LoadCacheFailed(e) => format!("Could not read the cache, starting a new one: {e}"),
+        Msg::SaveCacheFailed(e) => format!("Could not save the cache: {e}"),
+        Msg::LoadPositionsFailed(e) => format!("Could not read the positions: {e}"),
+        Msg::TuiUnavailable => {
+            "This build does not include the full-screen interface (enable the `tui` feature)"
+                .to_string()
+        }
+        Msg::JsonUnavailable => {
+            "This build does not include the JSON mode (enable the `json` feature)".to_string()
+        }
+        Msg::StatsTitle(name, games) => format!("{name}: {games} games"),
+        Msg::NoGames(name) => format!("No games logged for {name}"),
+        Msg::StatsOverall(t) => format!("Overall: {}", tally_en(t)),
+        Msg::StatsDifficulty(d, t) => format!("Against the {d} bot: {}", tally_en(t)),
+        Msg::StatsSide(p, t) => format!("As {p}: {}", tally_en(t)),
+        Msg::AverageLength(moves) => format!("Average length: {moves:.1} moves"),
+        Msg::StatsAccuracy(stats) => format!(
+            "Accuracy: {:.0}% ({} analyzed, {}, {})",
+            stats.accuracy().unwrap_or(0.0),
+            plural_en(stats.analyzed as i32, "game"),
+            plural_en(stats.inaccuracies as i32, "inaccurate move"),
+            plural_en(stats.mistakes as i32, "mistake")
+        ),
+        Msg::StatsOpening(opening, t) => format!("Opening {opening}: {}", tally_en(t)),
+        Msg::OpeningLine(opening) => {
+            let played = match opening.played.games() {
+                0 => "never played".to_string(),
+                1 => format!("played once: {}", tally_en(opening.played)),
+                n => format!("played {n} times: {}", tally_en(opening.played)),
+            };
+            let value = english(opening_value(opening));
+            format!("{:<9}{value:<14}{played}", opening_moves(opening))
+        }
+        Msg::OpeningResults(opening, s) => format!(
+            "Opening {opening}: X {} - {} O ({} draws)",
+            s.x_wins, s.o_wins, s.draws
+        ),
+        Msg::ReportTitle => "Game report".to_string(),
+        Msg::ReportPeriod(games, first, last) => {
+            format!("{games} games played from {first} to {last}")
+        }
+        Msg::ReportSection(section) => match section {
+            Section::Results => "Results",
+            Section::Players => "Players",
+            Section::Openings => "Openings",
+            Section::Notable => "Notable games",
+        }
+        .to_string(),
+        Msg::ReportColumn(column) => match column {
+            Column::Player => "Player",
+            Column::Games => "Games",
+            Column::Wins => "Wins",
+            Column::Draws => "Draws",
+            Column::Losses => "Losses",
+            Column::WinRate => "Win rate",
+            Column::Rating => "Elo",
+            Column::Accuracy => "Accuracy",
+            Column::Opening => "Opening",
+            Column::XWins => "X wins",
+            Column::OWins => "O wins",
+            Column::AverageLength => "Average length",
+        }
+        .to_string(),
+        Msg::ReportNotable(game) => {
+            let highlight = match game.highlight {
+                Highlight::ShortestWin => "Shortest win".to_string(),
+                Highlight::LongestGame => "Longest game".to_string(),
+                Highlight::Turnaround(m, before) => {
+                    format!("Turnaround: {m} threw away {}", outcome_en(before))
+                }
+            };
+            let r = &game.record;
+            format!("{highlight}, {} - {} on {} at {}", r.x, r.o, r.date, r.time)
+        }
+        Msg::Solution(solution) => format!(
+            "{} to play: {}. Best moves: {}",
+            solution.player,
+            value_en(solution.value),
+            squares(&solution.best_moves)
+        ),
+        Msg::FinishedPosition => "The game is already over".to_string(),
+        Msg::MoveValuesTitle => "Moves:".to_string(),
+        Msg::MoveValue(m, value, reasons) => format!(
+            "  {}  {:<12}{}",
+            square(m),
+            value_en(value),
+            reasons_en(reasons)
+        ),
+        Msg::Explanation(m, reasons) => format!("{} {}", square(m), reasons_en(reasons)),
+        Msg::Threats(p, threats, forks) => format!(
+            "{p} threatens {}, forks: {}",
+            or_none(squares(threats), "nothing"),
+            or_none(squares(forks), "none")
+        ),
+        Msg::PrincipalVariation(moves) => {
+            format!("Best play: {}", or_none(move_list(moves), "none"))
+        }
+        Msg::GameHeader(n, r) => format!("Game {n}, {} {}: {} - {}", r.date, r.time, r.x, r.o),
+        Msg::MoveReview(review) => format!(
+            "Move {}, {}: {} (best: {})",
+            review.ply + 1,
+            review.played,
+            comment_en(review),
+            squares(&review.best_moves)
+        ),
+        Msg::OfferAnalysis => "Show the analysis of the game? (y/n) : ".to_string(),
+        Msg::ReportMove(review) => {
+            let line = format!(
+                "{}. {}: {}",
+                review.ply + 1,
+                review.played,
+                value_en(review.after)
+            );
+            match review.verdict() {
+                Verdict::Best => line,
+                _ => format!(
+                    "{line} - {} (best: {})",
+                    comment_en(review),
+                    squares(&review.best_moves)
+                ),
+            }
+        }
+        Msg::NoMistakes => "Every move was one of the best".to_string(),
+        Msg::ArchiveAnalyzed(games, inaccuracies, mistakes) => format!(
+            "Annotated {}: {} and {}",
+            plural_en(games as i32, "game"),
+            plural_en(inaccuracies as i32, "inaccurate move"),
+            plural_en(mistakes as i32, "mistake")
+        ),
+        Msg::SaveAnnotationsFailed(e) => format!("Could not write the annotations: {e}"),
+        Msg::DailyPuzzle(date, moves) => {
+            format!("Puzzle of {date}: X to play and win in {moves} moves")
+        }
+        Msg::PuzzleSolved => "Puzzle solved!".to_string(),
+        Msg::PuzzleFailed => "Puzzle failed, try again tomorrow".to_string(),
+        Msg::DailyAlreadyPlayed => {
+            "You already played today's puzzle, come back tomorrow".to_string()
+        }
+        Msg::DailyRecord(p) => format!(
+            "{}: {} daily puzzles solved out of {}",
+            p.name, p.dailies_solved, p.dailies_played
+        ),
+        Msg::PuzzleStart(puzzle) => format!(
+            "Puzzle ({}): {} to play and win in {}",
+            puzzle.grade,
+            puzzle.grid.player_turn,
+            plural_en(puzzle.moves, "move")
+        ),
+        Msg::PuzzleMistake(mistake) => {
+            let reason = match mistake {
+                Mistake::MissedWin(s) => format!("you could have won at once at {}", square(s)),
+                Mistake::AllowsWin(s) => format!("that lets your opponent win at {}", square(s)),
+                Mistake::Slower => "that wins, but not in time".to_string(),
+                Mistake::Blocked(s) => {
+                    format!("your only threat is blocked at {}", square(s))
+                }
+                Mistake::NoThreat => {
+                    "that threatens nothing, which leaves time to defend".to_string()
+                }
+            };
+            format!("Not the answer: {reason}")
+        }
+        Msg::PuzzleAnswer(puzzle) => {
+            let answer = square(puzzle.solution);
+            let threats = puzzle.threats();
+            match threats.as_slice() {
+                _ if puzzle.moves == 1 => format!("The answer was {answer}, which wins at once"),
+                [] => format!("The answer was {answer}"),
+                [s] => format!(
+                    "The answer was {answer}, which forces a block at {}",
+                    square(*s)
+                ),
+                _ => format!(
+                    "The answer was {answer}, which threatens {} at once",
+                    squares(&threats)
+                ),
+            }
+        }
+        Msg::PuzzleStreak(p) => format!(
+            "{}: {} in a row (best {})",
+            p.name,
+            plural_en(p.puzzle_streak as i32, "puzzle"),
+            p.best_puzzle_streak
+        ),
+        Msg::NextPuzzle => "Next puzzle? (y/n) : ".to_string(),
+        Msg::Advice(advice) => {
+            let review = &advice.review;
+            let opponent = review.played.player.opponent();
+            let reason = match (advice.flaw, review.verdict(), review.after) {
+                (Flaw::AllowsWin(s), _, _) => format!("that lets {opponent} win at {}", square(s)),
+                (Flaw::AllowsFork(s), _, _) => format!("that allows a fork at {}", square(s)),
+                (Flaw::MissedWin(s), _, _) => format!("you could have won at {}", square(s)),
+                (_, Verdict::Inaccuracy, Value::Win(_)) => "that wins slower".to_string(),
+                (_, Verdict::Inaccuracy, _) => "that loses faster".to_string(),
+                _ => format!(
+                    "that turns {} into {}",
+                    outcome_en(review.before),
+                    outcome_en(review.after)
+                ),
+            };
+            let best: Vec<String> = review.best_moves.iter().map(|&m| square(m)).collect();
+            let why = english(Msg::Explanation(review.best_moves[0], &advice.explanation));
+            format!("Coach: {reason}; {} was better: {why}", best.join(" or "))
+        }
+        Msg::SearchInfo(info) => format!(
+            "Searched {} {} deep in {:.1} ms, {} from the cache; best play: {}",
+            plural_en(i32::try_from(info.nodes).unwrap_or(i32::MAX), "position"),
+            plural_en(info.depth as i32, "move"),
+            info.elapsed.as_secs_f64() * 1000.0,
+            info.cache_hits,
+            or_none(pv(&info.pv), "none")
+        ),
+        Msg::Bench(n, elapsed) => format!(
+            "Solved the empty board {n} times in {:.2} s ({:.1} ms each)",
+            elapsed.as_secs_f64(),
+            elapsed.as_secs_f64() * 1000.0 / n as f64
+        ),
+        Msg::Verified(n, elapsed) => format!(
+            "Checked {n} positions in {:.2} s: the retrograde analysis agrees with minimax",
+            elapsed.as_secs_f64()
+        ),
+        Msg::Disagreement(d) => {
+            let (value, moves) = &d.retrograde;
+            let minimax = match &d.minimax {
+                Some((value, moves)) => format!("{} ({})", value_en(*value), squares(moves)),
+                None => "no solution".to_string(),
+            };
+            format!(
+                "{}: retrograde analysis {} ({}), minimax {minimax}",
+                position(&d.grid),
+                value_en(*value),
+                squares(moves)
+            )
+        }
+        Msg::Tuning(generations, seed) => {
+            format!("Tuning the evaluation over {generations} generations (seed {seed})")
+        }
+        Msg::Generation(g) => format!(
+            "Generation {}: {:.0}% of the points for {}",
+            g.number,
+            g.score * 100.0,
+            weights(&g.best)
+        ),
+        Msg::WeightsSaved(path) => format!("Weights saved to {path}"),
+        Msg::SaveWeightsFailed(e) => format!("Could not save the weights: {e}"),
+        Msg::LoadWeightsFailed(e) => format!("Could not read the weights: {e}"),
+        Msg::TreeSaved(path, tree) => format!(
+            "Search tree written to {path}: {} searched, {} cut off",
+            plural_en(i32::try_from(tree.searched()).unwrap_or(i32::MAX), "position"),
+            plural_en(i32::try_from(tree.pruned()).unwrap_or(i32::MAX), "move"),
+        ),
+        Msg::SaveTreeFailed(e) => format!("Could not write the search tree: {e}"),
+        Msg::Tournament(entrants, games, seed) => format!(
+            "Round robin between {entrants} entrants, {games} games per pairing (seed {seed})"
+        ),
+        Msg::Crosstable(table) => crosstable(table, "Score"),
+        Msg::SwissTournament(entrants, rounds, games, seed) => format!(
+            "Swiss tournament between {entrants} entrants, {rounds} rounds of {games} games (seed {seed})"
+        ),
+        Msg::SwissStandings(swiss) => swiss_standings(swiss, "Score"),
+        Msg::KnockoutTournament(entrants, best_of, seed) => format!(
+            "Knockout tournament between {entrants} entrants, best of {best_of} (seed {seed})"
+        ),
+        Msg::Bracket(bracket) => bracket_en(bracket),
+        Msg::TournamentGame(x, o) => format!("{x} plays X against {o}"),
+        Msg::EngineMatch(x, o, games, seed) => {
+            format!("{x} (X) against {o} (O), {games} games (seed {seed})")
+        }
+        Msg::EngineMatchResults(x, o, results) => {
+            let (score, margin) = results.score();
+            format!(
+                "{x} won {}, drew {}, {o} won {}\nScore of {x}: {:.1}% ± {:.1}% (95% confidence)",
+                rate(results, results.x_wins, "% to", "%"),
+                rate(results, results.draws, "% to", "%"),
+                rate(results, results.o_wins, "% to", "%"),
+                score * 100.0,
+                margin * 100.0
+            )
+        }
+        Msg::TournamentTurn(name) => format!("{name}, your move"),
+        Msg::Ratings(ratings) => format!("Elo ratings: {}", ratings_list(ratings)),
+        Msg::ServeUnavailable => "This version was built without the server feature".to_string(),
+        Msg::SchemaUnavailable => "This version was built without the schema feature".to_string(),
+        Msg::ImageUnavailable => "This version was built without the image feature".to_string(),
+        Msg::ImageSaved(path) => format!("Board drawn in {path}"),
+        Msg::SaveImageFailed(e) => format!("Could not save the picture: {e}"),
+        Msg::UnknownSchema(name) => {
+            format!("There is no schema named {name}, `tictactoe schema` gives them all")
+        }
+        Msg::UnknownCommand(name) => format!("Unknown command: {name}"),
+        Msg::UnknownArgument(arg) => format!("Unknown argument: {arg}"),
+        Msg::NotAProfileName(name) => format!("Invalid profile name: {name:?}"),
+        Msg::Usage => USAGE_EN.to_string(),
+        Msg::Needs(what, wanted) => format!("{what} needs {}", wanted_en(wanted)),
+        Msg::InvalidValue(what, value) => format!("Invalid value for {what}: {value}"),
+        Msg::Entrants(e) => e.to_string(),
+        Msg::Position(e) => e.to_string(),
+        Msg::Code(e) => e.to_string(),
+        Msg::Clocks(x, o) => format!("X {} | O {}", clock(x), clock(o)),
+        Msg::Serving(port) => {
+            format!("Serving games on port {port}: REST API at /games, WebSockets at /ws")
+        }
+        Msg::Watching(game, address) => format!("Watching game {game} on {address}"),
+        Msg::Kibitz(solution) => format!("Kibitzer: {}", english(Msg::Solution(solution))),
+        Msg::Hosting(port) => format!("Waiting for another player on port {port}..."),
+        Msg::Opponent(name, player) => format!("You play {player} against {name}"),
+        Msg::Searching => "Looking for games on the local network...".to_string(),
+        Msg::NetworkUnavailable => {
+            "This version was built without network games (the json feature)".to_string()
+        }
+        Msg::NoHosts => "No game was found on the local network".to_string(),
+        Msg::Hosts(hosts) => hosts_list(hosts),
+        Msg::ChooseHost(n) => format!("Which game do you want to join? (1-{n}, empty to cancel)"),
+        Msg::WaitingFor(name) => format!("Waiting for {name} to play..."),
+        Msg::OpponentLeft(name) => format!("{name} has left the game"),
+        Msg::NetworkError(e) => format!("Network error: {e}"),
+        Msg::ChatHelp => "Type `say` and a message to chat with your opponent.".to_string(),
+        Msg::Chat(name, text) => format!("{name}: {text}"),
+        Msg::NoOneToChat => "There is no one to talk to in this game".to_string(),
+        Msg::DrawOffered(name) => {
+            format!("{name} offers a draw: type `draw` to accept it, or play to decline it")
+        }
+        Msg::DrawOfferSent => "Draw offered, it stands until your opponent plays".to_string(),
+        Msg::DrawDeclined => "The draw is declined".to_string(),
+        Msg::NoDrawOffers => "Draws cannot be offered in this game".to_string(),
+        Msg::CorrespondenceTurn(player) => format!("You play {player}"),
+        Msg::SendCode(code) => format!("Send this code to your opponent: {code}"),
+        Msg::Thinking(player, elapsed) => {
+            format!("{player} is thinking... {:.1} s", elapsed.as_secs_f64())
+        }
+        Msg::SearchFailed => "The bot failed to find a move, the game stops here".to_string(),
+        Msg::BoardRow(y, row) => {
+            let squares: Vec<String> = row
+                .iter()
+                .map(|p| match p {
+                    Player::Empty => "empty".to_string(),
+                    p => p.to_string(),
+                })
+                .collect();
+            format!("Row {}: {}", row_name(y), squares.join(", "))
+        }
+        Msg::Plays(m) => format!("{} plays {}", m.player, coordinates(m.x, m.y)),
+        Msg::EvaluationTitle => "Evaluation".to_string(),
+        Msg::Evaluating => "Evaluating…".to_string(),
+        Msg::HistoryTitle => "History".to_string(),
+        Msg::WinsIn(p, n) => format!("{p} wins in {n}"),
+        Msg::DrawnPosition => "Draw".to_string(),
+        Msg::SquareTaken(p) => format!("This square already has an {p}."),
+        Msg::Replaying(ply) => format!("Replaying move {ply}. Esc or Enter: back to the game"),
+        Msg::TuiHelp => "Arrows or click: move, Enter: play, m: heatmap, q: quit".to_string(),
+        Msg::TuiGameOverHelp => "r: rematch, q: quit".to_string(),
+        Msg::GuiNewGame => "New game".to_string(),
+        Msg::GuiHint => "Hint".to_string(),
+        Msg::GuiUndo => "Undo".to_string(),
+        Msg::GuiYourTurn => "Your move, you play X".to_string(),
+    }
+}
+
+fn french(msg: Msg) -> String {
+    match msg {
+        Msg::EnterCoordinates => "Veuillez entrer des coordonnées : ".to_string(),
+        Msg::EnterKeypad => "Veuillez entrer une case (1-9) : ".to_string(),
+        Msg::InvalidMove(e) => match e {
+            ParseError::Empty => "Veuillez entrer un coup".to_string(),
+            ParseError::Unexpected(c) => {
+                format!("{c:?} inattendu, veuillez entrer une ligne (a-c) et une colonne (1-3)")
+            }
+            ParseError::InvalidRow(c) => format!("La ligne doit être a-c, pas {c}"),
+            ParseError::InvalidColumn(c) => format!("La colonne doit être 1-3, pas {c}"),
+            ParseError::MissingRow => "Il manque la ligne (a-c)".to_string(),
+            ParseError::MissingColumn => "Il manque la colonne (1-3)".to_string(),
+            ParseError::TooLong => {
+                "Veuillez entrer une seule ligne et une seule colonne".to_string()
+            }
+            ParseError::InvalidSquare => "Veuillez entrer un nombre entre 1 et 9".to_string(),
+        },
+        Msg::Occupied(p) => format!("Ces coordonnées contiennent déjà un {p}."),
+        Msg::Won(p) => format!("Le joueur {p} a gagné !"),
+        Msg::Draw => "Match nul :(".to_string(),
+        Msg::DeadDraw => "Match nul : plus personne ne peut aligner trois marques :(".to_string(),
+        Msg::Timeout(p) => format!(
+            "Le joueur {p} n'a plus de temps, {} a gagné !",
+            p.opponent()
+        ),
+        Msg::Resigned(p) => format!("Le joueur {p} abandonne, {} a gagné !", p.opponent()),
+        Msg::DrawAgreed => "Les joueurs se sont mis d'accord sur la nulle".to_string(),
+        Msg::CommandsHelp => {
+            "Tapez `abandon` pour abandonner la partie, `nulle` pour proposer la nulle ou `quitter` pour partir."
+                .to_string()
+        }
+        Msg::BlindfoldHelp(penalty) => format!(
+            "À l'aveugle : la grille reste cachée. Tapez `regarder` pour la voir, au prix de {penalty} s."
+        ),
+        Msg::Peeked(penalty) => format!("Vous regardez la grille (-{penalty} s)"),
+        Msg::Score(s) => format!("Score : X {} - {} O ({} nuls)", s.x_wins, s.o_wins, s.draws),
+        Msg::PlayAgain => "Rejouer ? (o/n) : ".to_string(),
+        Msg::ResumeGame(age) => format!(
+            "Partie inachevée d'il y a {}, la continuer ? (o/n) : ",
+            match age.as_secs() / 60 {
+                0 => "moins d'une minute".to_string(),
+                m @ 1..=59 => plural_fr(m as i32, "minute"),
+                m @ 60..=1439 => plural_fr(m as i32 / 60, "heure"),
+                m => plural_fr((m / 1440) as i32, "jour"),
+            }
+        ),
+        Msg::MatchGame(m) => format!("Partie {} sur {}", m.next_game(), m.length),
+        Msg::MatchScore(m) => format!("Match : X {} - {} O", m.score.x_wins, m.score.o_wins),
+        Msg::MatchWon(p) => format!("Le joueur {p} a gagné le match !"),
+        Msg::MatchTied => "Match nul".to_string(),
+        Msg::BotSettings(difficulty, seed) => {
+            format!(
+                "Difficulté du bot : {}, graine : {seed}",
+                difficulty_fr(difficulty)
+            )
+        }
+        Msg::AskProfileName => {
+            "Entrez votre nom pour garder vos résultats (laissez vide pour jouer en invité) : "
+                .to_string()
+        }
+        Msg::InvalidProfileName => "Veuillez entrer un nom valide : ".to_string(),
+        Msg::ProfileRecord(p) => format!(
+            "{} : {} victoires, {} défaites, {} nuls, classement {:.0}",
+            p.name, p.wins, p.losses, p.draws, p.rating
+        ),
+        Msg::NoDataDir => "Impossible de savoir où enregistrer les données".to_string(),
+        Msg::LoadProfilesFailed(e) => format!("Impossible de charger les profils : {e}"),
+        Msg::SaveProfilesFailed(e) => format!("Impossible d'enregistrer les profils : {e}"),
+        Msg::AutosaveFailed(e) => format!(
+            "Impossible d'enregistrer la partie, elle ne pourra pas être reprise après un plantage : {e}"
+        ),
+        Msg::SaveLogFailed(e) => format!("Impossible d'enregistrer la partie : {e}"),
+        Msg::LoadLogFailed(e) => format!("Impossible de lire l'historique des parties : {e}"),
+        Msg::LoadCacheFailed(e) => {
+            format!("Impossible de lire le cache, un nouveau est commencé : {e}")
+        }
+        Msg::SaveCacheFailed(e) => format!("Impossible d'enregistrer le cache : {e}"),
+        Msg::LoadPositionsFailed(e) => format!("Impossible de lire les positions : {e}"),
+        Msg::TuiUnavailable => {
+            "Cette version n'inclut pas l'interface plein écran (activez la fonctionnalité `tui`)"
+                .to_string()
+        }
+        Msg::JsonUnavailable => {
+            "Cette version n'inclut pas le mode JSON (activez la fonctionnalité `json`)".to_string()
+        }
+        Msg::StatsTitle(name, games) => format!("{name} : {games} parties"),
+        Msg::NoGames(name) => format!("Aucune partie enregistrée pour {name}"),
+        Msg::StatsOverall(t) => format!("Au total : {}", tally_fr(t)),
+        Msg::StatsDifficulty(d, t) => {
+            format!("Contre le bot {} : {}", difficulty_fr(d), tally_fr(t))
+        }
+        Msg::StatsSide(p, t) => format!("Avec les {p} : {}", tally_fr(t)),
+        Msg::StatsOpening(opening, t) => format!("Ouverture {opening} : {}", tally_fr(t)),
+        Msg::OpeningLine(opening) => {
+            let played = match opening.played.games() {
+                0 => "jamais jouée".to_string(),
+                1 => format!("jouée une fois : {}", tally_fr(opening.played)),
+                n => format!("jouée {n} fois : {}", tally_fr(opening.played)),
+            };
+            let value = french(opening_value(opening));
+            format!("{:<9}{value:<14}{played}", opening_moves(opening))
+        }
+        Msg::OpeningResults(opening, s) => format!(
+            "Ouverture {opening} : X {} - {} O ({} nuls)",
+            s.x_wins, s.o_wins, s.draws
+        ),
+        Msg::StatsAccuracy(stats) => format!(
+            "Précision : {:.0} % (analyse de {}, {}, {})",
+            stats.accuracy().unwrap_or(0.0),
+            plural_fr(stats.analyzed as i32, "partie"),
+            plural_fr(stats.inaccuracies as i32, "imprécision"),
+            plural_fr(stats.mistakes as i32, "erreur")
+        ),
+        Msg::AverageLength(moves) => {
+            format!(
+                "Durée moyenne : {} coups",
+                format!("{moves:.1}").replace('.', ",")
+            )
+        }
+        Msg::ReportTitle => "Bilan des parties".to_string(),
+        Msg::ReportPeriod(games, first, last) => {
+            format!("{games} parties jouées du {first} au {last}")
+        }
+        Msg::ReportSection(section) => match section {
+            Section::Results => "Résultats",
+            Section::Players => "Joueurs",
+            Section::Openings => "Ouvertures",
+            Section::Notable => "Parties marquantes",
+        }
+        .to_string(),
+        Msg::ReportColumn(column) => match column {
+            Column::Player => "Joueur",
+            Column::Games => "Parties",
+            Column::Wins => "Victoires",
+            Column::Draws => "Nuls",
+            Column::Losses => "Défaites",
+            Column::WinRate => "Taux de victoire",
+            Column::Rating => "Elo",
+            Column::Accuracy => "Précision",
+            Column::Opening => "Ouverture",
+            Column::XWins => "Victoires de X",
+            Column::OWins => "Victoires de O",
+            Column::AverageLength => "Durée moyenne",
+        }
+        .to_string(),
+        Msg::ReportNotable(game) => {
+            let highlight = match game.highlight {
+                Highlight::ShortestWin => "Victoire la plus rapide".to_string(),
+                Highlight::LongestGame => "Partie la plus longue".to_string(),
+                Highlight::Turnaround(m, before) => {
+                    format!("Retournement : {m} a laissé filer {}", outcome_fr(before))
+                }
+            };
+            let r = &game.record;
+            format!("{highlight}, {} - {} le {} à {}", r.x, r.o, r.date, r.time)
+        }
+        Msg::Solution(solution) => format!(
+            "{} joue : {}. Meilleurs coups : {}",
+            solution.player,
+            value_fr(solution.value),
+            squares(&solution.best_moves)
+        ),
+        Msg::FinishedPosition => "La partie est déjà finie".to_string(),
+        Msg::MoveValuesTitle => "Coups :".to_string(),
+        Msg::MoveValue(m, value, reasons) => format!(
+            "  {}  {:<12}{}",
+            square(m),
+            value_fr(value),
+            reasons_fr(reasons)
+        ),
+        Msg::Explanation(m, reasons) => format!("{} {}", square(m), reasons_fr(reasons)),
+        Msg::Threats(p, threats, forks) => format!(
+            "{p} menace {}, fourchettes : {}",
+            or_none(squares(threats), "rien"),
+            or_none(squares(forks), "aucune")
+        ),
+        Msg::PrincipalVariation(moves) => {
+            format!("Meilleur jeu : {}", or_none(move_list(moves), "aucun"))
+        }
+        Msg::GameHeader(n, r) => format!("Partie {n}, {} {} : {} - {}", r.date, r.time, r.x, r.o),
+        Msg::MoveReview(review) => format!(
+            "Coup {}, {} : {} (meilleur : {})",
+            review.ply + 1,
+            review.played,
+            comment_fr(review),
+            squares(&review.best_moves)
+        ),
+        Msg::OfferAnalysis => "Afficher l'analyse de la partie ? (o/n) : ".to_string(),
+        Msg::ReportMove(review) => {
+            let line = format!(
+                "{}. {} : {}",
+                review.ply + 1,
+                review.played,
+                value_fr(review.after)
+            );
+            match review.verdict() {
+                Verdict::Best => line,
+                _ => format!(
+                    "{line} - {} (meilleur : {})",
+                    comment_fr(review),
+                    squares(&review.best_moves)
+                ),
+            }
+        }
+        Msg::NoMistakes => "Tous les coups étaient parmi les meilleurs".to_string(),
+        Msg::ArchiveAnalyzed(games, inaccuracies, mistakes) => format!(
+            "Parties annotées : {games}, avec {} et {}",
+            plural_fr(inaccuracies as i32, "imprécision"),
+            plural_fr(mistakes as i32, "erreur")
+        ),
+        Msg::SaveAnnotationsFailed(e) => format!("Impossible d'écrire les annotations : {e}"),
+        Msg::DailyPuzzle(date, moves) => {
+            format!("Problème du {date} : X joue et gagne en {moves} coups")
+        }
+        Msg::PuzzleSolved => "Problème résolu !".to_string(),
+        Msg::PuzzleFailed => "Problème raté, réessayez demain".to_string(),
+        Msg::DailyAlreadyPlayed => {
+            "Vous avez déjà joué le problème du jour, revenez demain".to_string()
+        }
+        Msg::DailyRecord(p) => format!(
+            "{} : {} problèmes du jour résolus sur {}",
+            p.name, p.dailies_solved, p.dailies_played
+        ),
+        Msg::PuzzleStart(puzzle) => format!(
+            "Problème ({}) : {} joue et gagne en {}",
+            grade_fr(puzzle.grade),
+            puzzle.grid.player_turn,
+            plural_fr(puzzle.moves, "coup")
+        ),
+        Msg::PuzzleMistake(mistake) => {
+            let reason = match mistake {
+                Mistake::MissedWin(s) => format!("vous pouviez gagner tout de suite en {}", square(s)),
+                Mistake::AllowsWin(s) => format!("cela laisse l'adversaire gagner en {}", square(s)),
+                Mistake::Slower => "cela gagne, mais trop tard".to_string(),
+                Mistake::Blocked(s) => format!("votre seule menace est bloquée en {}", square(s)),
+                Mistake::NoThreat => {
+                    "cela ne menace rien et laisse le temps de se défendre".to_string()
+                }
+            };
+            format!("Ce n'est pas la solution : {reason}")
+        }
+        Msg::PuzzleAnswer(puzzle) => {
+            let answer = square(puzzle.solution);
+            let threats = puzzle.threats();
+            match threats.as_slice() {
+                _ if puzzle.moves == 1 => {
+                    format!("La solution était {answer}, qui gagne tout de suite")
+                }
+                [] => format!("La solution était {answer}"),
+                [s] => format!(
+                    "La solution était {answer}, qui force un blocage en {}",
+                    square(*s)
+                ),
+                _ => format!(
+                    "La solution était {answer}, qui menace à la fois {}",
+                    squares(&threats)
+                ),
+            }
+        }
+        Msg::PuzzleStreak(p) => format!(
+            "{} : {} d'affilée (record : {})",
+            p.name,
+            plural_fr(p.puzzle_streak as i32, "problème"),
+            p.best_puzzle_streak
+        ),
+        Msg::NextPuzzle => "Problème suivant ? (o/n) : ".to_string(),
+        Msg::Advice(advice) => {
+            let review = &advice.review;
+            let opponent = review.played.player.opponent();
+            let reason = match (advice.flaw, review.verdict(), review.after) {
+                (Flaw::AllowsWin(s), _, _) => {
+                    format!("cela laisse {opponent} gagner en {}", square(s))
+                }
+                (Flaw::AllowsFork(s), _, _) => {
+                    format!("cela permet une fourchette en {}", square(s))
+                }
+                (Flaw::MissedWin(s), _, _) => format!("vous pouviez gagner en {}", square(s)),
+                (_, Verdict::Inaccuracy, Value::Win(_)) => "cela gagne moins vite".to_string(),
+                (_, Verdict::Inaccuracy, _) => "cela perd plus vite".to_string(),
+                _ => format!(
+                    "cela transforme {} en {}",
+                    outcome_fr(review.before),
+                    outcome_fr(review.after)
+                ),
+            };
+            let best: Vec<String> = review.best_moves.iter().map(|&m| square(m)).collect();
+            let why = french(Msg::Explanation(review.best_moves[0], &advice.explanation));
+            format!("Coach : {reason} ; {} était meilleur : {why}", best.join(" ou "))
+        }
+        Msg::SearchInfo(info) => format!(
+            "Recherche : {} sur {} en {} ms, {} depuis le cache ; meilleur jeu : {}",
+            plural_fr(i32::try_from(info.nodes).unwrap_or(i32::MAX), "position"),
+            plural_fr(info.depth as i32, "coup"),
+            format!("{:.1}", info.elapsed.as_secs_f64() * 1000.0).replace('.', ","),
+            info.cache_hits,
+            or_none(pv(&info.pv), "aucun")
+        ),
+        Msg::Bench(n, elapsed) => format!(
+            "Grille vide résolue {n} fois en {:.2} s ({:.1} ms chacune)",
+            elapsed.as_secs_f64(),
+            elapsed.as_secs_f64() * 1000.0 / n as f64
+        )
+        .replace('.', ","),
+        Msg::Verified(n, elapsed) => format!(
+            "{n} positions vérifiées en {:.2} s : l'analyse rétrograde est d'accord avec minimax",
+            elapsed.as_secs_f64()
+        )
+        .replace('.', ","),
+        Msg::Disagreement(d) => {
+            let (value, moves) = &d.retrograde;
+            let minimax = match &d.minimax {
+                Some((value, moves)) => format!("{} ({})", value_fr(*value), squares(moves)),
+                None => "pas de solution".to_string(),
+            };
+            format!(
+                "{} : analyse rétrograde {} ({}), minimax {minimax}",
+                position(&d.grid),
+                value_fr(*value),
+                squares(moves)
+            )
+        }
+        Msg::Tuning(generations, seed) => {
+            format!("Réglage de l'évaluation sur {generations} générations (graine {seed})")
+        }
+        Msg::Generation(g) => format!(
+            "Génération {} : {:.0} % des points pour {}",
+            g.number,
+            g.score * 100.0,
+            weights(&g.best)
+        ),
+        Msg::WeightsSaved(path) => format!("Poids enregistrés dans {path}"),
+        Msg::SaveWeightsFailed(e) => format!("Impossible d'enregistrer les poids : {e}"),
+        Msg::LoadWeightsFailed(e) => format!("Impossible de lire les poids : {e}"),
+        Msg::TreeSaved(path, tree) => format!(
+            "Arbre de recherche écrit dans {path} (positions explorées : {}, coups coupés : {})",
+            tree.searched(),
+            tree.pruned()
+        ),
+        Msg::SaveTreeFailed(e) => format!("Impossible d'écrire l'arbre de recherche : {e}"),
+        Msg::Tournament(entrants, games, seed) => format!(
+            "Tournoi toutes rondes entre {entrants} participants, {games} parties par paire (graine {seed})"
+        ),
+        Msg::Crosstable(table) => crosstable(table, "Points"),
+        Msg::SwissTournament(entrants, rounds, games, seed) => format!(
+            "Tournoi suisse entre {entrants} participants, {rounds} rondes de {games} parties (graine {seed})"
+        ),
+        Msg::SwissStandings(swiss) => swiss_standings(swiss, "Points"),
+        Msg::KnockoutTournament(entrants, best_of, seed) => format!(
+            "Tournoi à élimination directe entre {entrants} participants, en {best_of} parties (graine {seed})"
+        ),
+        Msg::Bracket(bracket) => bracket_fr(bracket),
+        Msg::TournamentGame(x, o) => format!("{x} joue les X contre {o}"),
+        Msg::EngineMatch(x, o, games, seed) => {
+            format!("{x} (X) contre {o} (O), {games} parties (graine {seed})")
+        }
+        Msg::EngineMatchResults(x, o, results) => {
+            let (score, margin) = results.score();
+            format!(
+                "{x} a gagné {}, nulles {}, {o} a gagné {}\nScore de {x} : {:.1} % ± {:.1} % (confiance à 95 %)",
+                rate(results, results.x_wins, " % à", " %"),
+                rate(results, results.draws, " % à", " %"),
+                rate(results, results.o_wins, " % à", " %"),
+                score * 100.0,
+                margin * 100.0
+            )
+            .replace('.', ",")
+        }
+        Msg::TournamentTurn(name) => format!("{name}, à vous de jouer"),
+        Msg::Ratings(ratings) => format!("Classement Elo : {}", ratings_list(ratings)),
+        Msg::ServeUnavailable => {
+            "Cette version a été compilée sans la fonctionnalité server".to_string()
+        }
+        Msg::SchemaUnavailable => {
+            "Cette version a été compilée sans la fonctionnalité schema".to_string()
+        }
+        Msg::ImageUnavailable => {
+            "Cette version a été compilée sans la fonctionnalité image".to_string()
+        }
+        Msg::ImageSaved(path) => format!("Grille dessinée dans {path}"),
+        Msg::SaveImageFailed(e) => format!("Impossible d'enregistrer l'image : {e}"),
+        Msg::UnknownSchema(name) => {
+            format!("Il n'y a pas de schéma nommé {name}, `tictactoe schema` les donne tous")
+        }
+        Msg::UnknownCommand(name) => format!("Commande inconnue : {name}"),
+        Msg::UnknownArgument(arg) => format!("Argument inconnu : {arg}"),
+        Msg::NotAProfileName(name) => format!("Nom de profil invalide : {name:?}"),
+        Msg::Usage => USAGE_FR.to_string(),
+        Msg::Needs(what, wanted) => format!("{what} a besoin {}", wanted_fr(wanted)),
+        Msg::InvalidValue(what, value) => format!("Valeur invalide pour {what} : {value}"),
+        Msg::Entrants(e) => match e {
+            EntrantError::Invalid(name) => format!("Participant invalide : {name:?}"),
+            EntrantError::Twice(name) => format!("{name} est inscrit deux fois"),
+            EntrantError::TooFew => "Un tournoi a besoin d'au moins deux participants".to_string(),
+        },
+        Msg::Position(e) => match e {
+            PositionError::InvalidSquare(c) => {
+                format!("{c:?} n'est ni X, ni O, ni une case vide (.)")
+            }
+            PositionError::WrongLength(n) => format!("9 cases attendues, {n} données"),
+            PositionError::Unreachable => {
+                "cette position ne peut pas arriver dans une partie".to_string()
+            }
+        },
+        Msg::Code(e) => match e {
+            CodeError::Malformed => "ce n'est pas un code de partie, tel que X519-X".to_string(),
+            CodeError::Checksum => {
+                "le code a une faute de frappe, sa somme de contrôle ne correspond pas".to_string()
+            }
+            CodeError::IllegalMove(n) => format!("le coup {n} du code ne peut pas être joué"),
+        },
+        Msg::Clocks(x, o) => format!("X {} | O {}", clock(x), clock(o)),
+        Msg::Serving(port) => format!("Parties servies sur le port {port} : API REST sur /games, WebSockets sur /ws"),
+        Msg::Watching(game, address) => format!("Vous suivez la partie {game} sur {address}"),
+        Msg::Kibitz(solution) => format!("Kibitzeur : {}", french(Msg::Solution(solution))),
+        Msg::Hosting(port) => format!("En attente d'un autre joueur sur le port {port}..."),
+        Msg::Opponent(name, player) => format!("Vous jouez {player} contre {name}"),
+        Msg::Searching => "Recherche de parties sur le réseau local...".to_string(),
+        Msg::NetworkUnavailable => {
+            "Cette version a été compilée sans les parties en réseau (fonctionnalité json)"
+                .to_string()
+        }
+        Msg::NoHosts => "Aucune partie n'a été trouvée sur le réseau local".to_string(),
+        Msg::Hosts(hosts) => hosts_list(hosts),
+        Msg::ChooseHost(n) => {
+            format!("Quelle partie voulez-vous rejoindre ? (1-{n}, vide pour annuler)")
+        }
+        Msg::WaitingFor(name) => format!("En attente du coup de {name}..."),
+        Msg::OpponentLeft(name) => format!("{name} a quitté la partie"),
+        Msg::NetworkError(e) => format!("Erreur réseau : {e}"),
+        Msg::ChatHelp => {
+            "Tapez `dire` et un message pour discuter avec votre adversaire.".to_string()
+        }
+        Msg::Chat(name, text) => format!("{name} : {text}"),
+        Msg::NoOneToChat => "Il n'y a personne à qui parler dans cette partie".to_string(),
+        Msg::DrawOffered(name) => format!(
+            "{name} propose la nulle : tapez `nulle` pour l'accepter, ou jouez pour la refuser"
+        ),
+        Msg::DrawOfferSent => {
+            "Nulle proposée, l'offre tient jusqu'au coup de votre adversaire".to_string()
+        }
+        Msg::DrawDeclined => "La nulle est refusée".to_string(),
+        Msg::NoDrawOffers => "La nulle ne peut pas être proposée dans cette partie".to_string(),
+        Msg::CorrespondenceTurn(player) => format!("Vous jouez {player}"),
+        Msg::SendCode(code) => format!("Envoyez ce code à votre adversaire : {code}"),
+        Msg::Thinking(player, elapsed) => {
+            let elapsed = format!("{:.1}", elapsed.as_secs_f64()).replace('.', ",");
+            format!("{player} réfléchit... {elapsed} s")
+        }
+        Msg::SearchFailed => "Le bot n'a pas trouvé de coup, la partie s'arrête là".to_string(),
+        Msg::BoardRow(y, row) => {
+            let squares: Vec<String> = row
+                .iter()
+                .map(|p| match p {
+                    Player::Empty => "vide".to_string(),
+                    p => p.to_string(),
+                })
+                .collect();
+            format!("Ligne {} : {}", row_name(y), squares.join(", "))
+        }
+        Msg::Plays(m) => format!("{} joue {}", m.player, coordinates(m.x, m.y)),
+        Msg::EvaluationTitle => "Évaluation".to_string(),
+        Msg::Evaluating => "Évaluation…".to_string(),
+        Msg::HistoryTitle => "Historique".to_string(),
+        Msg::WinsIn(p, n) => format!("{p} gagne en {n}"),
+        Msg::DrawnPosition => "Nul".to_string(),
+        Msg::SquareTaken(p) => format!("Cette case contient déjà un {p}."),
+        Msg::Replaying(ply) => format!("Coup {ply} rejoué. Échap ou Entrée : retour à la partie"),
+        Msg::TuiHelp => "Flèches ou clic : déplacer, Entrée : jouer, m : carte des coups, q : quitter".to_string(),
+        Msg::TuiGameOverHelp => "r : revanche, q : quitter".to_string(),
+        Msg::GuiNewGame => "Nouvelle partie".to_string(),
+        Msg::GuiHint => "Conseil".to_string(),
+        Msg::GuiUndo => "Annuler".to_string(),
+        Msg::GuiYourTurn => "À vous de jouer, vous avez les X".to_string(),
+    }
+}
+
+// The moves of an opening, indented by their number so that the replies come
+// under the first moves
+fn opening_moves(opening: &Opening) -> String {
+    let moves: Vec<String> = opening.moves.iter().map(|&m| square(m)).collect();
+    format!(
+        "{}{}",
+        "  ".repeat(moves.len().saturating_sub(1)),
+        moves.join(" ")
+    )
+}
+
+// Who wins an opening with the best play, and in how many moves
+fn opening_value(opening: &Opening) -> Msg<'static> {
+    let next = opening.grid().player_turn;
+    match opening.value {
+        Value::Win(n) => Msg::WinsIn(next, n),
+        Value::Loss(n) => Msg::WinsIn(next.opponent(), n),
+        Value::Draw => Msg::DrawnPosition,
+    }
+}
+
+fn tally_en(t: Tally) -> String {
+    format!(
+        "{} wins, {} losses, {} draws ({:.0}% won)",
+        t.wins,
+        t.losses,
+        t.draws,
+        t.win_rate()
+    )
+}
+
+fn tally_fr(t: Tally) -> String {
+    format!(
+        "{} victoires, {} défaites, {} nuls ({:.0} % gagnées)",
+        t.wins,
+        t.losses,
+        t.draws,
+        t.win_rate()
+    )
+}
+
+// One line per entrant from the best to the worst, with their score against
+// each other entrant, numbered as in the first column, and their total
+fn crosstable(table: &Crosstable, score: &str) -> String {
+    let ranking = table.ranking();
+    let width = table
+        .names
+        .iter()
+        .map(|n| n.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    let mut header = format!("{:width$}    ", "");
+    for rank in 1..=ranking.len() {
+        header += &format!("{rank:>5}");
+    }
+    lines.push(format!("{header}  {score:>6}  +/=/-"));
+
+    for (rank, &i) in ranking.iter().enumerate() {
+        let mut line = format!("{:>2}. {:width$}", rank + 1, table.names[i]);
+        for &j in ranking.iter() {
+            match i == j {
+                true => line += "    -",
+                false => line += &format!("{:>5.1}", table.tallies[i][j].score()),
+            }
+        }
+        let total = table.total(i);
+        line += &format!(
+            "  {:>6.1}  {}/{}/{}",
+            total.score(),
+            total.wins,
+            total.draws,
+            total.losses
+        );
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+fn swiss_standings(swiss: &Swiss, score: &str) -> String {
+    let width = swiss
+        .names
+        .iter()
+        .map(|n| n.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines = vec![format!("{:width$}      {score:>6}  Buchholz", "")];
+    for (rank, &i) in swiss.standings().iter().enumerate() {
+        lines.push(format!(
+            "{:>2}. {:width$}  {:>6.1}  {:>8.1}",
+            rank + 1,
+            swiss.names[i],
+            swiss.score(i),
+            swiss.buchholz(i)
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn bracket_en(bracket: &Bracket) -> String {
+    let mut lines = Vec::new();
+    for (i, round) in bracket.rounds.iter().enumerate() {
+        lines.push(match bracket_rounds(bracket) - i {
+            1 => "Final".to_string(),
+            2 => "Semi-finals".to_string(),
+            3 => "Quarter-finals".to_string(),
+            _ => format!("Round {}", i + 1),
+        });
+        for heat in round {
+            let draws = |n| match n {
+                1 => "1 draw".to_string(),
+                n => format!("{n} draws"),
+            };
+            lines.push(heat_line(bracket, heat, "bye", draws));
+        }
+    }
+    if let Some(champion) = bracket.champion() {
+        lines.push(format!("Champion: {}", bracket.names[champion]));
+    }
+
+    lines.join("\n")
+}
+
+fn bracket_fr(bracket: &Bracket) -> String {
+    let mut lines = Vec::new();
+    for (i, round) in bracket.rounds.iter().enumerate() {
+        lines.push(match bracket_rounds(bracket) - i {
+            1 => "Finale".to_string(),
+            2 => "Demi-finales".to_string(),
+            3 => "Quarts de finale".to_string(),
+            _ => format!("Tour {}", i + 1),
+        });
+        for heat in round {
+            let draws = |n| match n {
+                1 => "1 nul".to_string(),
+                n => format!("{n} nuls"),
+            };
+            lines.push(heat_line(bracket, heat, "exempt", draws));
+        }
+    }
+    if let Some(champion) = bracket.champion() {
+        lines.push(format!("Vainqueur : {}", bracket.names[champion]));
+    }
+
+    lines.join("\n")
+}
+
+// The number of rounds until the final
+fn bracket_rounds(bracket: &Bracket) -> usize {
+    bracket.names.len().next_power_of_two().trailing_zeros() as usize
+}
+
+// The entrants of a heat with their seeds, and its score
+fn heat_line(bracket: &Bracket, heat: &Heat, bye: &str, draws: impl Fn(u32) -> String) -> String {
+    let width = bracket
+        .names
+        .iter()
+        .map(|n| n.chars().count())
+        .max()
+        .unwrap_or(0);
+    let entrant = |i: usize| format!("({}) {:width$}", i + 1, bracket.names[i]);
+    let Some(b) = heat.b else {
+        return format!("  {}  {bye}", entrant(heat.a));
+    };
+
+    let score = heat.score.score;
+    let mut line = format!(
+        "  {}  {}-{}  {}",
+        entrant(heat.a),
+        score.x_wins,
+        score.o_wins,
+        entrant(b)
+    );
+    if score.draws > 0 {
+        line = format!("{}, {}", line.trim_end(), draws(score.draws));
+    }
+    line.trim_end().to_string()
+}
+
+// Minutes and seconds, as a clock shows them
+fn clock(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn ratings_list(ratings: &Ratings) -> String {
+    let list: Vec<String> = ratings
+        .ranking()
+        .iter()
+        .map(|(name, rating)| format!("{name} {rating:.0}"))
+        .collect();
+    list.join(", ")
+}
+
+fn wanted_en(wanted: Wanted) -> &'static str {
+    match wanted {
+        Wanted::Path => "a path",
+        Wanted::File => "a file",
+        Wanted::Directory => "a directory",
+        Wanted::Name => "a name",
+        Wanted::Number => "a number",
+        Wanted::Seconds => "a number of seconds",
+        Wanted::Games => "a number of games",
+        Wanted::Rounds => "a number of rounds",
+        Wanted::Runs => "a number of runs",
+        Wanted::Generations => "a number of generations",
+        Wanted::Moves => "a number of moves",
+        Wanted::Pixels => "a number of pixels",
+        Wanted::Rate => "a rate between 0 and 1",
+        Wanted::Port => "a port",
+        Wanted::Level => "a level",
+        Wanted::Engine => "an engine",
+        Wanted::Palette => "a palette",
+        Wanted::Style => "a style",
+        Wanted::Theme => "a theme",
+        Wanted::Language => "a language",
+        Wanted::Scheme => "a scheme",
+        Wanted::Entrants => "a list of entrants",
+        Wanted::Position => "a position",
+        Wanted::PositionOrLog => "a position or a log file",
+        Wanted::PositionOrFile => "a position or --file",
+        Wanted::LogPositionOrArchive => "a log file, a position or --archive",
+        Wanted::AddressAndGame => "an address and a game",
+    }
+}
+
+fn wanted_fr(wanted: Wanted) -> &'static str {
+    match wanted {
+        Wanted::Path => "d'un chemin",
+        Wanted::File => "d'un fichier",
+        Wanted::Directory => "d'un dossier",
+        Wanted::Name => "d'un nom",
+        Wanted::Number => "d'un nombre",
+        Wanted::Seconds => "d'un nombre de secondes",
+        Wanted::Games => "d'un nombre de parties",
+        Wanted::Rounds => "d'un nombre de rondes",
+        Wanted::Runs => "d'un nombre d'essais",
+        Wanted::Generations => "d'un nombre de générations",
+        Wanted::Moves => "d'un nombre de coups",
+        Wanted::Pixels => "d'un nombre de pixels",
+        Wanted::Rate => "d'un taux entre 0 et 1",
+        Wanted::Port => "d'un port",
+        Wanted::Level => "d'un niveau",
+        Wanted::Engine => "d'un moteur",
+        Wanted::Palette => "d'une palette",
+        Wanted::Style => "d'un style",
+        Wanted::Theme => "d'un thème",
+        Wanted::Language => "d'une langue",
+        Wanted::Scheme => "d'une notation",
+        Wanted::Entrants => "d'une liste de participants",
+        Wanted::Position => "d'une position",
+        Wanted::PositionOrLog => "d'une position ou d'un fichier de parties",
+        Wanted::PositionOrFile => "d'une position ou de --file",
+        Wanted::LogPositionOrArchive => "d'un fichier de parties, d'une position ou de --archive",
+        Wanted::AddressAndGame => "d'une adresse et d'une partie",
+    }
+}
+
+fn value_en(value: Value) -> String {
+    match value {
+        Value::Win(n) => format!("wins in {n}"),
+        Value::Draw => "draw".to_string(),
+        Value::Loss(n) => format!("loses in {n}"),
+    }
+}
+
+fn value_fr(value: Value) -> String {
+    match value {
+        Value::Win(n) => format!("gagne en {n}"),
+        Value::Draw => "nul".to_string(),
+        Value::Loss(n) => format!("perd en {n}"),
+    }
+}
+
+// Why a move is not one of the best
+fn comment_en(review: &Review) -> String {
+    match (review.verdict(), review.after) {
+        (Verdict::Inaccuracy, Value::Win(_)) => "wins slower".to_string(),
+        (Verdict::Inaccuracy, _) => "loses faster".to_string(),
+        _ => format!(
+            "turned {} into {}",
+            outcome_en(review.before),
+            outcome_en(review.after)
+        ),
+    }
+}
+
+fn comment_fr(review: &Review) -> String {
+    match (review.verdict(), review.after) {
+        (Verdict::Inaccuracy, Value::Win(_)) => "gagne moins vite".to_string(),
+        (Verdict::Inaccuracy, _) => "perd plus vite".to_string(),
+        _ => format!(
+            "a transformé {} en {}",
+            outcome_fr(review.before),
+            outcome_fr(review.after)
+        ),
+    }
+}
+
+fn outcome_en(value: Value) -> &'static str {
+    match value {
+        Value::Win(_) => "a win",
+        Value::Draw => "a draw",
+        Value::Loss(_) => "a loss",
+    }
+}
+
+fn outcome_fr(value: Value) -> &'static str {
+    match value {
+        Value::Win(_) => "une victoire",
+        Value::Draw => "une nulle",
+        Value::Loss(_) => "une défaite",
+    }
+}
+
+// `n` followed by `word`, in the plural if needed
+fn plural_en(n: i32, word: &str) -> String {
+    match n {
+        1 => format!("1 {word}"),
+        _ => format!("{n} {word}s"),
+    }
+}
+
+// In French, zero is singular too
+fn plural_fr(n: i32, word: &str) -> String {
+    match n {
+        0 | 1 => format!("{n} {word}"),
+        _ => format!("{n} {word}s"),
+    }
+}
+
+fn square((x, y): (usize, usize)) -> String {
+    coordinates(x, y)
+}
+
+fn squares(moves: &[(usize, usize)]) -> String {
+    let squares: Vec<String> = moves.iter().map(|&(x, y)| coordinates(x, y)).collect();
+    squares.join(", ")
+}
+
+// A line of play, such as "b2 a1 c3"
+fn pv(moves: &[(usize, usize)]) -> String {
+    let squares: Vec<String> = moves.iter().map(|&(x, y)| coordinates(x, y)).collect();
+    squares.join(" ")
+}
+
+// The weights as in their file, on a single line, such as "one = 1, two = 3"
+fn weights(weights: &Weights) -> String {
+    weights.to_string().trim_end().replace('\n', ", ")
+}
+
+// A number of games with the 95% interval of their rate, such as
+// "812 (78.8% to 83.6%)"
+fn rate(results: &Results, count: u32, to: &str, percent: &str) -> String {
+    let (low, high) = results.interval(count);
+    format!(
+        "{count} ({:.1}{to} {:.1}{percent})",
+        low * 100.0,
+        high * 100.0
+    )
+}
+
+// What a move does, such as "blocks the threat on row a and threatens c3"
+fn reasons_en(reasons: &[Reason]) -> String {
+    let reasons: Vec<String> = reasons
+        .iter()
+        .map(|&reason| match reason {
+            Reason::Wins(line) => format!("wins on {}", line_en(line)),
+            Reason::Blocks(line) => format!("blocks the threat on {}", line_en(line)),
+            Reason::Forks(a, b) => {
+                format!(
+                    "creates a fork, threatening {} and {}",
+                    square(a),
+                    square(b)
+                )
+            }
+            Reason::Threatens(s) => format!("threatens to win at {}", square(s)),
+            Reason::PreventsFork => "stops a fork".to_string(),
+            Reason::AllowsWin(s) => format!("lets the opponent win at {}", square(s)),
+            Reason::AllowsFork(s) => format!("lets the opponent fork at {}", square(s)),
+            Reason::TakesCenter => "takes the center".to_string(),
+            Reason::TakesCorner => "takes a corner".to_string(),
+            Reason::TakesSide => "takes a side".to_string(),
+        })
+        .collect();
+    join_last(&reasons, " and ")
+}
+
+fn reasons_fr(reasons: &[Reason]) -> String {
+    let reasons: Vec<String> = reasons
+        .iter()
+        .map(|&reason| match reason {
+            Reason::Wins(line) => format!("gagne sur {}", line_fr(line)),
+            Reason::Blocks(line) => format!("bloque la menace sur {}", line_fr(line)),
+            Reason::Forks(a, b) => {
+                format!(
+                    "crée une fourchette, menaçant {} et {}",
+                    square(a),
+                    square(b)
+                )
+            }
+            Reason::Threatens(s) => format!("menace de gagner en {}", square(s)),
+            Reason::PreventsFork => "empêche une fourchette".to_string(),
+            Reason::AllowsWin(s) => format!("laisse l'adversaire gagner en {}", square(s)),
+            Reason::AllowsFork(s) => {
+                format!("permet une fourchette à l'adversaire en {}", square(s))
+            }
+            Reason::TakesCenter => "prend le centre".to_string(),
+            Reason::TakesCorner => "prend un coin".to_string(),
+            Reason::TakesSide => "prend un bord".to_string(),
+        })
+        .collect();
+    join_last(&reasons, " et ")
+}
+
+fn line_en(line: Line) -> String {
+    match line {
+        Line::Row(y) => format!("row {}", (b'a' + y as u8) as char),
+        Line::Column(x) => format!("column {}", x + 1),
+        Line::Diagonal => "the a1-c3 diagonal".to_string(),
+        Line::AntiDiagonal => "the a3-c1 diagonal".to_string(),
+    }
+}
+
+fn line_fr(line: Line) -> String {
+    match line {
+        Line::Row(y) => format!("la ligne {}", (b'a' + y as u8) as char),
+        Line::Column(x) => format!("la colonne {}", x + 1),
+        Line::Diagonal => "la diagonale a1-c3".to_string(),
+        Line::AntiDiagonal => "la diagonale a3-c1".to_string(),
+    }
+}
+
+// Separated by commas, but for the last one
+fn join_last(items: &[String], last: &str) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [rest @ .., item] => format!("{}{last}{item}", rest.join(", ")),
+    }
+}
+
+fn move_list(moves: &[Move]) -> String {
+    let moves: Vec<String> = moves.iter().map(|m| m.to_string()).collect();
+    moves.join(", ")
+}
+
+fn or_none(list: String, none: &str) -> String {
+    if list.is_empty() {
+        none.to_string()
+    } else {
+        list
+    }
+}
+
+// One numbered line per host, the same in every language
+fn hosts_list(hosts: &[Host]) -> String {
+    let lines: Vec<String> = hosts
+        .iter()
+        .enumerate()
+        .map(|(i, host)| format!("{}. {} ({})", i + 1, host.name, host.address))
+        .collect();
+    lines.join("\n")
+}
+
+fn difficulty_fr(difficulty: Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Easy => "facile",
+        Difficulty::Medium => "moyen",
+        Difficulty::Hard => "difficile",
+    }
+}
+
+fn grade_fr(grade: Grade) -> &'static str {
+    match grade {
+        Grade::Easy => "facile",
+        Grade::Medium => "moyen",
+        Grade::Hard => "difficile",
+    }
+}
+
+fn row_name(y: usize) -> char {
+    (b'a' + y as u8) as char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_locale() {
+        assert_eq!(Lang::from_locale("fr_FR.UTF-8"), Some(Lang::Fr));
+        assert_eq!(Lang::from_locale("en_US"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("de_DE.UTF-8"), None);
+        assert_eq!(Lang::from_locale("C"), None);
+    }
+
+    #[test]
+    fn translations() {
+        assert_eq!(Lang::En.text(Msg::Won(Player::O)), "Player O won!");
+        assert_eq!(Lang::Fr.text(Msg::Won(Player::O)), "Le joueur O a gagné !");
+    }
+
+    #[test]
+    fn command_line() {
+        assert_eq!(
+            Lang::En.text(Msg::UnknownCommand("plya")),
+            "Unknown command: plya"
+        );
+        assert_eq!(
+            Lang::Fr.text(Msg::UnknownArgument("-x")),
+            "Argument inconnu : -x"
+        );
+        let clocks = Msg::Clocks(Duration::from_secs(65), Duration::from_secs(600));
+        assert_eq!(Lang::Fr.text(clocks), "X 1:05 | O 10:00");
+
+        let needs = Msg::Needs("--file", Wanted::Path);
+        assert_eq!(Lang::En.text(needs), "--file needs a path");
+        assert_eq!(Lang::Fr.text(needs), "--file a besoin d'un chemin");
+        let invalid = Msg::InvalidValue("--difficulty", "impossible");
+        assert_eq!(
+            Lang::Fr.text(invalid),
+            "Valeur invalide pour --difficulty : impossible"
+        );
+        let unreachable = Msg::Position(PositionError::Unreachable);
+        assert_eq!(
+            Lang::En.text(unreachable),
+            PositionError::Unreachable.to_string()
+        );
+        assert!(Lang::Fr.text(Msg::Usage).starts_with("Usage : tictactoe"));
+    }
+
+    #[test]
+    fn puzzle() {
+        let (e, x, o) = (Player::Empty, Player::X, Player::O);
+        let grid = Grid::from([[e, e, o], [e, e, e], [x, o, x]]);
+        let puzzle = Puzzle {
+            grid,
+            moves: 2,
+            solution: (0, 0),
+            grade: Grade::Medium,
+        };
+
+        assert_eq!(
+            Lang::En.text(Msg::PuzzleStart(&puzzle)),
+            "Puzzle (medium): X to play and win in 2 moves"
+        );
+        assert_eq!(
+            Lang::En.text(Msg::PuzzleAnswer(&puzzle)),
+            "The answer was a1, which threatens b1, b2 at once"
+        );
+        assert_eq!(
+            Lang::Fr.text(Msg::PuzzleMistake(Mistake::Blocked((0, 0)))),
+            "Ce n'est pas la solution : votre seule menace est bloquée en a1"
+        );
+    }
+
+    #[test]
+    fn search_info() {
+        let info = SearchInfo {
+            nodes: 1234,
+            depth: 5,
+            elapsed: Duration::from_micros(2500),
+            cache_hits: 0,
+            pv: vec![(2, 0), (1, 1)],
+        };
+        assert_eq!(
+            Lang::En.text(Msg::SearchInfo(&info)),
+            "Searched 1234 positions 5 moves deep in 2.5 ms, 0 from the cache; best play: a3 b2"
+        );
+        assert_eq!(
+            Lang::Fr.text(Msg::SearchInfo(&info)),
+            "Recherche : 1234 positions sur 5 coups en 2,5 ms, 0 depuis le cache ; meilleur jeu : a3 b2"
+        );
+    }
+
+    #[test]
+    fn position_analysis() {
+        assert_eq!(
+            Lang::En.text(Msg::Threats(Player::O, &[(2, 1)], &[])),
+            "O threatens b3, forks: none"
+        );
+        let reasons = [Reason::Blocks(Line::Row(0)), Reason::Threatens((1, 2))];
+        assert_eq!(
+            Lang::En.text(Msg::Explanation((2, 0), &reasons)),
+            "a3 blocks the threat on row a and threatens to win at c2"
+        );
+        let moves = [
+            Move {
+                player: Player::X,
+                x: 2,
+                y: 0,
+            },
+            Move {
+                player: Player::O,
+                x: 1,
+                y: 1,
+            },
+        ];
+        assert_eq!(
+            Lang::Fr.text(Msg::PrincipalVariation(&moves)),
+            "Meilleur jeu : X a3, O b2"
+        );
+    }
+
+    #[test]
+    fn advice() {
+        let mut grid = Grid::new();
+        for (x, y) in [(0, 0), (1, 1), (2, 2)] {
+            grid.set(x, y).unwrap();
+        }
+        let played = Move {
+            player: Player::O,
+            x: 2,
+            y: 0,
+        };
+        let advice = crate::analysis::coach(&grid, played).unwrap();
+
+        assert_eq!(
+            Lang::En.text(Msg::Advice(&advice)),
+            "Coach: that allows a fork at c1; a2 or b1 or b3 or c2 was better: \
+             a2 threatens to win at c2"
+        );
+    }
+
+    #[test]
+    fn describe_board() {
+        let grid = Grid::from([
+            [Player::X, Player::Empty, Player::O],
+            [Player::Empty, Player::X, Player::Empty],
+            [Player::Empty, Player::Empty, Player::Empty],
+        ]);
+
+        assert_eq!(
+            Lang::En.describe_board(&grid),
+            "Row a: X, empty, O\nRow b: empty, X, empty\nRow c: empty, empty, empty"
+        );
+        assert_eq!(
+            Lang::Fr.text(Msg::Plays(Move {
+                player: Player::O,
+                x: 1,
+                y: 1
+            })),
+            "O joue b2"
+        );
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(Lang::En.command(" Resign\n"), Some(Command::Resign));
+        assert_eq!(Lang::Fr.command("quit"), Some(Command::Quit));
+        assert_eq!(Lang::Fr.command("abandon"), Some(Command::Resign));
+        assert_eq!(Lang::En.command("abandon"), None);
+        assert_eq!(Lang::En.command("b2"), None);
+    }
+
+    #[test]
+    fn chat() {
+        assert_eq!(Lang::En.chat("Say good game!"), Some("good game!"));
+        assert_eq!(Lang::Fr.chat("dire  bien joué"), Some("bien joué"));
+        assert_eq!(Lang::Fr.chat("say gg"), Some("gg"));
+        assert_eq!(Lang::En.chat("dire gg"), None);
+        assert_eq!(Lang::En.chat("b2"), None);
+    }
+
+    #[test]
+    fn resume_game() {
+        assert_eq!(
+            Lang::En.text(Msg::ResumeGame(Duration::from_secs(600))),
+            "Unfinished game from 10 minutes ago, continue? (y/n) : "
+        );
+        assert_eq!(
+            Lang::Fr.text(Msg::ResumeGame(Duration::from_secs(90_000))),
+            "Partie inachevée d'il y a 1 jour, la continuer ? (o/n) : "
+        );
+    }
+
+    #[test]
+    fn is_yes() {
+        assert!(Lang::En.is_yes("Yes\n"));
+        assert!(!Lang::En.is_yes("oui"));
+        assert!(Lang::Fr.is_yes("o"));
+        assert!(!Lang::Fr.is_yes("n"));
+    }
+}
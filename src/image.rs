@@ -0,0 +1,256 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::analysis::winning_line;
+use crate::record::GameRecord;
+use crate::{Grid, Player};
+
+pub type Rgb = [u8; 3];
+
+// The colors of a picture of the board
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Theme {
+    pub background: Rgb,
+    pub lines: Rgb,
+    pub x: Rgb,
+    pub o: Rgb,
+    // The stroke through the three marks of the winner
+    pub win: Rgb,
+}
+
+impl Theme {
+    pub const LIGHT: Theme = Theme {
+        background: [255, 255, 255],
+        lines: [60, 60, 60],
+        x: [200, 40, 40],
+        o: [40, 90, 200],
+        win: [240, 180, 0],
+    };
+
+    pub const DARK: Theme = Theme {
+        background: [30, 30, 36],
+        lines: [200, 200, 200],
+        x: [255, 110, 100],
+        o: [100, 170, 255],
+        win: [255, 215, 60],
+    };
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::LIGHT
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "light" => Ok(Theme::LIGHT),
+            "dark" => Ok(Theme::DARK),
+            _ => Err(format!("Unknown theme: {s}")),
+        }
+    }
+}
+
+// The width and height of a picture, in pixels
+pub const DEFAULT_SIZE: u32 = 300;
+pub const MIN_SIZE: u32 = 32;
+pub const MAX_SIZE: u32 = 4096;
+
+// A stroke, drawn with antialiased edges
+enum Shape {
+    Segment([f32; 2], [f32; 2]),
+    Ring([f32; 2], f32),
+}
+
+impl Shape {
+    // From the middle of the stroke
+    fn distance(&self, p: [f32; 2]) -> f32 {
+        match *self {
+            Shape::Segment(a, b) => {
+                let (ab, ap) = ([b[0] - a[0], b[1] - a[1]], [p[0] - a[0], p[1] - a[1]]);
+                let t = (ap[0] * ab[0] + ap[1] * ab[1]) / (ab[0] * ab[0] + ab[1] * ab[1]);
+                let t = t.clamp(0.0, 1.0);
+                (ap[0] - t * ab[0]).hypot(ap[1] - t * ab[1])
+            }
+            Shape::Ring(center, radius) => {
+                ((p[0] - center[0]).hypot(p[1] - center[1]) - radius).abs()
+            }
+        }
+    }
+}
+
+// The pixels of a picture of the board, `size` pixels wide and high, row by
+// row with three bytes each. The size is kept between `MIN_SIZE` and
+// `MAX_SIZE`.
+pub fn render(grid: &Grid, size: u32, theme: &Theme) -> Vec<u8> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let s = size as f32;
+    let margin = s * 0.05;
+    let cell = (s - 2.0 * margin) / 3.0;
+    let center = |x: usize, y: usize| {
+        [
+            margin + cell * (x as f32 + 0.5),
+            margin + cell * (y as f32 + 0.5),
+        ]
+    };
+
+    // Drawn in order, with their width and color
+    let mut shapes = Vec::new();
+    for i in 1..3 {
+        let at = margin + cell * i as f32;
+        shapes.push((
+            Shape::Segment([at, margin], [at, s - margin]),
+            s * 0.02,
+            theme.lines,
+        ));
+        shapes.push((
+            Shape::Segment([margin, at], [s - margin, at]),
+            s * 0.02,
+            theme.lines,
+        ));
+    }
+    let reach = cell * 0.3;
+    for (y, row) in grid.matrix().iter().enumerate() {
+        for (x, &square) in row.iter().enumerate() {
+            let [cx, cy] = center(x, y);
+            match square {
+                Player::X => {
+                    let stroke = s * 0.035;
+                    let (a, b) = ([cx - reach, cy - reach], [cx + reach, cy + reach]);
+                    shapes.push((Shape::Segment(a, b), stroke, theme.x));
+                    let (a, b) = ([cx - reach, cy + reach], [cx + reach, cy - reach]);
+                    shapes.push((Shape::Segment(a, b), stroke, theme.x));
+                }
+                Player::O => shapes.push((Shape::Ring([cx, cy], reach), s * 0.035, theme.o)),
+                Player::Empty => {}
+            }
+        }
+    }
+    if let Some(line) = winning_line(grid) {
+        let (a, b) = (center(line[0].0, line[0].1), center(line[2].0, line[2].1));
+        shapes.push((Shape::Segment(a, b), s * 0.025, theme.win));
+    }
+
+    let mut pixels = Vec::with_capacity((size * size * 3) as usize);
+    for py in 0..size {
+        for px in 0..size {
+            let p = [px as f32 + 0.5, py as f32 + 0.5];
+            let mut color = theme.background.map(f32::from);
+            for (shape, width, ink) in &shapes {
+                // The share of the pixel covered by the stroke
+                let coverage = (width / 2.0 + 0.5 - shape.distance(p)).clamp(0.0, 1.0);
+                for (c, &i) in color.iter_mut().zip(ink) {
+                    *c += (f32::from(i) - *c) * coverage;
+                }
+            }
+            pixels.extend(color.map(|c| c.round() as u8));
+        }
+    }
+
+    pixels
+}
+
+// The picture of the board as a PNG file
+pub fn write_png<W: Write>(out: W, grid: &Grid, size: u32, theme: &Theme) -> io::Result<()> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let mut encoder = png::Encoder::new(out, size, size);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&render(grid, size, theme))?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+// How long each move shows in an animation, and the final position before it
+// starts again, in hundredths of a second
+pub const MOVE_DELAY: u16 = 80;
+pub const END_DELAY: u16 = 300;
+
+// The position before the first move of a game and after each of its moves
+pub fn frames(record: &GameRecord) -> Vec<Grid> {
+    let session = record.replay();
+    (0..=session.history().len())
+        .map(|ply| session.position_at(ply))
+        .collect()
+}
+
+// Positions one after the other as a looping GIF animation, such as the
+// `frames` of a game
+pub fn write_gif<W: Write>(out: W, frames: &[Grid], size: u32, theme: &Theme) -> io::Result<()> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let side = size as u16;
+    let mut encoder = gif::Encoder::new(out, side, side, &[]).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+    for (i, grid) in frames.iter().enumerate() {
+        let mut frame = gif::Frame::from_rgb_speed(side, side, &render(grid, size, theme), 10);
+        frame.delay = if i + 1 == frames.len() {
+            END_DELAY
+        } else {
+            MOVE_DELAY
+        };
+        encoder.write_frame(&frame).map_err(io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(pixels: &[u8], size: u32, x: u32, y: u32) -> Rgb {
+        let i = ((y * size + x) * 3) as usize;
+        [pixels[i], pixels[i + 1], pixels[i + 2]]
+    }
+
+    #[test]
+    fn marks() {
+        let (e, x, o) = (Player::Empty, Player::X, Player::O);
+        let grid = Grid::from([[x, e, e], [e, o, e], [e, e, e]]);
+        let pixels = render(&grid, 90, &Theme::LIGHT);
+        assert_eq!(pixels.len(), 90 * 90 * 3);
+
+        // The middle of the X, the middle and the ring of the O, and a corner
+        assert_eq!(pixel(&pixels, 90, 17, 17), Theme::LIGHT.x);
+        assert_eq!(pixel(&pixels, 90, 45, 45), Theme::LIGHT.background);
+        assert_eq!(pixel(&pixels, 90, 45, 36), Theme::LIGHT.o);
+        assert_eq!(pixel(&pixels, 90, 0, 0), Theme::LIGHT.background);
+    }
+
+    #[test]
+    fn png() {
+        let mut file = Vec::new();
+        write_png(&mut file, &Grid::new(), 10, &Theme::DARK).unwrap();
+        assert!(file.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // Made as large as the smallest size
+        assert_eq!(&file[16..24], [0, 0, 0, 32, 0, 0, 0, 32]);
+        assert_eq!("Dark".parse(), Ok(Theme::DARK));
+    }
+
+    #[test]
+    fn animation() {
+        let record = GameRecord::parse(
+            "[Date \"2026-10-15\"]\n[Time \"20:00:00\"]\n[X \"alice\"]\n[O \"bob\"]\n\
+             [First \"X\"]\n[Result \"1-0\"]\n[Termination \"normal\"]\n\n\
+             b2 a1 c3 a3 a2 c1 c2 1-0\n",
+        )
+        .unwrap();
+        let frames = frames(&record);
+        assert_eq!(frames.len(), 8);
+        assert!(frames[7].has_winner());
+
+        let mut file = Vec::new();
+        write_gif(&mut file, &frames, 40, &Theme::LIGHT).unwrap();
+        assert!(file.starts_with(b"GIF89a"));
+        // Each frame starts with an image descriptor
+        let descriptors = file.windows(5).filter(|w| w == &[0x2c, 0, 0, 0, 0]);
+        assert_eq!(descriptors.count(), 8);
+    }
+}
@@ -0,0 +1,283 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::{self, IsTerminal};
+use std::process;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use tictactoe::replay::Recorder;
+
+// Says what is wrong with a line as it is being typed, if anything
+pub type Check = Box<dyn Fn(&str) -> Option<String> + Send>;
+
+pub enum Typed {
+    Line(String),
+    // Ctrl-C, which only readline catches
+    #[cfg_attr(not(feature = "readline"), allow(dead_code))]
+    Interrupted,
+    // Nothing was typed in time
+    Timeout,
+}
+
+// Lines typed by the player, read on a separate thread so that the game can go
+// on (e.g. a clock can run out) while waiting for them. On a terminal they are
+// edited with readline, which keeps the history of the previous lines.
+pub struct Input {
+    lines: mpsc::Receiver<Typed>,
+    // Asks the readline thread for a line, checked as it is typed. Without
+    // readline, the lines are read as soon as they are typed.
+    requests: Option<mpsc::Sender<Option<Check>>>,
+    // A line has been asked for and not received yet
+    waiting: Cell<bool>,
+    // Lines read ahead of time, e.g. while the bot was thinking
+    pending: RefCell<VecDeque<Typed>>,
+    // The lines come from a recording, and are only read when asked for
+    replaying: bool,
+    // Where the lines read are recorded
+    recorder: Option<Recorder>,
+}
+
+impl Input {
+    // Lines typed by the player, kept by `recorder` if there is one
+    pub fn spawn(recorder: Option<Recorder>) -> Input {
+        let (sender, lines) = mpsc::channel();
+        let requests = if io::stdin().is_terminal() {
+            readline::spawn(sender.clone())
+        } else {
+            None
+        };
+        if requests.is_none() {
+            spawn_plain(sender);
+        }
+
+        Input {
+            lines,
+            requests,
+            waiting: Cell::new(false),
+            pending: RefCell::new(VecDeque::new()),
+            replaying: false,
+            recorder,
+        }
+    }
+
+    // The lines of a recording, in place of the player
+    pub fn replay(inputs: Vec<String>) -> Input {
+        let (sender, lines) = mpsc::channel();
+        for line in inputs {
+            let _ = sender.send(Typed::Line(line));
+        }
+
+        Input {
+            lines,
+            requests: None,
+            waiting: Cell::new(false),
+            pending: RefCell::new(VecDeque::new()),
+            replaying: true,
+            recorder: None,
+        }
+    }
+
+    // The next line, leaving the game if there is no more input
+    pub fn read_line(&self) -> String {
+        match self.read(None, None) {
+            Typed::Line(line) => line,
+            _ => process::exit(0),
+        }
+    }
+
+    // The next line, checked with `check` as it is typed when that is
+    // possible, leaving the game if there is no more input
+    pub fn read(&self, check: Option<Check>, timeout: Option<Duration>) -> Typed {
+        let typed = self.next(check, timeout);
+        if let (Some(recorder), Typed::Line(line)) = (&self.recorder, &typed) {
+            let _ = recorder.record(line);
+        }
+
+        typed
+    }
+
+    fn next(&self, check: Option<Check>, timeout: Option<Duration>) -> Typed {
+        if let Some(typed) = self.pending.borrow_mut().pop_front() {
+            return typed;
+        }
+
+        if let Some(requests) = &self.requests {
+            if !self.waiting.get() {
+                if requests.send(check).is_err() {
+                    process::exit(0);
+                }
+                self.waiting.set(true);
+            }
+        }
+
+        let typed = match timeout {
+            Some(timeout) => match self.lines.recv_timeout(timeout) {
+                Ok(typed) => typed,
+                Err(RecvTimeoutError::Timeout) => return Typed::Timeout,
+                Err(RecvTimeoutError::Disconnected) => process::exit(0),
+            },
+            None => match self.lines.recv() {
+                Ok(typed) => typed,
+                Err(_) => process::exit(0),
+            },
+        };
+        self.waiting.set(false);
+
+        typed
+    }
+
+    // A line typed ahead of time, which will still be returned by the next
+    // read unless it is taken back with `take_pending`. With readline, lines
+    // are only read when asked for.
+    pub fn read_ahead(&self) -> Option<String> {
+        // Lines taken back are not recorded, so they are not read ahead when
+        // replayed either
+        if self.replaying || self.requests.is_some() && !self.waiting.get() {
+            return None;
+        }
+
+        let typed = self.lines.try_recv().ok()?;
+        self.waiting.set(false);
+        let line = match &typed {
+            Typed::Line(line) => Some(line.clone()),
+            _ => None,
+        };
+        self.pending.borrow_mut().push_back(typed);
+
+        line
+    }
+
+    // Like `read_ahead`, but also asks readline for a line, so that the player
+    // can type while waiting for the other player
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    pub fn type_ahead(&self) -> Option<String> {
+        if let (Some(requests), false) = (&self.requests, self.waiting.get()) {
+            if requests.send(None).is_err() {
+                process::exit(0);
+            }
+            self.waiting.set(true);
+        }
+
+        self.read_ahead()
+    }
+
+    // Take back the last line read ahead
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    pub fn take_last(&self) {
+        self.pending.borrow_mut().pop_back();
+    }
+
+    pub fn take_pending(&self) {
+        self.pending.borrow_mut().clear();
+    }
+}
+
+fn spawn_plain(sender: mpsc::Sender<Typed>) {
+    thread::spawn(move || {
+        for line in io::stdin().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(Typed::Line(line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(feature = "readline")]
+mod readline {
+    use std::borrow::Cow;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use rustyline::completion::Completer;
+    use rustyline::error::ReadlineError;
+    use rustyline::highlight::{CmdKind, Highlighter};
+    use rustyline::hint::{Hint, Hinter};
+    use rustyline::history::DefaultHistory;
+    use rustyline::validate::Validator;
+    use rustyline::{Context, Editor, Helper};
+
+    use super::{Check, Typed};
+
+    // Reads a line each time it is asked for, or returns `None` if the
+    // terminal cannot be used
+    pub fn spawn(sender: mpsc::Sender<Typed>) -> Option<mpsc::Sender<Option<Check>>> {
+        let mut editor: Editor<Checker, DefaultHistory> = Editor::new().ok()?;
+        let (requests, received) = mpsc::channel::<Option<Check>>();
+
+        thread::spawn(move || {
+            for check in received {
+                editor.set_helper(Some(Checker(check)));
+                let typed = match editor.readline("") {
+                    Ok(line) => {
+                        if !line.trim().is_empty() {
+                            let _ = editor.add_history_entry(line.as_str());
+                        }
+                        Typed::Line(line)
+                    }
+                    Err(ReadlineError::Interrupted) => Typed::Interrupted,
+                    Err(_) => break,
+                };
+                if sender.send(typed).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Some(requests)
+    }
+
+    struct Checker(Option<Check>);
+
+    // What is wrong with the line, shown dimmed after it
+    struct Problem(String);
+
+    impl Hint for Problem {
+        fn display(&self) -> &str {
+            &self.0
+        }
+
+        fn completion(&self) -> Option<&str> {
+            None
+        }
+    }
+
+    impl Hinter for Checker {
+        type Hint = Problem;
+
+        fn hint(&self, line: &str, _: usize, _: &Context<'_>) -> Option<Problem> {
+            let problem = self.0.as_ref()?(line)?;
+            Some(Problem(format!("  {problem}")))
+        }
+    }
+
+    impl Highlighter for Checker {
+        fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+            Cow::Owned(format!("\x1b[2m{hint}\x1b[0m"))
+        }
+
+        fn highlight_char(&self, _: &str, _: usize, _: CmdKind) -> bool {
+            false
+        }
+    }
+
+    impl Completer for Checker {
+        type Candidate = String;
+    }
+
+    impl Validator for Checker {}
+
+    impl Helper for Checker {}
+}
+
+#[cfg(not(feature = "readline"))]
+mod readline {
+    use std::sync::mpsc;
+
+    use super::{Check, Typed};
+
+    pub fn spawn(_: mpsc::Sender<Typed>) -> Option<mpsc::Sender<Option<Check>>> {
+        None
+    }
+}
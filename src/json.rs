@@ -0,0 +1,246 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{legal_moves, Bot};
+use crate::notation::parse_coordinates;
+use crate::record::termination_tag;
+use crate::session::{Clock, GameSession, Move, PlayError};
+use crate::{coordinates, Player};
+
+// A line-based JSON protocol for programs playing X against the bot. Each
+// time X has to play, a `State` is written on one line:
+//
+//     {"board":[["Empty","Empty","Empty"],["Empty","X","Empty"],["O","Empty","Empty"]],
+//      "turn":"X","legal_moves":["a1","a2",...],
+//      "events":[{"type":"move","player":"X","square":"b2"},{"type":"move","player":"O","square":"c1"}]}
+//
+// and one request is read back:
+//
+//     {"type":"move","square":"a3"}
+//     {"type":"resign"}
+//     {"type":"quit"}
+//
+// The last state has no `turn` and a `game_over` event.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Move { square: String },
+    Resign,
+    Quit,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Move {
+        player: Player,
+        square: String,
+    },
+    // The last request could not be played
+    Rejected {
+        reason: String,
+    },
+    GameOver {
+        winner: Option<Player>,
+        termination: String,
+    },
+}
+
+impl From<Move> for Event {
+    fn from(m: Move) -> Self {
+        Event::Move {
+            player: m.player,
+            square: coordinates(m.x, m.y),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct State {
+    pub board: [[Player; 3]; 3],
+    // `None` once the game is over
+    pub turn: Option<Player>,
+    pub legal_moves: Vec<String>,
+    // What happened since the last state
+    pub events: Vec<Event>,
+    // Only in games with a clock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockState>,
+}
+
+// The time left to each player, in milliseconds
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClockState {
+    pub x: u64,
+    pub o: u64,
+    // Whose time is running, `None` once the game is over
+    pub running: Option<Player>,
+}
+
+impl ClockState {
+    pub fn of(clock: &Clock) -> ClockState {
+        let millis = |player| clock.remaining(player).as_millis() as u64;
+        ClockState {
+            x: millis(Player::X),
+            o: millis(Player::O),
+            running: clock.running(),
+        }
+    }
+}
+
+impl State {
+    pub fn of(session: &GameSession, events: Vec<Event>) -> State {
+        let grid = session.grid();
+        let over = session.is_over();
+
+        State {
+            board: *grid.matrix(),
+            turn: (!over).then_some(grid.player_turn),
+            legal_moves: if over {
+                Vec::new()
+            } else {
+                legal_moves(grid)
+                    .into_iter()
+                    .map(|(x, y)| coordinates(x, y))
+                    .collect()
+            },
+            events,
+            clock: session.clock().map(ClockState::of),
+        }
+    }
+}
+
+// Play one game with the requests read from `input`, until it is over, the
+// program quits or the input ends
+pub fn run(input: impl BufRead, mut output: impl Write, bot: &mut Bot) -> io::Result<()> {
+    let mut session = GameSession::new();
+    let mut events = Vec::new();
+    let mut lines = input.lines();
+
+    loop {
+        while !session.is_over() && session.grid().player_turn == Player::O {
+            match session.play_with(bot) {
+                Some(m) => events.push(Event::from(m)),
+                None => break,
+            }
+        }
+        if let Some(result) = session.result() {
+            events.push(Event::GameOver {
+                winner: result.winner(),
+                termination: termination_tag(result).to_string(),
+            });
+        }
+
+        serde_json::to_writer(&mut output, &State::of(&session, events.split_off(0)))?;
+        writeln!(output)?;
+        output.flush()?;
+
+        if session.is_over() {
+            return Ok(());
+        }
+
+        let line = loop {
+            match lines.next() {
+                Some(line) if line.as_ref().is_ok_and(|l| l.trim().is_empty()) => continue,
+                Some(line) => break line?,
+                None => return Ok(()),
+            }
+        };
+
+        match serde_json::from_str(&line) {
+            Ok(Request::Move { square }) => match parse_coordinates(&square) {
+                Ok((x, y)) => match session.play(x, y) {
+                    Ok(m) => events.push(Event::from(m)),
+                    Err(PlayError::Occupied(p)) => events.push(Event::Rejected {
+                        reason: format!("{square} already has an {p}"),
+                    }),
+                    Err(PlayError::GameOver) => (),
+                },
+                Err(e) => events.push(Event::Rejected {
+                    reason: format!("{square}: {e}"),
+                }),
+            },
+            Ok(Request::Resign) => {
+                let _ = session.resign(Player::X);
+            }
+            Ok(Request::Quit) => return Ok(()),
+            Err(e) => events.push(Event::Rejected {
+                reason: e.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Difficulty;
+    use serde_json::Value;
+
+    fn play(input: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        let mut bot = Bot::new(Difficulty::Hard, 0);
+        run(input.as_bytes(), &mut output, &mut bot).unwrap();
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn first_state() {
+        let states = play("");
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0]["turn"], "X");
+        assert_eq!(states[0]["legal_moves"].as_array().unwrap().len(), 9);
+        assert_eq!(states[0]["events"], Value::Array(vec![]));
+        assert_eq!(states[0]["board"][1][1], "Empty");
+    }
+
+    #[test]
+    fn moves() {
+        let states = play("{\"type\":\"move\",\"square\":\"b2\"}\n");
+        assert_eq!(states.len(), 2);
+
+        let events = &states[1]["events"];
+        assert_eq!(events[0]["type"], "move");
+        assert_eq!(events[0]["player"], "X");
+        assert_eq!(events[0]["square"], "b2");
+        assert_eq!(events[1]["player"], "O");
+        assert_eq!(states[1]["board"][1][1], "X");
+        assert_eq!(states[1]["legal_moves"].as_array().unwrap().len(), 7);
+    }
+
+    #[test]
+    fn rejected() {
+        let states = play(
+            "{\"type\":\"move\",\"square\":\"d4\"}\n\
+             \n\
+             not json\n",
+        );
+        assert_eq!(states.len(), 3);
+        assert_eq!(states[1]["events"][0]["type"], "rejected");
+        assert_eq!(states[2]["events"][0]["type"], "rejected");
+        assert_eq!(states[2]["turn"], "X");
+    }
+
+    #[test]
+    fn resign_and_quit() {
+        let states = play("{\"type\":\"resign\"}\n{\"type\":\"quit\"}\n");
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[1]["turn"], Value::Null);
+        assert_eq!(
+            states[1]["events"][0],
+            serde_json::json!({"type": "game_over", "winner": "O", "termination": "resignation"})
+        );
+
+        assert_eq!(play("{\"type\":\"quit\"}\n").len(), 1);
+    }
+}
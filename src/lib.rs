@@ -1,19 +1,141 @@
-use std::fmt;
+// Without the `std` feature, only the grid and the search are left, for
+// targets such as microcontrollers
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "arcade")]
+pub mod arcade;
+#[cfg(feature = "storage")]
+pub mod archive;
+#[cfg(feature = "std")]
+pub mod arena;
+#[cfg(feature = "bevy")]
+pub mod bevy_plugin;
+#[cfg(feature = "std")]
+pub mod code;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod dataset;
+#[cfg(feature = "std")]
+pub mod elo;
+#[cfg(feature = "std")]
+pub mod engine;
+#[cfg(feature = "std")]
+pub mod eval;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod game;
+#[cfg(feature = "gui")]
+pub mod gui;
+#[cfg(feature = "std")]
+pub mod i18n;
+#[cfg(feature = "image")]
+pub mod image;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "server")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod net;
+#[cfg(feature = "std")]
+pub mod notation;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+#[cfg(feature = "std")]
+pub mod openings;
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+#[cfg(feature = "std")]
+pub mod protocol;
+#[cfg(feature = "std")]
+pub mod puzzle;
+#[cfg(feature = "std")]
+pub mod record;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod replay;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod retrograde;
+#[cfg(feature = "std")]
+pub mod rng;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "std")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod shared;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "std")]
+pub mod tournament;
+#[cfg(feature = "std")]
+pub mod tree;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "std")]
+pub mod tuning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Grid {
-    pub matrix: [[Player; 3]; 3],
+    // Only changed by `set`, see `matrix()`
+    matrix: [[Player; 3]; 3],
     pub number_of_turns: i32,
     pub player_turn: Player,
+    // Kept up to date by `set`, which only looks at the lines through the
+    // square played
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "schema", schemars(skip))]
+    status: Status,
+}
+
+// Whether a player has three in a row, as far as the grid knows
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+enum Status {
+    // Not looked at yet, e.g. for a grid read from JSON
+    #[default]
+    Unknown,
+    Ongoing,
+    Won(Player),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Player {
     X,
     O,
     Empty,
 }
 
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Self::X => Self::O,
+            Self::O => Self::X,
+            Self::Empty => Self::Empty,
+        }
+    }
+}
+
 impl fmt::Display for Player {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let c = match self {
@@ -26,44 +148,154 @@ impl fmt::Display for Player {
     }
 }
 
+// The rows, columns and diagonals, as (x, y) squares: the rows from a to c,
+// the columns from 1 to 3, then the diagonal from a1 to c3 and the one from a3
+// to c1
+pub const LINES: [[(usize, usize); 3]; 8] = [
+    [(0, 0), (1, 0), (2, 0)],
+    [(0, 1), (1, 1), (2, 1)],
+    [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (0, 1), (0, 2)],
+    [(1, 0), (1, 1), (1, 2)],
+    [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)],
+    [(2, 0), (1, 1), (0, 2)],
+];
+
+// The number of values given by `Grid::to_features`
+pub const FEATURES: usize = 27;
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid::new()
+    }
+}
+
+// Why a picture of a grid cannot be read as one
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PictureError {
+    // Neither `X`, `O`, a space nor a dot
+    InvalidSquare(char),
+    // A row without three squares
+    RowLength(usize),
+}
+
+impl fmt::Display for PictureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSquare(c) => write!(f, "invalid square {c:?}"),
+            Self::RowLength(n) => write!(f, "a row has {n} squares instead of 3"),
+        }
+    }
+}
+
+// A grid drawn row by row with `X`, `O`, and a space or a dot for the empty
+// squares, much easier to read than the players:
+//
+//     let grid = Grid::try_from(&["X.O", ".X.", "..."])?;
+impl TryFrom<[[char; 3]; 3]> for Grid {
+    type Error = PictureError;
+
+    fn try_from(picture: [[char; 3]; 3]) -> Result<Grid, PictureError> {
+        let mut matrix = [[Player::Empty; 3]; 3];
+        for (row, chars) in matrix.iter_mut().zip(picture) {
+            for (square, c) in row.iter_mut().zip(chars) {
+                *square = match c {
+                    'X' => Player::X,
+                    'O' => Player::O,
+                    ' ' | '.' => Player::Empty,
+                    c => return Err(PictureError::InvalidSquare(c)),
+                };
+            }
+        }
+
+        Ok(Grid::from(matrix))
+    }
+}
+
+impl TryFrom<&[&str; 3]> for Grid {
+    type Error = PictureError;
+
+    fn try_from(picture: &[&str; 3]) -> Result<Grid, PictureError> {
+        let mut chars = [[' '; 3]; 3];
+        for (row, line) in chars.iter_mut().zip(picture) {
+            let count = line.chars().count();
+            if count != 3 {
+                return Err(PictureError::RowLength(count));
+            }
+            for (square, c) in row.iter_mut().zip(line.chars()) {
+                *square = c;
+            }
+        }
+
+        Grid::try_from(chars)
+    }
+}
+
 impl Grid {
     pub fn new() -> Grid {
-        return Grid::from([
+        Grid::from([
             [Player::Empty, Player::Empty, Player::Empty],
             [Player::Empty, Player::Empty, Player::Empty],
             [Player::Empty, Player::Empty, Player::Empty],
-        ]);
+        ])
     }
 
     pub fn from(matrix: [[Player; 3]; 3]) -> Grid {
-        let number_of_turn = Grid::count_number_of_turns(matrix);
-
-        let player_turn = if number_of_turn & 1 == 0 {
-            Player::X
-        } else {
-            Player::O
+        let mut grid = Grid {
+            matrix,
+            number_of_turns: 0,
+            player_turn: Player::X,
+            status: Grid::find_winner(&matrix).map_or(Status::Ongoing, Status::Won),
         };
+        grid.number_of_turns = grid.occupied() as i32;
+        if grid.number_of_turns & 1 == 1 {
+            grid.player_turn = Player::O;
+        }
+
+        grid
+    }
+
+    // An empty grid where `player` plays first
+    pub fn starting_with(player: Player) -> Grid {
         Grid {
-            matrix,
-            number_of_turns: number_of_turn,
-            player_turn,
+            player_turn: player,
+            ..Grid::new()
         }
     }
 
-    fn count_number_of_turns(matrix: [[Player; 3]; 3]) -> i32 {
-        let mut number_of_turn = 0;
+    // The squares row by row, `matrix()[y][x]`. They only change through
+    // `set`, which keeps what the grid knows of the winner up to date: to
+    // change them otherwise, build another grid with `Grid::from`, as
+    // `with_mark` does.
+    pub fn matrix(&self) -> &[[Player; 3]; 3] {
+        &self.matrix
+    }
 
-        for &row in matrix.iter() {
-            for &square in row.iter() {
-                match square {
-                    Player::Empty => (),
-                    Player::O => number_of_turn += 1,
-                    Player::X => number_of_turn += 1,
-                }
-            }
-        }
+    // The grid with a mark of `player` on the square `(x, y)`, whoever is to
+    // move, for the analyses asking what a mark there would make
+    pub fn with_mark(&self, x: usize, y: usize, player: Player) -> Grid {
+        let mut matrix = self.matrix;
+        matrix[y][x] = player;
+        Grid::from(matrix)
+    }
+
+    // The marks of `player`, or the empty squares for `Player::Empty`
+    pub fn count(&self, player: Player) -> usize {
+        self.matrix
+            .iter()
+            .flatten()
+            .filter(|&&s| s == player)
+            .count()
+    }
 
-        number_of_turn
+    pub fn empty_count(&self) -> usize {
+        self.count(Player::Empty)
+    }
+
+    // The squares taken by either player
+    pub fn occupied(&self) -> usize {
+        9 - self.empty_count()
     }
 
     pub fn set(&mut self, x: usize, y: usize) -> Result<(), Player> {
@@ -71,12 +303,14 @@ impl Grid {
             return Err(self.matrix[y][x]);
         }
 
-        self.matrix[y][x] = self.player_turn;
+        let player = self.player_turn;
+        self.matrix[y][x] = player;
         self.number_of_turns += 1;
-        self.player_turn = if self.player_turn == Player::X {
-            Player::O
-        } else {
-            Player::X
+        self.player_turn = player.opponent();
+        self.status = match self.status {
+            Status::Ongoing if self.makes_line(x, y, player) => Status::Won(player),
+            Status::Unknown => Grid::find_winner(&self.matrix).map_or(Status::Ongoing, Status::Won),
+            status => status,
         };
 
         Ok(())
@@ -87,243 +321,469 @@ impl Grid {
     }
 
     pub fn best_play(&self) -> Option<(usize, usize)> {
-        let mut best_play = None;
-        let mut best_x = None;
-        let mut best_y = None;
-
-        let mut update_best_score = |score, x, y| match best_play {
-            Some(best_score) => {
-                if best_score < score {
-                    best_play = Some(score);
-                    best_x = Some(x);
-                    best_y = Some(y);
-                }
-            }
-            None => {
-                best_play = Some(score);
-                best_x = Some(x);
-                best_y = Some(y);
-            }
-        };
-
-        for (y, row) in self.matrix.iter().enumerate() {
-            for (x, _) in row.iter().enumerate() {
-                let mut g = self.clone();
-
-                match g.set(x, y) {
-                    Ok(_) => {
-                        let score = minimax(g);
-                        update_best_score(score, x, y);
-                    }
-                    Err(_) => (),
-                }
-            }
-        }
+        game::best_move(self)
+    }
 
-        let res = match best_play {
-            Some(_) => Some((best_x.unwrap(), best_y.unwrap())),
-            None => None,
-        };
+    // Every move tied for the best `score`, row by row, to choose among them
+    // or check that one is the only one. Empty once the game is over.
+    #[cfg(feature = "std")]
+    pub fn best_plays(&self) -> Vec<(usize, usize)> {
+        game::best_moves(self)
+    }
 
-        return res;
+    // The value of the position for the player to move when both players play
+    // their best. 0 for a draw. Otherwise the game ends with a line after
+    // `10 - score.abs()` marks on the board, and the score is positive if the
+    // player to move makes it and negative if their opponent does: 5 for a
+    // win on the fifth mark, -4 for a loss on the sixth. The sooner the win,
+    // the higher the score, and the later the loss. `score().signum()` gives
+    // just 1, 0 or -1.
+    //
+    // A finished game is valued the same way: a line made on the seventh
+    // mark is -3 for the player who did not make it, now to move.
+    pub fn score(&self) -> i32 {
+        game::negamax(self)
     }
 
-    pub fn has_winner(&self) -> bool {
-        if self.check_diag() {
-            return true;
-        }
-        for i in 0..3 {
-            if self.check_col(i) {
-                return true;
-            }
-            if self.check_row(i) {
-                return true;
-            }
+    // The score of playing each square for the player to move, indexed as
+    // `matrix`: the `score` of the player to move once they have played
+    // there. `None` for the taken squares, and everywhere once the game is
+    // over.
+    pub fn heatmap(&self) -> [[Option<i32>; 3]; 3] {
+        let mut heatmap = [[None; 3]; 3];
+        if game::Game::value(self).is_some() {
+            return heatmap;
         }
 
-        return false;
+        for (x, y) in game::Game::moves(self) {
+            let mut next = self.clone();
+            let _ = next.set(x, y);
+            heatmap[y][x] = Some(-next.score());
+        }
+        heatmap
     }
 
-    fn check_col(&self, col: usize) -> bool {
-        if self.matrix[0][col] == Player::Empty {
-            return false;
+    // The position as numbers for machine learning: three planes of nine
+    // squares each, row by row (a1, a2, a3, b1 and so on), holding the marks of
+    // the player to move, then those of their opponent, then the empty
+    // squares, as 1.0 where there is one and 0.0 elsewhere. Seen from the
+    // player to move, so that a model learns one game rather than one for X
+    // and one for O.
+    pub fn to_features(&self) -> [f32; FEATURES] {
+        let mut features = [0.0; FEATURES];
+        for (i, &square) in self.matrix.iter().flatten().enumerate() {
+            let plane = match square {
+                Player::Empty => 2,
+                p if p == self.player_turn => 0,
+                _ => 1,
+            };
+            features[9 * plane + i] = 1.0;
         }
 
-        self.matrix[0][col] == self.matrix[1][col] && self.matrix[1][col] == self.matrix[2][col]
+        features
     }
 
-    fn check_row(&self, row: usize) -> bool {
-        if self.matrix[row][0] == Player::Empty {
-            return false;
-        }
+    // Each line of `LINES` as its three squares, with their coordinates and
+    // who holds them:
+    //
+    //     let open = grid.lines().filter(|l| l.iter().all(|&(_, p)| p != Player::O));
+    pub fn lines(&self) -> impl Iterator<Item = [((usize, usize), Player); 3]> + '_ {
+        LINES
+            .iter()
+            .map(|line| line.map(|(x, y)| ((x, y), self.matrix[y][x])))
+    }
 
-        self.matrix[row][0] == self.matrix[row][1] && self.matrix[row][1] == self.matrix[row][2]
+    pub fn has_winner(&self) -> bool {
+        self.winner().is_some()
     }
 
-    fn check_diag(&self) -> bool {
-        if self.matrix[0][0] == self.matrix[1][1]
-            && self.matrix[1][1] == self.matrix[2][2]
-            && self.matrix[0][0] != Player::Empty
-        {
-            return true;
+    // Whether neither player can make a line anymore, however the game goes
+    // on: each line holds a mark of both players, or needs more marks than its
+    // player has moves left. The game is then a draw, full board or not.
+    pub fn is_dead(&self) -> bool {
+        if self.has_winner() {
+            return false;
         }
 
-        if self.matrix[2][0] == self.matrix[1][1]
-            && self.matrix[1][1] == self.matrix[0][2]
-            && self.matrix[0][2] != Player::Empty
-        {
-            return true;
-        }
+        let left = self.empty_count();
+        let moves_left = |player| {
+            if player == self.player_turn {
+                left.div_ceil(2)
+            } else {
+                left / 2
+            }
+        };
+        let open = |line: &[((usize, usize), Player); 3], player: Player| {
+            let empty = line.iter().filter(|&&(_, p)| p == Player::Empty).count();
+            line.iter().all(|&(_, p)| p != player.opponent()) && empty <= moves_left(player)
+        };
 
-        return false;
+        !self
+            .lines()
+            .any(|line| open(&line, Player::X) || open(&line, Player::O))
     }
-}
 
-pub fn minimax(grid: Grid) -> i32 {
-    if grid.has_winner() {
-        if grid.player_turn == Player::X {
-            // If the player O has won (as it has just played, it's X's turn)
-            return 10 - grid.number_of_turns;
-        } else {
-            return grid.number_of_turns - 10;
+    // The player with three in a row, if any: the last one to play, as the
+    // game stops there
+    pub fn winner(&self) -> Option<Player> {
+        match self.status {
+            Status::Won(player) => Some(player),
+            Status::Ongoing => None,
+            Status::Unknown => Grid::find_winner(&self.matrix),
         }
     }
 
-    if grid.is_full() {
-        return 0;
+    // The empty squares where `player` would make three in a row, row by row.
+    // The game may be over already: it is up to the caller to check.
+    pub fn winning_moves(&self, player: Player) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..9).map(|i| (i % 3, i / 3)).filter(move |&(x, y)| {
+            self.matrix[y][x] == Player::Empty && self.makes_line(x, y, player)
+        })
     }
 
-    let mut score = None;
+    // The squares where the opponent would win on their next move, which the
+    // player to move has to take unless they can win first
+    pub fn must_block(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.winning_moves(self.player_turn.opponent())
+    }
 
-    let mut update_score = |v2| match score {
-        Some(v) => {
-            if grid.player_turn == Player::X && v > v2 {
-                score = Some(v2);
-            }
-            if grid.player_turn == Player::O && v < v2 {
-                score = Some(v2);
-            }
-        }
-        None => score = Some(v2),
-    };
-
-    for (y, &row) in grid.matrix.iter().enumerate() {
-        for (x, &square) in row.iter().enumerate() {
-            if square == Player::Empty {
-                let mut ng = grid.clone();
-                let _ = ng.set(x, y);
-                let s = minimax(ng);
-                update_score(s);
+    // Whether a mark of `player` at (x, y) makes a line of three, looking only
+    // at the row, the column and the diagonals going through it
+    fn makes_line(&self, x: usize, y: usize, player: Player) -> bool {
+        let holds = |i: usize, j: usize| (i, j) == (x, y) || self.matrix[j][i] == player;
+        (holds(0, y) && holds(1, y) && holds(2, y))
+            || (holds(x, 0) && holds(x, 1) && holds(x, 2))
+            || (x == y && holds(0, 0) && holds(1, 1) && holds(2, 2))
+            || (x + y == 2 && holds(2, 0) && holds(1, 1) && holds(0, 2))
+    }
+
+    fn find_winner(matrix: &[[Player; 3]; 3]) -> Option<Player> {
+        for line in LINES {
+            let [a, b, c] = line.map(|(x, y)| matrix[y][x]);
+            if a != Player::Empty && a == b && b == c {
+                return Some(a);
             }
         }
+
+        None
+    }
+}
+
+// The positions searched by `minimax` since the program started
+#[cfg(feature = "std")]
+pub(crate) static NODES: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "std")]
+pub fn nodes_searched() -> u64 {
+    NODES.load(Ordering::Relaxed)
+}
+
+// Print the spans and events of the `tracing` feature to stderr, filtered by
+// `RUST_LOG` such as `tictactoe=debug`
+#[cfg(feature = "tracing")]
+pub fn init_tracing() {
+    let filter = tracing_subscriber::EnvFilter::from_default_env();
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
+
+// The `Grid::score` of `grid` from O's point of view rather than the player to
+// move: positive if O wins, the sooner the higher, negative if X wins, and 0
+// for a draw
+pub fn minimax(grid: Grid) -> i32 {
+    let value = grid.score();
+    if grid.player_turn == Player::O {
+        value
+    } else {
+        -value
     }
+}
 
-    return score.unwrap_or(0);
+#[cfg(feature = "std")]
+pub fn coordinates(x: usize, y: usize) -> String {
+    format!("{}{}", (b'a' + y as u8) as char, x + 1)
 }
 
+#[cfg(feature = "std")]
 pub fn display(grid: &Grid) {
-    let m = grid.matrix;
-    println!("   1   2   3");
-    println!("a  {} | {} | {} ", m[0][0], m[0][1], m[0][2]);
-    println!("  -----------");
-    println!("b  {} | {} | {} ", m[1][0], m[1][1], m[1][2]);
-    println!("  -----------");
-    println!("c  {} | {} | {} ", m[2][0], m[2][1], m[2][2]);
+    print!("{}", render::render(grid, render::Style::Small));
+}
+
+// The board with the empty squares scored by `Grid::heatmap` as `layout` draws
+// them, e.g. shaded from red for the quickest loss to green for the quickest
+// win
+#[cfg(feature = "std")]
+pub fn display_heatmap(grid: &Grid, layout: render::Layout, palette: render::Palette) {
+    print!("{}", layout.render_heatmap(grid, palette));
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn heat_label(score: i32) -> String {
+    if score == 0 {
+        "0".to_string()
+    } else {
+        format!("{score:+}")
+    }
+}
+
+// The shade of a score of `Grid::heatmap`: brighter green the sooner the win,
+// brighter red the sooner the loss, and yellow for a draw
+#[cfg(feature = "std")]
+pub fn heat_color(score: i32) -> (u8, u8, u8) {
+    // Scores are at most 9 either way
+    let strength = (score.unsigned_abs().min(9) * 15) as u8;
+    match score {
+        0 => (200, 190, 80),
+        s if s > 0 => (60, 120 + strength, 60),
+        _ => (120 + strength, 60, 60),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn heatmap() {
+        let grid = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        let heatmap = grid.heatmap();
+
+        assert_eq!(heatmap[0][0], None);
+        // X wins on the spot, on the 5th move
+        assert_eq!(heatmap[0][2], Some(5));
+        // Blocking O is only a draw, and anything else loses on the 6th move
+        assert_eq!(heatmap[1][2], Some(0));
+        assert_eq!(heatmap[2][0], Some(-4));
+        assert!(Grid::from([[Player::X; 3]; 3]).heatmap() == [[None; 3]; 3]);
+    }
+
+    #[test]
+    fn score() {
+        // X to move wins on the spot, with the fifth mark
+        let grid = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        assert_eq!(grid.score(), 5);
+        // O to move can only delay the loss to the seventh mark
+        let grid = Grid::try_from(&["XX.", "O..", "..."]).unwrap();
+        assert_eq!(grid.score(), -3);
+        assert_eq!(minimax(grid.clone()), grid.score());
+
+        assert_eq!(Grid::new().score(), 0);
+        // X made a line on the fifth mark, and O is left to move
+        let grid = Grid::try_from(&["XXX", "OO.", "..."]).unwrap();
+        assert_eq!(grid.score(), -5);
+        // Which `minimax` gives as X's win
+        assert_eq!(minimax(grid), -5);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn best_plays() {
+        // X can only win at once on a3
+        let grid = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        assert_eq!(grid.best_plays(), [(2, 0)]);
+        // Every first move keeps the draw
+        assert_eq!(Grid::new().best_plays().len(), 9);
+        assert_eq!(Grid::new().best_play(), Some((0, 0)));
+        assert!(Grid::try_from(&["XXX", "OO.", "..."])
+            .unwrap()
+            .best_plays()
+            .is_empty());
+    }
+
+    #[test]
+    fn features() {
+        let mut grid = Grid::new();
+        let _ = grid.set(1, 1);
+        let _ = grid.set(0, 0);
+        let features = grid.to_features();
+
+        // X to move has b2, O has a1, and the other seven squares are empty
+        assert_eq!(features[4], 1.0);
+        assert_eq!(features[9], 1.0);
+        assert_eq!(features[18..].iter().sum::<f32>(), 7.0);
+        assert_eq!(features.iter().sum::<f32>(), 9.0);
+    }
+
+    #[test]
+    fn lines() {
+        let grid = Grid::try_from(&["X.O", ".X.", "O.."]).unwrap();
+        let lines: Vec<_> = grid.lines().collect();
+
+        assert_eq!(lines.len(), 8);
+        assert_eq!(
+            lines[0],
+            [
+                ((0, 0), Player::X),
+                ((1, 0), Player::Empty),
+                ((2, 0), Player::O)
+            ]
+        );
+        // The diagonal from a1 to c3 is open to X only
+        assert_eq!(
+            lines[6].map(|(_, p)| p),
+            [Player::X, Player::X, Player::Empty]
+        );
+        assert_eq!(lines[7].map(|(square, _)| square), [(2, 0), (1, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn pictures() {
+        let grid = Grid::try_from([['X', ' ', 'O'], [' ', 'X', ' '], [' ', ' ', ' ']]).unwrap();
+        assert_eq!(grid.matrix()[0], [Player::X, Player::Empty, Player::O]);
+        assert_eq!(grid.player_turn, Player::O);
+        assert_eq!(
+            Grid::try_from(&["X O", ".X.", "..."]).unwrap().matrix(),
+            grid.matrix()
+        );
+
+        assert_eq!(
+            Grid::try_from(&["X.O", ".x.", "..."]).err(),
+            Some(PictureError::InvalidSquare('x'))
+        );
+        assert_eq!(
+            Grid::try_from(&["X.O", ".X", "..."]).err(),
+            Some(PictureError::RowLength(2))
+        );
+    }
+
     mod win {
         use super::*;
 
         #[test]
         fn check_diag() {
-            let grid = Grid::from([
-                [Player::X, Player::Empty, Player::Empty],
-                [Player::O, Player::X, Player::Empty],
-                [Player::O, Player::Empty, Player::X],
-            ]);
+            let grid = Grid::try_from(&["X..", "OX.", "O.X"]).unwrap();
 
-            assert_eq!(grid.has_winner(), true);
+            assert!(grid.has_winner());
         }
 
         #[test]
         fn check_antidiag() {
-            let grid = Grid::from([
-                [Player::O, Player::Empty, Player::X],
-                [Player::X, Player::X, Player::Empty],
-                [Player::X, Player::O, Player::O],
-            ]);
+            let grid = Grid::try_from(&["O.X", "XX.", "XOO"]).unwrap();
 
-            assert_eq!(grid.has_winner(), true);
+            assert!(grid.has_winner());
         }
 
         #[test]
         fn check_col() {
-            let grid = Grid::from([
-                [Player::O, Player::X, Player::Empty],
-                [Player::O, Player::X, Player::X],
-                [Player::O, Player::Empty, Player::X],
-            ]);
+            let grid = Grid::try_from(&["OX.", "OXX", "O.X"]).unwrap();
 
-            assert_eq!(grid.has_winner(), true);
+            assert!(grid.has_winner());
         }
 
         #[test]
         fn check_row() {
-            let grid = Grid::from([
-                [Player::X, Player::X, Player::X],
-                [Player::O, Player::X, Player::Empty],
-                [Player::O, Player::Empty, Player::O],
-            ]);
+            let grid = Grid::try_from(&["XXX", "OX.", "O.O"]).unwrap();
 
-            assert_eq!(grid.has_winner(), true);
+            assert!(grid.has_winner());
         }
 
         #[test]
         fn no_winner() {
-            let grid = Grid::from([
-                [Player::X, Player::Empty, Player::Empty],
-                [Player::O, Player::O, Player::X],
-                [Player::O, Player::X, Player::X],
-            ]);
+            let grid = Grid::try_from(&["X..", "OOX", "OXX"]).unwrap();
+
+            assert!(!grid.has_winner());
+        }
+
+        #[test]
+        fn winning_and_blocking_moves() {
+            let grid = Grid::try_from(&["X.X", "OO.", "X.."]).unwrap();
+            let wins = |player| grid.winning_moves(player).collect::<Vec<_>>();
+
+            // O to move can win on b3, and would otherwise have to stop X on a2
+            assert_eq!(wins(Player::O), [(2, 1)]);
+            assert_eq!(grid.must_block().collect::<Vec<_>>(), [(1, 0)]);
+            assert_eq!(wins(Player::X), grid.must_block().collect::<Vec<_>>());
+            assert_eq!(Grid::new().winning_moves(Player::X).count(), 0);
+        }
 
-            assert_eq!(grid.has_winner(), false);
+        #[test]
+        fn dead_positions() {
+            // Every line is blocked with c3 left to play
+            let grid = Grid::try_from(&["XOX", "XOO", "OX."]).unwrap();
+            assert!(grid.is_dead());
+            // Column 3 is still empty but for X, who has one move left
+            let grid = Grid::try_from(&["XOX", "XO.", "OX."]).unwrap();
+            assert!(grid.is_dead());
+            let grid = Grid::try_from(&["XOX", "OX.", "O.."]).unwrap();
+            assert!(!grid.is_dead());
+
+            // Never a position that someone can still win
+            fn walk(grid: &Grid, dead: &mut usize) {
+                if grid.is_dead() {
+                    assert_eq!(grid.score(), 0);
+                    *dead += 1;
+                }
+                if grid.has_winner() {
+                    return;
+                }
+                for (x, y) in game::Game::moves(grid) {
+                    let mut next = grid.clone();
+                    let _ = next.set(x, y);
+                    walk(&next, dead);
+                }
+            }
+            let mut dead = 0;
+            walk(&Grid::new(), &mut dead);
+            assert!(dead > 0);
+        }
+
+        #[test]
+        fn kept_by_set() {
+            // Every game there is, checking the lines through the last move
+            // against all of them
+            fn walk(grid: &Grid) {
+                assert_eq!(grid.winner(), Grid::find_winner(grid.matrix()));
+                if grid.has_winner() {
+                    return;
+                }
+                for (x, y) in game::Game::moves(grid) {
+                    let mut next = grid.clone();
+                    let _ = next.set(x, y);
+                    walk(&next);
+                }
+            }
+            walk(&Grid::new());
+
+            // Not known until looked at, as for a grid read from JSON
+            let mut grid = Grid {
+                status: Status::Unknown,
+                ..Grid::try_from(&["XX.", "OO.", "..."]).unwrap()
+            };
+            assert_eq!(grid.winner(), None);
+            let _ = grid.set(2, 0);
+            assert_eq!(grid.status, Status::Won(Player::X));
         }
 
         #[test]
         fn empty_no_winner() {
-            let grid = Grid::from([
-                [Player::Empty, Player::Empty, Player::Empty],
-                [Player::Empty, Player::Empty, Player::Empty],
-                [Player::Empty, Player::Empty, Player::Empty],
-            ]);
+            let grid = Grid::try_from(&["...", "...", "..."]).unwrap();
 
-            assert_eq!(grid.has_winner(), false);
+            assert!(!grid.has_winner());
         }
 
         #[test]
         fn is_full() {
-            let grid = Grid::from([
-                [Player::X, Player::X, Player::O],
-                [Player::O, Player::O, Player::X],
-                [Player::O, Player::X, Player::X],
-            ]);
+            let grid = Grid::try_from(&["XXO", "OOX", "OXX"]).unwrap();
 
-            assert_eq!(grid.is_full(), true);
+            assert!(grid.is_full());
         }
 
         #[test]
         fn is_not_full() {
-            let grid = Grid::from([
-                [Player::Empty, Player::X, Player::O],
-                [Player::O, Player::Empty, Player::X],
-                [Player::O, Player::X, Player::X],
-            ]);
+            let grid = Grid::try_from(&[".XO", "O.X", "OXX"]).unwrap();
 
-            assert_eq!(grid.is_full(), false);
+            assert!(!grid.is_full());
+        }
+
+        #[test]
+        fn counts() {
+            let grid = Grid::try_from(&[".XO", "..X", "..."]).unwrap();
+
+            assert_eq!((grid.count(Player::X), grid.count(Player::O)), (2, 1));
+            assert_eq!((grid.empty_count(), grid.occupied()), (6, 3));
+            assert_eq!(grid.player_turn, Player::O);
         }
     }
 
@@ -332,24 +792,32 @@ mod tests {
 
         #[test]
         fn immediate_win() {
-            let grid = Grid::from([
-                [Player::Empty, Player::O, Player::Empty],
-                [Player::X, Player::O, Player::X],
-                [Player::Empty, Player::Empty, Player::X],
-            ]);
+            let grid = Grid::try_from(&[".O.", "XOX", "..X"]).unwrap();
 
             assert_eq!(grid.best_play(), Some((1, 2)));
         }
 
         #[test]
         fn immediate_lose() {
-            let grid = Grid::from([
-                [Player::O, Player::Empty, Player::X],
-                [Player::Empty, Player::Empty, Player::X],
-                [Player::Empty, Player::Empty, Player::Empty],
-            ]);
+            let grid = Grid::try_from(&["O.X", "..X", "..."]).unwrap();
 
             assert_eq!(grid.best_play(), Some((2, 2)));
         }
+
+        #[test]
+        fn plays_as_x() {
+            let grid = Grid::try_from(&["XO.", ".X.", "O.."]).unwrap();
+
+            assert_eq!(grid.best_play(), Some((2, 2)));
+        }
+
+        #[test]
+        fn starting_with_o() {
+            let mut grid = Grid::starting_with(Player::O);
+            let _ = grid.set(1, 1);
+
+            assert_eq!(grid.matrix()[1][1], Player::O);
+            assert_eq!(grid.player_turn, Player::X);
+        }
     }
 }
@@ -1,116 +1,1482 @@
-use std::io;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::Duration;
+use tictactoe::analysis;
+use tictactoe::arena::Engine;
+use tictactoe::config::{self, Config};
+use tictactoe::dataset;
+use tictactoe::elo;
+use tictactoe::engine::{Bot, Cache, Difficulty, OpponentModel, WaitError};
+use tictactoe::eval::Weights;
+use tictactoe::i18n::{Lang, Msg, Wanted};
+use tictactoe::net;
+use tictactoe::notation::{Command, InputScheme};
+use tictactoe::profile::{self, Profile, Profiles};
+use tictactoe::puzzle::Grade;
+use tictactoe::record::{self, GameRecord};
+use tictactoe::render::{self, Layout, Palette, Style};
+use tictactoe::replay::{Recorder, Recording};
+use tictactoe::report;
+use tictactoe::rng;
+use tictactoe::session::{self, GameResult, GameSession, Match, Move, PlayError, Scoreboard};
+use tictactoe::tournament::{self, Entrant, Format};
+use tictactoe::tuning::Tuning;
 use tictactoe::*;
 
+use crate::input::{Check, Input, Typed};
+
+mod commands;
+mod input;
+#[cfg(feature = "json")]
+mod network;
+
+// Network games send the messages of the protocol as JSON
+#[cfg(not(feature = "json"))]
+mod network {
+    use std::process;
+    use tictactoe::i18n::Msg;
+
+    use crate::Ui;
+
+    pub fn host(ui: &Ui, _: &str, _: u16) {
+        eprintln!("{}", ui.lang.text(Msg::NetworkUnavailable));
+        process::exit(2);
+    }
+
+    pub fn join(ui: &Ui, _: &str, _: Option<&str>) {
+        host(ui, "", 0);
+    }
+}
+
+// What peeking at the board costs in blindfold mode, unless set otherwise
+const PEEK_PENALTY: Duration = Duration::from_secs(5);
+
+// The name of the player in the game log when they have no profile
+const GUEST: &str = "Guest";
+
+// The port of `tictactoe serve` when none is given
+const SERVE_PORT: u16 = 8080;
+
+// How long the bot thinks before it is shown
+const THINKING_DELAY: Duration = Duration::from_millis(200);
+
+// How often something else is done while waiting for the player to type
+const POLL: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+enum Subcommand {
+    #[default]
+    Play,
+    Help,
+    Stats,
+    Openings,
+    // Without a position, those of the file given with `--file` are solved
+    Solve(Option<String>),
+    Dataset(dataset::Format),
+    Report(report::Format),
+    // A position, or a log file for the final board of each game
+    Image(String),
+    // A position
+    Tree(String),
+    // Without a log file or a position, the games given with `--archive` are
+    // annotated
+    Analyze(Option<PathBuf>),
+    Bench(u32),
+    Verify,
+    // The number of generations
+    Tune(u32),
+    // The number of games of each pairing
+    Tournament(u32),
+    // The engines are given with `--white` and `--black`
+    EngineMatch,
+    Daily,
+    // Without a grade, puzzles of every grade are given
+    Puzzle(Option<Grade>),
+    Host(u16),
+    // Without an address, the host is looked for on the local network
+    Join(Option<String>),
+    Serve(u16),
+    // The address of a server and the number of one of its games
+    Watch(String, u64),
+    // Without a code, a new game is started
+    Continue(Option<String>),
+    // Without a name, all the schemas are printed
+    Schema(Option<String>),
+}
+
+#[derive(Default)]
+struct Options {
+    command: Subcommand,
+    // Read the moves from stdin without prompting, for scripts
+    script: bool,
+    // Talk JSON on stdin and stdout, for other programs
+    json: bool,
+    tui: bool,
+    profile: Option<String>,
+    clock: Option<Duration>,
+    difficulty: Option<Difficulty>,
+    // How often the bot expects the player to blunder, to set traps for them
+    exploit: Option<f64>,
+    // The weights of the static evaluation, for the bot to search with them
+    weights: Option<PathBuf>,
+    // Show what the searches of the bot and `analyze` went through
+    verbose: bool,
+    // Where the bot and `analyze` keep the positions they searched
+    cache: Option<PathBuf>,
+    seed: Option<u64>,
+    lang: Option<Lang>,
+    scheme: Option<InputScheme>,
+    // `None` when neither the command line nor the configuration say
+    accessible: Option<bool>,
+    config: Option<PathBuf>,
+    log: Option<bool>,
+    log_file: Option<PathBuf>,
+    // The positions solved by `solve`
+    file: Option<PathBuf>,
+    // The games annotated by `analyze`
+    archive: Option<PathBuf>,
+    // The pictures drawn by `image`
+    output: Option<PathBuf>,
+    // How deep the tree of `tree` is drawn
+    depth: Option<u32>,
+    size: Option<u32>,
+    // The colors of the pictures drawn by `image`
+    theme: Option<String>,
+    // How the board is drawn in the terminal
+    style: Style,
+    palette: Option<Palette>,
+    // Play a best-of-N match instead of single games
+    best_of: Option<u32>,
+    coach: bool,
+    // Call a draw as soon as nobody can make a line
+    early_draw: bool,
+    blindfold: bool,
+    peek_penalty: Option<Duration>,
+    // The clients allowed to start games on the server
+    auth_file: Option<PathBuf>,
+    // Where the server saves its games
+    storage: Option<PathBuf>,
+    // Serve the web client along with the games
+    ui: bool,
+    // Have the engine follow the game of `watch`
+    kibitz: bool,
+    // The engines and players of a tournament
+    entrants: Option<Vec<Entrant>>,
+    // The engines of `match`, playing X and O, and how many games they play
+    white: Option<Engine>,
+    black: Option<Engine>,
+    games: Option<u32>,
+    format: Format,
+    // How much a game can change an Elo rating
+    k_factor: Option<u32>,
+    // Where to record the run, and the recorder once it is started
+    record: Option<PathBuf>,
+    recorder: Option<Recorder>,
+    // The run to replay, and the lines typed during it once it is read
+    replay: Option<PathBuf>,
+    inputs: Option<Vec<String>>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let lang = args_lang(args);
+    let needs = |what: &str, wanted| lang.text(Msg::Needs(what, wanted));
+    let invalid = |what: &str, value: &str| lang.text(Msg::InvalidValue(what, value));
+    let mut options = Options::default();
+    let mut args = args.iter().cloned().peekable();
+
+    if let Some(name) = args.next_if(|arg| !arg.starts_with('-')) {
+        options.command = match name.as_str() {
+            "play" => Subcommand::Play,
+            "help" => Subcommand::Help,
+            "stats" => Subcommand::Stats,
+            "openings" => Subcommand::Openings,
+            "dataset" => match args.next_if(|arg| !arg.starts_with('-')) {
+                Some(format) => {
+                    Subcommand::Dataset(format.parse().map_err(|_| invalid("dataset", &format))?)
+                }
+                None => Subcommand::Dataset(dataset::Format::Csv),
+            },
+            "image" => {
+                let target = args
+                    .next()
+                    .ok_or_else(|| needs("image", Wanted::PositionOrLog))?;
+                Subcommand::Image(target)
+            }
+            "tree" => Subcommand::Tree(args.next().ok_or_else(|| needs("tree", Wanted::Position))?),
+            "report" => match args.next_if(|arg| !arg.starts_with('-')) {
+                Some(format) => {
+                    Subcommand::Report(format.parse().map_err(|_| invalid("report", &format))?)
+                }
+                None => Subcommand::Report(report::Format::Html),
+            },
+            "solve" => Subcommand::Solve(args.next_if(|arg| !arg.starts_with('-'))),
+            "analyze" => {
+                Subcommand::Analyze(args.next_if(|arg| !arg.starts_with('-')).map(PathBuf::from))
+            }
+            "bench" => {
+                let count = args.next_if(|arg| !arg.starts_with('-'));
+                let count = match count {
+                    Some(count) => count
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| needs("bench", Wanted::Runs))?,
+                    None => 10,
+                };
+                Subcommand::Bench(count)
+            }
+            "verify" => Subcommand::Verify,
+            "tune" => match args.next_if(|arg| !arg.starts_with('-')) {
+                Some(generations) => Subcommand::Tune(
+                    generations
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| needs("tune", Wanted::Generations))?,
+                ),
+                None => Subcommand::Tune(Tuning::default().generations),
+            },
+            "tournament" => {
+                let games = args.next_if(|arg| !arg.starts_with('-'));
+                let games = match games {
+                    Some(games) => games
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| needs("tournament", Wanted::Games))?,
+                    None => 10,
+                };
+                Subcommand::Tournament(games)
+            }
+            "match" => Subcommand::EngineMatch,
+            "daily" => Subcommand::Daily,
+            "puzzle" => match args.next_if(|arg| !arg.starts_with('-')) {
+                Some(grade) => {
+                    Subcommand::Puzzle(Some(grade.parse().map_err(|_| invalid("puzzle", &grade))?))
+                }
+                None => Subcommand::Puzzle(None),
+            },
+            "host" => match args.next_if(|arg| !arg.starts_with('-')) {
+                Some(port) => {
+                    Subcommand::Host(port.parse().map_err(|_| needs("host", Wanted::Port))?)
+                }
+                None => Subcommand::Host(net::DEFAULT_PORT),
+            },
+            "join" => Subcommand::Join(args.next_if(|arg| !arg.starts_with('-'))),
+            "serve" => match args.next_if(|arg| !arg.starts_with('-')) {
+                Some(port) => {
+                    Subcommand::Serve(port.parse().map_err(|_| needs("serve", Wanted::Port))?)
+                }
+                None => Subcommand::Serve(SERVE_PORT),
+            },
+            "watch" => {
+                let error = || needs("watch", Wanted::AddressAndGame);
+                let address = args.next().ok_or_else(error)?;
+                let game = args.next().and_then(|g| g.parse().ok()).ok_or_else(error)?;
+                Subcommand::Watch(address, game)
+            }
+            "continue" => Subcommand::Continue(args.next_if(|arg| !arg.starts_with('-'))),
+            "schema" => Subcommand::Schema(args.next_if(|arg| !arg.starts_with('-'))),
+            _ => {
+                let e = lang.text(Msg::UnknownCommand(&name));
+                return Err(format!("{e}\n\n{}", lang.text(Msg::Usage)));
+            }
+        };
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => options.command = Subcommand::Help,
+            "--tui" => options.tui = true,
+            "--script" => options.script = true,
+            "--json" => options.json = true,
+            "--ui" => options.ui = true,
+            "--accessible" => options.accessible = Some(true),
+            "--no-accessible" => options.accessible = Some(false),
+            "--coach" => options.coach = true,
+            "--verbose" => options.verbose = true,
+            "--kibitz" => options.kibitz = true,
+            "--early-draw" => options.early_draw = true,
+            "--blindfold" => options.blindfold = true,
+            "--peek-penalty" => {
+                let seconds = args
+                    .next()
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .ok_or_else(|| needs("--peek-penalty", Wanted::Seconds))?;
+                options.peek_penalty = Some(Duration::from_secs(seconds));
+            }
+            "--log" => options.log = Some(true),
+            "--no-log" => options.log = Some(false),
+            "--file" => {
+                let path = args.next().ok_or_else(|| needs("--file", Wanted::Path))?;
+                options.file = Some(PathBuf::from(path));
+            }
+            "--archive" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| needs("--archive", Wanted::Path))?;
+                options.archive = Some(PathBuf::from(path));
+            }
+            "--log-file" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| needs("--log-file", Wanted::Path))?;
+                options.log = Some(true);
+                options.log_file = Some(PathBuf::from(path));
+            }
+            "--auth-file" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| needs("--auth-file", Wanted::Path))?;
+                options.auth_file = Some(PathBuf::from(path));
+            }
+            "--storage" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| needs("--storage", Wanted::Directory))?;
+                options.storage = Some(PathBuf::from(path));
+            }
+            "--config" => {
+                let path = args.next().ok_or_else(|| needs("--config", Wanted::Path))?;
+                options.config = Some(PathBuf::from(path));
+            }
+            "--palette" => {
+                let palette = args
+                    .next()
+                    .ok_or_else(|| needs("--palette", Wanted::Palette))?;
+                options.palette = Some(
+                    palette
+                        .parse()
+                        .map_err(|_| invalid("--palette", &palette))?,
+                );
+            }
+            "--record" => {
+                let path = args.next().ok_or_else(|| needs("--record", Wanted::Path))?;
+                options.record = Some(PathBuf::from(path));
+            }
+            "--replay" => {
+                let path = args.next().ok_or_else(|| needs("--replay", Wanted::Path))?;
+                options.replay = Some(PathBuf::from(path));
+            }
+            "--profile" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| needs("--profile", Wanted::Name))?;
+                if !profile::is_valid_name(&name) {
+                    return Err(lang.text(Msg::NotAProfileName(&name)));
+                }
+                options.profile = Some(name);
+            }
+            "--clock" => {
+                let seconds = args
+                    .next()
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .filter(|&s| s > 0)
+                    .ok_or_else(|| needs("--clock", Wanted::Seconds))?;
+                options.clock = Some(Duration::from_secs(seconds));
+            }
+            "--match" => {
+                let games = args
+                    .next()
+                    .and_then(|s| s.parse::<u32>().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| needs("--match", Wanted::Games))?;
+                options.best_of = Some(games);
+            }
+            "--difficulty" => {
+                let difficulty = args
+                    .next()
+                    .ok_or_else(|| needs("--difficulty", Wanted::Level))?;
+                options.difficulty = Some(
+                    difficulty
+                        .parse()
+                        .map_err(|_| invalid("--difficulty", &difficulty))?,
+                );
+            }
+            "--seed" => {
+                let seed = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(|| needs("--seed", Wanted::Number))?;
+                options.seed = Some(seed);
+            }
+            "--entrants" => {
+                let list = args
+                    .next()
+                    .ok_or_else(|| needs("--entrants", Wanted::Entrants))?;
+                options.entrants = Some(
+                    tournament::parse_entrants(&list).map_err(|e| lang.text(Msg::Entrants(&e)))?,
+                );
+            }
+            "--knockout" => {
+                let best_of = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| needs("--knockout", Wanted::Games))?;
+                options.format = Format::Knockout(best_of);
+            }
+            "--swiss" => {
+                let rounds = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| needs("--swiss", Wanted::Rounds))?;
+                options.format = Format::Swiss(rounds);
+            }
+            "--output" => {
+                let path = args.next().ok_or_else(|| needs("--output", Wanted::Path))?;
+                options.output = Some(PathBuf::from(path));
+            }
+            "--size" => {
+                let size = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|&size| size > 0)
+                    .ok_or_else(|| needs("--size", Wanted::Pixels))?;
+                options.size = Some(size);
+            }
+            "--style" => {
+                let style = args.next().ok_or_else(|| needs("--style", Wanted::Style))?;
+                options.style = style.parse().map_err(|_| invalid("--style", &style))?;
+            }
+            "--theme" => {
+                let theme = args.next().ok_or_else(|| needs("--theme", Wanted::Theme))?;
+                options.theme = Some(theme);
+            }
+            "--exploit" => {
+                let rate = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|r| (0.0..=1.0).contains(r))
+                    .ok_or_else(|| needs("--exploit", Wanted::Rate))?;
+                options.exploit = Some(rate);
+            }
+            "--white" => {
+                let engine = args
+                    .next()
+                    .ok_or_else(|| needs("--white", Wanted::Engine))?;
+                options.white = Some(engine.parse().map_err(|_| invalid("--white", &engine))?);
+            }
+            "--black" => {
+                let engine = args
+                    .next()
+                    .ok_or_else(|| needs("--black", Wanted::Engine))?;
+                options.black = Some(engine.parse().map_err(|_| invalid("--black", &engine))?);
+            }
+            "--weights" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| needs("--weights", Wanted::File))?;
+                options.weights = Some(PathBuf::from(path));
+            }
+            "--cache" => {
+                let path = args.next().ok_or_else(|| needs("--cache", Wanted::File))?;
+                options.cache = Some(PathBuf::from(path));
+            }
+            "--depth" => {
+                let depth = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(|| needs("--depth", Wanted::Moves))?;
+                options.depth = Some(depth);
+            }
+            "--games" => {
+                let games = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| needs("--games", Wanted::Games))?;
+                options.games = Some(games);
+            }
+            "--k-factor" => {
+                let k = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|&k| k > 0)
+                    .ok_or_else(|| needs("--k-factor", Wanted::Number))?;
+                options.k_factor = Some(k);
+            }
+            "--lang" => {
+                let lang = args
+                    .next()
+                    .ok_or_else(|| needs("--lang", Wanted::Language))?;
+                options.lang = Some(lang.parse().map_err(|_| invalid("--lang", &lang))?);
+            }
+            "--notation" => {
+                let scheme = args
+                    .next()
+                    .ok_or_else(|| needs("--notation", Wanted::Scheme))?;
+                options.scheme = Some(scheme.parse().map_err(|_| invalid("--notation", &scheme))?);
+            }
+            _ => return Err(lang.text(Msg::UnknownArgument(&arg))),
+        }
+    }
+
+    Ok(options)
+}
+
+// The language of the errors of the command line, which are found before the
+// configuration file is read
+fn args_lang(args: &[String]) -> Lang {
+    args.iter()
+        .position(|arg| arg == "--lang")
+        .and_then(|i| args.get(i + 1)?.parse().ok())
+        .unwrap_or_else(Lang::from_env)
+}
+
+// The options of the command line, filled in from the configuration file, or
+// those of the run to replay
+fn load_options() -> Result<Options, String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut options = parse_args(&args)?;
+
+    if let Some(path) = options.replay.take() {
+        let recording = Recording::load(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut options = parse_args(&recording.args)?;
+        if let Some(config) = &recording.config {
+            apply_config(&mut options, &path, config)?;
+        }
+        options.seed = Some(recording.seed);
+        options.inputs = Some(recording.inputs);
+        return Ok(options);
+    }
+
+    let config = read_config(&options)?;
+    if let Some((path, content)) = &config {
+        apply_config(&mut options, path, content)?;
+    }
+
+    if let Some(path) = options.record.take() {
+        // Fixed now so that the bots play the same moves when replayed
+        let seed = *options.seed.get_or_insert_with(rng::random_seed);
+        // Replayed with the same arguments, but without recording again
+        let mut kept = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--record" {
+                args.next();
+            } else {
+                kept.push(arg);
+            }
+        }
+        let recording = Recording {
+            args: kept,
+            seed,
+            config: config.map(|(_, content)| content),
+            inputs: Vec::new(),
+        };
+        let recorder =
+            Recorder::create(&path, &recording).map_err(|e| format!("{}: {e}", path.display()))?;
+        options.recorder = Some(recorder);
+    }
+
+    Ok(options)
+}
+
+// The configuration file and its content, if there is one. Only the default
+// file may be missing, not one given with `--config`.
+fn read_config(options: &Options) -> Result<Option<(PathBuf, String)>, String> {
+    let Some(path) = options.config.clone().or_else(config::config_file) else {
+        return Ok(None);
+    };
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(Some((path, content))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound && options.config.is_none() => Ok(None),
+        Err(e) => Err(format!("{}: {e}", path.display())),
+    }
+}
+
+// Fill the options not given on the command line from the configuration file
+fn apply_config(options: &mut Options, path: &Path, content: &str) -> Result<(), String> {
+    let config = Config::parse(content).map_err(|e| format!("{}: {e}", path.display()))?;
+
+    if let Some(name) = &config.profile {
+        if !profile::is_valid_name(name) {
+            let lang = options.lang.or(config.lang).unwrap_or_else(Lang::from_env);
+            let e = lang.text(Msg::NotAProfileName(name));
+            return Err(format!("{}: {e}", path.display()));
+        }
+    }
+
+    options.profile = options.profile.take().or(config.profile);
+    options.clock = options.clock.or(config.clock.map(Duration::from_secs));
+    options.difficulty = options.difficulty.or(config.difficulty);
+    options.lang = options.lang.or(config.lang);
+    options.scheme = options.scheme.or(config.notation);
+    options.accessible = options.accessible.or(config.accessible);
+    options.log_file = options.log_file.take().or(config.log_file);
+    options.log = options.log.or(config.log);
+    options.k_factor = options.k_factor.or(config.k_factor);
+    options.palette = options.palette.or(config.palette);
+
+    Ok(())
+}
+
 fn main() {
-    let grid = Grid::new();
-    main_loop(grid);
-}
-
-fn main_loop(mut grid: Grid) {
-    while !grid.is_full() && !grid.has_winner() {
-        let mut x;
-        let mut y;
-        if grid.player_turn == Player::X {
-            display(&grid);
-            loop {
-                (x, y) = player_turn();
-                match grid.set(x as usize, y as usize) {
-                    Ok(_) => break,
-                    Err(p) => {
-                        println!("Theses coordinates already have an {p}.")
+    #[cfg(feature = "tracing")]
+    tictactoe::init_tracing();
+
+    let options = match load_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(2);
+        }
+    };
+
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+
+    match &options.command {
+        Subcommand::Play => play(options, lang),
+        Subcommand::Help => println!("{}", lang.text(Msg::Usage)),
+        Subcommand::Stats => {
+            let name = options.profile.as_deref().unwrap_or(GUEST);
+            commands::stats(lang, name, options.log_file.or_else(record::log_file));
+        }
+        Subcommand::Openings => {
+            commands::openings(lang, options.log_file.or_else(record::log_file));
+        }
+        Subcommand::Solve(Some(position)) => {
+            let layout = Layout {
+                style: options.style,
+                ansi: colors(&options),
+            };
+            commands::solve(lang, position, layout, options.palette.unwrap_or_default())
+        }
+        Subcommand::Solve(None) => match &options.file {
+            Some(path) => commands::solve_file(lang, path),
+            None => {
+                eprintln!("{}", lang.text(Msg::Needs("solve", Wanted::PositionOrFile)));
+                process::exit(2);
+            }
+        },
+        Subcommand::Dataset(format) => commands::dataset(lang, *format),
+        Subcommand::Image(target) => commands::image(
+            lang,
+            target,
+            options.output,
+            options.size,
+            options.theme.as_deref(),
+        ),
+        Subcommand::Report(format) => {
+            commands::report(lang, *format, options.log_file.or_else(record::log_file))
+        }
+        Subcommand::Analyze(Some(path)) => {
+            commands::analyze(lang, path, options.verbose, options.cache.as_deref())
+        }
+        Subcommand::Analyze(None) => match &options.archive {
+            Some(path) => commands::annotate(lang, path),
+            None => {
+                eprintln!(
+                    "{}",
+                    lang.text(Msg::Needs("analyze", Wanted::LogPositionOrArchive))
+                );
+                process::exit(2);
+            }
+        },
+        Subcommand::Bench(count) => commands::bench(lang, *count),
+        Subcommand::Verify => commands::verify(lang),
+        Subcommand::Tree(position) => {
+            commands::search_tree(lang, position, options.depth, options.output)
+        }
+        Subcommand::Tune(generations) => {
+            let seed = options.seed.unwrap_or_else(rng::random_seed);
+            commands::tune(lang, *generations, seed, options.output)
+        }
+        Subcommand::Tournament(games) => {
+            let ui = Ui::new(&options, lang);
+            let log_file = log_file(&options, lang);
+            let entrants = options.entrants.unwrap_or_else(|| {
+                // From the best seed to the worst
+                let all = [Difficulty::Hard, Difficulty::Medium, Difficulty::Easy];
+                all.map(Entrant::new).to_vec()
+            });
+            let seed = options.seed.unwrap_or_else(rng::random_seed);
+            let k = options.k_factor.map_or(elo::DEFAULT_K, f64::from);
+            let format = options.format;
+            commands::tournament(&ui, &entrants, format, *games, seed, k, log_file);
+        }
+        Subcommand::EngineMatch => {
+            let x = options.white.unwrap_or(Engine::Minimax);
+            let o = options.black.unwrap_or(Engine::Minimax);
+            let seed = options.seed.unwrap_or_else(rng::random_seed);
+            let games = options.games.unwrap_or(100);
+            let weights = options.weights.map(|path| load_weights(lang, &path));
+            let weights = weights.unwrap_or_default();
+            commands::engine_match(lang, (x, o), weights, games, seed, options.log_file)
+        }
+        Subcommand::Daily => {
+            let ui = Ui::new(&options, lang);
+            let profile = options.profile.or_else(|| ask_profile_name(&ui));
+            commands::daily(&ui, profile.as_deref());
+        }
+        Subcommand::Puzzle(grade) => {
+            let ui = Ui::new(&options, lang);
+            let seed = options.seed.unwrap_or_else(rng::random_seed);
+            let profile = options.profile.or_else(|| ask_profile_name(&ui));
+            commands::puzzles(&ui, *grade, seed, profile.as_deref());
+        }
+        Subcommand::Host(port) => {
+            let ui = Ui::new(&options, lang);
+            network::host(&ui, options.profile.as_deref().unwrap_or(GUEST), *port);
+        }
+        Subcommand::Join(address) => {
+            let ui = Ui::new(&options, lang);
+            let name = options.profile.as_deref().unwrap_or(GUEST);
+            network::join(&ui, name, address.as_deref());
+        }
+        Subcommand::Serve(port) => {
+            let (auth_file, storage) = (options.auth_file, options.storage);
+            commands::serve(lang, *port, auth_file, storage, options.ui, options.seed)
+        }
+        Subcommand::Watch(address, game) => {
+            let ui = Ui::new(&options, lang);
+            // Names are unique on a server, and many may watch without one
+            let name = options
+                .profile
+                .clone()
+                .unwrap_or_else(|| format!("{GUEST}-{:04x}", rng::random_seed() & 0xffff));
+            commands::watch(&ui, &name, address, *game, options.kibitz);
+        }
+        Subcommand::Continue(code) => {
+            let ui = Ui::new(&options, lang);
+            commands::correspond(&ui, code.as_deref());
+        }
+        Subcommand::Schema(name) => commands::schema(lang, name.as_deref()),
+    }
+}
+
+fn play(options: Options, lang: Lang) {
+    let difficulty = options.difficulty.unwrap_or(Difficulty::Hard);
+    let seed = options.seed.unwrap_or_else(rng::random_seed);
+    let mut bot = Bot::new(difficulty, seed);
+    if let Some(rate) = options.exploit {
+        bot = bot.exploiting(OpponentModel::new(rate));
+    }
+    if let Some(path) = &options.weights {
+        bot = bot.with_weights(load_weights(lang, path));
+    }
+
+    if options.tui {
+        run_tui(lang, options.palette.unwrap_or_default(), bot);
+        return;
+    }
+
+    if options.json {
+        run_json(lang, &mut bot);
+        return;
+    }
+
+    if options.script {
+        let scheme = options.scheme.unwrap_or_default();
+        let lines: Box<dyn Iterator<Item = String>> = match options.inputs {
+            Some(inputs) => Box::new(inputs.into_iter()),
+            None => Box::new(io::stdin().lines().map_while(Result::ok)),
+        };
+        let recorder = options.recorder;
+        let lines = lines.inspect(|line| {
+            if let Some(recorder) = &recorder {
+                let _ = recorder.record(line);
+            }
+        });
+        process::exit(run_script(lang, scheme, &mut bot, lines));
+    }
+
+    if let Some(path) = &options.cache {
+        bot = bot.with_cache(load_cache(lang, path));
+    }
+    let ui = Ui::new(&options, lang);
+    let log_file = log_file(&options, lang);
+    let profile = options.profile.or_else(|| ask_profile_name(&ui));
+    println!("{}", lang.text(Msg::CommandsHelp));
+    if let Some(penalty) = ui.blindfold {
+        println!("{}", lang.text(Msg::BlindfoldHelp(penalty.as_secs())));
+    }
+
+    // Printed so that a game can be reproduced with `--seed`
+    println!("{}", lang.text(Msg::BotSettings(difficulty, seed)));
+
+    let mut scoreboard = Scoreboard::default();
+    let mut first_player = Player::X;
+    let autosave = session::autosave_file();
+    let mut resumed = autosave.as_deref().and_then(|path| offer_resume(&ui, path));
+    if let Some(session) = &resumed {
+        first_player = session.first_player();
+    }
+    let mut series = options
+        .best_of
+        .map(|games| (Match::new(games), record::utc_now()));
+    // The games of a match are logged together once it is over
+    let mut records = Vec::new();
+
+    loop {
+        if let Some((m, _)) = &series {
+            println!("{}", lang.text(Msg::MatchGame(m)));
+        }
+        let mut session = resumed
+            .take()
+            .unwrap_or_else(|| GameSession::from(Grid::starting_with(first_player)));
+        // A resumed game keeps the time it was saved with
+        if let (Some(per_player), None) = (options.clock, session.clock()) {
+            session = session.with_clock(per_player);
+        }
+        if options.early_draw {
+            session = session.with_early_draw();
+        }
+        if let Some(path) = &autosave {
+            session = session.with_autosave(path.clone());
+        }
+        let Some(session) = main_loop(&ui, &mut bot, session) else {
+            break;
+        };
+        let result = end_game(&ui, &session);
+        if !session.history().is_empty() {
+            offer_analysis(&ui, &session);
+        }
+        scoreboard.record(result);
+        if let Some(name) = &profile {
+            let k = options.k_factor.map_or(elo::DEFAULT_K, f64::from);
+            record_profile(lang, name, result, elo::bot_rating(difficulty), k);
+        }
+
+        let x = profile.as_deref().unwrap_or(GUEST);
+        let record = GameRecord::from_session(&session, x, "Bot").map(|mut record| {
+            let event = if series.is_some() { "match" } else { "casual" };
+            record.set_tag("Event", event);
+            record.set_tag("Difficulty", &difficulty.to_string());
+            record
+        });
+
+        match &mut series {
+            Some((m, (date, time))) => {
+                if let Some(mut record) = record {
+                    record.set_tag("Match", &format!("{date} {time}"));
+                    record.set_tag("Round", &format!("{}/{}", m.next_game(), m.length));
+                    records.push(record);
+                }
+                m.record(result);
+                println!("{}", lang.text(Msg::MatchScore(m)));
+                if m.is_over() {
+                    match m.winner() {
+                        Some(p) => println!("{}", lang.text(Msg::MatchWon(p))),
+                        None => println!("{}", lang.text(Msg::MatchTied)),
                     }
+                    break;
                 }
             }
-        } else {
-            match grid.best_play() {
-                Some(coo) => {
-                    let _ = grid.set(coo.0, coo.1);
+            None => {
+                records.extend(record);
+                println!("{}", lang.text(Msg::Score(&scoreboard)));
+                if let Some(path) = &log_file {
+                    log_games(lang, path, &records);
                 }
-                None => {
+                records.clear();
+                if !play_again(&ui) {
                     break;
                 }
             }
         }
+        // Take turns at starting the game
+        first_player = first_player.opponent();
+    }
+
+    if let Some(path) = &log_file {
+        log_games(lang, path, &records);
     }
-    end_game(grid);
 }
 
-fn end_game(grid: Grid) {
-    let mut winner = Player::X;
-    if winner == grid.player_turn {
-        winner = Player::O;
+// Exit codes of the scripted mode, apart from the 1 of an error and the 2 of
+// bad arguments
+const X_WON: i32 = 0;
+const O_WON: i32 = 10;
+const DRAWN: i32 = 11;
+const INVALID_MOVE: i32 = 12;
+const UNFINISHED: i32 = 13;
+
+// Play one game with the moves of X read from `lines` (stdin, unless a run is
+// replayed), and print the final board and result. Returns the exit code.
+fn run_script(
+    lang: Lang,
+    scheme: InputScheme,
+    bot: &mut Bot,
+    mut lines: impl Iterator<Item = String>,
+) -> i32 {
+    let mut session = GameSession::new();
+
+    while !session.is_over() {
+        if session.grid().player_turn == Player::O {
+            session.play_with(bot);
+            continue;
+        }
+
+        let Some(line) = lines.next() else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if lang.command(&line) == Some(Command::Resign) {
+            let _ = session.resign(Player::X);
+            break;
+        }
+
+        let played = match scheme.parse(&line) {
+            Ok((x, y)) => session.play(x, y),
+            Err(e) => {
+                eprintln!("{}: {}", line.trim(), lang.text(Msg::InvalidMove(e)));
+                return INVALID_MOVE;
+            }
+        };
+        if let Err(PlayError::Occupied(p)) = played {
+            eprintln!("{}: {}", line.trim(), lang.text(Msg::Occupied(p)));
+            return INVALID_MOVE;
+        }
     }
-    display(&grid);
-    if !grid.is_full() {
-        println!("Player {} won!", winner);
-    } else {
-        println!("Draw :(");
+
+    display(session.grid());
+    match session.result() {
+        Some(result) => {
+            println!("{}", lang.text(Msg::from(result)));
+            match result.winner() {
+                Some(Player::X) => X_WON,
+                Some(_) => O_WON,
+                None => DRAWN,
+            }
+        }
+        None => UNFINISHED,
+    }
+}
+
+// Whether the output can be colored: not on a screen reader, nor when it goes
+// to a file or a pipe, nor when NO_COLOR is set
+fn colors(options: &Options) -> bool {
+    !options.accessible.unwrap_or(false)
+        && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && io::stdout().is_terminal()
+}
+
+// What the game needs to talk with the player
+struct Ui {
+    lang: Lang,
+    scheme: InputScheme,
+    // Describe the game in words instead of drawing it, for screen readers
+    accessible: bool,
+    // Point out the moves of the player that are not the best
+    coach: bool,
+    // Show what each search of the bot went through
+    verbose: bool,
+    // Where the bot saves its cache after each search that added to it
+    cache: Option<PathBuf>,
+    // Never show the board, unless the player pays this to peek at it
+    blindfold: Option<Duration>,
+    style: Style,
+    input: Input,
+}
+
+impl Ui {
+    fn new(options: &Options, lang: Lang) -> Ui {
+        Ui {
+            lang,
+            scheme: options.scheme.unwrap_or_default(),
+            accessible: options.accessible.unwrap_or(false),
+            coach: options.coach,
+            verbose: options.verbose,
+            cache: options.cache.clone(),
+            blindfold: options
+                .blindfold
+                .then(|| options.peek_penalty.unwrap_or(PEEK_PENALTY)),
+            style: options.style,
+            input: match &options.inputs {
+                Some(inputs) => Input::replay(inputs.clone()),
+                None => Input::spawn(options.recorder.clone()),
+            },
+        }
+    }
+
+    fn show_board(&self, grid: &Grid) {
+        if self.blindfold.is_none() {
+            self.reveal_board(grid);
+        }
+    }
+
+    fn reveal_board(&self, grid: &Grid) {
+        if self.accessible {
+            println!("{}", self.lang.describe_board(grid));
+        } else {
+            print!("{}", render::render(grid, self.style));
+        }
+    }
+
+    // Feedback on the move `m`, played in `grid`
+    fn coach(&self, grid: &Grid, m: Move) {
+        if !self.coach {
+            return;
+        }
+        if let Some(advice) = analysis::coach(grid, m) {
+            println!("{}", self.lang.text(Msg::Advice(&advice)));
+        }
+    }
+
+    fn announce(&self, m: Move) {
+        if self.accessible || self.blindfold.is_some() {
+            println!("{}", self.lang.text(Msg::Plays(m)));
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(lang: Lang, palette: Palette, bot: Bot) {
+    if let Err(e) = tictactoe::tui::run(lang, palette, bot) {
+        eprintln!("{e}");
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(lang: Lang, _: Palette, _: Bot) {
+    eprintln!("{}", lang.text(Msg::TuiUnavailable));
+    process::exit(2);
+}
+
+#[cfg(feature = "json")]
+fn run_json(_: Lang, bot: &mut Bot) {
+    if let Err(e) = tictactoe::json::run(io::stdin().lock(), io::stdout().lock(), bot) {
+        eprintln!("{e}");
+        process::exit(1);
     }
 }
 
-fn player_turn() -> (i32, i32) {
-    println!("Please enter some coordinates : ");
+#[cfg(not(feature = "json"))]
+fn run_json(lang: Lang, _: &mut Bot) {
+    eprintln!("{}", lang.text(Msg::JsonUnavailable));
+    process::exit(2);
+}
+
+fn ask_profile_name(ui: &Ui) -> Option<String> {
+    println!("{}", ui.lang.text(Msg::AskProfileName));
     loop {
-        match read_player_trial() {
-            Ok(res) => {
-                return res;
-            }
-            Err(e) => println!("{e} : "),
+        let name = ui.input.read_line();
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        if profile::is_valid_name(name) {
+            return Some(name.to_string());
         }
+        println!("{}", ui.lang.text(Msg::InvalidProfileName));
     }
 }
 
-fn read_player_trial() -> Result<(i32, i32), &'static str> {
-    let mut trial = String::new();
+// Record a game against the bot, rated `bot_rating`
+fn record_profile(lang: Lang, name: &str, result: GameResult, bot_rating: f64, k: f64) {
+    update_profile(lang, name, |profile| {
+        // The human always plays X
+        profile.record(result, Player::X);
+        profile.rate(result, Player::X, bot_rating, k);
+        println!("{}", lang.text(Msg::ProfileRecord(profile)));
+    });
+}
+
+// Load the profile called `name`, change it and save it back
+fn update_profile(lang: Lang, name: &str, update: impl FnOnce(&mut Profile)) {
+    let Some(path) = profile::profiles_file() else {
+        eprintln!("{}", lang.text(Msg::NoDataDir));
+        return;
+    };
+
+    let mut profiles = match Profiles::load(&path) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            let e = e.to_string();
+            eprintln!("{}", lang.text(Msg::LoadProfilesFailed(&e)));
+            return;
+        }
+    };
 
-    io::stdin()
-        .read_line(&mut trial)
-        .expect("Failed to read line");
+    update(profiles.get_or_create(name));
 
-    let trimmed = trial.trim();
-    let mut x = None;
-    let mut y = None;
+    if let Err(e) = profiles.save(&path) {
+        let e = e.to_string();
+        eprintln!("{}", lang.text(Msg::SaveProfilesFailed(&e)));
+    }
+}
 
-    if trimmed.chars().count() != 2 {
-        return Err("Please enter valid coordinates");
+// Where to log the games, if they are logged
+fn log_file(options: &Options, lang: Lang) -> Option<PathBuf> {
+    if options.log != Some(true) {
+        return None;
     }
+    let file = options.log_file.clone().or_else(record::log_file);
+    if file.is_none() {
+        eprintln!("{}", lang.text(Msg::NoDataDir));
+    }
+    file
+}
 
-    for (i, _) in trimmed.char_indices() {
-        let char = &trimmed[i..=i];
+// A missing cache is a new one, and one that cannot be read is started again
+pub(crate) fn load_cache(lang: Lang, path: &Path) -> Cache {
+    match Cache::load(path) {
+        Ok(cache) => cache,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Cache::new(),
+        Err(e) => {
+            let e = format!("{}: {e}", path.display());
+            eprintln!("{}", lang.text(Msg::LoadCacheFailed(&e)));
+            Cache::new()
+        }
+    }
+}
 
-        match char.parse::<i32>() {
-            Ok(n) => match x {
-                None => {
-                    x = {
-                        if n < 4 && n > 0 {
-                            Some(n - 1)
-                        } else {
-                            return Err("Please enter valid coordinates");
-                        }
-                    }
+// Weights that cannot be read stop the program, as the bot would not play
+// the way it was asked to
+fn load_weights(lang: Lang, path: &Path) -> Weights {
+    Weights::load(path).unwrap_or_else(|e| {
+        let e = format!("{}: {e}", path.display());
+        eprintln!("{}", lang.text(Msg::LoadWeightsFailed(&e)));
+        process::exit(1);
+    })
+}
+
+pub(crate) fn save_cache(lang: Lang, path: &Path, cache: &Cache) {
+    if let Err(e) = cache.save(path) {
+        let e = format!("{}: {e}", path.display());
+        eprintln!("{}", lang.text(Msg::SaveCacheFailed(&e)));
+    }
+}
+
+pub(crate) fn log_games(lang: Lang, path: &Path, records: &[GameRecord]) {
+    for record in records {
+        if let Err(e) = record::append_to_log(path, record) {
+            let e = e.to_string();
+            eprintln!("{}", lang.text(Msg::SaveLogFailed(&e)));
+            return;
+        }
+    }
+}
+
+// The finished game, or `None` if the player has left
+fn main_loop(ui: &Ui, bot: &mut Bot, mut session: GameSession) -> Option<GameSession> {
+    while !session.is_over() {
+        if session.grid().player_turn == Player::X {
+            human_turn(ui, &mut session, true)?;
+        } else {
+            let (x, y) = bot_turn(ui, bot, &session)?;
+            match session.play(x, y) {
+                Ok(m) => ui.announce(m),
+                Err(_) => break,
+            }
+        }
+        if let Some(e) = session.take_autosave_error() {
+            eprintln!("{}", ui.lang.text(Msg::AutosaveFailed(&e)));
+        }
+    }
+    Some(session)
+}
+
+// Let the player to move play, or resign, peek... Draws can only be offered
+// against the bot, which takes them when it cannot do better. `None` if the
+// player leaves.
+fn human_turn(ui: &Ui, session: &mut GameSession, against_bot: bool) -> Option<()> {
+    let me = session.grid().player_turn;
+    ui.show_board(session.grid());
+    display_clock(ui.lang, session);
+    loop {
+        let (x, y) = match player_turn(ui, session) {
+            Turn::Move(x, y) => (x, y),
+            Turn::Command(Command::Resign) => {
+                let _ = session.resign(me);
+                break;
+            }
+            Turn::Command(Command::Quit) => return None,
+            Turn::Command(Command::Peek) => {
+                peek(ui, session);
+                if session.is_over() {
+                    break;
+                }
+                continue;
+            }
+            Turn::Timeout => {
+                session.check_clock();
+                break;
+            }
+            Turn::Chat(_) => {
+                println!("{}", ui.lang.text(Msg::NoOneToChat));
+                continue;
+            }
+            Turn::Command(Command::Draw) if against_bot => {
+                let solution = analysis::solve(session.grid());
+                if solution.is_some_and(|s| s.value == analysis::Value::Draw) {
+                    let _ = session.agree_draw();
+                    break;
                 }
-                Some(_) => {
-                    return Err("Please enter valid coordinates");
+                println!("{}", ui.lang.text(Msg::DrawDeclined));
+                continue;
+            }
+            Turn::Command(Command::Draw) => {
+                println!("{}", ui.lang.text(Msg::NoDrawOffers));
+                continue;
+            }
+        };
+        let before = session.grid().clone();
+        match session.play(x, y) {
+            Ok(m) => {
+                ui.announce(m);
+                ui.coach(&before, m);
+                break;
+            }
+            Err(PlayError::Occupied(p)) => println!("{}", ui.lang.text(Msg::Occupied(p))),
+            Err(PlayError::GameOver) => break,
+        }
+    }
+    Some(())
+}
+
+// A game of a tournament, where the humans play in turn at the keyboard.
+// `None` if one of them leaves.
+fn tournament_game(ui: &Ui, x: &Entrant, o: &Entrant, seed: u64) -> Option<GameSession> {
+    if x.difficulty.is_some() && o.difficulty.is_some() {
+        return tournament::bot_game(x, o, seed);
+    }
+
+    println!("{}", ui.lang.text(Msg::TournamentGame(&x.name, &o.name)));
+    let mut bots = [x.bot(seed), o.bot(seed ^ 1)];
+    let mut session = GameSession::new();
+    while !session.is_over() {
+        let (me, opponent) = match session.grid().player_turn {
+            Player::X => (0, 1),
+            _ => (1, 0),
+        };
+        let against_bot = bots[opponent].is_some();
+        match &mut bots[me] {
+            Some(bot) => {
+                let (x, y) = bot_turn(ui, bot, &session)?;
+                match session.play(x, y) {
+                    Ok(m) => ui.announce(m),
+                    Err(_) => break,
                 }
-            },
-            Err(_) => match y {
-                None => {
-                    if char.eq("a") {
-                        y = Some(0);
-                    } else if char.eq("b") {
-                        y = Some(1);
-                    } else if char.eq("c") {
-                        y = Some(2);
-                    } else {
-                        return Err("Please enter valid coordinates");
+            }
+            None => {
+                let name = [x, o][me].name.as_str();
+                println!("{}", ui.lang.text(Msg::TournamentTurn(name)));
+                human_turn(ui, &mut session, against_bot)?
+            }
+        }
+    }
+    end_game(ui, &session);
+    Some(session)
+}
+
+// Let the bot search in the background, showing how long it has been thinking
+// when it takes a while. Typing `quit` meanwhile stops it; other lines are kept
+// for the next turn of the player. Returns `None` if the player leaves, or if
+// the search fails.
+fn bot_turn(ui: &Ui, bot: &mut Bot, session: &GameSession) -> Option<(usize, usize)> {
+    const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+    let cached = bot.cache().map_or(0, Cache::len);
+    let search = bot.think(session.grid());
+    let mut frame = 0;
+    let thought = loop {
+        match search.wait(Duration::from_millis(100)) {
+            Ok(thought) => break thought,
+            Err(WaitError::Timeout) => (),
+            Err(WaitError::Died) => {
+                eprintln!("{}", ui.lang.text(Msg::SearchFailed));
+                return None;
+            }
+        }
+
+        if let Some(line) = ui.input.read_ahead() {
+            if ui.lang.command(&line) == Some(Command::Quit) {
+                ui.input.take_pending();
+                search.cancel();
+                println!();
+                return None;
+            }
+        }
+
+        if !ui.accessible && search.elapsed() >= THINKING_DELAY {
+            let text = ui
+                .lang
+                .text(Msg::Thinking(session.grid().player_turn, search.elapsed()));
+            print!("\r{} {text}", SPINNER[frame % SPINNER.len()]);
+            let _ = io::stdout().flush();
+            frame += 1;
+        }
+    };
+    if frame > 0 {
+        // Clear the indicator
+        print!("\r\x1b[2K");
+        let _ = io::stdout().flush();
+    }
+
+    *bot = thought.bot;
+    if let (Some(path), Some(cache)) = (&ui.cache, bot.cache()) {
+        if cache.len() > cached {
+            save_cache(ui.lang, path, cache);
+        }
+    }
+    if ui.verbose && thought.choice.is_some() {
+        println!("{}", ui.lang.text(Msg::SearchInfo(&thought.info)));
+    }
+    thought.choice
+}
+
+// Show the board in blindfold mode, taking the penalty off the clock of the
+// player, or making them wait if there is no clock
+fn peek(ui: &Ui, session: &mut GameSession) {
+    let Some(penalty) = ui.blindfold else {
+        ui.show_board(session.grid());
+        return;
+    };
+
+    println!("{}", ui.lang.text(Msg::Peeked(penalty.as_secs())));
+    if session.clock().is_some() {
+        session.penalize(Player::X, penalty);
+    } else {
+        thread::sleep(penalty);
+    }
+    if !session.is_over() {
+        ui.reveal_board(session.grid());
+    }
+}
+
+fn end_game(ui: &Ui, session: &GameSession) -> GameResult {
+    ui.reveal_board(session.grid());
+    let result = session.result().unwrap_or(GameResult::Draw);
+    if result == GameResult::Draw && !session.grid().is_full() {
+        println!("{}", ui.lang.text(Msg::DeadDraw));
+    } else {
+        println!("{}", ui.lang.text(Msg::from(result)));
+    }
+
+    result
+}
+
+// Offer to list the moves of the game with their evaluation and mistakes
+fn offer_analysis(ui: &Ui, session: &GameSession) {
+    println!("{}", ui.lang.text(Msg::OfferAnalysis));
+    if !ui.lang.is_yes(&ui.input.read_line()) {
+        return;
+    }
+
+    for review in analysis::review(session) {
+        println!("{}", ui.lang.text(Msg::ReportMove(&review)));
+    }
+}
+
+fn display_clock(lang: Lang, session: &GameSession) {
+    let Some(clock) = session.clock() else {
+        return;
+    };
+
+    let (x, o) = (clock.remaining(Player::X), clock.remaining(Player::O));
+    println!("{}", lang.text(Msg::Clocks(x, o)));
+}
+
+// The game left unfinished last time, if the player wants to continue it
+fn offer_resume(ui: &Ui, path: &Path) -> Option<GameSession> {
+    let unfinished = session::load_autosave(path)?;
+    println!("{}", ui.lang.text(Msg::ResumeGame(unfinished.age())));
+    if ui.lang.is_yes(&ui.input.read_line()) {
+        Some(unfinished.session)
+    } else {
+        let _ = fs::remove_file(path);
+        None
+    }
+}
+
+fn play_again(ui: &Ui) -> bool {
+    println!("{}", ui.lang.text(Msg::PlayAgain));
+    let answer = ui.input.read_line();
+
+    ui.lang.is_yes(&answer)
+}
+
+enum Turn {
+    Move(usize, usize),
+    Command(Command),
+    // Something to say to the opponent, only read in network games
+    Chat(#[cfg_attr(not(feature = "json"), allow(dead_code))] String),
+    // The player has run out of time
+    Timeout,
+}
+
+fn player_turn(ui: &Ui, session: &GameSession) -> Turn {
+    player_turn_meanwhile(ui, session, None)
+}
+
+// The turn of the player, calling `meanwhile` every `POLL` until they have
+// typed it, which ends the turn if it breaks
+fn player_turn_meanwhile(
+    ui: &Ui,
+    session: &GameSession,
+    mut meanwhile: Option<&mut dyn FnMut() -> ControlFlow<Turn>>,
+) -> Turn {
+    let prompt = match ui.scheme {
+        InputScheme::Coordinates => Msg::EnterCoordinates,
+        InputScheme::Keypad => Msg::EnterKeypad,
+    };
+    println!("{}", ui.lang.text(prompt));
+
+    loop {
+        let remaining = session
+            .clock()
+            .map(|clock| clock.remaining(session.grid().player_turn));
+        let timeout = match meanwhile {
+            Some(_) => Some(remaining.map_or(POLL, |remaining| remaining.min(POLL))),
+            None => remaining,
+        };
+        let trial = match ui.input.read(Some(check_move(ui, session.grid())), timeout) {
+            Typed::Line(trial) => trial,
+            Typed::Interrupted => return Turn::Command(Command::Resign),
+            Typed::Timeout => match meanwhile.as_mut() {
+                Some(meanwhile) if remaining.is_none_or(|remaining| remaining > POLL) => {
+                    match meanwhile() {
+                        ControlFlow::Continue(()) => continue,
+                        ControlFlow::Break(turn) => return turn,
                     }
                 }
-                Some(_) => {
-                    return Err("Please enter valid coordinates");
-                }
+                _ => return Turn::Timeout,
             },
+        };
+
+        if let Some(command) = ui.lang.command(&trial) {
+            return Turn::Command(command);
+        }
+        if let Some(text) = ui.lang.chat(&trial) {
+            return Turn::Chat(text.to_string());
+        }
+
+        match ui.scheme.parse(&trial) {
+            Ok((x, y)) => {
+                return Turn::Move(x, y);
+            }
+            Err(e) => println!("{} : ", ui.lang.text(Msg::InvalidMove(e))),
         }
     }
+}
 
-    Ok((x.unwrap(), y.unwrap()))
+// What is wrong with a move being typed, before it is played
+fn check_move(ui: &Ui, grid: &Grid) -> Check {
+    let (lang, scheme) = (ui.lang, ui.scheme);
+    let matrix = *grid.matrix();
+
+    Box::new(move |line| {
+        if line.trim().is_empty() || lang.command(line).is_some() || lang.chat(line).is_some() {
+            return None;
+        }
+        match scheme.parse(line) {
+            Ok((x, y)) if matrix[y][x] != Player::Empty => {
+                Some(lang.text(Msg::Occupied(matrix[y][x])))
+            }
+            Ok(_) => None,
+            Err(e) => Some(lang.text(Msg::InvalidMove(e))),
+        }
+    })
 }
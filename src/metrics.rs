@@ -0,0 +1,141 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::nodes_searched;
+
+// The upper bounds of the buckets of the move durations, in seconds
+const BUCKETS: [f64; 8] = [0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+
+// What the server has done since it started, written for Prometheus at
+// `/metrics`
+pub struct Metrics {
+    games_created: AtomicU64,
+    games_finished: AtomicU64,
+    connections: AtomicU64,
+    // The moves that took at most each bucket, and those that took longer
+    moves: [AtomicU64; BUCKETS.len() + 1],
+    moves_micros: AtomicU64,
+}
+
+pub static METRICS: Metrics = Metrics {
+    games_created: AtomicU64::new(0),
+    games_finished: AtomicU64::new(0),
+    connections: AtomicU64::new(0),
+    moves: [const { AtomicU64::new(0) }; BUCKETS.len() + 1],
+    moves_micros: AtomicU64::new(0),
+};
+
+impl Metrics {
+    pub fn game_created(&self) {
+        self.games_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn game_finished(&self) {
+        self.games_finished.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn connected(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn disconnected(&self) {
+        self.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // A move has been played, with the answer of the engine if any, in
+    // `elapsed`
+    pub fn move_played(&self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let bucket = BUCKETS
+            .iter()
+            .position(|&bound| seconds <= bound)
+            .unwrap_or(BUCKETS.len());
+        self.moves[bucket].fetch_add(1, Ordering::Relaxed);
+        self.moves_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    // The metrics in the text format of Prometheus
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let counters = [
+            (
+                "tictactoe_games_created_total",
+                "counter",
+                "Games started on the server",
+                self.games_created.load(Ordering::Relaxed),
+            ),
+            (
+                "tictactoe_games_finished_total",
+                "counter",
+                "Games of the server that are over",
+                self.games_finished.load(Ordering::Relaxed),
+            ),
+            (
+                "tictactoe_connections",
+                "gauge",
+                "WebSocket connections open",
+                self.connections.load(Ordering::Relaxed),
+            ),
+            (
+                "tictactoe_engine_nodes_total",
+                "counter",
+                "Positions searched by the engine",
+                nodes_searched(),
+            ),
+        ];
+        for (name, kind, help, value) in counters {
+            let _ = writeln!(text, "# HELP {name} {help}.");
+            let _ = writeln!(text, "# TYPE {name} {kind}");
+            let _ = writeln!(text, "{name} {value}");
+        }
+
+        let name = "tictactoe_move_duration_seconds";
+        let _ = writeln!(text, "# HELP {name} Time to play a move and answer it.");
+        let _ = writeln!(text, "# TYPE {name} histogram");
+        let mut count = 0;
+        for (i, bucket) in self.moves.iter().enumerate() {
+            count += bucket.load(Ordering::Relaxed);
+            match BUCKETS.get(i) {
+                Some(bound) => {
+                    let _ = writeln!(text, "{name}_bucket{{le=\"{bound}\"}} {count}");
+                }
+                None => {
+                    let _ = writeln!(text, "{name}_bucket{{le=\"+Inf\"}} {count}");
+                }
+            }
+        }
+        let seconds = self.moves_micros.load(Ordering::Relaxed) as f64 / 1e6;
+        let _ = writeln!(text, "{name}_sum {seconds}");
+        let _ = writeln!(text, "{name}_count {count}");
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let metrics = Metrics {
+            games_created: AtomicU64::new(2),
+            games_finished: AtomicU64::new(0),
+            connections: AtomicU64::new(0),
+            moves: [const { AtomicU64::new(0) }; BUCKETS.len() + 1],
+            moves_micros: AtomicU64::new(0),
+        };
+        metrics.move_played(Duration::from_micros(700));
+        metrics.move_played(Duration::from_secs(2));
+
+        let text = metrics.render();
+        assert!(text.contains("\ntictactoe_games_created_total 2\n"));
+        assert!(text.contains("tictactoe_move_duration_seconds_bucket{le=\"0.0005\"} 0\n"));
+        assert!(text.contains("tictactoe_move_duration_seconds_bucket{le=\"0.001\"} 1\n"));
+        assert!(text.contains("tictactoe_move_duration_seconds_bucket{le=\"1\"} 1\n"));
+        assert!(text.contains("tictactoe_move_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("tictactoe_move_duration_seconds_sum 2.0007\n"));
+    }
+}
@@ -0,0 +1,393 @@
+use std::fmt;
+use std::io;
+#[cfg(feature = "json")]
+use std::io::{BufRead, BufReader, Read, Write};
+#[cfg(feature = "json")]
+use std::net::TcpStream;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+#[cfg(feature = "json")]
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "json")]
+use crate::protocol::{self, Message};
+use crate::protocol::{HandshakeError, VERSION};
+
+// The port used when none is given
+pub const DEFAULT_PORT: u16 = 7878;
+
+// How long the other end may stay silent before it is given up on. Pings are
+// sent more often than that, to show that a player is still there.
+const SILENCE: Duration = Duration::from_secs(30);
+#[cfg(feature = "json")]
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+// The longest message accepted, newline included, leaving room for chat
+// messages even with their quotes escaped
+const MAX_FRAME: usize = 512;
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    // The other end has closed the connection
+    Closed,
+    // Nothing has come from the other end for `SILENCE`
+    Silent,
+    Malformed(String),
+    Handshake(HandshakeError),
+    // The other end has refused a message, for this reason
+    Rejected(String),
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Closed => write!(f, "the connection was closed"),
+            Self::Silent => write!(
+                f,
+                "the other end has not answered for {} seconds",
+                SILENCE.as_secs()
+            ),
+            Self::Malformed(line) => write!(f, "unexpected message {line:?}"),
+            Self::Handshake(e) => write!(f, "{e}"),
+            Self::Rejected(reason) => write!(f, "the other end refused it: {reason}"),
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+// A connection to the other player, over which the messages of the protocol
+// are sent as JSON, one per line
+#[cfg(feature = "json")]
+pub struct Peer<S = TcpStream> {
+    stream: BufReader<S>,
+    // When the last message was sent
+    sent: Instant,
+}
+
+#[cfg(feature = "json")]
+impl<S: Read + Write> Peer<S> {
+    pub fn new(stream: S) -> Peer<S> {
+        Peer {
+            stream: BufReader::new(stream),
+            sent: Instant::now(),
+        }
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError> {
+        let stream = self.stream.get_mut();
+        serde_json::to_writer(&mut *stream, message).map_err(io::Error::from)?;
+        writeln!(stream)?;
+        stream.flush()?;
+        self.sent = Instant::now();
+
+        Ok(())
+    }
+
+    // Send a ping if nothing else has been sent for a while
+    pub fn keep_alive(&mut self) -> Result<(), NetError> {
+        if self.sent.elapsed() < PING_INTERVAL {
+            return Ok(());
+        }
+
+        self.send(&Message::Ping)
+    }
+
+    // The next message, other than a ping
+    pub fn receive(&mut self) -> Result<Message, NetError> {
+        loop {
+            let mut line = String::new();
+            let read = match (&mut self.stream)
+                .take(MAX_FRAME as u64)
+                .read_line(&mut line)
+            {
+                Ok(read) => read,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Err(NetError::Silent)
+                }
+                Err(e) => return Err(NetError::Io(e)),
+            };
+            if read == 0 {
+                return Err(NetError::Closed);
+            }
+            if !line.ends_with('\n') && read == MAX_FRAME {
+                return Err(NetError::Malformed(line));
+            }
+
+            let line = line.trim_end_matches(['\r', '\n']);
+            match serde_json::from_str(line) {
+                Ok(Message::Ping) => continue,
+                Ok(message) => return Ok(message),
+                Err(_) => return Err(NetError::Malformed(line.to_string())),
+            }
+        }
+    }
+
+    // Introduce the player called `name`, and return the name of the other one
+    pub fn handshake(&mut self, name: &str) -> Result<String, NetError> {
+        self.send(&Message::hello(name))?;
+        protocol::check_hello(self.receive()?).map_err(NetError::Handshake)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Peer {
+    // A peer over `stream`, giving up on the other end after `SILENCE`
+    pub fn tcp(stream: TcpStream) -> io::Result<Peer> {
+        stream.set_read_timeout(Some(SILENCE))?;
+
+        Ok(Peer::new(stream))
+    }
+
+    // Receive the messages on a separate thread, so that they arrive while the
+    // player is typing. The peer returned only sends.
+    pub fn listen(self) -> io::Result<(Peer, mpsc::Receiver<Result<Message, NetError>>)> {
+        let sender = Peer::new(self.stream.get_ref().try_clone()?);
+        let (messages, received) = mpsc::channel();
+        let mut receiver = self;
+        thread::spawn(move || loop {
+            let message = receiver.receive();
+            let failed = message.is_err();
+            if messages.send(message).is_err() || failed {
+                break;
+            }
+        });
+
+        Ok((sender, received))
+    }
+}
+
+// An address to connect to, with the default port if it has none. An IPv6
+// address with a port is written `[address]:port`, and one without a port
+// may be bare.
+pub fn with_default_port(address: &str) -> String {
+    let has_port = address
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    match address.parse::<Ipv6Addr>() {
+        Ok(ip) => SocketAddr::from((ip, DEFAULT_PORT)).to_string(),
+        Err(_) if has_port => address.to_string(),
+        Err(_) => format!("{address}:{DEFAULT_PORT}"),
+    }
+}
+
+// Hosts are found on the local network by broadcasting a probe on this UDP
+// port, which they answer with the port of their game and the name of their
+// player:
+//
+//     tictactoe? 1
+//     tictactoe! 1 7878 alice
+pub const DISCOVERY_PORT: u16 = 7878;
+
+const PROBE: &str = "tictactoe?";
+const ANSWER: &str = "tictactoe!";
+
+// A game waiting for a player on the local network
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Host {
+    pub name: String,
+    pub address: SocketAddr,
+}
+
+// Answer the probes of players looking for a game, in the background, for the
+// game of `name` on `port`
+pub fn announce(name: &str, port: u16) -> io::Result<()> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))?;
+    answer_probes(socket, name.to_string(), port);
+
+    Ok(())
+}
+
+fn answer_probes(socket: UdpSocket, name: String, port: u16) {
+    thread::spawn(move || {
+        let mut buffer = [0; MAX_FRAME];
+        while let Ok((read, from)) = socket.recv_from(&mut buffer) {
+            if buffer[..read] == *format!("{PROBE} {VERSION}").as_bytes() {
+                let answer = format!("{ANSWER} {VERSION} {port} {name}");
+                let _ = socket.send_to(answer.as_bytes(), from);
+            }
+        }
+    });
+}
+
+// The hosts answering within `timeout` on the local network
+pub fn discover(timeout: Duration) -> io::Result<Vec<Host>> {
+    let targets = [
+        SocketAddr::from((Ipv4Addr::BROADCAST, DISCOVERY_PORT)),
+        // Broadcasts do not always reach this computer
+        SocketAddr::from((Ipv4Addr::LOCALHOST, DISCOVERY_PORT)),
+    ];
+    let mut hosts = probe(&targets, timeout)?;
+
+    // A host on this computer may answer both
+    let found = hosts.clone();
+    hosts.retain(|host| {
+        !host.address.ip().is_loopback()
+            || !found.iter().any(|other| {
+                !other.address.ip().is_loopback()
+                    && other.name == host.name
+                    && other.address.port() == host.address.port()
+            })
+    });
+
+    Ok(hosts)
+}
+
+fn probe(targets: &[SocketAddr], timeout: Duration) -> io::Result<Vec<Host>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    for target in targets {
+        // Some targets may not be reachable, the others still are
+        let _ = socket.send_to(format!("{PROBE} {VERSION}").as_bytes(), target);
+    }
+
+    let mut hosts = Vec::new();
+    let mut buffer = [0; MAX_FRAME];
+    let deadline = Instant::now() + timeout;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(left))?;
+
+        let (read, from) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                break
+            }
+            Err(e) => return Err(e),
+        };
+        let answer = String::from_utf8_lossy(&buffer[..read]);
+        if let Some(host) = parse_answer(&answer, from) {
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+    }
+
+    Ok(hosts)
+}
+
+fn parse_answer(answer: &str, from: SocketAddr) -> Option<Host> {
+    let mut fields = answer.splitn(4, ' ');
+    if fields.next()? != ANSWER || fields.next()?.parse::<u32>().ok()? != VERSION {
+        return None;
+    }
+    let port = fields.next()?.parse().ok()?;
+
+    Some(Host {
+        name: fields.next()?.to_string(),
+        address: SocketAddr::new(from.ip(), port),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn over_tcp() {
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let guest = std::thread::spawn(move || {
+            let mut peer = Peer::new(TcpStream::connect(address).unwrap());
+            let host = peer.handshake("bob").unwrap();
+            peer.send(&Message::Ping).unwrap();
+            peer.send(&Message::Resign).unwrap();
+            host
+        });
+
+        let mut peer = Peer::new(listener.accept().unwrap().0);
+        assert_eq!(peer.handshake("alice").unwrap(), "bob");
+        assert_eq!(peer.receive().unwrap(), Message::Resign);
+        assert!(matches!(peer.receive(), Err(NetError::Closed)));
+        assert_eq!(guest.join().unwrap(), "alice");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn listening() {
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let guest = std::thread::spawn(move || {
+            let mut peer = Peer::new(TcpStream::connect(address).unwrap());
+            peer.send(&Message::chat("hi")).unwrap();
+            assert_eq!(peer.receive().unwrap(), Message::chat("hello"));
+        });
+
+        let (mut peer, received) = Peer::new(listener.accept().unwrap().0).listen().unwrap();
+        assert_eq!(received.recv().unwrap().unwrap(), Message::chat("hi"));
+        peer.send(&Message::chat("hello")).unwrap();
+        guest.join().unwrap();
+        assert!(matches!(received.recv().unwrap(), Err(NetError::Closed)));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn silence() {
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(50)))
+            .unwrap();
+
+        let _host = listener.accept().unwrap();
+        assert!(matches!(Peer::new(stream).receive(), Err(NetError::Silent)));
+    }
+
+    #[test]
+    fn discovery() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let target = socket.local_addr().unwrap();
+        answer_probes(socket, "Jean Dupont".to_string(), 4000);
+
+        let hosts = probe(&[target, target], Duration::from_millis(500)).unwrap();
+        assert_eq!(
+            hosts,
+            vec![Host {
+                name: "Jean Dupont".to_string(),
+                address: "127.0.0.1:4000".parse().unwrap(),
+            }]
+        );
+
+        let from = "10.0.0.2:7878".parse().unwrap();
+        assert_eq!(parse_answer("tictactoe! 2 7878 bob", from), None);
+    }
+
+    #[test]
+    fn ports() {
+        assert_eq!(with_default_port("192.168.1.2"), "192.168.1.2:7878");
+        assert_eq!(with_default_port("localhost:4000"), "localhost:4000");
+        assert_eq!(with_default_port("::1"), "[::1]:7878");
+        assert_eq!(with_default_port("fe80::1"), "[fe80::1]:7878");
+        assert_eq!(with_default_port("[::1]"), "[::1]:7878");
+        assert_eq!(with_default_port("[::1]:4000"), "[::1]:4000");
+    }
+}
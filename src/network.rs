@@ -0,0 +1,278 @@
+use std::net::{TcpListener, TcpStream};
+use std::ops::ControlFlow;
+use std::process;
+use std::sync::mpsc::{self, RecvTimeoutError, TryRecvError};
+use std::time::Duration;
+use tictactoe::i18n::{Lang, Msg};
+use tictactoe::net::{self, Host, NetError, Peer};
+use tictactoe::notation::Command;
+use tictactoe::protocol::{self, Message};
+use tictactoe::session::{GameResult, GameSession, PlayError};
+use tictactoe::*;
+
+use crate::{end_game, peek, player_turn_meanwhile, Turn, Ui, POLL};
+
+// Games between two players over the network
+
+// How long to wait for the hosts on the local network to answer
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Wait for another player to join on `port`, then play X against them
+pub fn host(ui: &Ui, name: &str, port: u16) {
+    let lang = ui.lang;
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => listener,
+        Err(e) => network_error(lang, NetError::Io(e)),
+    };
+    println!("{}", lang.text(Msg::Hosting(port)));
+    // Without it, the other player can still join with the address
+    let _ = net::announce(name, port);
+
+    let stream = match listener.accept() {
+        Ok((stream, _)) => stream,
+        Err(e) => network_error(lang, NetError::Io(e)),
+    };
+    match Peer::tcp(stream) {
+        Ok(peer) => network_game(ui, peer, name, Player::X),
+        Err(e) => network_error(lang, NetError::Io(e)),
+    }
+}
+
+// Join the game hosted at `address`, or one found on the local network, and
+// play O
+pub fn join(ui: &Ui, name: &str, address: Option<&str>) {
+    let address = match address {
+        Some(address) => net::with_default_port(address),
+        None => match choose_host(ui) {
+            Some(host) => host.address.to_string(),
+            None => return,
+        },
+    };
+
+    match TcpStream::connect(address).and_then(Peer::tcp) {
+        Ok(peer) => network_game(ui, peer, name, Player::O),
+        Err(e) => network_error(ui.lang, NetError::Io(e)),
+    }
+}
+
+// Let the player choose between the hosts on the local network
+fn choose_host(ui: &Ui) -> Option<Host> {
+    let lang = ui.lang;
+    println!("{}", lang.text(Msg::Searching));
+    let mut hosts = match net::discover(DISCOVERY_TIMEOUT) {
+        Ok(hosts) => hosts,
+        Err(e) => network_error(lang, NetError::Io(e)),
+    };
+    if hosts.is_empty() {
+        println!("{}", lang.text(Msg::NoHosts));
+        return None;
+    }
+
+    println!("{}", lang.text(Msg::Hosts(&hosts)));
+    println!("{}", lang.text(Msg::ChooseHost(hosts.len())));
+    loop {
+        let answer = ui.input.read_line();
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return None;
+        }
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=hosts.len()).contains(&n) => return Some(hosts.swap_remove(n - 1)),
+            _ => println!("{}", lang.text(Msg::ChooseHost(hosts.len()))),
+        }
+    }
+}
+
+// Play a game against another player, X moving first. Each move is proposed
+// to the other end, which checks it against its own grid before accepting it.
+// A draw is offered on one's turn, and the other player accepts it on theirs or
+// declines it by playing. Both players can chat at any time. Once the game is
+// over, each end sends its result, which the other checks against its own.
+fn network_game(ui: &Ui, mut peer: Peer, name: &str, me: Player) {
+    let lang = ui.lang;
+    let opponent = match peer.handshake(name) {
+        Ok(opponent) => opponent,
+        Err(e) => network_error(lang, e),
+    };
+    let (mut peer, incoming) = match peer.listen() {
+        Ok(listening) => listening,
+        Err(e) => network_error(lang, NetError::Io(e)),
+    };
+    println!("{}", lang.text(Msg::Opponent(&opponent, me)));
+    println!("{}", lang.text(Msg::ChatHelp));
+
+    let mut session = GameSession::new();
+    // The player whose draw offer stands
+    let mut draw_offer = None;
+    while !session.is_over() {
+        if session.grid().player_turn == me {
+            ui.show_board(session.grid());
+            let mut meanwhile = || show_chat(ui, &mut peer, &incoming, &opponent);
+            match player_turn_meanwhile(ui, &session, Some(&mut meanwhile)) {
+                Turn::Move(x, y) => match session.play(x, y) {
+                    Ok(m) => {
+                        ui.announce(m);
+                        if draw_offer == Some(me.opponent()) {
+                            draw_offer = None;
+                        }
+                        let square = coordinates(x, y);
+                        send(lang, &mut peer, Message::MoveProposed { square });
+                        match receive(ui, &mut peer, &incoming, &opponent) {
+                            Ok(Message::MoveAccepted { .. }) => (),
+                            Ok(Message::Rejected { reason }) => {
+                                network_error(lang, NetError::Rejected(reason))
+                            }
+                            Ok(message) => network_error(lang, unexpected(message)),
+                            Err(NetError::Closed) => {
+                                println!("{}", lang.text(Msg::OpponentLeft(&opponent)));
+                                return;
+                            }
+                            Err(e) => network_error(lang, e),
+                        }
+                    }
+                    Err(PlayError::Occupied(p)) => println!("{}", lang.text(Msg::Occupied(p))),
+                    Err(PlayError::GameOver) => break,
+                },
+                // Running out of time gives the game up, which the other
+                // player is told before the connection closes
+                Turn::Command(Command::Resign) | Turn::Timeout => {
+                    let _ = session.resign(me);
+                    send(lang, &mut peer, Message::Resign);
+                }
+                Turn::Command(Command::Draw) if draw_offer == Some(me.opponent()) => {
+                    let _ = session.agree_draw();
+                    send(lang, &mut peer, Message::DrawAccepted);
+                }
+                Turn::Command(Command::Draw) => {
+                    if draw_offer.is_none() {
+                        send(lang, &mut peer, Message::DrawOffer);
+                        draw_offer = Some(me);
+                    }
+                    println!("{}", lang.text(Msg::DrawOfferSent));
+                }
+                Turn::Command(Command::Peek) => peek(ui, &mut session),
+                // Closing the connection tells the other player
+                Turn::Command(Command::Quit) => return,
+                Turn::Chat(text) => send(lang, &mut peer, Message::chat(&text)),
+            }
+        } else {
+            println!("{}", lang.text(Msg::WaitingFor(&opponent)));
+            match receive(ui, &mut peer, &incoming, &opponent) {
+                Ok(Message::MoveProposed { square }) => {
+                    let answer = protocol::play_proposed(&mut session, &square);
+                    let accepted = matches!(answer, Message::MoveAccepted { .. });
+                    send(lang, &mut peer, answer);
+                    if let (true, Some(&m)) = (accepted, session.history().last()) {
+                        ui.announce(m);
+                        if draw_offer == Some(me) {
+                            draw_offer = None;
+                            println!("{}", lang.text(Msg::DrawDeclined));
+                        }
+                    }
+                }
+                Ok(Message::Resign) => {
+                    let _ = session.resign(me.opponent());
+                }
+                Ok(Message::DrawOffer) if draw_offer.is_none() => {
+                    draw_offer = Some(me.opponent());
+                    println!("{}", lang.text(Msg::DrawOffered(&opponent)));
+                }
+                Ok(Message::DrawAccepted) if draw_offer == Some(me) => {
+                    let _ = session.agree_draw();
+                }
+                Ok(message) => network_error(lang, unexpected(message)),
+                Err(NetError::Closed) => {
+                    println!("{}", lang.text(Msg::OpponentLeft(&opponent)));
+                    return;
+                }
+                Err(e) => network_error(lang, e),
+            }
+        }
+    }
+
+    let result = session.result().unwrap_or(GameResult::Draw);
+    send(lang, &mut peer, Message::game_over(result));
+    match receive(ui, &mut peer, &incoming, &opponent) {
+        Ok(message) if message == Message::game_over(result) => (),
+        Ok(message) => network_error(lang, unexpected(message)),
+        Err(e) => network_error(lang, e),
+    }
+
+    end_game(ui, &session);
+}
+
+// The next message of the other player, showing what they say meanwhile and
+// sending them what the player says. The other lines typed are kept for the
+// next turn of the player.
+fn receive(
+    ui: &Ui,
+    peer: &mut Peer,
+    incoming: &mpsc::Receiver<Result<Message, NetError>>,
+    opponent: &str,
+) -> Result<Message, NetError> {
+    loop {
+        send_ping(ui.lang, peer);
+        match incoming.recv_timeout(POLL) {
+            Ok(Ok(Message::Chat { text })) => {
+                println!("{}", ui.lang.text(Msg::Chat(opponent, &text)))
+            }
+            Ok(message) => return message,
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => return Err(NetError::Closed),
+        }
+
+        if let Some(line) = ui.input.type_ahead() {
+            if let Some(text) = ui.lang.chat(&line) {
+                ui.input.take_last();
+                send(ui.lang, peer, Message::chat(text));
+            }
+        }
+    }
+}
+
+// Show what the other player has said while the player is typing. Any other
+// message ends the turn.
+fn show_chat(
+    ui: &Ui,
+    peer: &mut Peer,
+    incoming: &mpsc::Receiver<Result<Message, NetError>>,
+    opponent: &str,
+) -> ControlFlow<Turn> {
+    send_ping(ui.lang, peer);
+    loop {
+        match incoming.try_recv() {
+            Ok(Ok(Message::Chat { text })) => {
+                println!("{}", ui.lang.text(Msg::Chat(opponent, &text)))
+            }
+            Ok(Ok(message)) => network_error(ui.lang, unexpected(message)),
+            Ok(Err(NetError::Closed)) | Err(TryRecvError::Disconnected) => {
+                println!("{}", ui.lang.text(Msg::OpponentLeft(opponent)));
+                return ControlFlow::Break(Turn::Command(Command::Quit));
+            }
+            Ok(Err(e)) => network_error(ui.lang, e),
+            Err(TryRecvError::Empty) => return ControlFlow::Continue(()),
+        }
+    }
+}
+
+fn send(lang: Lang, peer: &mut Peer, message: Message) {
+    if let Err(e) = peer.send(&message) {
+        network_error(lang, e);
+    }
+}
+
+// Show the other player that this one is still there
+fn send_ping(lang: Lang, peer: &mut Peer) {
+    if let Err(e) = peer.keep_alive() {
+        network_error(lang, e);
+    }
+}
+
+fn unexpected(message: Message) -> NetError {
+    NetError::Malformed(format!("{message:?}"))
+}
+
+fn network_error(lang: Lang, e: NetError) -> ! {
+    eprintln!("{}", lang.text(Msg::NetworkError(&e.to_string())));
+    process::exit(1);
+}
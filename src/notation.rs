@@ -0,0 +1,302 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Grid, Player};
+
+// How the player enters their moves
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum InputScheme {
+    // A column and a row, e.g. `2b`
+    #[default]
+    Coordinates,
+    // A single digit laid out like a phone keypad, `1` being the top left
+    // square and `9` the bottom right one
+    Keypad,
+}
+
+impl fmt::Display for InputScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Coordinates => "coordinates",
+            Self::Keypad => "keypad",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for InputScheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "coordinates" => Ok(Self::Coordinates),
+            "keypad" => Ok(Self::Keypad),
+            _ => Err(format!("Unknown notation: {s}")),
+        }
+    }
+}
+
+// What the player can type instead of a move
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Command {
+    Resign,
+    Quit,
+    // Show the board in blindfold mode
+    Peek,
+    // Offer a draw, or accept the one offered
+    Draw,
+}
+
+impl InputScheme {
+    pub fn parse(self, input: &str) -> Result<(usize, usize), ParseError> {
+        match self {
+            Self::Coordinates => parse_coordinates(input),
+            Self::Keypad => parse_keypad(input).ok_or(ParseError::InvalidSquare),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ParseError {
+    Empty,
+    Unexpected(char),
+    // The row is a letter outside of `a-c`
+    InvalidRow(char),
+    // The column is a digit outside of `1-3`
+    InvalidColumn(char),
+    MissingRow,
+    MissingColumn,
+    // More than one row or column
+    TooLong,
+    // Not a keypad digit
+    InvalidSquare,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no move given"),
+            Self::Unexpected(c) => write!(f, "unexpected character {c:?}"),
+            Self::InvalidRow(c) => write!(f, "row must be a-c, not {c}"),
+            Self::InvalidColumn(c) => write!(f, "column must be 1-3, not {c}"),
+            Self::MissingRow => write!(f, "missing the row (a-c)"),
+            Self::MissingColumn => write!(f, "missing the column (1-3)"),
+            Self::TooLong => write!(f, "a move is one row and one column"),
+            Self::InvalidSquare => write!(f, "square must be 1-9"),
+        }
+    }
+}
+
+// Parse coordinates made of a row letter and a column digit, in any order and
+// case, optionally separated by spaces or a comma: `b2`, `2b`, `B2`, `b 2`
+// and `2,b` are all the middle square.
+pub fn parse_coordinates(input: &str) -> Result<(usize, usize), ParseError> {
+    let mut x = None;
+    let mut y = None;
+    let mut empty = true;
+
+    for c in input.chars() {
+        if c.is_whitespace() || c == ',' {
+            continue;
+        }
+        empty = false;
+
+        if c.is_ascii_digit() {
+            if x.is_some() {
+                return Err(ParseError::TooLong);
+            }
+            x = match c {
+                '1'..='3' => Some(c as usize - '1' as usize),
+                _ => return Err(ParseError::InvalidColumn(c)),
+            };
+        } else if c.is_alphabetic() {
+            if y.is_some() {
+                return Err(ParseError::TooLong);
+            }
+            y = match c.to_ascii_lowercase() {
+                r @ 'a'..='c' => Some(r as usize - 'a' as usize),
+                _ => return Err(ParseError::InvalidRow(c)),
+            };
+        } else {
+            return Err(ParseError::Unexpected(c));
+        }
+    }
+
+    match (x, y) {
+        (Some(x), Some(y)) => Ok((x, y)),
+        _ if empty => Err(ParseError::Empty),
+        (None, _) => Err(ParseError::MissingColumn),
+        (_, None) => Err(ParseError::MissingRow),
+    }
+}
+
+pub fn parse_keypad(input: &str) -> Option<(usize, usize)> {
+    let digit = input.trim().parse::<usize>().ok()?;
+    if !(1..=9).contains(&digit) {
+        return None;
+    }
+
+    Some(((digit - 1) % 3, (digit - 1) / 3))
+}
+
+pub fn keypad(x: usize, y: usize) -> usize {
+    y * 3 + x + 1
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PositionError {
+    InvalidSquare(char),
+    // The number of squares, if not 9
+    WrongLength(usize),
+    // The players cannot have played these moves in turns
+    Unreachable,
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSquare(c) => write!(f, "{c:?} is not X, O or an empty square (.)"),
+            Self::WrongLength(n) => write!(f, "expected 9 squares, got {n}"),
+            Self::Unreachable => write!(f, "this position cannot happen in a game"),
+        }
+    }
+}
+
+// A position written row by row, such as `X.O/.X./...`: `X`, `O` and `.` (or
+// `-`) for an empty square, the slashes being optional. When both players have
+// played as many moves, X is to move.
+pub fn parse_position(input: &str) -> Result<Grid, PositionError> {
+    let mut squares = Vec::new();
+    for c in input.chars().filter(|&c| c != '/' && !c.is_whitespace()) {
+        squares.push(match c {
+            'x' | 'X' => Player::X,
+            'o' | 'O' | '0' => Player::O,
+            '.' | '-' => Player::Empty,
+            c => return Err(PositionError::InvalidSquare(c)),
+        });
+    }
+    if squares.len() != 9 {
+        return Err(PositionError::WrongLength(squares.len()));
+    }
+
+    let mut matrix = [[Player::Empty; 3]; 3];
+    for (i, &square) in squares.iter().enumerate() {
+        matrix[i / 3][i % 3] = square;
+    }
+
+    let mut grid = Grid::from(matrix);
+    grid.player_turn = match grid.count(Player::X) as i32 - grid.count(Player::O) as i32 {
+        0 | -1 => Player::X,
+        1 => Player::O,
+        _ => return Err(PositionError::Unreachable),
+    };
+    // The game ends with the first three in a row, made by the player who
+    // has just moved, so the player to move cannot have one
+    let wins = |player| {
+        grid.lines()
+            .any(|line| line.iter().all(|&(_, p)| p == player))
+    };
+    if wins(grid.player_turn) {
+        return Err(PositionError::Unreachable);
+    }
+
+    Ok(grid)
+}
+
+pub fn position(grid: &Grid) -> String {
+    let rows: Vec<String> = grid
+        .matrix()
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|p| match p {
+                    Player::X => 'X',
+                    Player::O => 'O',
+                    Player::Empty => '.',
+                })
+                .collect()
+        })
+        .collect();
+
+    rows.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgiving_coordinates() {
+        for input in ["b2", "2b", "B2", "2B", "b 2", "2,b", " 2, b\n"] {
+            assert_eq!(parse_coordinates(input), Ok((1, 1)), "{input:?}");
+        }
+        assert_eq!(parse_coordinates("a3"), Ok((2, 0)));
+        assert_eq!(parse_coordinates("1c"), Ok((0, 2)));
+    }
+
+    #[test]
+    fn coordinates_errors() {
+        assert_eq!(parse_coordinates(""), Err(ParseError::Empty));
+        assert_eq!(parse_coordinates(" , "), Err(ParseError::Empty));
+        assert_eq!(parse_coordinates("d2"), Err(ParseError::InvalidRow('d')));
+        assert_eq!(parse_coordinates("b4"), Err(ParseError::InvalidColumn('4')));
+        assert_eq!(parse_coordinates("b"), Err(ParseError::MissingColumn));
+        assert_eq!(parse_coordinates("2"), Err(ParseError::MissingRow));
+        assert_eq!(parse_coordinates("b22"), Err(ParseError::TooLong));
+        assert_eq!(parse_coordinates("ab2"), Err(ParseError::TooLong));
+        assert_eq!(parse_coordinates("b-2"), Err(ParseError::Unexpected('-')));
+        assert_eq!(parse_coordinates("é2"), Err(ParseError::InvalidRow('é')));
+    }
+
+    #[test]
+    fn keypad_layout() {
+        assert_eq!(parse_keypad("1"), Some((0, 0)));
+        assert_eq!(parse_keypad("3"), Some((2, 0)));
+        assert_eq!(parse_keypad(" 5\n"), Some((1, 1)));
+        assert_eq!(parse_keypad("7"), Some((0, 2)));
+        assert_eq!(parse_keypad("9"), Some((2, 2)));
+
+        for y in 0..3 {
+            for x in 0..3 {
+                assert_eq!(parse_keypad(&keypad(x, y).to_string()), Some((x, y)));
+            }
+        }
+    }
+
+    #[test]
+    fn positions() {
+        let grid = parse_position("x.o/.X./-- -").unwrap();
+        assert_eq!(grid.matrix()[0], [Player::X, Player::Empty, Player::O]);
+        assert_eq!(grid.matrix()[1][1], Player::X);
+        assert_eq!(grid.player_turn, Player::O);
+        assert_eq!(position(&grid), "X.O/.X./...");
+
+        assert_eq!(parse_position(".........").unwrap().player_turn, Player::X);
+        assert_eq!(parse_position("o........").unwrap().player_turn, Player::X);
+
+        let error = |input| parse_position(input).err();
+        assert_eq!(error("x..."), Some(PositionError::WrongLength(4)));
+        assert_eq!(
+            error("x.o/.z./..."),
+            Some(PositionError::InvalidSquare('z'))
+        );
+        assert_eq!(error("xx./.../..."), Some(PositionError::Unreachable));
+        assert_eq!(error("xxx/ooo/..."), Some(PositionError::Unreachable));
+        // The player to move cannot have made the line
+        assert_eq!(error("XXX/OO./O.."), Some(PositionError::Unreachable));
+        assert_eq!(error("OOO/XX./XX."), Some(PositionError::Unreachable));
+        assert_eq!(
+            parse_position("XXX/OO./...").unwrap().player_turn,
+            Player::O
+        );
+    }
+
+    #[test]
+    fn invalid_keypad() {
+        assert_eq!(parse_keypad("0"), None);
+        assert_eq!(parse_keypad("10"), None);
+        assert_eq!(parse_keypad("2b"), None);
+        assert_eq!(parse_keypad(""), None);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use tract_onnx::prelude::*;
+
+use crate::eval::{encode, Evaluator};
+use crate::Grid;
+
+// A model trained elsewhere, such as on `tictactoe dataset`, and exported to
+// ONNX, to evaluate positions in `eval::search`. It takes the squares of a
+// position as given by `eval::encode`, as a tensor of shape [1, 9], and gives
+// its value for the player to move, from -1 to 1, as its first output.
+pub struct Model {
+    plan: Arc<TypedRunnableModel>,
+}
+
+impl Model {
+    pub fn load(path: &Path) -> io::Result<Model> {
+        Model::read(&mut File::open(path)?)
+    }
+
+    pub fn read(reader: &mut dyn Read) -> io::Result<Model> {
+        Model::new(onnx().model_for_read(reader).map_err(error)?)
+    }
+
+    fn new(model: InferenceModel) -> io::Result<Model> {
+        let plan = model
+            .with_input_fact(0, f32::fact([1, 9]).into())
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(error)?;
+        let model = Model { plan };
+
+        // Better to fail now than at every position
+        model.run(&Grid::new())?;
+        Ok(model)
+    }
+
+    fn run(&self, grid: &Grid) -> io::Result<f32> {
+        let input = Tensor::from_shape(&[1, 9], &encode(grid)).map_err(error)?;
+        let outputs = self.plan.run(tvec!(input.into())).map_err(error)?;
+        let value = outputs.first().and_then(|output| {
+            output
+                .to_plain_array_view::<f32>()
+                .ok()?
+                .iter()
+                .next()
+                .copied()
+        });
+
+        value.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the model gives no value as a float",
+            )
+        })
+    }
+}
+
+impl Evaluator for Model {
+    // A position the model cannot value is taken as even
+    fn evaluate(&self, grid: &Grid) -> f32 {
+        self.run(grid).unwrap_or(0.0)
+    }
+}
+
+fn error(e: TractError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::search;
+    use crate::notation::parse_position;
+    use tract_onnx::pb;
+
+    // The sum of the squares, weighted by `weights`, as an ONNX graph
+    fn linear(weights: [f32; 9]) -> pb::ModelProto {
+        let tensor = |dims: Vec<i64>| {
+            let dim = dims
+                .into_iter()
+                .map(|d| pb::tensor_shape_proto::Dimension {
+                    value: Some(pb::tensor_shape_proto::dimension::Value::DimValue(d)),
+                    ..Default::default()
+                })
+                .collect();
+            Some(pb::TypeProto {
+                value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                    elem_type: pb::tensor_proto::DataType::Float as i32,
+                    shape: Some(pb::TensorShapeProto { dim }),
+                })),
+                ..Default::default()
+            })
+        };
+        let value = |name: &str, dims| pb::ValueInfoProto {
+            name: name.to_string(),
+            r#type: tensor(dims),
+            ..Default::default()
+        };
+
+        pb::ModelProto {
+            ir_version: 8,
+            opset_import: vec![pb::OperatorSetIdProto {
+                domain: String::new(),
+                version: 13,
+            }],
+            graph: Some(pb::GraphProto {
+                node: vec![pb::NodeProto {
+                    input: vec!["board".to_string(), "weights".to_string()],
+                    output: vec!["value".to_string()],
+                    op_type: "MatMul".to_string(),
+                    ..Default::default()
+                }],
+                initializer: vec![pb::TensorProto {
+                    name: "weights".to_string(),
+                    dims: vec![9, 1],
+                    data_type: pb::tensor_proto::DataType::Float as i32,
+                    float_data: weights.to_vec(),
+                    ..Default::default()
+                }],
+                input: vec![value("board", vec![1, 9])],
+                output: vec![value("value", vec![1, 1])],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn evaluates_with_a_model() {
+        // Corners are worth the most, then the center
+        let weights = [0.3, 0.1, 0.3, 0.1, 0.2, 0.1, 0.3, 0.1, 0.3];
+        let proto = linear(weights);
+        let model = Model::new(onnx().model_for_proto_model(&proto).unwrap()).unwrap();
+
+        let grid = parse_position(".../.X./...").unwrap();
+        // The center is the opponent's
+        assert!((model.evaluate(&grid) + 0.2).abs() < 1e-6);
+        // So O takes a corner
+        let (m, _) = search(&grid, 1, &model).unwrap();
+        assert_eq!(m, (0, 0));
+    }
+
+    #[test]
+    fn invalid_models() {
+        let mut proto = linear([0.0; 9]);
+        let graph = proto.graph.as_mut().unwrap();
+        graph.initializer[0].dims = vec![3, 1];
+        graph.initializer[0].float_data.truncate(3);
+        let model = onnx().model_for_proto_model(&proto);
+        assert!(model.map_or(true, |model| Model::new(model).is_err()));
+
+        assert!(Model::read(&mut &b"not a model"[..]).is_err());
+    }
+}
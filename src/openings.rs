@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::analysis::{canonical, solve, Value};
+use crate::engine::{legal_moves, Difficulty};
+use crate::record::GameRecord;
+use crate::stats::Tally;
+use crate::{Grid, Player};
+
+// A few moves from the empty board, standing for all the openings that are the
+// same up to a rotation or a reflection
+#[derive(Debug, PartialEq, Clone)]
+pub struct Opening {
+    pub moves: Vec<(usize, usize)>,
+    // The value of the position reached, for the player to move next
+    pub value: Value,
+    // The games of the archive that started this way, counted when a human
+    // played the last move of the opening, with their results for that human
+    pub played: Tally,
+}
+
+impl Opening {
+    pub fn grid(&self) -> Grid {
+        let mut grid = Grid::new();
+        for &(x, y) in &self.moves {
+            let _ = grid.set(x, y);
+        }
+        grid
+    }
+}
+
+// The openings of `plies` moves, in the order of the squares, X moving first.
+// Openings that end the game are left out.
+pub fn explore(records: &[GameRecord], plies: usize) -> Vec<Opening> {
+    let mut seen = HashSet::new();
+    let mut openings = Vec::new();
+    let mut stack = vec![Vec::new()];
+
+    while let Some(moves) = stack.pop() {
+        let opening = Opening {
+            moves,
+            value: Value::Draw,
+            played: Tally::default(),
+        };
+        let grid = opening.grid();
+        if !seen.insert(canonical(&grid)) {
+            continue;
+        }
+        if opening.moves.len() < plies {
+            // Reversed, so that the first square is explored first
+            for m in legal_moves(&grid).into_iter().rev() {
+                let mut next = opening.moves.clone();
+                next.push(m);
+                stack.push(next);
+            }
+            continue;
+        }
+        if let Some(solution) = solve(&grid) {
+            openings.push(Opening {
+                value: solution.value,
+                ..opening
+            });
+        }
+    }
+
+    for record in records {
+        let Some(moves) = record.moves.get(..plies) else {
+            continue;
+        };
+        let opening = Opening {
+            moves: moves.to_vec(),
+            value: Value::Draw,
+            played: Tally::default(),
+        };
+        let key = canonical(&opening.grid());
+        let side = if plies % 2 == 1 {
+            record.first_player
+        } else {
+            record.first_player.opponent()
+        };
+        if !played_by_human(record, side) {
+            continue;
+        }
+        if let Some(o) = openings.iter_mut().find(|o| canonical(&o.grid()) == key) {
+            o.played.record(record.result, side);
+        }
+    }
+
+    openings
+}
+
+// The bot is logged as `Bot` in games against it, and the engines of a
+// tournament by their difficulty
+fn played_by_human(record: &GameRecord, side: Player) -> bool {
+    let name = if side == Player::X {
+        &record.x
+    } else {
+        &record.o
+    };
+    let bot = name == "Bot" && record.tag("Difficulty").is_some();
+    !bot && Difficulty::from_str(name).is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::test_game;
+    use crate::session::GameResult;
+
+    #[test]
+    fn first_moves() {
+        let openings = explore(&[], 1);
+        let moves: Vec<_> = openings.iter().map(|o| o.moves[0]).collect();
+        // A corner, a side and the center
+        assert_eq!(moves, [(0, 0), (1, 0), (1, 1)]);
+        assert!(openings.iter().all(|o| o.value == Value::Draw));
+    }
+
+    #[test]
+    fn replies() {
+        let openings = explore(&[], 2);
+        assert_eq!(openings.len(), 12);
+        // Only the corners hold the draw against the center
+        let center: Vec<_> = openings.iter().filter(|o| o.moves[0] == (1, 1)).collect();
+        assert_eq!(center.len(), 2);
+        assert_eq!(center[0].moves[1], (0, 0));
+        assert_eq!(center[0].value, Value::Draw);
+        assert!(matches!(center[1].value, Value::Win(_)));
+    }
+
+    #[test]
+    fn archive() {
+        let game = |x: &str, o: &str, moves: Vec<(usize, usize)>, result| {
+            let mut record = test_game(x, o, &moves, result);
+            record.set_tag("Difficulty", "hard");
+            record
+        };
+        let records = [
+            game("alice", "Bot", vec![(2, 2), (1, 1)], GameResult::Draw),
+            game(
+                "alice",
+                "Bot",
+                vec![(0, 0), (1, 0)],
+                GameResult::Win(Player::X),
+            ),
+            game("Bot", "alice", vec![(1, 1), (2, 0)], GameResult::Draw),
+        ];
+
+        let openings = explore(&records, 1);
+        // The last game was opened by the bot
+        let corner = &openings[0];
+        assert_eq!(corner.played.games(), 2);
+        assert_eq!(corner.played.wins, 1);
+        assert_eq!(openings[2].played.games(), 0);
+
+        let openings = explore(&records, 2);
+        let center = openings.iter().find(|o| o.moves == [(1, 1), (0, 0)]);
+        assert_eq!(center.unwrap().played.draws, 1);
+    }
+}
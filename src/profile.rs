@@ -0,0 +1,319 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::elo::{self, INITIAL_RATING};
+use crate::session::GameResult;
+use crate::Player;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub dailies_played: u32,
+    pub dailies_solved: u32,
+    // The date of the last daily puzzle played
+    pub last_daily: Option<String>,
+    // The Elo rating of the player
+    pub rating: f64,
+    // The puzzles solved in a row, and the longest such run
+    pub puzzle_streak: u32,
+    pub best_puzzle_streak: u32,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            dailies_played: 0,
+            dailies_solved: 0,
+            last_daily: None,
+            rating: INITIAL_RATING,
+            puzzle_streak: 0,
+            best_puzzle_streak: 0,
+        }
+    }
+
+    // Record the first attempt at the daily puzzle of `date`, returning whether
+    // it was recorded
+    pub fn record_daily(&mut self, date: &str, solved: bool) -> bool {
+        if self.last_daily.as_deref() == Some(date) {
+            return false;
+        }
+
+        self.dailies_played += 1;
+        if solved {
+            self.dailies_solved += 1;
+        }
+        self.last_daily = Some(date.to_string());
+        true
+    }
+
+    // Count the attempt at the daily puzzle of `date`, recorded unsolved
+    // when it started, as solved
+    pub fn solve_daily(&mut self, date: &str) {
+        if self.last_daily.as_deref() == Some(date) && self.dailies_solved < self.dailies_played {
+            self.dailies_solved += 1;
+        }
+    }
+
+    // Record an attempt at a puzzle, a failure ending the streak
+    pub fn record_puzzle(&mut self, solved: bool) {
+        if solved {
+            self.puzzle_streak += 1;
+            self.best_puzzle_streak = self.best_puzzle_streak.max(self.puzzle_streak);
+        } else {
+            self.puzzle_streak = 0;
+        }
+    }
+
+    // Record the result of a game where this profile played `side`
+    pub fn record(&mut self, result: GameResult, side: Player) {
+        match result.winner() {
+            Some(p) if p == side => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    // Update the rating after a game against an opponent rated `opponent`
+    pub fn rate(&mut self, result: GameResult, side: Player, opponent: f64, k: f64) {
+        self.rating = elo::update(self.rating, opponent, elo::score(result, side), k);
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} wins, {} losses, {} draws",
+            self.name, self.wins, self.losses, self.draws
+        )
+    }
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['\t', '\n', '\r'])
+}
+
+// All the profiles of a data file, stored one per line as
+// `name<TAB>wins<TAB>losses<TAB>draws<TAB>dailies played<TAB>dailies solved<TAB>last daily<TAB>rating<TAB>puzzle streak<TAB>best puzzle streak`
+// (older files only have the first four, seven or eight fields).
+#[derive(Debug, Default, Clone)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    pub fn load(path: &Path) -> io::Result<Profiles> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Profiles::default()),
+            Err(e) => return Err(e),
+        };
+
+        Profiles::parse(&content)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid profiles file"))
+    }
+
+    fn parse(content: &str) -> Option<Profiles> {
+        let mut profiles = Vec::new();
+
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let mut profile = match fields.len() {
+                4 | 7 | 8 | 10 => Profile {
+                    wins: fields[1].parse().ok()?,
+                    losses: fields[2].parse().ok()?,
+                    draws: fields[3].parse().ok()?,
+                    ..Profile::new(fields[0])
+                },
+                _ => return None,
+            };
+            if fields.len() >= 7 {
+                profile.dailies_played = fields[4].parse().ok()?;
+                profile.dailies_solved = fields[5].parse().ok()?;
+                profile.last_daily = Some(fields[6].to_string()).filter(|d| !d.is_empty());
+            }
+            if fields.len() >= 8 {
+                profile.rating = fields[7].parse().ok()?;
+            }
+            if fields.len() == 10 {
+                profile.puzzle_streak = fields[8].parse().ok()?;
+                profile.best_puzzle_streak = fields[9].parse().ok()?;
+            }
+
+            profiles.push(profile);
+        }
+
+        Some(Profiles { profiles })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut content = String::new();
+        for p in self.profiles.iter() {
+            content += &format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.1}\t{}\t{}\n",
+                p.name,
+                p.wins,
+                p.losses,
+                p.draws,
+                p.dailies_played,
+                p.dailies_solved,
+                p.last_daily.as_deref().unwrap_or(""),
+                p.rating,
+                p.puzzle_streak,
+                p.best_puzzle_streak
+            );
+        }
+
+        fs::write(path, content)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    // The profile called `name`, created if it does not exist yet
+    pub fn get_or_create(&mut self, name: &str) -> &mut Profile {
+        let i = match self.profiles.iter().position(|p| p.name == name) {
+            Some(i) => i,
+            None => {
+                self.profiles.push(Profile::new(name));
+                self.profiles.len() - 1
+            }
+        };
+
+        &mut self.profiles[i]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.iter()
+    }
+}
+
+// Where the data of the game is kept, following the XDG base directories
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("tictactoe"));
+    }
+
+    let home = env::var_os("HOME").filter(|h| !h.is_empty())?;
+    Some(PathBuf::from(home).join(".local/share/tictactoe"))
+}
+
+pub fn profiles_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("profiles"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record() {
+        let mut profile = Profile::new("alice");
+        profile.record(GameResult::Win(Player::X), Player::X);
+        profile.record(GameResult::Win(Player::X), Player::O);
+        profile.record(GameResult::Draw, Player::O);
+
+        assert_eq!((profile.wins, profile.losses, profile.draws), (1, 1, 1));
+        assert_eq!(profile.games(), 3);
+
+        profile.rate(GameResult::Win(Player::X), Player::X, INITIAL_RATING, 20.0);
+        assert_eq!(profile.rating, INITIAL_RATING + 10.0);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = env::temp_dir().join(format!("tictactoe-profiles-{}", std::process::id()));
+
+        let mut profiles = Profiles::default();
+        profiles.get_or_create("alice").wins = 3;
+        profiles.get_or_create("bob").draws = 1;
+        profiles.get_or_create("alice").losses = 2;
+        profiles.get_or_create("alice").rating = 1612.34;
+        profiles
+            .get_or_create("bob")
+            .record_daily("2026-10-14", true);
+        profiles.get_or_create("bob").record_puzzle(true);
+        profiles.save(&path).unwrap();
+
+        let loaded = Profiles::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.iter().count(), 2);
+        assert_eq!(
+            loaded.get("alice").map(|p| (p.wins, p.losses)),
+            Some((3, 2))
+        );
+        assert_eq!(loaded.get("bob").map(|p| p.draws), Some(1));
+        assert_eq!(loaded.get("alice").map(|p| p.rating), Some(1612.3));
+        assert_eq!(loaded.get("bob").map(|p| p.rating), Some(INITIAL_RATING));
+        assert_eq!(
+            loaded.get("bob").and_then(|p| p.last_daily.as_deref()),
+            Some("2026-10-14")
+        );
+        assert_eq!(loaded.get("alice").and_then(|p| p.last_daily.clone()), None);
+        assert_eq!(
+            loaded
+                .get("bob")
+                .map(|p| (p.puzzle_streak, p.best_puzzle_streak)),
+            Some((1, 1))
+        );
+    }
+
+    #[test]
+    fn daily() {
+        let mut profile = Profile::new("alice");
+        assert!(profile.record_daily("2026-10-13", false));
+        assert!(profile.record_daily("2026-10-14", true));
+        assert!(!profile.record_daily("2026-10-14", false));
+
+        assert_eq!((profile.dailies_played, profile.dailies_solved), (2, 1));
+
+        // Recorded when the attempt starts, then solved
+        assert!(profile.record_daily("2026-10-15", false));
+        profile.solve_daily("2026-10-15");
+        profile.solve_daily("2026-10-14");
+        assert_eq!((profile.dailies_played, profile.dailies_solved), (3, 2));
+    }
+
+    #[test]
+    fn puzzle_streaks() {
+        let mut profile = Profile::new("alice");
+        for solved in [true, true, false, true] {
+            profile.record_puzzle(solved);
+        }
+
+        assert_eq!((profile.puzzle_streak, profile.best_puzzle_streak), (1, 2));
+    }
+
+    #[test]
+    fn invalid_file() {
+        assert!(Profiles::parse("alice\t1\t2\n").is_none());
+        assert!(Profiles::parse("alice\t1\ttwo\t3\n").is_none());
+        assert!(Profiles::parse("\n").is_some());
+        assert!(Profiles::parse("alice\t1\t2\t3\n").is_some());
+        assert!(Profiles::parse("alice\t1\t2\t3\t1\t1\t\t1512.5\n").is_some());
+        assert!(Profiles::parse("alice\t1\t2\t3\t1\t1\t\tgood\n").is_none());
+        assert!(Profiles::parse("alice\t1\t2\t3\t1\t1\n").is_none());
+        assert!(Profiles::parse("alice\t1\t2\t3\t1\t1\t\t1512.5\t2\t4\n").is_some());
+        assert!(Profiles::parse("alice\t1\t2\t3\t1\t1\t\t1512.5\t2\n").is_none());
+    }
+}
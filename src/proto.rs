@@ -0,0 +1,1087 @@
+use std::fmt;
+
+use prost::Message as _;
+
+use crate::notation::parse_coordinates;
+use crate::protocol;
+use crate::record;
+use crate::session;
+use crate::{coordinates, Player as GridPlayer};
+#[cfg(feature = "server")]
+use crate::{engine, json, server};
+
+// The types of `proto/tictactoe.proto`, written as prost would generate them,
+// so that building needs no `protoc`: both files change together, and the
+// `descriptor_matches` test reads the .proto file to check that their fields
+// have the same names, tags and wire types. The conversions from and to the
+// types of the crate are at the end.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Player {
+    Empty = 0,
+    X = 1,
+    O = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Message {
+    #[prost(oneof = "message::Kind", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10")]
+    pub kind: Option<message::Kind>,
+}
+
+pub mod message {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "1")]
+        Hello(super::Hello),
+        #[prost(message, tag = "2")]
+        MoveProposed(super::MoveProposed),
+        #[prost(message, tag = "3")]
+        MoveAccepted(super::MoveAccepted),
+        #[prost(message, tag = "4")]
+        Rejected(super::Rejected),
+        #[prost(message, tag = "5")]
+        Resign(super::Resign),
+        #[prost(message, tag = "6")]
+        DrawOffer(super::DrawOffer),
+        #[prost(message, tag = "7")]
+        DrawAccepted(super::DrawAccepted),
+        #[prost(message, tag = "8")]
+        GameOver(super::GameOver),
+        #[prost(message, tag = "9")]
+        Ping(super::Ping),
+        #[prost(message, tag = "10")]
+        Chat(super::Chat),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Hello {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MoveProposed {
+    #[prost(string, tag = "1")]
+    pub square: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MoveAccepted {
+    #[prost(enumeration = "Player", tag = "1")]
+    pub player: i32,
+    #[prost(string, tag = "2")]
+    pub square: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Rejected {
+    #[prost(string, tag = "1")]
+    pub reason: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Resign {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DrawOffer {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DrawAccepted {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GameOver {
+    #[prost(enumeration = "Player", tag = "1")]
+    pub winner: i32,
+    #[prost(string, tag = "2")]
+    pub termination: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Ping {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Chat {
+    #[prost(string, tag = "1")]
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GameRecord {
+    #[prost(string, tag = "1")]
+    pub date: String,
+    #[prost(string, tag = "2")]
+    pub time: String,
+    #[prost(string, tag = "3")]
+    pub x: String,
+    #[prost(string, tag = "4")]
+    pub o: String,
+    #[prost(enumeration = "Player", tag = "5")]
+    pub first_player: i32,
+    #[prost(string, repeated, tag = "6")]
+    pub moves: Vec<String>,
+    #[prost(message, optional, tag = "7")]
+    pub result: Option<GameResult>,
+    #[prost(message, repeated, tag = "8")]
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GameResult {
+    #[prost(enumeration = "game_result::Kind", tag = "1")]
+    pub kind: i32,
+    #[prost(enumeration = "Player", tag = "2")]
+    pub player: i32,
+}
+
+pub mod game_result {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Kind {
+        Win = 0,
+        Draw = 1,
+        Timeout = 2,
+        Resigned = 3,
+        DrawAgreed = 4,
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Tag {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Request {
+    #[prost(oneof = "request::Kind", tags = "1, 2, 3, 4, 5, 6, 7")]
+    pub kind: Option<request::Kind>,
+}
+
+pub mod request {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "1")]
+        New(super::New),
+        #[prost(message, tag = "2")]
+        Join(super::Join),
+        #[prost(message, tag = "3")]
+        List(super::List),
+        #[prost(message, tag = "4")]
+        QuickMatch(super::QuickMatch),
+        #[prost(message, tag = "5")]
+        Watch(super::Watch),
+        #[prost(message, tag = "6")]
+        Resume(super::Resume),
+        #[prost(message, tag = "7")]
+        Message(super::Message),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Opponent {
+    Bot = 0,
+    Human = 1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Difficulty {
+    Easy = 0,
+    Medium = 1,
+    Hard = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct New {
+    #[prost(enumeration = "Opponent", tag = "1")]
+    pub opponent: i32,
+    #[prost(enumeration = "Difficulty", optional, tag = "2")]
+    pub difficulty: Option<i32>,
+    #[prost(uint64, optional, tag = "3")]
+    pub clock: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Join {
+    #[prost(uint64, tag = "1")]
+    pub game: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct List {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QuickMatch {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Watch {
+    #[prost(uint64, tag = "1")]
+    pub game: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Resume {
+    #[prost(string, tag = "1")]
+    pub token: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Reply {
+    #[prost(oneof = "reply::Kind", tags = "1, 2, 3, 4, 5, 6, 7")]
+    pub kind: Option<reply::Kind>,
+}
+
+pub mod reply {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "1")]
+        Joined(super::Joined),
+        #[prost(message, tag = "2")]
+        Watching(super::Watching),
+        #[prost(message, tag = "3")]
+        Opponent(super::OpponentFound),
+        #[prost(message, tag = "4")]
+        OpenGames(super::OpenGames),
+        #[prost(message, tag = "5")]
+        State(super::State),
+        #[prost(message, tag = "6")]
+        Chat(super::ChatFrom),
+        #[prost(message, tag = "7")]
+        Message(super::Message),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Joined {
+    #[prost(uint64, tag = "1")]
+    pub game: u64,
+    #[prost(enumeration = "Player", tag = "2")]
+    pub player: i32,
+    #[prost(string, tag = "3")]
+    pub token: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Watching {
+    #[prost(uint64, tag = "1")]
+    pub game: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OpponentFound {
+    #[prost(string, tag = "1")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OpenGames {
+    #[prost(message, repeated, tag = "1")]
+    pub games: Vec<OpenGame>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OpenGame {
+    #[prost(uint64, tag = "1")]
+    pub game: u64,
+    #[prost(string, tag = "2")]
+    pub host: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct State {
+    #[prost(enumeration = "Player", repeated, tag = "1")]
+    pub board: Vec<i32>,
+    #[prost(enumeration = "Player", tag = "2")]
+    pub turn: i32,
+    #[prost(string, repeated, tag = "3")]
+    pub legal_moves: Vec<String>,
+    #[prost(message, repeated, tag = "4")]
+    pub events: Vec<Event>,
+    #[prost(message, optional, tag = "5")]
+    pub clock: Option<ClockState>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Event {
+    #[prost(oneof = "event::Kind", tags = "1, 2, 3")]
+    pub kind: Option<event::Kind>,
+}
+
+pub mod event {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "1")]
+        Move(super::MoveAccepted),
+        #[prost(message, tag = "2")]
+        Rejected(super::Rejected),
+        #[prost(message, tag = "3")]
+        GameOver(super::GameOver),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ClockState {
+    #[prost(uint64, tag = "1")]
+    pub x: u64,
+    #[prost(uint64, tag = "2")]
+    pub o: u64,
+    #[prost(enumeration = "Player", tag = "3")]
+    pub running: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ChatFrom {
+    #[prost(string, tag = "1")]
+    pub from: String,
+    #[prost(string, tag = "2")]
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ProtoError {
+    // Not a protobuf message of this type
+    Decode(String),
+    // A message without its kind, or a game record without its result
+    Missing(&'static str),
+    // A player or result kind this version does not know
+    Unknown(&'static str, i32),
+    // A move of a game record that is not a square
+    Square(String),
+    // A board without its 9 squares
+    Board(usize),
+}
+
+impl fmt::Display for ProtoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(e) => write!(f, "not a valid message: {e}"),
+            Self::Missing(field) => write!(f, "the {field} is missing"),
+            Self::Unknown(field, value) => write!(f, "unknown {field} {value}"),
+            Self::Square(square) => write!(f, "{square} is not a square"),
+            Self::Board(n) => write!(f, "a board of {n} squares instead of 9"),
+        }
+    }
+}
+
+// Encode a message of the network protocol
+pub fn encode(message: &protocol::Message) -> Vec<u8> {
+    Message::from(message.clone()).encode_to_vec()
+}
+
+// Decode a message written by `encode`
+pub fn decode(bytes: &[u8]) -> Result<protocol::Message, ProtoError> {
+    let message = Message::decode(bytes).map_err(|e| ProtoError::Decode(e.to_string()))?;
+    message.try_into()
+}
+
+impl From<GridPlayer> for Player {
+    fn from(player: GridPlayer) -> Self {
+        match player {
+            GridPlayer::X => Player::X,
+            GridPlayer::O => Player::O,
+            GridPlayer::Empty => Player::Empty,
+        }
+    }
+}
+
+impl From<Player> for GridPlayer {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::X => GridPlayer::X,
+            Player::O => GridPlayer::O,
+            Player::Empty => GridPlayer::Empty,
+        }
+    }
+}
+
+fn player(value: i32) -> Result<GridPlayer, ProtoError> {
+    Player::try_from(value)
+        .map(GridPlayer::from)
+        .map_err(|_| ProtoError::Unknown("player", value))
+}
+
+impl From<protocol::Message> for Message {
+    fn from(message: protocol::Message) -> Self {
+        use message::Kind;
+
+        let kind = match message {
+            protocol::Message::Hello { version, name } => Kind::Hello(Hello { version, name }),
+            protocol::Message::MoveProposed { square } => {
+                Kind::MoveProposed(MoveProposed { square })
+            }
+            protocol::Message::MoveAccepted { player, square } => {
+                Kind::MoveAccepted(MoveAccepted {
+                    player: Player::from(player).into(),
+                    square,
+                })
+            }
+            protocol::Message::Rejected { reason } => Kind::Rejected(Rejected { reason }),
+            protocol::Message::Resign => Kind::Resign(Resign {}),
+            protocol::Message::DrawOffer => Kind::DrawOffer(DrawOffer {}),
+            protocol::Message::DrawAccepted => Kind::DrawAccepted(DrawAccepted {}),
+            protocol::Message::GameOver {
+                winner,
+                termination,
+            } => Kind::GameOver(GameOver {
+                winner: Player::from(winner.unwrap_or(GridPlayer::Empty)).into(),
+                termination,
+            }),
+            protocol::Message::Ping => Kind::Ping(Ping {}),
+            protocol::Message::Chat { text } => Kind::Chat(Chat { text }),
+        };
+
+        Message { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<Message> for protocol::Message {
+    type Error = ProtoError;
+
+    fn try_from(message: Message) -> Result<Self, Self::Error> {
+        use message::Kind;
+
+        Ok(match message.kind.ok_or(ProtoError::Missing("kind"))? {
+            Kind::Hello(Hello { version, name }) => protocol::Message::Hello { version, name },
+            Kind::MoveProposed(MoveProposed { square }) => {
+                protocol::Message::MoveProposed { square }
+            }
+            Kind::MoveAccepted(MoveAccepted { player: p, square }) => {
+                protocol::Message::MoveAccepted {
+                    player: player(p)?,
+                    square,
+                }
+            }
+            Kind::Rejected(Rejected { reason }) => protocol::Message::Rejected { reason },
+            Kind::Resign(_) => protocol::Message::Resign,
+            Kind::DrawOffer(_) => protocol::Message::DrawOffer,
+            Kind::DrawAccepted(_) => protocol::Message::DrawAccepted,
+            Kind::GameOver(GameOver {
+                winner,
+                termination,
+            }) => protocol::Message::GameOver {
+                winner: Some(player(winner)?).filter(|&p| p != GridPlayer::Empty),
+                termination,
+            },
+            Kind::Ping(_) => protocol::Message::Ping,
+            Kind::Chat(Chat { text }) => protocol::Message::Chat { text },
+        })
+    }
+}
+
+impl From<session::GameResult> for GameResult {
+    fn from(result: session::GameResult) -> Self {
+        use game_result::Kind;
+
+        let (kind, player) = match result {
+            session::GameResult::Win(p) => (Kind::Win, p),
+            session::GameResult::Draw => (Kind::Draw, GridPlayer::Empty),
+            session::GameResult::Timeout(p) => (Kind::Timeout, p),
+            session::GameResult::Resigned(p) => (Kind::Resigned, p),
+            session::GameResult::DrawAgreed => (Kind::DrawAgreed, GridPlayer::Empty),
+        };
+
+        GameResult {
+            kind: kind.into(),
+            player: Player::from(player).into(),
+        }
+    }
+}
+
+impl TryFrom<GameResult> for session::GameResult {
+    type Error = ProtoError;
+
+    fn try_from(result: GameResult) -> Result<Self, Self::Error> {
+        use game_result::Kind;
+
+        let kind = Kind::try_from(result.kind)
+            .map_err(|_| ProtoError::Unknown("result kind", result.kind))?;
+        let p = player(result.player)?;
+        Ok(match kind {
+            Kind::Win => session::GameResult::Win(p),
+            Kind::Draw => session::GameResult::Draw,
+            Kind::Timeout => session::GameResult::Timeout(p),
+            Kind::Resigned => session::GameResult::Resigned(p),
+            Kind::DrawAgreed => session::GameResult::DrawAgreed,
+        })
+    }
+}
+
+impl From<record::GameRecord> for GameRecord {
+    fn from(record: record::GameRecord) -> Self {
+        GameRecord {
+            date: record.date,
+            time: record.time,
+            x: record.x,
+            o: record.o,
+            first_player: Player::from(record.first_player).into(),
+            moves: record
+                .moves
+                .iter()
+                .map(|&(x, y)| coordinates(x, y))
+                .collect(),
+            result: Some(record.result.into()),
+            tags: record
+                .tags
+                .into_iter()
+                .map(|(name, value)| Tag { name, value })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<GameRecord> for record::GameRecord {
+    type Error = ProtoError;
+
+    fn try_from(record: GameRecord) -> Result<Self, Self::Error> {
+        let moves = record
+            .moves
+            .iter()
+            .map(|square| parse_coordinates(square).map_err(|_| ProtoError::Square(square.clone())))
+            .collect::<Result<_, _>>()?;
+        let result = record.result.ok_or(ProtoError::Missing("result"))?;
+
+        Ok(record::GameRecord {
+            date: record.date,
+            time: record.time,
+            x: record.x,
+            o: record.o,
+            first_player: player(record.first_player)?,
+            moves,
+            result: result.try_into()?,
+            tags: record
+                .tags
+                .into_iter()
+                .map(|tag| (tag.name, tag.value))
+                .collect(),
+        })
+    }
+}
+
+// The requests and replies of the server, which needs the `server` feature
+
+#[cfg(feature = "server")]
+fn optional_player(player: Option<GridPlayer>) -> i32 {
+    Player::from(player.unwrap_or(GridPlayer::Empty)).into()
+}
+
+#[cfg(feature = "server")]
+fn player_or_none(value: i32) -> Result<Option<GridPlayer>, ProtoError> {
+    Ok(Some(player(value)?).filter(|&p| p != GridPlayer::Empty))
+}
+
+#[cfg(feature = "server")]
+fn engine_difficulty(value: i32) -> Result<engine::Difficulty, ProtoError> {
+    match Difficulty::try_from(value) {
+        Ok(Difficulty::Easy) => Ok(engine::Difficulty::Easy),
+        Ok(Difficulty::Medium) => Ok(engine::Difficulty::Medium),
+        Ok(Difficulty::Hard) => Ok(engine::Difficulty::Hard),
+        Err(_) => Err(ProtoError::Unknown("difficulty", value)),
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<server::Request> for Request {
+    fn from(request: server::Request) -> Self {
+        use request::Kind;
+
+        let kind = match request {
+            server::Request::New {
+                opponent,
+                difficulty,
+                clock,
+            } => Kind::New(New {
+                opponent: match opponent {
+                    server::Opponent::Bot => Opponent::Bot,
+                    server::Opponent::Human => Opponent::Human,
+                }
+                .into(),
+                difficulty: difficulty.map(|d| {
+                    match d {
+                        engine::Difficulty::Easy => Difficulty::Easy,
+                        engine::Difficulty::Medium => Difficulty::Medium,
+                        engine::Difficulty::Hard => Difficulty::Hard,
+                    }
+                    .into()
+                }),
+                clock,
+            }),
+            server::Request::Join { game } => Kind::Join(Join { game }),
+            server::Request::List => Kind::List(List {}),
+            server::Request::QuickMatch => Kind::QuickMatch(QuickMatch {}),
+            server::Request::Watch { game } => Kind::Watch(Watch { game }),
+            server::Request::Resume { token } => Kind::Resume(Resume { token }),
+            server::Request::Message(message) => Kind::Message(message.into()),
+        };
+
+        Request { kind: Some(kind) }
+    }
+}
+
+#[cfg(feature = "server")]
+impl TryFrom<Request> for server::Request {
+    type Error = ProtoError;
+
+    fn try_from(request: Request) -> Result<Self, Self::Error> {
+        use request::Kind;
+
+        Ok(match request.kind.ok_or(ProtoError::Missing("kind"))? {
+            Kind::New(New {
+                opponent,
+                difficulty,
+                clock,
+            }) => server::Request::New {
+                opponent: match Opponent::try_from(opponent) {
+                    Ok(Opponent::Bot) => server::Opponent::Bot,
+                    Ok(Opponent::Human) => server::Opponent::Human,
+                    Err(_) => return Err(ProtoError::Unknown("opponent", opponent)),
+                },
+                difficulty: difficulty.map(engine_difficulty).transpose()?,
+                clock,
+            },
+            Kind::Join(Join { game }) => server::Request::Join { game },
+            Kind::List(_) => server::Request::List,
+            Kind::QuickMatch(_) => server::Request::QuickMatch,
+            Kind::Watch(Watch { game }) => server::Request::Watch { game },
+            Kind::Resume(Resume { token }) => server::Request::Resume { token },
+            Kind::Message(message) => server::Request::Message(message.try_into()?),
+        })
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<server::Reply> for Reply {
+    fn from(reply: server::Reply) -> Self {
+        use reply::Kind;
+
+        let kind = match reply {
+            server::Reply::Joined {
+                game,
+                player,
+                token,
+            } => Kind::Joined(Joined {
+                game,
+                player: Player::from(player).into(),
+                token,
+            }),
+            server::Reply::Watching { game } => Kind::Watching(Watching { game }),
+            server::Reply::Opponent { name } => Kind::Opponent(OpponentFound { name }),
+            server::Reply::OpenGames { games } => Kind::OpenGames(OpenGames {
+                games: games
+                    .into_iter()
+                    .map(|g| OpenGame {
+                        game: g.game,
+                        host: g.host,
+                    })
+                    .collect(),
+            }),
+            server::Reply::State(state) => Kind::State(state.into()),
+            server::Reply::Chat { from, text } => Kind::Chat(ChatFrom { from, text }),
+            server::Reply::Message(message) => Kind::Message(message.into()),
+        };
+
+        Reply { kind: Some(kind) }
+    }
+}
+
+#[cfg(feature = "server")]
+impl TryFrom<Reply> for server::Reply {
+    type Error = ProtoError;
+
+    fn try_from(reply: Reply) -> Result<Self, Self::Error> {
+        use reply::Kind;
+
+        Ok(match reply.kind.ok_or(ProtoError::Missing("kind"))? {
+            Kind::Joined(Joined {
+                game,
+                player: p,
+                token,
+            }) => server::Reply::Joined {
+                game,
+                player: player(p)?,
+                token,
+            },
+            Kind::Watching(Watching { game }) => server::Reply::Watching { game },
+            Kind::Opponent(OpponentFound { name }) => server::Reply::Opponent { name },
+            Kind::OpenGames(OpenGames { games }) => server::Reply::OpenGames {
+                games: games
+                    .into_iter()
+                    .map(|g| server::OpenGame {
+                        game: g.game,
+                        host: g.host,
+                    })
+                    .collect(),
+            },
+            Kind::State(state) => server::Reply::State(state.try_into()?),
+            Kind::Chat(ChatFrom { from, text }) => server::Reply::Chat { from, text },
+            Kind::Message(message) => server::Reply::Message(message.try_into()?),
+        })
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<json::State> for State {
+    fn from(state: json::State) -> Self {
+        State {
+            board: state
+                .board
+                .iter()
+                .flatten()
+                .map(|&p| Player::from(p).into())
+                .collect(),
+            turn: optional_player(state.turn),
+            legal_moves: state.legal_moves,
+            events: state.events.into_iter().map(Event::from).collect(),
+            clock: state.clock.map(|clock| ClockState {
+                x: clock.x,
+                o: clock.o,
+                running: optional_player(clock.running),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl TryFrom<State> for json::State {
+    type Error = ProtoError;
+
+    fn try_from(state: State) -> Result<Self, Self::Error> {
+        if state.board.len() != 9 {
+            return Err(ProtoError::Board(state.board.len()));
+        }
+        let mut board = [[GridPlayer::Empty; 3]; 3];
+        for (i, &p) in state.board.iter().enumerate() {
+            board[i / 3][i % 3] = player(p)?;
+        }
+        let clock = match state.clock {
+            Some(clock) => Some(json::ClockState {
+                x: clock.x,
+                o: clock.o,
+                running: player_or_none(clock.running)?,
+            }),
+            None => None,
+        };
+
+        Ok(json::State {
+            board,
+            turn: player_or_none(state.turn)?,
+            legal_moves: state.legal_moves,
+            events: state
+                .events
+                .into_iter()
+                .map(json::Event::try_from)
+                .collect::<Result<_, _>>()?,
+            clock,
+        })
+    }
+}
+
+#[cfg(feature = "server")]
+impl From<json::Event> for Event {
+    fn from(event: json::Event) -> Self {
+        use event::Kind;
+
+        let kind = match event {
+            json::Event::Move { player, square } => Kind::Move(MoveAccepted {
+                player: Player::from(player).into(),
+                square,
+            }),
+            json::Event::Rejected { reason } => Kind::Rejected(Rejected { reason }),
+            json::Event::GameOver {
+                winner,
+                termination,
+            } => Kind::GameOver(GameOver {
+                winner: optional_player(winner),
+                termination,
+            }),
+        };
+
+        Event { kind: Some(kind) }
+    }
+}
+
+#[cfg(feature = "server")]
+impl TryFrom<Event> for json::Event {
+    type Error = ProtoError;
+
+    fn try_from(event: Event) -> Result<Self, Self::Error> {
+        use event::Kind;
+
+        Ok(match event.kind.ok_or(ProtoError::Missing("kind"))? {
+            Kind::Move(MoveAccepted { player: p, square }) => json::Event::Move {
+                player: player(p)?,
+                square,
+            },
+            Kind::Rejected(Rejected { reason }) => json::Event::Rejected { reason },
+            Kind::GameOver(GameOver {
+                winner,
+                termination,
+            }) => json::Event::GameOver {
+                winner: player_or_none(winner)?,
+                termination,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // A field of `proto/tictactoe.proto`: its type, name and tag, and the
+    // `oneof` it belongs to
+    struct Field {
+        kind: String,
+        name: String,
+        tag: u32,
+        oneof: Option<String>,
+    }
+
+    // The fields of each message of the .proto file, and its enums
+    fn descriptor() -> (HashMap<String, Vec<Field>>, Vec<String>) {
+        let mut messages: HashMap<String, Vec<Field>> = HashMap::new();
+        let mut enums = Vec::new();
+        // The blocks the line is in, as `message Name`, `oneof name`, ...
+        let mut blocks: Vec<(String, String)> = Vec::new();
+        for line in include_str!("../proto/tictactoe.proto").lines() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            if line.starts_with('}') {
+                blocks.pop();
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [block @ ("message" | "enum" | "oneof"), name, open, ..] => {
+                    if *block == "enum" {
+                        enums.push(name.to_string());
+                    }
+                    // Not `message Resign {}`
+                    if *open == "{" {
+                        blocks.push((block.to_string(), name.to_string()));
+                    }
+                    if *block == "message" {
+                        messages.entry(name.to_string()).or_default();
+                    }
+                }
+                [.., kind, name, "=", tag] if !blocks.is_empty() => {
+                    let Some((_, message)) = blocks.iter().rev().find(|(b, _)| b == "message")
+                    else {
+                        continue;
+                    };
+                    if blocks.last().is_some_and(|(b, _)| b == "enum") {
+                        continue;
+                    }
+                    let oneof = blocks
+                        .last()
+                        .filter(|(b, _)| b == "oneof")
+                        .map(|(_, name)| name.clone());
+                    messages.get_mut(message).unwrap().push(Field {
+                        kind: kind.to_string(),
+                        name: name.to_string(),
+                        tag: tag.trim_end_matches(';').parse().unwrap(),
+                        oneof,
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        (messages, enums)
+    }
+
+    // Check that `T` has the fields of the message `name` of the .proto file,
+    // with the same names in the same order (from its `Debug`) and the same
+    // tags and wire types (by decoding each field alone, which a wrong tag
+    // would skip)
+    fn check<T: prost::Message + Default + PartialEq + fmt::Debug>(name: &str) {
+        let (messages, enums) = descriptor();
+        let fields = &messages[name];
+
+        let mut names: Vec<&str> = Vec::new();
+        for field in fields {
+            let name = field.oneof.as_deref().unwrap_or(&field.name);
+            if names.last() != Some(&name) {
+                names.push(name);
+            }
+        }
+        let debug = format!("{:?}", T::default());
+        let debugged: Vec<&str> = debug
+            .split_once('{')
+            .map(|(_, inner)| inner.trim_end_matches('}'))
+            .into_iter()
+            .flat_map(|inner| inner.split(", "))
+            .filter_map(|field| field.split_once(':'))
+            .map(|(name, _)| name.trim())
+            .collect();
+        assert_eq!(debugged, names, "fields of {name}");
+
+        for field in fields {
+            let (wire_type, value): (u8, &[u8]) = match field.kind.as_str() {
+                "string" => (2, &[1, b'a']),
+                "uint32" | "uint64" => (0, &[1]),
+                kind if enums.iter().any(|e| e == kind) => (0, &[1]),
+                // An empty message, which is still set
+                _ => (2, &[0]),
+            };
+            let mut bytes = vec![(field.tag << 3) as u8 | wire_type];
+            bytes.extend_from_slice(value);
+            let decoded = T::decode(bytes.as_slice());
+            assert!(
+                decoded.is_ok_and(|m| m != T::default()),
+                "{name}.{} = {}",
+                field.name,
+                field.tag
+            );
+        }
+    }
+
+    #[test]
+    fn descriptor_matches() {
+        check::<Message>("Message");
+        check::<Hello>("Hello");
+        check::<MoveProposed>("MoveProposed");
+        check::<MoveAccepted>("MoveAccepted");
+        check::<Rejected>("Rejected");
+        check::<Resign>("Resign");
+        check::<DrawOffer>("DrawOffer");
+        check::<DrawAccepted>("DrawAccepted");
+        check::<GameOver>("GameOver");
+        check::<Ping>("Ping");
+        check::<Chat>("Chat");
+        check::<GameRecord>("GameRecord");
+        check::<GameResult>("GameResult");
+        check::<Tag>("Tag");
+        check::<Request>("Request");
+        check::<New>("New");
+        check::<Join>("Join");
+        check::<List>("List");
+        check::<QuickMatch>("QuickMatch");
+        check::<Watch>("Watch");
+        check::<Resume>("Resume");
+        check::<Reply>("Reply");
+        check::<Joined>("Joined");
+        check::<Watching>("Watching");
+        check::<OpponentFound>("OpponentFound");
+        check::<OpenGames>("OpenGames");
+        check::<OpenGame>("OpenGame");
+        check::<State>("State");
+        check::<Event>("Event");
+        check::<ClockState>("ClockState");
+        check::<ChatFrom>("ChatFrom");
+
+        // Every message of the file has its type
+        assert_eq!(descriptor().0.len(), 31);
+    }
+
+    #[test]
+    fn messages() {
+        let messages = [
+            protocol::Message::hello("alice"),
+            protocol::Message::MoveAccepted {
+                player: GridPlayer::O,
+                square: "b2".to_string(),
+            },
+            protocol::Message::GameOver {
+                winner: None,
+                termination: "normal".to_string(),
+            },
+            protocol::Message::DrawOffer,
+        ];
+        for message in messages {
+            assert_eq!(decode(&encode(&message)), Ok(message));
+        }
+
+        assert_eq!(decode(&[]), Err(ProtoError::Missing("kind")));
+        assert!(matches!(decode(&[0xff]), Err(ProtoError::Decode(_))));
+    }
+
+    #[test]
+    fn records() {
+        let record = record::GameRecord {
+            date: "2026-10-14".to_string(),
+            time: "18:53:51".to_string(),
+            x: "alice".to_string(),
+            o: "Bot".to_string(),
+            first_player: GridPlayer::X,
+            moves: vec![(1, 1), (0, 0)],
+            result: session::GameResult::Resigned(GridPlayer::O),
+            tags: vec![("Difficulty".to_string(), "hard".to_string())],
+        };
+
+        let bytes = GameRecord::from(record.clone()).encode_to_vec();
+        let decoded = GameRecord::decode(bytes.as_slice()).unwrap();
+        assert_eq!(record::GameRecord::try_from(decoded), Ok(record));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn server() {
+        let requests = [
+            server::Request::New {
+                opponent: server::Opponent::Human,
+                difficulty: Some(engine::Difficulty::Medium),
+                clock: Some(60),
+            },
+            server::Request::New {
+                opponent: server::Opponent::Bot,
+                difficulty: None,
+                clock: None,
+            },
+            server::Request::Watch { game: 3 },
+            server::Request::Message(protocol::Message::hello("bob")),
+        ];
+        for request in requests {
+            let bytes = Request::from(request.clone()).encode_to_vec();
+            let decoded = Request::decode(bytes.as_slice()).unwrap();
+            assert_eq!(server::Request::try_from(decoded), Ok(request));
+        }
+
+        let mut session = session::GameSession::new();
+        session.play(1, 1).unwrap();
+        session.resign(GridPlayer::O).unwrap();
+        let events = vec![
+            json::Event::Move {
+                player: GridPlayer::X,
+                square: "b2".to_string(),
+            },
+            json::Event::GameOver {
+                winner: Some(GridPlayer::X),
+                termination: "resignation".to_string(),
+            },
+        ];
+        let replies = [
+            server::Reply::Joined {
+                game: 1,
+                player: GridPlayer::O,
+                token: "t0k3n".to_string(),
+            },
+            server::Reply::OpenGames {
+                games: vec![server::OpenGame {
+                    game: 2,
+                    host: "alice".to_string(),
+                }],
+            },
+            server::Reply::State(json::State::of(&session, events)),
+            server::Reply::Message(protocol::Message::rejected("no such game")),
+        ];
+        for reply in replies {
+            let bytes = Reply::from(reply.clone()).encode_to_vec();
+            let decoded = Reply::decode(bytes.as_slice()).unwrap();
+            assert_eq!(server::Reply::try_from(decoded), Ok(reply));
+        }
+
+        let state = State {
+            board: vec![0; 8],
+            ..State::default()
+        };
+        assert_eq!(json::State::try_from(state), Err(ProtoError::Board(8)));
+    }
+}
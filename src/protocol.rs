@@ -0,0 +1,220 @@
+use std::fmt;
+
+use crate::notation::parse_coordinates;
+use crate::record::termination_tag;
+use crate::session::{GameResult, GameSession, PlayError};
+use crate::{coordinates, Player};
+
+// Both ends of a connection must speak the same version of the protocol
+pub const VERSION: u32 = 1;
+
+// The longest chat message, in bytes
+pub const MAX_CHAT: usize = 200;
+
+// The messages of network games, whatever carries them (TCP, WebSockets...).
+// With serde, they are tagged by their `type`:
+//
+//     {"type":"hello","version":1,"name":"alice"}
+//     {"type":"move_proposed","square":"b2"}
+//     {"type":"move_accepted","player":"X","square":"b2"}
+//
+// A draw offer stands until the player it is made to plays a move, which
+// declines it, or accepts it with `draw_accepted`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum Message {
+    // The first message of each end, see `check_hello`
+    Hello {
+        version: u32,
+        name: String,
+    },
+    // A move, which the other end checks against its own grid
+    MoveProposed {
+        square: String,
+    },
+    // The answer to a legal `MoveProposed`, once played
+    MoveAccepted {
+        player: Player,
+        square: String,
+    },
+    // The last message could not be played or understood
+    Rejected {
+        reason: String,
+    },
+    Resign,
+    DrawOffer,
+    DrawAccepted,
+    GameOver {
+        winner: Option<Player>,
+        termination: String,
+    },
+    // Keeps the connection alive, and needs no answer
+    Ping,
+    // Something to say to the other player, which needs no answer either
+    Chat {
+        text: String,
+    },
+}
+
+impl Message {
+    pub fn hello(name: &str) -> Message {
+        Message::Hello {
+            version: VERSION,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn rejected(reason: impl Into<String>) -> Message {
+        Message::Rejected {
+            reason: reason.into(),
+        }
+    }
+
+    // The result of a game, which each end sends once its game is over so
+    // that both check they agree on it
+    pub fn game_over(result: GameResult) -> Message {
+        Message::GameOver {
+            winner: result.winner(),
+            termination: termination_tag(result).to_string(),
+        }
+    }
+
+    // A chat message with the control characters of `text` left out, cut to
+    // `MAX_CHAT` bytes
+    pub fn chat(text: &str) -> Message {
+        let mut text: String = text.chars().filter(|c| !c.is_control()).collect();
+        let mut end = text.len().min(MAX_CHAT);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+
+        Message::Chat { text }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum HandshakeError {
+    // The other end speaks another version
+    Version(u32),
+    // The other end started with something else than `Hello`
+    NoHello,
+}
+
+impl fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version(version) => write!(
+                f,
+                "the other end speaks version {version} of the protocol, not {VERSION}"
+            ),
+            Self::NoHello => write!(f, "the other end did not say hello"),
+        }
+    }
+}
+
+// The name given by the first message of the other end, if it speaks our
+// version of the protocol
+pub fn check_hello(message: Message) -> Result<String, HandshakeError> {
+    match message {
+        Message::Hello { version, name } if version == VERSION => Ok(name),
+        Message::Hello { version, .. } => Err(HandshakeError::Version(version)),
+        _ => Err(HandshakeError::NoHello),
+    }
+}
+
+// Play a move proposed by the other end, giving the answer to send back
+pub fn play_proposed(session: &mut GameSession, square: &str) -> Message {
+    let (x, y) = match parse_coordinates(square) {
+        Ok(square) => square,
+        Err(e) => return Message::rejected(format!("{square}: {e}")),
+    };
+
+    match session.play(x, y) {
+        Ok(m) => Message::MoveAccepted {
+            player: m.player,
+            square: coordinates(x, y),
+        },
+        Err(PlayError::Occupied(p)) => Message::rejected(format!("{square} already has an {p}")),
+        Err(PlayError::GameOver) => Message::rejected("the game is over"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshake() {
+        assert_eq!(
+            check_hello(Message::hello("alice")),
+            Ok("alice".to_string())
+        );
+
+        let hello = Message::Hello {
+            version: 0,
+            name: "bob".to_string(),
+        };
+        assert_eq!(check_hello(hello), Err(HandshakeError::Version(0)));
+        assert_eq!(check_hello(Message::Ping), Err(HandshakeError::NoHello));
+    }
+
+    #[test]
+    fn proposed_moves() {
+        let mut session = GameSession::new();
+        assert_eq!(
+            play_proposed(&mut session, "B2"),
+            Message::MoveAccepted {
+                player: Player::X,
+                square: "b2".to_string()
+            }
+        );
+        assert!(matches!(
+            play_proposed(&mut session, "b2"),
+            Message::Rejected { .. }
+        ));
+        assert!(matches!(
+            play_proposed(&mut session, "d4"),
+            Message::Rejected { .. }
+        ));
+        assert_eq!(session.history().len(), 1);
+    }
+
+    #[test]
+    fn chat() {
+        assert_eq!(
+            Message::chat("gg\n"),
+            Message::Chat {
+                text: "gg".to_string()
+            }
+        );
+        let Message::Chat { text } = Message::chat(&"é".repeat(MAX_CHAT)) else {
+            unreachable!();
+        };
+        assert_eq!(text.len(), MAX_CHAT);
+    }
+
+    #[test]
+    fn game_over() {
+        assert_eq!(
+            Message::game_over(GameResult::Resigned(Player::X)),
+            Message::GameOver {
+                winner: Some(Player::O),
+                termination: "resignation".to_string()
+            }
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json() {
+        let message = Message::MoveProposed {
+            square: "b2".to_string(),
+        };
+        let json = serde_json::to_string(&message).unwrap();
+        assert_eq!(json, r#"{"type":"move_proposed","square":"b2"}"#);
+        assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+    }
+}
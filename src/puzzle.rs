@@ -0,0 +1,299 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::analysis::{canonical, solve, threats, Value};
+use crate::engine::legal_moves;
+use crate::rng::{RandomSource, Rng};
+use crate::{Grid, Player};
+
+// A position where the player to move can force a win with a single move
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub grid: Grid,
+    // The number of moves of the player needed to win against the best defence
+    pub moves: i32,
+    // The only move that keeps the win
+    pub solution: (usize, usize),
+    pub grade: Grade,
+}
+
+impl Puzzle {
+    // The squares where the player threatens to win after the solution
+    pub fn threats(&self) -> Vec<(usize, usize)> {
+        let mut grid = self.grid.clone();
+        let _ = grid.set(self.solution.0, self.solution.1);
+        threats(&grid, self.grid.player_turn)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Grade {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Grade::Easy => "easy",
+            Grade::Medium => "medium",
+            Grade::Hard => "hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Grade {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            _ => Err(format!("Unknown grade: {s}")),
+        }
+    }
+}
+
+// Why a move does not keep the fastest win, told with the threats on the board
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Mistake {
+    // The player could have won at once on this square
+    MissedWin((usize, usize)),
+    // The opponent can now win on this square
+    AllowsWin((usize, usize)),
+    // The move still wins, but it takes longer
+    Slower,
+    // The opponent blocks the only threat of the move on this square
+    Blocked((usize, usize)),
+    // The move threatens nothing, which gives the opponent time to defend
+    NoThreat,
+}
+
+// What is wrong with playing `(x, y)` in a puzzle position, or `None` if it
+// keeps the fastest win
+pub fn mistake(grid: &Grid, (x, y): (usize, usize)) -> Option<Mistake> {
+    if solve(grid)?.best_moves.contains(&(x, y)) {
+        return None;
+    }
+
+    let player = grid.player_turn;
+    let mut after = grid.clone();
+    let _ = after.set(x, y);
+    let mistake = if let Some(&square) = threats(grid, player).first() {
+        Mistake::MissedWin(square)
+    } else if let Some(&square) = threats(&after, player.opponent()).first() {
+        Mistake::AllowsWin(square)
+    } else if solve(&after).is_some_and(|s| matches!(s.value, Value::Loss(_))) {
+        Mistake::Slower
+    } else if let Some(&square) = threats(&after, player).first() {
+        Mistake::Blocked(square)
+    } else {
+        Mistake::NoThreat
+    };
+
+    Some(mistake)
+}
+
+// The puzzle of the day, the same for everyone on a given date (`YYYY-MM-DD`):
+// one of the puzzles for X that take more than a move
+pub fn daily(date: &str) -> Puzzle {
+    let puzzles: Vec<Puzzle> = puzzle_set()
+        .into_iter()
+        .filter(|p| p.grid.player_turn == Player::X && p.moves >= 2)
+        .collect();
+    let mut rng = Rng::new(seed(date));
+
+    puzzles[rng.below(puzzles.len())].clone()
+}
+
+// Every puzzle, from the easiest to the hardest
+pub fn puzzle_set() -> Vec<Puzzle> {
+    let mut puzzles = search(|_| true);
+    puzzles.sort_by_key(|p| (p.grade, p.moves));
+    puzzles
+}
+
+// The positions of games started by X where the player to move wins in
+// exactly `plies` moves of both players, and where only one move wins
+pub fn generate(plies: i32) -> Vec<Puzzle> {
+    search(|n| n == plies)
+}
+
+// The puzzles that win in a number of plies accepted by `keep`. Of the
+// positions that are the same up to a rotation or a reflection, only the first
+// found is kept.
+fn search(keep: impl Fn(i32) -> bool) -> Vec<Puzzle> {
+    let mut seen = HashSet::new();
+    let mut puzzles = Vec::new();
+    let mut stack = vec![Grid::new()];
+
+    while let Some(grid) = stack.pop() {
+        if grid.has_winner() || !seen.insert(canonical(&grid)) {
+            continue;
+        }
+        for (x, y) in legal_moves(&grid) {
+            let mut next = grid.clone();
+            let _ = next.set(x, y);
+            stack.push(next);
+        }
+        if let Some(puzzle) = puzzle(grid, &keep) {
+            puzzles.push(puzzle);
+        }
+    }
+
+    puzzles
+}
+
+fn puzzle(grid: Grid, keep: impl Fn(i32) -> bool) -> Option<Puzzle> {
+    let solution = solve(&grid)?;
+    let plies = match solution.value {
+        Value::Win(plies) if keep(plies) && solution.best_moves.len() == 1 => plies,
+        _ => return None,
+    };
+    // A slower win would do as well
+    let winning = legal_moves(&grid)
+        .into_iter()
+        .filter(|&(x, y)| {
+            let mut g = grid.clone();
+            let _ = g.set(x, y);
+            g.has_winner() || solve(&g).is_some_and(|s| matches!(s.value, Value::Loss(_)))
+        })
+        .count();
+    if winning != 1 {
+        return None;
+    }
+
+    let square = solution.best_moves[0];
+    Some(Puzzle {
+        grade: grade(&grid, square, plies),
+        moves: (plies + 1) / 2,
+        solution: square,
+        grid,
+    })
+}
+
+// The longer the win, the harder. A winning move that threatens nothing is
+// harder to see, and so is a win among many moves that make a threat.
+fn grade(grid: &Grid, (x, y): (usize, usize), plies: i32) -> Grade {
+    if plies == 1 {
+        return Grade::Easy;
+    }
+    let player = grid.player_turn;
+    let threatening = |(x, y): (usize, usize)| {
+        let mut g = grid.clone();
+        let _ = g.set(x, y);
+        !threats(&g, player).is_empty()
+    };
+    let decoys = legal_moves(grid)
+        .into_iter()
+        .filter(|&m| m != (x, y) && threatening(m))
+        .count();
+
+    let points = (plies - 1) / 2 + i32::from(!threatening((x, y))) + i32::from(decoys >= 3);
+    if points <= 1 {
+        Grade::Medium
+    } else {
+        Grade::Hard
+    }
+}
+
+// FNV-1a, so that the puzzle does not depend on the platform or the compiler
+fn seed(date: &str) -> u64 {
+    date.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Player;
+
+    #[test]
+    fn same_date_same_puzzle() {
+        let a = daily("2026-10-14");
+        let b = daily("2026-10-14");
+        assert_eq!(a.grid.matrix(), b.grid.matrix());
+        assert_eq!(a.moves, b.moves);
+    }
+
+    #[test]
+    fn forced_wins() {
+        for day in 1..=3 {
+            let puzzle = daily(&format!("2026-02-{day:02}"));
+            let solution = solve(&puzzle.grid).unwrap();
+
+            assert_eq!(solution.player, Player::X);
+            assert!(matches!(solution.value, Value::Win(n) if n >= 3));
+            assert!(puzzle.moves >= 2);
+        }
+    }
+
+    #[test]
+    fn generated_puzzles() {
+        let puzzles = puzzle_set();
+        assert!(puzzles.windows(2).all(|w| w[0].grade <= w[1].grade));
+        assert!(puzzles.iter().any(|p| p.grid.player_turn == Player::O));
+
+        for puzzle in &puzzles {
+            let solution = solve(&puzzle.grid).unwrap();
+            assert_eq!(solution.best_moves, [puzzle.solution]);
+            let Value::Win(plies) = solution.value else {
+                panic!("not a win: {:?}", puzzle.grid.matrix());
+            };
+            assert_eq!(puzzle.moves, (plies + 1) / 2);
+            assert_eq!(puzzle.grade == Grade::Easy, plies == 1);
+        }
+
+        // Never the same position twice, even turned around
+        let mut seen = HashSet::new();
+        assert!(puzzles.iter().all(|p| seen.insert(canonical(&p.grid))));
+    }
+
+    #[test]
+    fn mistakes() {
+        let (e, x, o) = (Player::Empty, Player::X, Player::O);
+        let grid = Grid::from([[e, e, o], [e, e, e], [x, o, x]]);
+        assert_eq!(mistake(&grid, (0, 0)), None);
+        // A single threat is blocked in the corner
+        assert_eq!(mistake(&grid, (1, 1)), Some(Mistake::Blocked((0, 0))));
+        assert_eq!(mistake(&grid, (0, 1)), Some(Mistake::Blocked((0, 0))));
+        assert_eq!(mistake(&grid, (2, 1)), Some(Mistake::NoThreat));
+
+        let puzzle = generate(3)
+            .into_iter()
+            .find(|p| p.grid.matrix() == grid.matrix())
+            .unwrap();
+        assert_eq!(puzzle.threats(), [(0, 1), (1, 1)]);
+
+        // X must win on the top row rather than block
+        let grid = Grid::from([[x, x, e], [o, o, e], [e, e, e]]);
+        assert_eq!(mistake(&grid, (2, 1)), Some(Mistake::MissedWin((2, 0))));
+        let grid = Grid::from([[x, e, e], [o, o, e], [x, e, e]]);
+        assert_eq!(mistake(&grid, (2, 2)), Some(Mistake::AllowsWin((2, 1))));
+    }
+
+    #[test]
+    fn win_in_three() {
+        let puzzles = generate(3);
+        assert!(!puzzles.is_empty());
+        assert!(puzzles
+            .iter()
+            .all(|p| p.moves == 2 && p.grade >= Grade::Medium));
+
+        // X holds the bottom corners, and only the top left corner makes a fork
+        let (e, x, o) = (Player::Empty, Player::X, Player::O);
+        let grid = Grid::from([[e, e, o], [e, e, e], [x, o, x]]);
+        let found = puzzle(grid, |n| n == 3).unwrap();
+        assert_eq!(found.solution, (0, 0));
+        assert_eq!(found.grade, Grade::Medium);
+
+        // Two squares make a fork here
+        let grid = Grid::from([[x, o, e], [e, x, e], [e, e, o]]);
+        assert!(puzzle(grid, |n| n == 3).is_none());
+    }
+}
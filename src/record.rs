@@ -0,0 +1,550 @@
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "storage")]
+use crate::archive::{self, Archive, Query};
+use crate::notation::parse_coordinates;
+use crate::profile::data_dir;
+use crate::session::{GameResult, GameSession};
+use crate::{coordinates, Grid, Player};
+
+// A finished game, written in a notation inspired by chess' PGN:
+//
+//     [Date "2026-10-14"]
+//     [Time "18:53:51"]
+//     [X "alice"]
+//     [O "Bot"]
+//     [First "X"]
+//     [Event "casual"]
+//     [Difficulty "hard"]
+//     [Result "1-0"]
+//     [Termination "resignation"]
+//
+//     b2 a1 c3 a3 a2 1-0
+//
+// The date and time are in UTC. `Result` is `1-0` if X won, `0-1` if O won
+// and `1/2-1/2` for a draw, and `Termination` tells how the game ended
+// (`normal`, `resignation`, `time forfeit` or `agreement` for a draw agreed by
+// the players). `Event` tells where the game was played: `casual` and `match`
+// for games against the bot, `server` for those of `tictactoe serve`, and the
+// kind and round of a tournament. Any other tag is kept as is.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GameRecord {
+    pub date: String,
+    pub time: String,
+    pub x: String,
+    pub o: String,
+    pub first_player: Player,
+    pub moves: Vec<(usize, usize)>,
+    pub result: GameResult,
+    pub tags: Vec<(String, String)>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RecordError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+impl GameRecord {
+    // The record of a finished session, dated now
+    pub fn from_session(session: &GameSession, x: &str, o: &str) -> Option<GameRecord> {
+        let (date, time) = utc_now();
+        Some(GameRecord {
+            date,
+            time,
+            x: x.to_string(),
+            o: o.to_string(),
+            first_player: session.first_player(),
+            moves: session.history().iter().map(|m| (m.x, m.y)).collect(),
+            result: session.result()?,
+            tags: Vec::new(),
+        })
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    // Replay the moves of the game
+    pub fn replay(&self) -> GameSession {
+        let mut session = GameSession::from(Grid::starting_with(self.first_player));
+        for &(x, y) in self.moves.iter() {
+            let _ = session.play(x, y);
+        }
+
+        session
+    }
+
+    pub fn to_notation(&self) -> String {
+        let mut s = String::new();
+        let mut tag = |name: &str, value: &str| s += &format!("[{name} \"{value}\"]\n");
+
+        tag("Date", &self.date);
+        tag("Time", &self.time);
+        tag("X", &self.x);
+        tag("O", &self.o);
+        tag("First", &self.first_player.to_string());
+        for (name, value) in self.tags.iter() {
+            tag(name, value);
+        }
+        tag("Result", result_tag(self.result));
+        tag("Termination", termination_tag(self.result));
+
+        s.push('\n');
+        for &(x, y) in self.moves.iter() {
+            s += &coordinates(x, y);
+            s.push(' ');
+        }
+        s += result_tag(self.result);
+        s.push('\n');
+
+        s
+    }
+
+    pub fn parse(notation: &str) -> Result<GameRecord, RecordError> {
+        let mut tags: Vec<(String, String)> = Vec::new();
+        let mut moves = Vec::new();
+        let mut last_line = 0;
+
+        for (i, line) in notation.lines().enumerate() {
+            let error = |message: &str| RecordError {
+                line: i + 1,
+                message: message.to_string(),
+            };
+            let line = line.trim();
+            last_line = i + 1;
+
+            if line.starts_with('[') {
+                let (name, value) = parse_tag(line).ok_or_else(|| error("invalid tag"))?;
+                tags.push((name, value));
+                continue;
+            }
+
+            for token in line.split_whitespace() {
+                if is_result_token(token) {
+                    continue;
+                }
+                let m = parse_coordinates(token).map_err(|e| error(&format!("{token}: {e}")))?;
+                moves.push((m, i + 1));
+            }
+        }
+
+        let error = |message: &str| RecordError {
+            line: last_line,
+            message: message.to_string(),
+        };
+        let mut take = |name: &str| {
+            let i = tags.iter().position(|(n, _)| n == name);
+            i.map(|i| tags.remove(i).1)
+                .ok_or_else(|| error(&format!("missing the {name} tag")))
+        };
+
+        let date = take("Date")?;
+        let time = take("Time")?;
+        let x = take("X")?;
+        let o = take("O")?;
+        let first_player = match take("First")?.as_str() {
+            "X" => Player::X,
+            "O" => Player::O,
+            _ => return Err(error("invalid First tag")),
+        };
+        let result = parse_result(&take("Result")?, &take("Termination")?)
+            .ok_or_else(|| error("invalid result"))?;
+
+        // The moves must be playable, so that replaying the game gives them all
+        let mut grid = Grid::starting_with(first_player);
+        for &((x, y), line) in &moves {
+            let error = |message: &str| RecordError {
+                line,
+                message: format!("{}: {message}", coordinates(x, y)),
+            };
+            if grid.has_winner() {
+                return Err(error("the game is already over"));
+            }
+            if grid.set(x, y).is_err() {
+                return Err(error("square already taken"));
+            }
+        }
+        let moves = moves.into_iter().map(|(m, _)| m).collect();
+
+        Ok(GameRecord {
+            date,
+            time,
+            x,
+            o,
+            first_player,
+            moves,
+            result,
+            tags,
+        })
+    }
+}
+
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    Some((name.to_string(), value.to_string()))
+}
+
+fn is_result_token(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2")
+}
+
+pub(crate) fn result_tag(result: GameResult) -> &'static str {
+    match result.winner() {
+        Some(Player::X) => "1-0",
+        Some(_) => "0-1",
+        None => "1/2-1/2",
+    }
+}
+
+pub(crate) fn termination_tag(result: GameResult) -> &'static str {
+    match result {
+        GameResult::Win(_) | GameResult::Draw => "normal",
+        GameResult::Resigned(_) => "resignation",
+        GameResult::Timeout(_) => "time forfeit",
+        GameResult::DrawAgreed => "agreement",
+    }
+}
+
+pub(crate) fn parse_result(result: &str, termination: &str) -> Option<GameResult> {
+    let winner = match result {
+        "1-0" => Some(Player::X),
+        "0-1" => Some(Player::O),
+        "1/2-1/2" => None,
+        _ => return None,
+    };
+
+    game_result(winner, termination)
+}
+
+// The result of a game won by `winner`, or drawn, as `termination_tag` says
+// it ended
+pub(crate) fn game_result(winner: Option<Player>, termination: &str) -> Option<GameResult> {
+    match (winner, termination) {
+        (None, "normal") => Some(GameResult::Draw),
+        (None, "agreement") => Some(GameResult::DrawAgreed),
+        (Some(winner), "normal") => Some(GameResult::Win(winner)),
+        (Some(winner), "resignation") => Some(GameResult::Resigned(winner.opponent())),
+        (Some(winner), "time forfeit") => Some(GameResult::Timeout(winner.opponent())),
+        _ => None,
+    }
+}
+
+// All the games of a log file, one after the other. A log is a plain text
+// file holding the notation of each game followed by a blank line, so that the
+// games of a session, a tournament or a server can be kept together and read
+// back the same way.
+pub fn parse_log(content: &str) -> Result<Vec<GameRecord>, RecordError> {
+    let mut records = Vec::new();
+    let mut game = String::new();
+    let mut first_line = 0;
+    let mut has_moves = false;
+
+    let mut flush = |game: &mut String, first_line: usize| {
+        if !game.trim().is_empty() {
+            let record = GameRecord::parse(game).map_err(|e| RecordError {
+                line: e.line + first_line,
+                ..e
+            })?;
+            records.push(record);
+        }
+        game.clear();
+        Ok(())
+    };
+
+    for (i, line) in content.lines().enumerate() {
+        let is_tag = line.trim_start().starts_with('[');
+        // The tags after some movetext start the next game
+        if is_tag && has_moves {
+            flush(&mut game, first_line)?;
+            first_line = i;
+            has_moves = false;
+        }
+        if !is_tag && !line.trim().is_empty() {
+            has_moves = true;
+        }
+        game += line;
+        game.push('\n');
+    }
+    flush(&mut game, first_line)?;
+
+    Ok(records)
+}
+
+pub fn load_log(path: &Path) -> io::Result<Vec<GameRecord>> {
+    #[cfg(feature = "storage")]
+    if archive::is_archive(path) {
+        return Archive::open(path)?.games(&Query::default());
+    }
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    parse_log(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// Games as a log
+pub fn write_log<W: Write>(mut out: W, records: &[GameRecord]) -> io::Result<()> {
+    for record in records {
+        writeln!(out, "{}", record.to_notation())?;
+    }
+
+    Ok(())
+}
+
+pub fn read_log<R: Read>(mut input: R) -> io::Result<Vec<GameRecord>> {
+    let mut content = String::new();
+    input.read_to_string(&mut content)?;
+
+    parse_log(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// Write games to a log file, replacing what it held
+pub fn save_log(path: &Path, records: &[GameRecord]) -> io::Result<()> {
+    #[cfg(feature = "storage")]
+    if archive::is_archive(path) {
+        return Archive::open(path)?.replace_all(records);
+    }
+
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    write_log(&mut out, records)?;
+    out.flush()
+}
+
+// Add a game at the end of a log file, creating it if needed
+pub fn append_to_log(path: &Path, record: &GameRecord) -> io::Result<()> {
+    #[cfg(feature = "storage")]
+    if archive::is_archive(path) {
+        return Archive::open(path)?.add(record);
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", record.to_notation())
+}
+
+// Where the games are logged unless another file is given
+pub fn log_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("games.txt"))
+}
+
+// The current date and time in UTC, as `YYYY-MM-DD` and `HH:MM:SS`
+pub fn utc_now() -> (String, String) {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    utc_date_time(seconds)
+}
+
+pub fn utc_date_time(unix_seconds: u64) -> (String, String) {
+    let days = (unix_seconds / 86400) as i64;
+    let seconds = unix_seconds % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    (
+        format!("{year:04}-{month:02}-{day:02}"),
+        format!(
+            "{:02}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        ),
+    )
+}
+
+// The Gregorian date of a number of days since 1970-01-01
+// (see http://howardhinnant.github.io/date_algorithms.html)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+// A game between `x` and `o` on 2026-10-15, for the tests of the modules
+// reading records
+#[cfg(test)]
+pub(crate) fn test_game(
+    x: &str,
+    o: &str,
+    moves: &[(usize, usize)],
+    result: GameResult,
+) -> GameRecord {
+    GameRecord {
+        date: "2026-10-15".to_string(),
+        time: "12:00:00".to_string(),
+        x: x.to_string(),
+        o: o.to_string(),
+        first_player: Player::X,
+        moves: moves.to_vec(),
+        result,
+        tags: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> GameRecord {
+        GameRecord {
+            date: "2026-10-14".to_string(),
+            time: "18:53:51".to_string(),
+            x: "alice".to_string(),
+            o: "Bot".to_string(),
+            first_player: Player::X,
+            moves: vec![(1, 1), (0, 0), (2, 2), (2, 0), (1, 0)],
+            result: GameResult::Resigned(Player::O),
+            tags: vec![("Difficulty".to_string(), "hard".to_string())],
+        }
+    }
+
+    #[test]
+    fn notation() {
+        assert_eq!(
+            sample().to_notation(),
+            "[Date \"2026-10-14\"]\n\
+             [Time \"18:53:51\"]\n\
+             [X \"alice\"]\n\
+             [O \"Bot\"]\n\
+             [First \"X\"]\n\
+             [Difficulty \"hard\"]\n\
+             [Result \"1-0\"]\n\
+             [Termination \"resignation\"]\n\
+             \n\
+             b2 a1 c3 a3 a2 1-0\n"
+        );
+    }
+
+    #[test]
+    fn parse_back() {
+        let mut record = sample();
+        assert_eq!(GameRecord::parse(&record.to_notation()), Ok(record.clone()));
+
+        record.result = GameResult::DrawAgreed;
+        assert_eq!(GameRecord::parse(&record.to_notation()), Ok(record));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let notation = sample().to_notation();
+
+        let missing = notation.replace("[X \"alice\"]\n", "");
+        assert!(GameRecord::parse(&missing).is_err());
+
+        let invalid = notation.replace("a3", "d3");
+        assert_eq!(GameRecord::parse(&invalid).unwrap_err().line, 10);
+
+        let taken = GameRecord::parse(&notation.replace("a3 a2", "a3 b2")).unwrap_err();
+        assert_eq!(taken.message, "b2: square already taken");
+        let over = notation.replace("a2 1-0", "a2 b1 c2 c1 1-0");
+        assert_eq!(
+            GameRecord::parse(&over).unwrap_err().message,
+            "c1: the game is already over"
+        );
+    }
+
+    #[test]
+    fn log() {
+        let mut other = sample();
+        other.result = GameResult::Draw;
+        let log = format!("{}\n{}\n", sample().to_notation(), other.to_notation());
+
+        assert_eq!(parse_log(&log), Ok(vec![sample(), other.clone()]));
+        assert_eq!(parse_log(""), Ok(vec![]));
+
+        let invalid = other
+            .to_notation()
+            .replace("[First \"X\"]", "[First \"Y\"]");
+        let invalid = format!("{}\n{invalid}", sample().to_notation());
+        assert_eq!(parse_log(&invalid).unwrap_err().line, 21);
+    }
+
+    #[test]
+    fn export_and_import() {
+        let mut other = sample();
+        other.set_tag("Event", "swiss, round 2");
+        let records = [sample(), other];
+
+        let mut log = Vec::new();
+        write_log(&mut log, &records).unwrap();
+        assert_eq!(read_log(log.as_slice()).unwrap(), records);
+
+        let path = std::env::temp_dir().join(format!("tictactoe-log-{}.txt", std::process::id()));
+        save_log(&path, &records).unwrap();
+        save_log(&path, &records[1..]).unwrap();
+        assert_eq!(load_log(&path).unwrap(), records[1..]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn from_session() {
+        let mut session = GameSession::new();
+        for (x, y) in [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)] {
+            session.play(x, y).unwrap();
+        }
+
+        let record = GameRecord::from_session(&session, "alice", "bob").unwrap();
+        assert_eq!(record.result, GameResult::Win(Player::X));
+        assert_eq!(record.moves.len(), 5);
+        assert_eq!(record.replay().grid().matrix(), session.grid().matrix());
+
+        assert!(GameRecord::from_session(&GameSession::new(), "alice", "bob").is_none());
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(utc_date_time(0), ("1970-01-01".into(), "00:00:00".into()));
+        assert_eq!(
+            utc_date_time(1_792_004_031),
+            ("2026-10-14".into(), "18:53:51".into())
+        );
+        assert_eq!(utc_date_time(951_782_400).0, "2000-02-29");
+    }
+}
@@ -0,0 +1,342 @@
+use std::str::FromStr;
+
+use crate::{heat_color, heat_label, Grid, Player};
+
+// How the board is drawn in the terminal, chosen with `--style`
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Style {
+    // A line of text per row
+    #[default]
+    Small,
+    // Each square as a block of five lines, to be read from afar on a stream
+    // or a projector
+    Large,
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "small" => Ok(Self::Small),
+            "large" => Ok(Self::Large),
+            _ => Err(format!("Unknown style: {s}")),
+        }
+    }
+}
+
+// The colors of the heatmap and the marks, chosen with `--palette` or the
+// `palette` setting
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Palette {
+    // Green for the wins and red for the losses
+    #[default]
+    Standard,
+    // Blue for the wins and orange for the losses, which are told apart with
+    // any kind of color blindness
+    Colorblind,
+    // Black and white only: the wins light, the losses dark and the draws
+    // grey in between
+    HighContrast,
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" => Ok(Self::Standard),
+            "colorblind" => Ok(Self::Colorblind),
+            "high-contrast" => Ok(Self::HighContrast),
+            _ => Err(format!("Unknown palette: {s}")),
+        }
+    }
+}
+
+impl Palette {
+    // The background and text colors of a square scored `score` by
+    // `Grid::heatmap`. The sign of the score is always written on it, so that
+    // the colors are never all there is to tell a win from a loss.
+    pub fn heat_colors(self, score: i32) -> ((u8, u8, u8), (u8, u8, u8)) {
+        const BLACK: (u8, u8, u8) = (0, 0, 0);
+        const WHITE: (u8, u8, u8) = (255, 255, 255);
+        // Scores are at most 9 either way
+        let strength = (score.unsigned_abs().min(9) * 10) as u8;
+        match (self, score.signum()) {
+            (Self::Standard, _) => (heat_color(score), BLACK),
+            (Self::Colorblind, 1) => ((80, 150 + strength, 230), BLACK),
+            (Self::Colorblind, 0) => ((190, 190, 190), BLACK),
+            (Self::Colorblind, _) => ((150 + strength, 100, 0), BLACK),
+            (Self::HighContrast, 1) => (WHITE, BLACK),
+            (Self::HighContrast, 0) => ((128, 128, 128), BLACK),
+            (Self::HighContrast, _) => (BLACK, WHITE),
+        }
+    }
+}
+
+// What the board needs to be drawn, whatever its size: square boards of any
+// width, with the columns numbered from 1 and the rows lettered from a
+pub trait Board {
+    fn size(&self) -> usize;
+    fn square(&self, x: usize, y: usize) -> Player;
+}
+
+impl Board for Grid {
+    fn size(&self) -> usize {
+        3
+    }
+
+    fn square(&self, x: usize, y: usize) -> Player {
+        self.matrix()[y][x]
+    }
+}
+
+impl<const N: usize> Board for [[Player; N]; N] {
+    fn size(&self) -> usize {
+        N
+    }
+
+    fn square(&self, x: usize, y: usize) -> Player {
+        self[y][x]
+    }
+}
+
+// The marks of the large board, each as wide as a square
+const WIDTH: usize = 9;
+const HEIGHT: usize = 5;
+const LARGE_X: [&str; HEIGHT] = [
+    " \\     / ",
+    "  \\   /  ",
+    "    X    ",
+    "  /   \\  ",
+    " /     \\ ",
+];
+const LARGE_O: [&str; HEIGHT] = [
+    "  .---.  ",
+    " /     \\ ",
+    " |     | ",
+    " \\     / ",
+    "  '---'  ",
+];
+
+impl Style {
+    // The width and height of a square, in characters
+    fn square_size(self) -> (usize, usize) {
+        match self {
+            Self::Small => (3, 1),
+            Self::Large => (WIDTH, HEIGHT),
+        }
+    }
+
+    // A line of a square holding `player`, as wide as the square
+    fn square_line(self, player: Player, line: usize) -> String {
+        match (self, player) {
+            (Self::Small, p) => format!(" {p} "),
+            (Self::Large, Player::X) => LARGE_X[line].to_string(),
+            (Self::Large, Player::O) => LARGE_O[line].to_string(),
+            (Self::Large, Player::Empty) => " ".repeat(WIDTH),
+        }
+    }
+
+    // Where the lines between the rows and the columns cross
+    fn crossing(self) -> char {
+        match self {
+            Self::Small => '-',
+            Self::Large => '+',
+        }
+    }
+}
+
+// The name of row `y`: a to z, then aa, ab and so on for larger boards
+pub fn row_label(y: usize) -> String {
+    let mut label = Vec::new();
+    let mut n = y + 1;
+    while n > 0 {
+        n -= 1;
+        label.push(b'a' + (n % 26) as u8);
+        n /= 26;
+    }
+    label.reverse();
+
+    String::from_utf8(label).unwrap_or_default()
+}
+
+// How a board is laid out as text: the squares of `style` in a grid, the
+// column numbers centered above them and the row letters on their left, as
+// wide as the longest one. Nothing depends on the size of the board.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Layout {
+    pub style: Style,
+    // Dim the lines between the squares with ANSI escape codes, so that the
+    // marks stand out in a terminal
+    pub ansi: bool,
+}
+
+impl Layout {
+    pub fn render(&self, board: &impl Board) -> String {
+        self.render_squares(board.size(), |x, y, line| {
+            self.style.square_line(board.square(x, y), line)
+        })
+    }
+
+    // `grid` with its empty squares scored by `Grid::heatmap`, shaded in the
+    // colors of `palette` with ANSI escape codes if `ansi` is set. The sign of
+    // the scores tells the wins from the losses without them.
+    pub fn render_heatmap(&self, grid: &Grid, palette: Palette) -> String {
+        let heatmap = grid.heatmap();
+        let (width, height) = self.style.square_size();
+        self.render_squares(3, |x, y, line| match heatmap[y][x] {
+            Some(score) => {
+                let label = if line == height / 2 {
+                    heat_label(score)
+                } else {
+                    String::new()
+                };
+                let square = format!("{label:^width$}");
+                if !self.ansi {
+                    return square;
+                }
+                let ((r, g, b), (fr, fg, fb)) = palette.heat_colors(score);
+                format!("\x1b[38;2;{fr};{fg};{fb};48;2;{r};{g};{b}m{square}\x1b[0m")
+            }
+            None => self.style.square_line(grid.matrix()[y][x], line),
+        })
+    }
+
+    // A board of `n` squares by `n`, each line of a square given by `square`
+    fn render_squares(&self, n: usize, square: impl Fn(usize, usize, usize) -> String) -> String {
+        let (width, height) = self.style.square_size();
+        let margin = row_label(n.saturating_sub(1)).len();
+        let dim = |s: String| {
+            if self.ansi {
+                format!("\x1b[2m{s}\x1b[0m")
+            } else {
+                s
+            }
+        };
+
+        let numbers: Vec<String> = (1..=n).map(|i| format!("{i:^width$}")).collect();
+        let header = format!("{:margin$} {}", "", numbers.join(" "));
+        let mut text = format!("{}\n", header.trim_end());
+
+        let rule = vec!["-".repeat(width); n].join(&self.style.crossing().to_string());
+        let rule = format!("{:margin$} {}\n", "", dim(rule));
+        let bar = dim("|".to_string());
+        for y in 0..n {
+            if y > 0 {
+                text += &rule;
+            }
+            for line in 0..height {
+                // The letter of the row on its middle line
+                let label = if line == height / 2 {
+                    row_label(y)
+                } else {
+                    String::new()
+                };
+                let squares: Vec<String> = (0..n).map(|x| square(x, y, line)).collect();
+                text += &format!("{label:margin$} {}\n", squares.join(&bar));
+            }
+        }
+
+        text
+    }
+}
+
+// The board as text, with the columns numbered above it and the rows lettered
+// on its left
+pub fn render(grid: &Grid, style: Style) -> String {
+    Layout { style, ansi: false }.render(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_board() {
+        let grid = Grid::try_from(&["X.O", ".X.", "..."]).unwrap();
+        assert_eq!(
+            render(&grid, Style::Small),
+            "   1   2   3\n\
+             a  X |   | O \n  -----------\n\
+             b    | X |   \n  -----------\n\
+             c    |   |   \n"
+        );
+    }
+
+    #[test]
+    fn large_board() {
+        let grid = Grid::try_from(&["X.O", ".X.", "..."]).unwrap();
+        let board = render(&grid, Style::Large);
+        let lines: Vec<&str> = board.lines().collect();
+
+        assert_eq!(lines.len(), 1 + 3 * 5 + 2);
+        assert_eq!(lines[0], "      1         2         3");
+        assert_eq!(lines[3], "a     X    |         | |     | ");
+        assert_eq!(lines[6], "  ---------+---------+---------");
+        // Every line of the board is as wide
+        assert!(lines[1..]
+            .iter()
+            .all(|l| l.chars().count() == 2 + 3 * WIDTH + 2));
+        assert_eq!("LARGE".parse(), Ok(Style::Large));
+    }
+
+    #[test]
+    fn heatmap() {
+        let grid = Grid::try_from(&["X.O", ".X.", "..."]).unwrap();
+        let plain = Layout::default().render_heatmap(&grid, Palette::Standard);
+        assert_eq!(plain.lines().nth(1), Some("a  X |-5 | O "));
+        assert!(!plain.contains('\x1b'));
+
+        let layout = Layout {
+            style: Style::Large,
+            ansi: true,
+        };
+        let colored = layout.render_heatmap(&grid, Palette::HighContrast);
+        assert!(colored.contains("\x1b[38;2;255;255;255;48;2;0;0;0m"));
+    }
+
+    #[test]
+    fn palettes() {
+        // Blue wins and orange losses, rather than green and red
+        let ((r, _, b), _) = Palette::Colorblind.heat_colors(3);
+        assert!(b > r);
+        let ((r, _, b), _) = Palette::Colorblind.heat_colors(-3);
+        assert!(r > b);
+
+        let light = |(r, g, b): (u8, u8, u8)| u32::from(r) + u32::from(g) + u32::from(b);
+        let contrast = |score| Palette::HighContrast.heat_colors(score);
+        assert!(light(contrast(5).0) > light(contrast(0).0));
+        assert!(light(contrast(0).0) > light(contrast(-5).0));
+        assert_eq!(contrast(-5).1, (255, 255, 255));
+        assert_eq!("high-contrast".parse(), Ok(Palette::HighContrast));
+    }
+
+    #[test]
+    fn any_size() {
+        let mut board = [[Player::Empty; 12]; 12];
+        board[0][11] = Player::X;
+        board[10][0] = Player::O;
+        let text = Layout::default().render(&board);
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines.len(), 1 + 12 + 11);
+        assert!(lines[0].ends_with(" 10  11  12"));
+        assert!(lines[1].starts_with("a  "));
+        assert!(lines[1].ends_with("| X "));
+        // The rows after z are aa, ab and so on
+        assert!(lines[21].starts_with("k  O |"));
+        assert_eq!(row_label(25), "z");
+        assert_eq!(row_label(27), "ab");
+        assert!(Layout::default()
+            .render(&[[Player::Empty; 30]; 30])
+            .lines()
+            .any(|l| l.starts_with("ad ")));
+
+        let ansi = Layout {
+            ansi: true,
+            ..Layout::default()
+        };
+        assert!(ansi.render(&board).contains("\x1b[2m|\x1b[0m"));
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::record::RecordError;
+
+// A run of `tictactoe` kept with `--record`, to be run again the same way with
+// `--replay` when it went wrong:
+//
+//     tictactoe recording 1
+//     arg play
+//     arg --difficulty
+//     arg easy
+//     seed 8417
+//     config lang = "fr"
+//     input b2
+//     input a1
+//
+// The arguments come first, one per line, then the seed of the bots and the
+// configuration file used, if any. The lines typed during the run follow in
+// the order they were read. How long the player took is not kept, so a clock
+// only runs out again if it ran out before any move.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Recording {
+    pub args: Vec<String>,
+    pub seed: u64,
+    pub config: Option<String>,
+    pub inputs: Vec<String>,
+}
+
+const HEADER: &str = "tictactoe recording 1";
+
+impl Recording {
+    pub fn parse(content: &str) -> Result<Recording, RecordError> {
+        let mut lines = content.lines().enumerate();
+        if lines.next().map(|(_, line)| line.trim_end()) != Some(HEADER) {
+            return Err(RecordError {
+                line: 1,
+                message: "not a recording of tictactoe".to_string(),
+            });
+        }
+
+        let mut recording = Recording::default();
+        let mut seed = None;
+        for (i, line) in lines {
+            let error = |message: String| RecordError {
+                line: i + 1,
+                message,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "arg" => recording.args.push(value.to_string()),
+                "seed" => {
+                    let value = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| error(format!("invalid seed {value}")))?;
+                    seed = Some(value);
+                }
+                "config" => {
+                    let config = recording.config.get_or_insert_with(String::new);
+                    config.push_str(value);
+                    config.push('\n');
+                }
+                "input" => recording.inputs.push(value.to_string()),
+                _ => return Err(error(format!("unknown entry {key}"))),
+            }
+        }
+        recording.seed = seed.ok_or_else(|| RecordError {
+            line: 1,
+            message: "the seed is missing".to_string(),
+        })?;
+
+        Ok(recording)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Recording> {
+        let content = fs::read_to_string(path)?;
+        Recording::parse(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        for arg in &self.args {
+            writeln!(f, "arg {arg}")?;
+        }
+        writeln!(f, "seed {}", self.seed)?;
+        for line in self.config.iter().flat_map(|c| c.lines()) {
+            writeln!(f, "config {line}")?;
+        }
+        for input in &self.inputs {
+            writeln!(f, "input {input}")?;
+        }
+        Ok(())
+    }
+}
+
+// Writes the lines typed by the player to a recording as they are read, so
+// that it is complete even if the program crashes. Clones write to the same
+// file.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    file: Arc<File>,
+}
+
+impl Recorder {
+    // Start a recording at `path` with the arguments, seed and configuration
+    // of `recording`, replacing any file there
+    pub fn create(path: &Path, recording: &Recording) -> io::Result<Recorder> {
+        let mut file = File::create(path)?;
+        file.write_all(recording.to_string().as_bytes())?;
+        Ok(Recorder {
+            file: Arc::new(file),
+        })
+    }
+
+    pub fn record(&self, input: &str) -> io::Result<()> {
+        // A single write, so that lines from clones are not mixed up
+        (&*self.file).write_all(format!("input {input}\n").as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let recording = Recording {
+            args: vec![
+                "play".to_string(),
+                "--profile".to_string(),
+                "ann lee".to_string(),
+            ],
+            seed: 42,
+            config: Some("difficulty = \"easy\"\nlang = \"fr\"\n".to_string()),
+            inputs: vec!["b2".to_string(), String::new(), "quit".to_string()],
+        };
+        assert_eq!(Recording::parse(&recording.to_string()), Ok(recording));
+
+        let no_config = "tictactoe recording 1\narg solve\nseed 7\n";
+        assert_eq!(Recording::parse(no_config).unwrap().config, None);
+        assert_eq!(
+            Recording::parse("tictactoe recording 1\narg play\n")
+                .unwrap_err()
+                .message,
+            "the seed is missing"
+        );
+        assert_eq!(Recording::parse("seed 7").unwrap_err().line, 1);
+        assert_eq!(
+            Recording::parse("tictactoe recording 1\nseed 7\nmove b2")
+                .unwrap_err()
+                .line,
+            3
+        );
+    }
+
+    #[test]
+    fn recorded_as_read() {
+        let path = std::env::temp_dir().join(format!("tictactoe-recording-{}", std::process::id()));
+        let recorder = Recorder::create(
+            &path,
+            &Recording {
+                args: vec!["play".to_string()],
+                seed: 3,
+                ..Recording::default()
+            },
+        )
+        .unwrap();
+        recorder.record("b2").unwrap();
+        recorder.clone().record("c3").unwrap();
+
+        let recording = Recording::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(recording.seed, 3);
+        assert_eq!(recording.inputs, ["b2", "c3"]);
+    }
+}
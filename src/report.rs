@@ -0,0 +1,463 @@
+use std::str::FromStr;
+
+use crate::analysis::{self, Value, Verdict};
+use crate::elo::{Ratings, DEFAULT_K};
+use crate::i18n::{Lang, Msg};
+use crate::record::{result_tag, GameRecord};
+use crate::session::Move;
+use crate::stats::{opening, Crosstable, Stats, Summary};
+use crate::{Grid, Player};
+
+// How `tictactoe report` writes the report
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    #[default]
+    Html,
+    Markdown,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "html" => Ok(Self::Html),
+            "md" | "markdown" => Ok(Self::Markdown),
+            _ => Err(format!("Unknown report format: {s}")),
+        }
+    }
+}
+
+// The parts of a report, each with its heading
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Section {
+    Results,
+    Players,
+    Openings,
+    Notable,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Column {
+    Player,
+    Games,
+    Wins,
+    Draws,
+    Losses,
+    WinRate,
+    Rating,
+    // Only when some games have been analyzed
+    Accuracy,
+    Opening,
+    XWins,
+    OWins,
+    AverageLength,
+}
+
+// Why a game is shown in full
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Highlight {
+    ShortestWin,
+    LongestGame,
+    // The move that threw the game away, and the value it threw away
+    Turnaround(Move, Value),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Notable {
+    pub highlight: Highlight,
+    pub record: GameRecord,
+}
+
+// How many openings are listed, the most played first
+const OPENINGS: usize = 5;
+
+// Everything a report tells about some games, such as those of a tournament or
+// of a club night
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub games: usize,
+    // The dates of the first and the last game
+    pub period: Option<(String, String)>,
+    pub summary: Summary,
+    pub crosstable: Crosstable,
+    pub ratings: Ratings,
+    // Of each player of the crosstable, in the games that were analyzed
+    pub accuracy: Vec<Option<f64>>,
+    pub notable: Vec<Notable>,
+}
+
+impl Report {
+    pub fn new(records: &[GameRecord]) -> Report {
+        let mut names: Vec<String> = Vec::new();
+        for record in records {
+            for name in [&record.x, &record.o] {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        let dates = records.iter().map(|r| &r.date);
+        let period = dates.clone().min().zip(dates.max());
+
+        Report {
+            games: records.len(),
+            period: period.map(|(first, last)| (first.clone(), last.clone())),
+            summary: Summary::of(records),
+            crosstable: Crosstable::new(&names, records),
+            ratings: Ratings::from_records(records, DEFAULT_K),
+            accuracy: names
+                .iter()
+                .map(|name| Stats::of(name, records).accuracy())
+                .collect(),
+            notable: notable(records),
+        }
+    }
+
+    pub fn render(&self, lang: Lang, format: Format) -> String {
+        let mut page = Page::new(format);
+        let title = lang.text(Msg::ReportTitle);
+        page.title(&title);
+        if let Some((first, last)) = &self.period {
+            page.paragraph(&lang.text(Msg::ReportPeriod(self.games, first, last)));
+        }
+
+        let column = |c| lang.text(Msg::ReportColumn(c));
+        let results = &self.summary.results;
+        page.heading(&lang.text(Msg::ReportSection(Section::Results)));
+        page.table(
+            &[
+                Column::XWins,
+                Column::OWins,
+                Column::Draws,
+                Column::AverageLength,
+            ]
+            .map(column),
+            &[vec![
+                results.x_wins.to_string(),
+                results.o_wins.to_string(),
+                results.draws.to_string(),
+                format!("{:.1}", self.summary.average_length()),
+            ]],
+        );
+
+        page.heading(&lang.text(Msg::ReportSection(Section::Players)));
+        let analyzed = self.accuracy.iter().any(Option::is_some);
+        let rows: Vec<Vec<String>> = self
+            .crosstable
+            .ranking()
+            .into_iter()
+            .map(|i| {
+                let name = &self.crosstable.names[i];
+                let total = self.crosstable.total(i);
+                let mut row = vec![
+                    name.clone(),
+                    total.games().to_string(),
+                    total.wins.to_string(),
+                    total.draws.to_string(),
+                    total.losses.to_string(),
+                    format!("{:.0}%", total.win_rate()),
+                    format!("{:.0}", self.ratings.get(name)),
+                ];
+                if analyzed {
+                    row.push(self.accuracy[i].map_or(String::new(), |a| format!("{a:.0}%")));
+                }
+                row
+            })
+            .collect();
+        let mut columns = vec![
+            Column::Player,
+            Column::Games,
+            Column::Wins,
+            Column::Draws,
+            Column::Losses,
+            Column::WinRate,
+            Column::Rating,
+        ];
+        if analyzed {
+            columns.push(Column::Accuracy);
+        }
+        let columns: Vec<String> = columns.into_iter().map(column).collect();
+        page.table(&columns, &rows);
+
+        page.heading(&lang.text(Msg::ReportSection(Section::Openings)));
+        let rows: Vec<Vec<String>> = self
+            .summary
+            .by_opening
+            .iter()
+            .take(OPENINGS)
+            .map(|(opening, s)| {
+                vec![
+                    opening.clone(),
+                    s.games().to_string(),
+                    s.x_wins.to_string(),
+                    s.o_wins.to_string(),
+                    s.draws.to_string(),
+                ]
+            })
+            .collect();
+        let columns = [
+            Column::Opening,
+            Column::Games,
+            Column::XWins,
+            Column::OWins,
+            Column::Draws,
+        ];
+        page.table(&columns.map(column), &rows);
+
+        if !self.notable.is_empty() {
+            page.heading(&lang.text(Msg::ReportSection(Section::Notable)));
+        }
+        for game in &self.notable {
+            page.paragraph(&lang.text(Msg::ReportNotable(game)));
+            let record = &game.record;
+            let moves = opening(record, record.moves.len());
+            page.paragraph(&format!("{moves} {}", result_tag(record.result)));
+            page.board(&record.replay().grid().clone());
+        }
+
+        page.finish(lang)
+    }
+}
+
+// The shortest win, the first game thrown away by a mistake and the longest
+// game, when there are some
+fn notable(records: &[GameRecord]) -> Vec<Notable> {
+    let mut notable = Vec::new();
+    let decisive = records.iter().filter(|r| r.result.winner().is_some());
+    if let Some(record) = decisive.min_by_key(|r| r.moves.len()) {
+        notable.push(Notable {
+            highlight: Highlight::ShortestWin,
+            record: record.clone(),
+        });
+    }
+    let turnaround = records.iter().find_map(|record| {
+        let loser = record.result.winner()?.opponent();
+        let review = analysis::review(&record.replay())
+            .into_iter()
+            .find(|r| r.played.player == loser && r.verdict() == Verdict::Mistake)?;
+        Some(Notable {
+            highlight: Highlight::Turnaround(review.played, review.before),
+            record: record.clone(),
+        })
+    });
+    notable.extend(turnaround);
+    // The first of the longest games, as `max_by_key` gives the last
+    if let Some(record) = records.iter().rev().max_by_key(|r| r.moves.len()) {
+        notable.push(Notable {
+            highlight: Highlight::LongestGame,
+            record: record.clone(),
+        });
+    }
+
+    // A game is only shown once
+    let mut shown: Vec<Notable> = Vec::new();
+    for game in notable {
+        if !shown.iter().any(|g| g.record == game.record) {
+            shown.push(game);
+        }
+    }
+    shown
+}
+
+// A document being written in either format
+struct Page {
+    format: Format,
+    body: String,
+    title: String,
+}
+
+impl Page {
+    fn new(format: Format) -> Page {
+        Page {
+            format,
+            body: String::new(),
+            title: String::new(),
+        }
+    }
+
+    fn title(&mut self, title: &str) {
+        self.title = title.to_string();
+        match self.format {
+            Format::Html => self.body += &format!("<h1>{}</h1>\n", escape(title)),
+            Format::Markdown => self.body += &format!("# {title}\n\n"),
+        }
+    }
+
+    fn heading(&mut self, heading: &str) {
+        match self.format {
+            Format::Html => self.body += &format!("<h2>{}</h2>\n", escape(heading)),
+            Format::Markdown => self.body += &format!("## {heading}\n\n"),
+        }
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        match self.format {
+            Format::Html => self.body += &format!("<p>{}</p>\n", escape(text)),
+            Format::Markdown => self.body += &format!("{text}\n\n"),
+        }
+    }
+
+    fn table(&mut self, columns: &[String], rows: &[Vec<String>]) {
+        match self.format {
+            Format::Html => {
+                let row = |cells: &[String], tag: &str| {
+                    let cells: Vec<String> = cells
+                        .iter()
+                        .map(|c| format!("<{tag}>{}</{tag}>", escape(c)))
+                        .collect();
+                    format!("<tr>{}</tr>\n", cells.concat())
+                };
+                self.body += "<table>\n";
+                self.body += &row(columns, "th");
+                for cells in rows {
+                    self.body += &row(cells, "td");
+                }
+                self.body += "</table>\n";
+            }
+            Format::Markdown => {
+                // A `|` in a name would end its cell
+                let row = |cells: &[String]| {
+                    let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+                    format!("| {} |\n", cells.join(" | "))
+                };
+                self.body += &row(columns);
+                self.body += &format!("|{}\n", "---|".repeat(columns.len()));
+                for cells in rows {
+                    self.body += &row(cells);
+                }
+                self.body.push('\n');
+            }
+        }
+    }
+
+    fn board(&mut self, grid: &Grid) {
+        let mark = |square| match square {
+            Player::Empty => " ".to_string(),
+            p => p.to_string(),
+        };
+        match self.format {
+            Format::Html => {
+                self.body += "<table class=\"board\">\n";
+                for row in grid.matrix() {
+                    let cells: Vec<String> = row
+                        .iter()
+                        .map(|&s| format!("<td>{}</td>", mark(s)))
+                        .collect();
+                    self.body += &format!("<tr>{}</tr>\n", cells.concat());
+                }
+                self.body += "</table>\n";
+            }
+            Format::Markdown => {
+                let rows: Vec<String> = grid
+                    .matrix()
+                    .iter()
+                    .map(|row| format!(" {} | {} | {}", mark(row[0]), mark(row[1]), mark(row[2])))
+                    .collect();
+                self.body += &format!("```\n{}\n```\n\n", rows.join("\n-----------\n"));
+            }
+        }
+    }
+
+    fn finish(self, lang: Lang) -> String {
+        match self.format {
+            Format::Html => {
+                let lang = match lang {
+                    Lang::En => "en",
+                    Lang::Fr => "fr",
+                };
+                format!(
+                    "<!DOCTYPE html>\n<html lang=\"{lang}\">\n<head>\n<meta charset=\"utf-8\">\n\
+                     <title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+                    escape(&self.title),
+                    self.body
+                )
+            }
+            Format::Markdown => self.body,
+        }
+    }
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 50em; margin: 2em auto; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; }
+.board td { width: 1.5em; height: 1.5em; text-align: center; font-weight: bold; }
+";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::test_game;
+    use crate::session::GameResult;
+
+    fn records() -> Vec<GameRecord> {
+        vec![
+            // O lets X fork with a3
+            test_game(
+                "alice",
+                "<bob>",
+                &[(0, 0), (1, 1), (2, 2), (2, 0), (0, 2), (0, 1), (1, 2)],
+                GameResult::Win(Player::X),
+            ),
+            test_game(
+                "<bob>",
+                "alice",
+                &[(1, 1), (0, 0)],
+                GameResult::Resigned(Player::X),
+            ),
+        ]
+    }
+
+    #[test]
+    fn notable_games() {
+        let report = Report::new(&records());
+        let highlights: Vec<_> = report.notable.iter().map(|n| n.highlight).collect();
+        let a3 = Move {
+            player: Player::O,
+            x: 2,
+            y: 0,
+        };
+        // The longest game is the one thrown away, shown once
+        assert_eq!(
+            highlights,
+            [
+                Highlight::ShortestWin,
+                Highlight::Turnaround(a3, Value::Draw)
+            ]
+        );
+    }
+
+    #[test]
+    fn formats() {
+        let report = Report::new(&records());
+        let markdown = report.render(Lang::En, Format::Markdown);
+        assert!(
+            markdown.starts_with("# Game report\n\n2 games played from 2026-10-15 to 2026-10-15")
+        );
+        assert!(markdown.contains("| alice | 2 | 2 | 0 | 0 | 100% | 1531 |"));
+        assert!(markdown.contains(" X |   | O\n-----------\n O | O |  \n"));
+
+        let html = report.render(Lang::Fr, Format::Html);
+        assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"fr\">"));
+        assert!(html.contains("<td>&lt;bob&gt;</td>"));
+    }
+
+    #[test]
+    fn accuracy() {
+        let mut records = records();
+        analysis::Annotations::of(&records[0]).write(&mut records[0]);
+        let markdown = Report::new(&records).render(Lang::En, Format::Markdown);
+        // The mistake of O on a3
+        assert!(markdown.contains("| Elo | Accuracy |"));
+        assert!(markdown.contains("| alice | 2 | 2 | 0 | 0 | 100% | 1531 | 100% |"));
+        assert!(markdown.contains("| <bob> | 2 | 0 | 0 | 2 | 0% | 1469 | 67% |"));
+    }
+}
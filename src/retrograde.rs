@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::{solve, Value};
+use crate::{Grid, Player};
+
+// The value of every position of a game started by X, found by retrograde
+// analysis rather than by searching: the finished games are valued first,
+// then the positions one move before them from the values of the positions
+// they lead to, and so on back to the empty board. It shares nothing with
+// `minimax` but the rules, so that each can check the other.
+#[derive(Debug, Clone)]
+pub struct Table {
+    values: HashMap<u32, Value>,
+}
+
+impl Table {
+    pub fn build() -> Table {
+        // The positions after each number of moves
+        let mut layers = vec![vec![Grid::new()]];
+        let mut seen = HashSet::new();
+        for turns in 0..9 {
+            let mut next = Vec::new();
+            for grid in &layers[turns] {
+                if grid.has_winner() {
+                    continue;
+                }
+                for (x, y) in empty_squares(grid) {
+                    let mut g = grid.clone();
+                    let _ = g.set(x, y);
+                    if seen.insert(key(&g)) {
+                        next.push(g);
+                    }
+                }
+            }
+            layers.push(next);
+        }
+
+        let mut values = HashMap::new();
+        for layer in layers.iter().rev() {
+            for grid in layer {
+                let value = if grid.has_winner() {
+                    // The player to move has just lost
+                    Value::Loss(0)
+                } else if grid.is_full() {
+                    Value::Draw
+                } else {
+                    empty_squares(grid)
+                        .into_iter()
+                        .map(|m| back(values[&key(&after(grid, m))]))
+                        .max_by_key(|&v| order(v))
+                        .unwrap_or(Value::Draw)
+                };
+                values.insert(key(grid), value);
+            }
+        }
+
+        Table { values }
+    }
+
+    // The number of positions, finished games included
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // The value of a position for the player to move, as given by `solve`:
+    // `None` if the game is over or if the position cannot be reached
+    pub fn value(&self, grid: &Grid) -> Option<Value> {
+        if grid.has_winner() || grid.is_full() {
+            return None;
+        }
+        self.values.get(&key(grid)).copied()
+    }
+
+    // The moves that keep the value of a position, in the order of the squares
+    pub fn best_moves(&self, grid: &Grid) -> Vec<(usize, usize)> {
+        let Some(value) = self.value(grid) else {
+            return Vec::new();
+        };
+        empty_squares(grid)
+            .into_iter()
+            .filter(|&m| {
+                let child = self.values.get(&key(&after(grid, m)));
+                child.is_some_and(|&v| back(v) == value)
+            })
+            .collect()
+    }
+}
+
+// A position where the retrograde analysis and the search disagree
+#[derive(Debug, Clone)]
+pub struct Disagreement {
+    pub grid: Grid,
+    pub retrograde: (Value, Vec<(usize, usize)>),
+    pub minimax: Option<(Value, Vec<(usize, usize)>)>,
+}
+
+// Compare the value and the best moves of every position still to play with
+// those of `solve`, returning the number of positions checked and those where
+// they differ
+pub fn verify(table: &Table) -> (usize, Vec<Disagreement>) {
+    let mut checked = 0;
+    let mut disagreements = Vec::new();
+    let mut grids: Vec<Grid> = Vec::new();
+    let mut stack = vec![Grid::new()];
+    let mut seen = HashSet::new();
+
+    while let Some(grid) = stack.pop() {
+        if !seen.insert(key(&grid)) || grid.has_winner() || grid.is_full() {
+            continue;
+        }
+        for m in empty_squares(&grid) {
+            stack.push(after(&grid, m));
+        }
+        grids.push(grid);
+    }
+
+    for grid in grids {
+        let Some(value) = table.value(&grid) else {
+            continue;
+        };
+        checked += 1;
+        let retrograde = (value, table.best_moves(&grid));
+        let minimax = solve(&grid).map(|s| (s.value, s.best_moves));
+        if minimax.as_ref() != Some(&retrograde) {
+            disagreements.push(Disagreement {
+                grid,
+                retrograde,
+                minimax,
+            });
+        }
+    }
+
+    (checked, disagreements)
+}
+
+// The value of a position for the player who moved into it, from its value for
+// the player to move
+fn back(value: Value) -> Value {
+    match value {
+        Value::Win(n) => Value::Loss(n + 1),
+        Value::Draw => Value::Draw,
+        Value::Loss(n) => Value::Win(n + 1),
+    }
+}
+
+// The quicker the win and the slower the loss, the better
+fn order(value: Value) -> (i32, i32) {
+    match value {
+        Value::Win(n) => (2, -n),
+        Value::Draw => (1, 0),
+        Value::Loss(n) => (0, n),
+    }
+}
+
+fn after(grid: &Grid, (x, y): (usize, usize)) -> Grid {
+    let mut g = grid.clone();
+    let _ = g.set(x, y);
+    g
+}
+
+// Row by row, as `Grid::set` takes them
+fn empty_squares(grid: &Grid) -> Vec<(usize, usize)> {
+    (0..9)
+        .map(|i| (i % 3, i / 3))
+        .filter(|&(x, y)| grid.matrix()[y][x] == Player::Empty)
+        .collect()
+}
+
+// The squares read as a number in base 3
+fn key(grid: &Grid) -> u32 {
+    grid.matrix().iter().flatten().fold(0, |n, &square| {
+        3 * n
+            + match square {
+                Player::Empty => 0,
+                Player::X => 1,
+                Player::O => 2,
+            }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table() {
+        let table = Table::build();
+        // All the positions of a game, the empty board and the finished ones
+        // included
+        assert_eq!(table.len(), 5478);
+        assert_eq!(table.value(&Grid::new()), Some(Value::Draw));
+        assert_eq!(table.best_moves(&Grid::new()).len(), 9);
+
+        let grid = Grid::from([
+            [Player::X, Player::X, Player::Empty],
+            [Player::O, Player::O, Player::Empty],
+            [Player::Empty, Player::Empty, Player::Empty],
+        ]);
+        assert_eq!(table.value(&grid), Some(Value::Win(1)));
+        assert_eq!(table.best_moves(&grid), [(2, 0)]);
+
+        // O cannot have played more than X
+        let grid = Grid::from([
+            [Player::O, Player::O, Player::Empty],
+            [Player::Empty; 3],
+            [Player::Empty; 3],
+        ]);
+        assert_eq!(table.value(&grid), None);
+    }
+
+    #[test]
+    fn agrees_with_minimax() {
+        let (checked, disagreements) = verify(&Table::build());
+        assert_eq!(checked, 4520);
+        assert!(disagreements.is_empty(), "{disagreements:?}");
+    }
+}
@@ -0,0 +1,60 @@
+use schemars::{schema_for, Schema};
+
+use crate::json;
+use crate::protocol::Message;
+use crate::record::GameRecord;
+use crate::session::Move;
+use crate::Grid;
+
+// The JSON Schemas of the objects the program reads and writes as JSON, by
+// name, for the clients written in other languages
+pub fn schemas() -> Vec<(&'static str, Schema)> {
+    #[allow(unused_mut)]
+    let mut schemas = vec![
+        ("grid", schema_for!(Grid)),
+        ("move", schema_for!(Move)),
+        ("game_record", schema_for!(GameRecord)),
+        ("message", schema_for!(Message)),
+        ("json_request", schema_for!(json::Request)),
+        ("json_state", schema_for!(json::State)),
+    ];
+    #[cfg(feature = "server")]
+    {
+        use crate::server;
+
+        schemas.extend([
+            ("ws_request", schema_for!(server::Request)),
+            ("ws_reply", schema_for!(server::Reply)),
+            ("new_game", schema_for!(server::NewGame)),
+            ("play", schema_for!(server::Play)),
+            ("game_view", schema_for!(server::GameView)),
+            ("best_move", schema_for!(server::BestMove)),
+        ]);
+    }
+
+    schemas
+}
+
+pub fn schema(name: &str) -> Option<Schema> {
+    schemas()
+        .into_iter()
+        .find_map(|(n, schema)| (n == name).then_some(schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schemas() {
+        let grid = schema("grid").unwrap();
+        let json = serde_json::to_value(&grid).unwrap();
+        assert_eq!(json["title"], "Grid");
+        assert!(json["properties"]["matrix"].is_object());
+
+        let message = serde_json::to_string(&schema("message").unwrap()).unwrap();
+        assert!(message.contains("move_proposed"), "{message}");
+
+        assert!(schema("nothing").is_none());
+    }
+}
@@ -0,0 +1,2290 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path as FilePath, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use axum::extract::ws::{self, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State as Shared};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio_tungstenite::{tungstenite, MaybeTlsStream, WebSocketStream};
+
+use crate::analysis::{self, Value};
+use crate::engine::{Bot, Difficulty, Thought};
+use crate::json::{ClockState, Event, State};
+use crate::metrics::METRICS;
+use crate::notation::parse_coordinates;
+use crate::protocol::{self, HandshakeError, Message};
+use crate::record::{self, termination_tag, GameRecord};
+use crate::rng;
+use crate::session::{GameResult, GameSession, PlayError};
+use crate::{coordinates, Grid, Player};
+
+// A server relaying the games of browser clients over WebSockets, at `/ws`,
+// next to a REST API (see `Games`). Each client says hello with the messages
+// of the protocol, then sends JSON requests, one per message:
+//
+//     {"type":"hello","version":1,"name":"alice"}
+//     {"type":"new","opponent":"bot","difficulty":"medium"}
+//     {"type":"new","opponent":"human","clock":300}
+//     {"type":"join","game":1}
+//     {"type":"list"}
+//     {"type":"quick_match"}
+//     {"type":"watch","game":1}
+//     {"type":"resume","token":"..."}
+//     {"type":"move_proposed","square":"b2"}
+//     {"type":"resign"}
+//     {"type":"draw_offer"}
+//     {"type":"draw_accepted"}
+//     {"type":"chat","text":"good luck"}
+//
+// The name of each client is registered with its hello, and must not be taken.
+// It gets `joined` when it takes a seat in a game (and `opponent` once the
+// other player is there), then the `state` of the game (as in the JSON mode)
+// after every move, or `rejected`. What players say is sent to their opponent
+// and the spectators as `chat`, with the name of who said it, and so are draw
+// offers to the opponent, which stand until it moves. The engine takes them
+// when it cannot do better. A client that drops during a game keeps its
+// seat, and takes it back by resuming with the token it was given when it
+// joined. When the server requires authentication (see `Auth`), only the
+// clients with a token may start games, and their name is the one of their
+// token. Other clients may watch a game: they get
+// `watching`, the `state` of the game with all its moves so far, then the same
+// states as the players, but cannot play.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    // Start a game as X, against the engine or waiting for another player
+    New {
+        #[serde(default)]
+        opponent: Opponent,
+        difficulty: Option<Difficulty>,
+        // The seconds each player has, for a game with a clock
+        clock: Option<u64>,
+    },
+    // Play O in a game started by another player
+    Join {
+        game: u64,
+    },
+    // The games waiting for a second player
+    List,
+    // Join the game that has been waiting the longest, or start one
+    QuickMatch,
+    // Follow a game without playing it
+    Watch {
+        game: u64,
+    },
+    // Take back a seat after a disconnection
+    Resume {
+        token: String,
+    },
+    #[serde(untagged)]
+    Message(Message),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Opponent {
+    #[default]
+    Bot,
+    Human,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reply {
+    Joined {
+        game: u64,
+        player: Player,
+        // What to resume the game with after a disconnection
+        token: String,
+    },
+    Watching {
+        game: u64,
+    },
+    // The other player has been found
+    Opponent {
+        name: String,
+    },
+    OpenGames {
+        games: Vec<OpenGame>,
+    },
+    State(State),
+    Chat {
+        from: String,
+        text: String,
+    },
+    #[serde(untagged)]
+    Message(Message),
+}
+
+// A game waiting for a second player
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OpenGame {
+    pub game: u64,
+    // The name of the player who started it
+    pub host: String,
+}
+
+// The name the server gives itself in its hello
+const NAME: &str = "tictactoe";
+
+struct Game {
+    session: GameSession,
+    // The engine plays O in games against it
+    bot: Option<Bot>,
+    // Where to send the states of the game, for X and O
+    players: [Option<UnboundedSender<Reply>>; 2],
+    names: [Option<String>; 2],
+    // The tokens of the seats taken, kept while their player is away
+    tokens: [Option<String>; 2],
+    // The player whose draw offer stands
+    draw_offer: Option<Player>,
+    // Whether the end of the game has been counted in the metrics
+    counted: bool,
+    // When the game ended, as `YYYY-MM-DD` and `HH:MM:SS` in UTC
+    ended: Option<(String, String)>,
+    // Where to send the states of the game for those watching it
+    spectators: Vec<UnboundedSender<Reply>>,
+    // The time each player has, in games with a clock. It starts once both
+    // players are there.
+    clock: Option<Duration>,
+}
+
+impl Game {
+    fn new(opponent: Opponent, difficulty: Option<Difficulty>, seed: u64) -> Game {
+        let bot = (opponent == Opponent::Bot)
+            .then(|| Bot::new(difficulty.unwrap_or(Difficulty::Hard), seed));
+
+        Game {
+            session: GameSession::new(),
+            bot,
+            players: [None, None],
+            names: [None, None],
+            tokens: [None, None],
+            draw_offer: None,
+            counted: false,
+            ended: None,
+            spectators: Vec::new(),
+            clock: None,
+        }
+    }
+
+    fn with_clock(mut self, per_player: Option<Duration>) -> Game {
+        self.clock = per_player;
+        if self.bot.is_some() {
+            self.start_clock();
+        }
+        self
+    }
+
+    fn start_clock(&mut self) {
+        if let Some(per_player) = self.clock {
+            if self.session.history().is_empty() {
+                self.session = GameSession::new().with_clock(per_player);
+            }
+        }
+    }
+
+    // Tell everyone when a player has run out of time since the last move
+    fn check_clock(&mut self) {
+        let over = self.session.is_over();
+        if self.session.check_clock() && !over {
+            self.update(Vec::new());
+        }
+    }
+
+    // Waiting for a second player
+    fn is_open(&self) -> bool {
+        self.bot.is_none()
+            && self.tokens[0].is_some()
+            && self.tokens[1].is_none()
+            && !self.session.is_over()
+    }
+
+    fn seat(player: Player) -> usize {
+        match player {
+            Player::O => 1,
+            _ => 0,
+        }
+    }
+
+    // Send the state to both players and to the spectators that are still
+    // there
+    fn update(&mut self, events: Vec<Event>) {
+        let state = self.state(events);
+        for player in self.players.iter().flatten() {
+            let _ = player.send(Reply::State(state.clone()));
+        }
+        self.spectators
+            .retain(|spectator| spectator.send(Reply::State(state.clone())).is_ok());
+    }
+
+    // The state of the game with all the moves played so far
+    fn replay(&self) -> State {
+        let session = &self.session;
+        let mut events: Vec<Event> = session.history().iter().map(|&m| Event::from(m)).collect();
+        if let Some(result) = session.result() {
+            events.push(Event::GameOver {
+                winner: result.winner(),
+                termination: termination_tag(result).to_string(),
+            });
+        }
+
+        State::of(session, events)
+    }
+
+    // The engine and the position it is to move in, when it is its turn. The
+    // move is searched away from the games, which stay free for the other
+    // clients meanwhile, then given to `bot_played`.
+    fn bot_turn(&self) -> Option<(Bot, Grid)> {
+        let bot = self.bot.as_ref()?;
+        let grid = self.session.grid();
+        (!self.session.is_over() && grid.player_turn == Player::O)
+            .then(|| (bot.clone(), grid.clone()))
+    }
+
+    // Play the move the engine chose in `grid`, unless the game has moved on
+    // meanwhile
+    fn bot_played(&mut self, grid: &Grid, thought: Thought) -> Option<Event> {
+        if self.session.is_over() || self.session.grid().matrix() != grid.matrix() {
+            return None;
+        }
+        let (x, y) = thought.choice?;
+        self.bot = Some(thought.bot);
+        let m = self.session.play(x, y).ok()?;
+
+        Some(Event::from(m))
+    }
+
+    // The state of the game with what happened, noting when it has ended
+    fn state(&mut self, mut events: Vec<Event>) -> State {
+        if let Some(result) = self.session.result() {
+            events.push(Event::GameOver {
+                winner: result.winner(),
+                termination: termination_tag(result).to_string(),
+            });
+            if !self.counted {
+                self.counted = true;
+                self.ended = Some(record::utc_now());
+                METRICS.game_finished();
+            }
+        }
+
+        State::of(&self.session, events)
+    }
+
+    fn save(&self) -> SavedGame {
+        SavedGame {
+            moves: self
+                .session
+                .history()
+                .iter()
+                .map(|m| coordinates(m.x, m.y))
+                .collect(),
+            result: self.session.result(),
+            difficulty: self.bot.as_ref().map(|bot| bot.difficulty),
+            names: self.names.clone(),
+            tokens: self.tokens.clone(),
+            draw_offer: self.draw_offer,
+            ended: self.ended.clone(),
+            clock: self.clock.map(|per_player| SavedClock {
+                per_player: per_player.as_millis() as u64,
+                remaining: self.session.clock().map(|clock| {
+                    let state = ClockState::of(clock);
+                    [state.x, state.o]
+                }),
+            }),
+        }
+    }
+
+    // The record of the game once it is over, as kept in game logs
+    fn record(&self) -> Option<GameRecord> {
+        let name = |seat: usize, default: &str| {
+            self.names[seat]
+                .clone()
+                .unwrap_or_else(|| default.to_string())
+        };
+        let o = if self.bot.is_some() { "Bot" } else { "Guest" };
+        let mut record = GameRecord::from_session(&self.session, &name(0, "Guest"), &name(1, o))?;
+        if let Some((date, time)) = &self.ended {
+            record.date = date.clone();
+            record.time = time.clone();
+        }
+        record.set_tag("Event", "server");
+        if let Some(bot) = &self.bot {
+            record.set_tag("Difficulty", &bot.difficulty.to_string());
+        }
+
+        Some(record)
+    }
+
+    // The game as it was saved, its players being away, or `None` if its
+    // moves cannot be played
+    fn restore(saved: SavedGame, seed: u64) -> Option<Game> {
+        let opponent = match saved.difficulty {
+            Some(_) => Opponent::Bot,
+            None => Opponent::Human,
+        };
+        let mut game = Game::new(opponent, saved.difficulty, seed);
+        let clock = saved.clock.unwrap_or_default();
+        game.clock = saved.clock.map(|_| Duration::from_millis(clock.per_player));
+        if clock.remaining.is_some() {
+            game.session = GameSession::new().with_clock(Duration::from_millis(clock.per_player));
+        }
+        for square in &saved.moves {
+            let (x, y) = parse_coordinates(square).ok()?;
+            game.session.play(x, y).ok()?;
+        }
+        match saved.result {
+            Some(GameResult::Resigned(player)) => game.session.resign(player).ok()?,
+            Some(GameResult::DrawAgreed) => game.session.agree_draw().ok()?,
+            _ => (),
+        }
+        // The time since the game was last saved is not counted
+        for (player, remaining) in [Player::X, Player::O]
+            .into_iter()
+            .zip(clock.remaining.unwrap_or_default())
+        {
+            let spent = clock.per_player.saturating_sub(remaining);
+            game.session.penalize(player, Duration::from_millis(spent));
+        }
+        game.names = saved.names;
+        game.tokens = saved.tokens;
+        game.draw_offer = saved.draw_offer;
+        game.counted = game.session.is_over();
+        game.ended = saved.ended;
+
+        Some(game)
+    }
+}
+
+// A game as it is written to disk, in JSON. The result is only needed when the
+// moves do not tell it.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+struct SavedGame {
+    moves: Vec<String>,
+    result: Option<GameResult>,
+    // The level of the engine, in games against it
+    difficulty: Option<Difficulty>,
+    names: [Option<String>; 2],
+    tokens: [Option<String>; 2],
+    draw_offer: Option<Player>,
+    // Left out by the versions that did not keep it
+    #[serde(default)]
+    ended: Option<(String, String)>,
+    #[serde(default)]
+    clock: Option<SavedClock>,
+}
+
+// The clock of a saved game, in milliseconds
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+struct SavedClock {
+    per_player: u64,
+    // The time left to X and O, once the clock has started
+    remaining: Option<[u64; 2]>,
+}
+
+// Where the games are saved, one file per game called after its id
+pub struct Store {
+    dir: PathBuf,
+}
+
+impl Store {
+    pub fn open(dir: PathBuf) -> io::Result<Store> {
+        fs::create_dir_all(&dir)?;
+        Ok(Store { dir })
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    // Write the game through a temporary file, so that a crash cannot leave
+    // half of it
+    fn save(&self, id: u64, json: &str) -> io::Result<()> {
+        let temporary = self.dir.join(format!("{id}.json.tmp"));
+        fs::write(&temporary, json)?;
+        fs::rename(temporary, self.path(id))
+    }
+
+    fn remove(&self, id: u64) -> io::Result<()> {
+        fs::remove_file(self.path(id))
+    }
+
+    // The saved games, leaving out the files that cannot be read back, their
+    // bots seeded as `game_seed` does
+    fn load(&self, seed: u64) -> io::Result<Vec<(u64, Game)>> {
+        let mut games = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let Some(id) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str()?.parse().ok())
+            else {
+                continue;
+            };
+            let saved = fs::read_to_string(&path)
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok());
+            if let Some(game) = saved.and_then(|saved| Game::restore(saved, game_seed(seed, id))) {
+                games.push((id, game));
+            }
+        }
+
+        Ok(games)
+    }
+}
+
+// A connection to the server
+pub struct Client {
+    replies: UnboundedSender<Reply>,
+    // The name it gave in its hello
+    name: Option<String>,
+    // The game it plays in, and its side
+    seat: Option<(u64, Player)>,
+    // The game it watches
+    watching: Option<u64>,
+    // The name of its authentication token
+    identity: Option<String>,
+    may_create: bool,
+}
+
+impl Client {
+    pub fn new(replies: UnboundedSender<Reply>) -> Client {
+        Client {
+            replies,
+            name: None,
+            seat: None,
+            watching: None,
+            identity: None,
+            may_create: true,
+        }
+    }
+
+    // A client of a server requiring authentication, known as `identity` if
+    // it has given a valid token
+    pub fn authenticated(replies: UnboundedSender<Reply>, identity: Option<String>) -> Client {
+        Client {
+            may_create: identity.is_some(),
+            identity,
+            ..Client::new(replies)
+        }
+    }
+
+    fn send(&self, message: Message) {
+        let _ = self.replies.send(Reply::Message(message));
+    }
+
+    fn error(&self, reason: impl Into<String>) {
+        self.send(Message::rejected(reason));
+    }
+}
+
+// The games being played on the server, and the names of the clients
+#[derive(Default)]
+pub struct Hub {
+    games: HashMap<u64, Game>,
+    next_game: u64,
+    names: HashSet<String>,
+    // The seat of each token
+    tokens: HashMap<String, (u64, Player)>,
+    store: Option<Store>,
+    // The games as they were last saved
+    saved: HashMap<u64, String>,
+    // Where the seeds of the bots come from, see `game_seed`
+    seed: u64,
+}
+
+impl Hub {
+    // The games saved in `store`, which keeps the games to come
+    pub fn open(store: Store, seed: u64) -> io::Result<Hub> {
+        let mut hub = Hub {
+            seed,
+            ..Hub::default()
+        };
+        for (id, game) in store.load(seed)? {
+            for (seat, token) in game.tokens.iter().enumerate() {
+                if let Some(token) = token {
+                    let player = if seat == 0 { Player::X } else { Player::O };
+                    hub.tokens.insert(token.clone(), (id, player));
+                }
+            }
+            if let Ok(json) = serde_json::to_string(&game.save()) {
+                hub.saved.insert(id, json);
+            }
+            hub.next_game = hub.next_game.max(id);
+            hub.games.insert(id, game);
+        }
+        hub.store = Some(store);
+
+        Ok(hub)
+    }
+
+    pub fn handle(&mut self, client: &mut Client, request: Request) {
+        // A request can only change the game the client was in, and the one
+        // it is in after it
+        let changes = !matches!(
+            request,
+            Request::List | Request::Message(Message::Ping | Message::Chat { .. })
+        );
+        let before = client.seat.map(|(id, _)| id);
+        if let Some(game) = before.and_then(|id| self.games.get_mut(&id)) {
+            game.check_clock();
+        }
+        self.apply(client, request);
+        if changes {
+            let after = client
+                .seat
+                .map(|(id, _)| id)
+                .filter(|&id| Some(id) != before);
+            for id in before.into_iter().chain(after) {
+                self.persist(id);
+            }
+        }
+    }
+
+    // The engine of the game `id` and the position it is to move in, when it
+    // is its turn (see `Game::bot_turn`)
+    pub fn bot_turn(&self, id: u64) -> Option<(Bot, Grid)> {
+        self.games.get(&id)?.bot_turn()
+    }
+
+    // Play the move the engine of the game `id` chose in `grid`
+    pub fn bot_played(&mut self, id: u64, grid: &Grid, thought: Thought) {
+        let Some(game) = self.games.get_mut(&id) else {
+            return;
+        };
+        if let Some(event) = game.bot_played(grid, thought) {
+            game.update(vec![event]);
+        }
+        self.persist(id);
+    }
+
+    // The state of the game `id`, with all its moves
+    pub fn view(&self, id: u64) -> Option<State> {
+        Some(self.games.get(&id)?.replay())
+    }
+
+    // The records of the games that are over, in the order they were started
+    pub fn records(&self) -> Vec<GameRecord> {
+        let mut ids: Vec<u64> = self.games.keys().copied().collect();
+        ids.sort_unstable();
+        ids.iter()
+            .filter_map(|id| self.games[id].record())
+            .collect()
+    }
+
+    // Save the game `id` if it has changed, or forget it if it is gone
+    fn persist(&mut self, id: u64) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let Some(game) = self.games.get(&id) else {
+            if self.saved.contains_key(&id) && store.remove(id).is_ok() {
+                self.saved.remove(&id);
+            }
+            return;
+        };
+
+        let Ok(json) = serde_json::to_string(&game.save()) else {
+            return;
+        };
+        if self.saved.get(&id) != Some(&json) && store.save(id, &json).is_ok() {
+            self.saved.insert(id, json);
+        }
+    }
+
+    fn apply(&mut self, client: &mut Client, request: Request) {
+        if client.name.is_none() {
+            return match request {
+                Request::Message(hello @ Message::Hello { .. }) => {
+                    let hello = protocol::check_hello(hello)
+                        .map(|name| client.identity.clone().unwrap_or(name));
+                    match hello {
+                        Ok(name) if self.names.contains(&name) || name == NAME => {
+                            client.error(format!("the name {name} is taken"))
+                        }
+                        Ok(name) => {
+                            self.names.insert(name.clone());
+                            client.name = Some(name);
+                            client.send(Message::hello(NAME));
+                        }
+                        Err(e) => client.error(e.to_string()),
+                    }
+                }
+                _ => client.error(HandshakeError::NoHello.to_string()),
+            };
+        }
+
+        match request {
+            Request::New { .. } if !client.may_create => {
+                client.error("only authenticated clients may start games")
+            }
+            Request::New {
+                opponent,
+                difficulty,
+                clock,
+            } => {
+                let seed = game_seed(self.seed, self.next_game + 1);
+                let game = Game::new(opponent, difficulty, seed)
+                    .with_clock(clock.map(Duration::from_secs));
+                self.create(client, game)
+            }
+            Request::Join { game: id } => {
+                if client.seat.is_some_and(|(current, _)| current == id) {
+                    return client.error(format!("already playing game {id}"));
+                }
+                match self.games.get(&id) {
+                    None => client.error(format!("there is no game {id}")),
+                    Some(game) if !game.is_open() => {
+                        client.error(format!("game {id} already has two players"))
+                    }
+                    Some(_) => self.join(client, id),
+                }
+            }
+            Request::List => {
+                let _ = client.replies.send(Reply::OpenGames {
+                    games: self.open_games(),
+                });
+            }
+            Request::QuickMatch => {
+                let current = client.seat.map(|(id, _)| id);
+                let oldest = self
+                    .open_games()
+                    .into_iter()
+                    .map(|open| open.game)
+                    .find(|&id| Some(id) != current);
+                match oldest {
+                    Some(id) => self.join(client, id),
+                    None if !client.may_create => client
+                        .error("no game is waiting, and only authenticated clients may start one"),
+                    None => self.create(client, Game::new(Opponent::Human, None, 0)),
+                }
+            }
+            Request::Watch { game: id } => {
+                if client.seat.is_some_and(|(current, _)| current == id) {
+                    return client.error(format!("already playing game {id}"));
+                }
+                if !self.games.contains_key(&id) {
+                    return client.error(format!("there is no game {id}"));
+                }
+                self.leave_game(client);
+
+                let game = self.games.get_mut(&id).unwrap();
+                game.check_clock();
+                game.spectators.push(client.replies.clone());
+                client.watching = Some(id);
+                let _ = client.replies.send(Reply::Watching { game: id });
+                let _ = client.replies.send(Reply::State(game.replay()));
+            }
+            Request::Resume { token } => {
+                let Some(&(id, player)) = self.tokens.get(&token) else {
+                    return client.error("there is no game for this token");
+                };
+                if client.seat == Some((id, player)) {
+                    return client.error(format!("already playing game {id}"));
+                }
+                let connected = self
+                    .games
+                    .get(&id)
+                    .map(|game| &game.players[Game::seat(player)]);
+                if connected.is_some_and(Option::is_some) {
+                    return client.error(format!("{player} is still connected to game {id}"));
+                }
+                self.leave_game(client);
+                let Some(game) = self.games.get_mut(&id) else {
+                    return client.error(format!("there is no game {id}"));
+                };
+
+                game.check_clock();
+                game.players[Game::seat(player)] = Some(client.replies.clone());
+                game.names[Game::seat(player)] = client.name.clone();
+                client.seat = Some((id, player));
+                let _ = client.replies.send(Reply::Joined {
+                    game: id,
+                    player,
+                    token,
+                });
+                let _ = client.replies.send(Reply::State(game.replay()));
+            }
+            Request::Message(Message::MoveProposed { square }) => {
+                let Some((game, player)) = self.game_of(client) else {
+                    return client.error("not playing a game");
+                };
+                if game.session.is_over() || game.session.grid().player_turn != player {
+                    return client.error("not your turn");
+                }
+                if player == Player::X && game.tokens[1].is_none() && game.bot.is_none() {
+                    return client.error("waiting for another player to join");
+                }
+
+                match parse_coordinates(&square) {
+                    Ok((x, y)) => match game.session.play(x, y) {
+                        Ok(m) => {
+                            if game.draw_offer == Some(player.opponent()) {
+                                game.draw_offer = None;
+                            }
+                            game.update(vec![Event::from(m)]);
+                        }
+                        Err(PlayError::Occupied(p)) => {
+                            client.error(format!("{square} already has an {p}"))
+                        }
+                        Err(PlayError::GameOver) => client.error("the game is over"),
+                    },
+                    Err(e) => client.error(format!("{square}: {e}")),
+                }
+            }
+            Request::Message(Message::Resign) => {
+                let Some((game, player)) = self.game_of(client) else {
+                    return client.error("not playing a game");
+                };
+                match game.session.resign(player) {
+                    Ok(()) => game.update(Vec::new()),
+                    Err(_) => client.error("the game is over"),
+                }
+            }
+            Request::Message(Message::DrawOffer) => {
+                let Some((game, player)) = self.game_of(client) else {
+                    return client.error("not playing a game");
+                };
+                if game.session.is_over() {
+                    return client.error("the game is over");
+                }
+                if game.draw_offer.is_some() {
+                    return client.error("a draw has already been offered");
+                }
+
+                if game.bot.is_some() {
+                    let drawn = analysis::solve(game.session.grid())
+                        .is_some_and(|solution| solution.value == Value::Draw);
+                    if drawn && game.session.agree_draw().is_ok() {
+                        game.update(Vec::new());
+                    } else {
+                        client.error("the engine declines the draw");
+                    }
+                    return;
+                }
+                game.draw_offer = Some(player);
+                if let Some(opponent) = &game.players[Game::seat(player.opponent())] {
+                    let _ = opponent.send(Reply::Message(Message::DrawOffer));
+                }
+            }
+            Request::Message(Message::DrawAccepted) => {
+                let Some((game, player)) = self.game_of(client) else {
+                    return client.error("not playing a game");
+                };
+                if game.draw_offer != Some(player.opponent()) {
+                    return client.error("no draw has been offered");
+                }
+                match game.session.agree_draw() {
+                    Ok(()) => game.update(Vec::new()),
+                    Err(_) => client.error("the game is over"),
+                }
+            }
+            Request::Message(Message::Chat { text }) => {
+                let from = client.name.clone().unwrap_or_default();
+                let Some((game, player)) = self.game_of(client) else {
+                    return client.error("not playing a game");
+                };
+                let Message::Chat { text } = Message::chat(&text) else {
+                    return;
+                };
+
+                let chat = Reply::Chat { from, text };
+                if let Some(opponent) = &game.players[Game::seat(player.opponent())] {
+                    let _ = opponent.send(chat.clone());
+                }
+                game.spectators
+                    .retain(|spectator| spectator.send(chat.clone()).is_ok());
+            }
+            Request::Message(Message::Ping) => (),
+            Request::Message(message) => client.error(format!("unexpected {message:?}")),
+        }
+    }
+
+    // The games waiting for a second player, the oldest first
+    fn open_games(&self) -> Vec<OpenGame> {
+        let mut games: Vec<OpenGame> = self
+            .games
+            .iter()
+            .filter(|(_, game)| game.is_open())
+            .map(|(&id, game)| OpenGame {
+                game: id,
+                host: game.names[0].clone().unwrap_or_default(),
+            })
+            .collect();
+        games.sort_by_key(|open| open.game);
+
+        games
+    }
+
+    // Start `game`, with `client` playing X
+    fn create(&mut self, client: &mut Client, mut game: Game) {
+        self.leave_game(client);
+        self.next_game += 1;
+        let id = self.next_game;
+
+        let token = self.new_token(id, Player::X);
+        game.players[0] = Some(client.replies.clone());
+        game.names[0] = client.name.clone();
+        game.tokens[0] = Some(token.clone());
+        client.seat = Some((id, Player::X));
+        let _ = client.replies.send(Reply::Joined {
+            game: id,
+            player: Player::X,
+            token,
+        });
+        game.update(Vec::new());
+        self.games.insert(id, game);
+        METRICS.game_created();
+    }
+
+    // Let `client` play O in the open game `id`
+    fn join(&mut self, client: &mut Client, id: u64) {
+        // Only leave the previous game once the new one is joined
+        let previous = client.seat.take();
+        let token = self.new_token(id, Player::O);
+        let Some(game) = self.games.get_mut(&id) else {
+            return;
+        };
+
+        game.players[1] = Some(client.replies.clone());
+        game.names[1] = client.name.clone();
+        game.tokens[1] = Some(token.clone());
+        game.start_clock();
+        let _ = client.replies.send(Reply::Joined {
+            game: id,
+            player: Player::O,
+            token,
+        });
+        for (player, opponent) in game.players.iter().zip(game.names.iter().rev()) {
+            if let (Some(player), Some(name)) = (player, opponent) {
+                let _ = player.send(Reply::Opponent { name: name.clone() });
+            }
+        }
+        game.update(Vec::new());
+
+        client.seat = previous;
+        self.leave_game(client);
+        client.seat = Some((id, Player::O));
+    }
+
+    // A token for the seat of `player` in the game `id`. They are not guessed
+    // easily, but are no secret either. Unlike the bots, they do not come from
+    // the seed of the server, which would let them be worked out.
+    fn new_token(&mut self, id: u64, player: Player) -> String {
+        let token = format!("{:016x}{:016x}", rng::random_seed(), rng::random_seed());
+        self.tokens.insert(token.clone(), (id, player));
+
+        token
+    }
+
+    // Forget `client`, which has gone. Its seat is kept for it to resume the
+    // game if it is not over.
+    pub fn disconnect(&mut self, client: &mut Client) {
+        let seat = client.seat.map(|(id, _)| id);
+        self.drop_client(client);
+        if let Some(id) = seat {
+            self.persist(id);
+        }
+    }
+
+    fn drop_client(&mut self, client: &mut Client) {
+        if let Some((id, player)) = client.seat {
+            if let Some(game) = self.games.get_mut(&id) {
+                if !game.session.is_over() {
+                    game.players[Game::seat(player)] = None;
+                    client.seat = None;
+                }
+            }
+        }
+        self.leave_game(client);
+        if let Some(name) = client.name.take() {
+            self.names.remove(&name);
+        }
+    }
+
+    // Leave the current game of `client`, which it loses if it is not over, or
+    // stop watching it. The game is kept for the API once over, unless nobody
+    // had joined it yet.
+    pub fn leave(&mut self, client: &mut Client) {
+        let seat = client.seat.map(|(id, _)| id);
+        self.leave_game(client);
+        if let Some(id) = seat {
+            self.persist(id);
+        }
+    }
+
+    // `leave`, the game being saved by the caller
+    fn leave_game(&mut self, client: &mut Client) {
+        if let Some(id) = client.watching.take() {
+            if let Some(game) = self.games.get_mut(&id) {
+                game.spectators
+                    .retain(|spectator| !spectator.same_channel(&client.replies));
+            }
+        }
+
+        let Some((id, player)) = client.seat.take() else {
+            return;
+        };
+        let Some(game) = self.games.get_mut(&id) else {
+            return;
+        };
+
+        game.players[Game::seat(player)] = None;
+        if let Some(token) = game.tokens[Game::seat(player)].take() {
+            self.tokens.remove(&token);
+        }
+        if game.bot.is_none() && game.names[1].is_none() {
+            for token in game.tokens.iter().flatten() {
+                self.tokens.remove(token);
+            }
+            self.games.remove(&id);
+        } else if game.session.resign(player).is_ok() {
+            game.update(Vec::new());
+        }
+    }
+
+    fn game_of(&mut self, client: &Client) -> Option<(&mut Game, Player)> {
+        let (id, player) = client.seat?;
+        Some((self.games.get_mut(&id)?, player))
+    }
+}
+
+// The REST API, for programs that would rather not keep a connection open:
+//
+//     POST /games                  {"opponent":"human","difficulty":"easy"}, both optional
+//     GET  /games/{id}
+//     POST /games/{id}/moves       {"square":"b2"}
+//     GET  /games/{id}/bestmove
+//     GET  /games/log
+//     GET  /games/ws/{id}
+//
+// Games are given by their id and state, whose events are all the moves played
+// so far, or those of the request when moving. Against the engine, the moves
+// are those of X and the engine answers right away; otherwise each request
+// plays for the side to move. Errors are given as `{"error":"..."}`.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NewGame {
+    #[serde(default)]
+    pub opponent: Opponent,
+    pub difficulty: Option<Difficulty>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Play {
+    pub square: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GameView {
+    pub id: u64,
+    #[serde(flatten)]
+    pub state: State,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct BestMove {
+    // `win`, `draw` or `loss` for the player to move, in `plies` moves
+    pub value: &'static str,
+    pub plies: Option<i32>,
+    pub best_moves: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> ApiError {
+        ApiError {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(status = %self.status, message = %self.message, "request refused");
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+// The games played through the REST API, which are kept once over
+#[derive(Default)]
+pub struct Games {
+    games: HashMap<u64, Game>,
+    next_game: u64,
+    store: Option<Store>,
+    // Where the seeds of the bots come from, see `game_seed`
+    seed: u64,
+}
+
+impl Games {
+    // The games saved in `store`, which keeps the games to come
+    pub fn open(store: Store, seed: u64) -> io::Result<Games> {
+        let games: HashMap<u64, Game> = store.load(seed)?.into_iter().collect();
+        Ok(Games {
+            next_game: games.keys().copied().max().unwrap_or(0),
+            games,
+            store: Some(store),
+            seed,
+        })
+    }
+
+    // A failure to save only loses the game if the server restarts
+    fn persist(&self, id: u64) {
+        let (Some(store), Some(game)) = (&self.store, self.games.get(&id)) else {
+            return;
+        };
+        if let Ok(json) = serde_json::to_string(&game.save()) {
+            let _ = store.save(id, &json);
+        }
+    }
+
+    pub fn create(&mut self, new: NewGame) -> GameView {
+        self.next_game += 1;
+        let id = self.next_game;
+
+        let mut game = Game::new(new.opponent, new.difficulty, game_seed(self.seed, id));
+        let state = game.state(Vec::new());
+        self.games.insert(id, game);
+        METRICS.game_created();
+        self.persist(id);
+
+        GameView { id, state }
+    }
+
+    pub fn view(&self, id: u64) -> Result<GameView, ApiError> {
+        Ok(GameView {
+            id,
+            state: self.get(id)?.replay(),
+        })
+    }
+
+    pub fn play(&mut self, id: u64, square: &str) -> Result<GameView, ApiError> {
+        let game = self.games.get_mut(&id).ok_or_else(|| {
+            ApiError::new(StatusCode::NOT_FOUND, format!("there is no game {id}"))
+        })?;
+
+        if game.bot_turn().is_some() {
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "the engine is choosing its move",
+            ));
+        }
+
+        let (x, y) = parse_coordinates(square).map_err(|e| {
+            ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("{square}: {e}"))
+        })?;
+        let m = game.session.play(x, y).map_err(|e| match e {
+            PlayError::Occupied(p) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("{square} already has an {p}"),
+            ),
+            PlayError::GameOver => ApiError::new(StatusCode::CONFLICT, "the game is over"),
+        })?;
+
+        let state = game.state(vec![Event::from(m)]);
+        self.persist(id);
+
+        Ok(GameView { id, state })
+    }
+
+    // The engine of the game `id` and the position it is to move in, when it
+    // is its turn (see `Game::bot_turn`)
+    pub fn bot_turn(&self, id: u64) -> Option<(Bot, Grid)> {
+        self.games.get(&id)?.bot_turn()
+    }
+
+    // Play the move the engine of the game `id` chose in `grid`, giving the
+    // state with `events` and that move
+    pub fn bot_played(
+        &mut self,
+        id: u64,
+        grid: &Grid,
+        thought: Thought,
+        mut events: Vec<Event>,
+    ) -> Result<GameView, ApiError> {
+        let game = self.games.get_mut(&id).ok_or_else(|| {
+            ApiError::new(StatusCode::NOT_FOUND, format!("there is no game {id}"))
+        })?;
+        events.extend(game.bot_played(grid, thought));
+        let state = game.state(events);
+        self.persist(id);
+
+        Ok(GameView { id, state })
+    }
+
+    pub fn best_move(&self, id: u64) -> Result<BestMove, ApiError> {
+        best_move_in(self.grid(id)?)
+    }
+
+    pub fn grid(&self, id: u64) -> Result<Grid, ApiError> {
+        Ok(self.get(id)?.session.grid().clone())
+    }
+
+    // The records of the games that are over, in the order they were started
+    pub fn records(&self) -> Vec<GameRecord> {
+        let mut ids: Vec<u64> = self.games.keys().copied().collect();
+        ids.sort_unstable();
+        ids.iter()
+            .filter_map(|id| self.games[id].record())
+            .collect()
+    }
+
+    // `records` as a game log
+    pub fn log(&self) -> String {
+        game_log_of(&self.records())
+    }
+
+    fn get(&self, id: u64) -> Result<&Game, ApiError> {
+        self.games
+            .get(&id)
+            .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("there is no game {id}")))
+    }
+}
+
+fn game_log_of(records: &[GameRecord]) -> String {
+    let mut log = Vec::new();
+    let _ = record::write_log(&mut log, records);
+    String::from_utf8(log).unwrap_or_default()
+}
+
+// The answer to `/games/{id}/bestmove` for the position of the game
+fn best_move_in(grid: Grid) -> Result<BestMove, ApiError> {
+    let solution = analysis::solve(&grid)
+        .ok_or_else(|| ApiError::new(StatusCode::CONFLICT, "the game is over"))?;
+
+    let (value, plies) = match solution.value {
+        Value::Win(n) => ("win", Some(n)),
+        Value::Draw => ("draw", None),
+        Value::Loss(n) => ("loss", Some(n)),
+    };
+    Ok(BestMove {
+        value,
+        plies,
+        best_moves: solution
+            .best_moves
+            .into_iter()
+            .map(|(x, y)| coordinates(x, y))
+            .collect(),
+    })
+}
+
+// The clients allowed to start games and ask the engine, each with a token to
+// give as `Authorization: Bearer <token>`, or as `/ws?token=<token>` since
+// browsers cannot set the headers of WebSockets. They are read from a file
+// with one client per line, its name then its token:
+//
+//     # name  token
+//     alice   4f9c0e2a7b1d
+//     bob     e83d51b06a2c
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Auth {
+    // The name of each token
+    tokens: HashMap<String, String>,
+}
+
+impl Auth {
+    pub fn parse(content: &str) -> Result<Auth, String> {
+        let mut tokens = HashMap::new();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, token] = fields[..] else {
+                return Err(format!("line {}: expected a name and a token", i + 1));
+            };
+            if tokens.insert(token.to_string(), name.to_string()).is_some() {
+                return Err(format!("line {}: this token is already given", i + 1));
+            }
+        }
+
+        Ok(Auth { tokens })
+    }
+
+    pub fn load(path: &FilePath) -> Result<Auth, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Auth::parse(&content)
+    }
+
+    // The name of the client with `token`
+    pub fn identify(&self, token: &str) -> Option<&str> {
+        self.tokens.get(token).map(String::as_str)
+    }
+}
+
+// The token of the `Authorization: Bearer` header
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+#[derive(Default)]
+struct Server {
+    hub: Mutex<Hub>,
+    games: Mutex<Games>,
+    auth: Option<Auth>,
+}
+
+impl Server {
+    // The games of the WebSockets. A panic while they are locked leaves them as
+    // they were, and they go on being served.
+    fn hub(&self) -> MutexGuard<'_, Hub> {
+        self.hub.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // The games of the REST API, likewise
+    fn games(&self) -> MutexGuard<'_, Games> {
+        self.games.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Check the token of a request, if the server requires one
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), ApiError> {
+        let Some(auth) = &self.auth else {
+            return Ok(());
+        };
+        match bearer(headers).and_then(|token| auth.identify(token)) {
+            Some(_) => Ok(()),
+            None => Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "a valid bearer token is needed",
+            )),
+        }
+    }
+}
+
+// The seed of the bot of the game `id` on a server seeded with `seed`, the
+// same after a restart
+fn game_seed(seed: u64, id: u64) -> u64 {
+    seed.wrapping_add(id)
+}
+
+// How the server is run
+#[derive(Default)]
+pub struct Settings {
+    // Only let these clients start games and ask the engine
+    pub auth: Option<Auth>,
+    // Save the games in this directory, to get them back after a restart
+    pub storage: Option<PathBuf>,
+    // Serve the web client at `/`
+    pub ui: bool,
+    // Where the seeds of the bots come from, so that a server started again
+    // with the same one plays the same moves. A random one if `None`.
+    pub seed: Option<u64>,
+}
+
+pub fn router(settings: Settings) -> io::Result<Router> {
+    let seed = settings.seed.unwrap_or_else(rng::random_seed);
+    let mut server = Server {
+        auth: settings.auth,
+        ..Server::default()
+    };
+    match settings.storage {
+        Some(dir) => {
+            server.hub = Mutex::new(Hub::open(Store::open(dir.join("ws"))?, seed)?);
+            server.games = Mutex::new(Games::open(Store::open(dir.join("api"))?, seed)?);
+        }
+        None => {
+            server.hub.get_mut().unwrap().seed = seed;
+            server.games.get_mut().unwrap().seed = seed;
+        }
+    }
+
+    let mut router = Router::new();
+    if settings.ui {
+        router = router.route("/", get(ui));
+    }
+    #[cfg(feature = "schema")]
+    {
+        router = router.route("/schemas/{name}", get(schema));
+    }
+    Ok(router
+        .route("/ws", get(upgrade))
+        .route("/games", post(create_game))
+        .route("/games/{id}", get(view_game))
+        .route("/games/{id}/moves", post(play_move))
+        .route("/games/{id}/bestmove", get(best_move))
+        .route("/games/log", get(game_log))
+        .route("/games/ws/{id}", get(view_ws_game))
+        .route("/metrics", get(metrics))
+        .with_state(Arc::new(server)))
+}
+
+// Serve games on `address` until the process is stopped
+pub fn serve(address: SocketAddr, settings: Settings) -> io::Result<()> {
+    let router = router(settings)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        axum::serve(listener, router).await
+    })
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+async fn create_game(
+    Shared(server): Shared<Arc<Server>>,
+    headers: HeaderMap,
+    new: Option<Json<NewGame>>,
+) -> Result<(StatusCode, Json<GameView>), ApiError> {
+    server.authorize(&headers)?;
+    let new = new.map(|Json(new)| new).unwrap_or_default();
+    let view = server.games().create(new);
+
+    Ok((StatusCode::CREATED, Json(view)))
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(server)))]
+async fn view_game(
+    Shared(server): Shared<Arc<Server>>,
+    Path(id): Path<u64>,
+) -> Result<Json<GameView>, ApiError> {
+    server.games().view(id).map(Json)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(server)))]
+async fn view_ws_game(
+    Shared(server): Shared<Arc<Server>>,
+    Path(id): Path<u64>,
+) -> Result<Json<GameView>, ApiError> {
+    let state = server
+        .hub()
+        .view(id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("there is no game {id}")))?;
+
+    Ok(Json(GameView { id, state }))
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(server)))]
+async fn play_move(
+    Shared(server): Shared<Arc<Server>>,
+    Path(id): Path<u64>,
+    Json(play): Json<Play>,
+) -> Result<Json<GameView>, ApiError> {
+    let start = Instant::now();
+    let (mut view, turn) = {
+        let mut games = server.games();
+        let view = games.play(id, &play.square)?;
+        (view, games.bot_turn(id))
+    };
+    METRICS.move_played(start.elapsed());
+    #[cfg(feature = "tracing")]
+    tracing::debug!(elapsed = ?start.elapsed(), "move played");
+
+    // The engine answers with the games unlocked
+    if let Some((bot, grid)) = turn {
+        if let Some(thought) = bot.think_async(&grid).await {
+            view = server
+                .games()
+                .bot_played(id, &grid, thought, view.state.events)?;
+        }
+    }
+
+    Ok(Json(view))
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(server, headers)))]
+async fn best_move(
+    Shared(server): Shared<Arc<Server>>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+) -> Result<Json<BestMove>, ApiError> {
+    server.authorize(&headers)?;
+    // Searched without holding the games, nor the executor
+    let grid = server.games().grid(id)?;
+    let search = tokio::task::spawn_blocking(|| best_move_in(grid));
+    search
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map(Json)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+async fn game_log(Shared(server): Shared<Arc<Server>>) -> impl IntoResponse {
+    // Those of the REST API, then those of the WebSockets
+    let mut records = server.games().records();
+    records.extend(server.hub().records());
+    let log = game_log_of(&records);
+    ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], log)
+}
+
+// The web client, a single page playing through the REST API and the WebSocket
+async fn ui() -> Html<&'static str> {
+    Html(include_str!("ui.html"))
+}
+
+// The JSON Schema named `name`, see `schema::schemas`
+#[cfg(feature = "schema")]
+async fn schema(Path(name): Path<String>) -> Result<Json<schemars::Schema>, ApiError> {
+    crate::schema::schema(&name)
+        .map(Json)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("there is no schema {name}")))
+}
+
+async fn metrics() -> impl IntoResponse {
+    let content_type = "text/plain; version=0.0.4";
+    ([(header::CONTENT_TYPE, content_type)], METRICS.render())
+}
+
+// Connections without a token are let in, but may not start games
+async fn upgrade(
+    upgrade: WebSocketUpgrade,
+    Shared(server): Shared<Arc<Server>>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let token = bearer(&headers).or(query.get("token").map(String::as_str));
+    let identity = match (&server.auth, token) {
+        (None, _) => None,
+        (Some(_), None) => Some(None),
+        (Some(auth), Some(token)) => match auth.identify(token) {
+            Some(name) => Some(Some(name.to_string())),
+            None => {
+                let e = ApiError::new(StatusCode::UNAUTHORIZED, "this token is not valid");
+                return e.into_response();
+            }
+        },
+    };
+
+    upgrade.on_upgrade(|socket| connection(socket, server, identity))
+}
+
+// `identity` is `None` when the server requires no authentication
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(identity = ?identity)))]
+async fn connection(mut socket: WebSocket, server: Arc<Server>, identity: Option<Option<String>>) {
+    let (sender, mut replies) = mpsc::unbounded_channel();
+    let mut client = match identity {
+        Some(identity) => Client::authenticated(sender, identity),
+        None => Client::new(sender),
+    };
+    METRICS.connected();
+    #[cfg(feature = "tracing")]
+    tracing::info!("connected");
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let Some(Ok(message)) = message else { break };
+                let ws::Message::Text(text) = message else { continue };
+                let turn = match serde_json::from_str(&text) {
+                    Ok(request @ Request::Message(Message::MoveProposed { .. })) => {
+                        let start = Instant::now();
+                        let mut hub = server.hub();
+                        hub.handle(&mut client, request);
+                        METRICS.move_played(start.elapsed());
+                        client.seat.and_then(|(id, _)| Some((id, hub.bot_turn(id)?)))
+                    }
+                    Ok(request) => {
+                        let mut hub = server.hub();
+                        hub.handle(&mut client, request);
+                        client.seat.and_then(|(id, _)| Some((id, hub.bot_turn(id)?)))
+                    }
+                    Err(e) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %e, "invalid request");
+                        client.error(e.to_string());
+                        None
+                    }
+                };
+                // The engine answers with the hub unlocked
+                if let Some((id, (bot, grid))) = turn {
+                    if let Some(thought) = bot.think_async(&grid).await {
+                        server.hub().bot_played(id, &grid, thought);
+                    }
+                }
+            }
+            Some(reply) = replies.recv() => {
+                let Ok(text) = serde_json::to_string(&reply) else { continue };
+                if socket.send(ws::Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    server.hub().disconnect(&mut client);
+    METRICS.disconnected();
+    #[cfg(feature = "tracing")]
+    tracing::info!("disconnected");
+}
+
+// What a spectator is told after each move of the game it watches
+#[derive(Debug, Clone)]
+pub struct Watched {
+    pub grid: Grid,
+    // Once the game is over
+    pub result: Option<GameResult>,
+}
+
+// A client following a game of another server from its WebSocket, as
+// `tictactoe watch` does: it says hello, asks to watch the game, then reads
+// the states the server sends after each move. What else it is sent, like
+// the chat, is left out.
+pub struct Spectator {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl Spectator {
+    // Watch the game `game` of the server at `address` (a host and a port),
+    // as `name`
+    pub async fn connect(address: &str, name: &str, game: u64) -> Result<Spectator, String> {
+        let url = format!("ws://{address}/ws");
+        let (socket, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut spectator = Spectator { socket };
+
+        spectator
+            .send(&Request::Message(Message::hello(name)))
+            .await?;
+        spectator
+            .expect(|reply| matches!(reply, Reply::Message(Message::Hello { .. })))
+            .await?;
+        spectator.send(&Request::Watch { game }).await?;
+        spectator
+            .expect(|reply| matches!(reply, Reply::Watching { .. }))
+            .await?;
+
+        Ok(spectator)
+    }
+
+    // The next state of the game, `None` once the server has closed the
+    // connection
+    pub async fn next(&mut self) -> Result<Option<Watched>, String> {
+        while let Some(reply) = self.receive().await? {
+            if let Reply::State(state) = reply {
+                return Ok(Some(Watched::from(state)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn send(&mut self, request: &Request) -> Result<(), String> {
+        let text = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        self.socket
+            .send(tungstenite::Message::text(text))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    // The next reply, whatever its type
+    async fn receive(&mut self) -> Result<Option<Reply>, String> {
+        while let Some(message) = self.socket.next().await {
+            match message.map_err(|e| e.to_string())? {
+                tungstenite::Message::Text(text) => {
+                    return serde_json::from_str(&text).map_err(|e| e.to_string());
+                }
+                tungstenite::Message::Close(_) => break,
+                _ => continue,
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Wait for a reply that `expected` accepts, failing with the reason of
+    // a rejection
+    async fn expect(&mut self, expected: impl Fn(&Reply) -> bool) -> Result<(), String> {
+        loop {
+            match self.receive().await? {
+                Some(reply) if expected(&reply) => return Ok(()),
+                Some(Reply::Message(Message::Rejected { reason })) => return Err(reason),
+                Some(_) => continue,
+                None => return Err("the server closed the connection".to_string()),
+            }
+        }
+    }
+}
+
+// The position and the result of a `state` reply
+impl From<State> for Watched {
+    fn from(state: State) -> Watched {
+        let result = state.events.iter().find_map(|event| match event {
+            Event::GameOver {
+                winner,
+                termination,
+            } => record::game_result(*winner, termination),
+            _ => None,
+        });
+
+        Watched {
+            grid: Grid::from(state.board),
+            result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    fn connect(hub: &mut Hub, name: &str) -> (Client, UnboundedReceiver<Reply>) {
+        let (sender, mut replies) = mpsc::unbounded_channel();
+        let mut client = Client::new(sender);
+        hub.handle(&mut client, Request::Message(Message::hello(name)));
+        assert_eq!(
+            replies.try_recv().unwrap(),
+            Reply::Message(Message::hello(NAME))
+        );
+
+        (client, replies)
+    }
+
+    // Let the engine of the game `id` answer, as `connection` does
+    fn answer(hub: &mut Hub, id: u64) {
+        let (bot, grid) = hub.bot_turn(id).unwrap();
+        let thought = bot.think(&grid).wait(Duration::from_secs(60)).unwrap();
+        hub.bot_played(id, &grid, thought);
+    }
+
+    // `Games::play`, with the answer of the engine as `play_move` gives it
+    fn play(games: &mut Games, id: u64, square: &str) -> Result<GameView, ApiError> {
+        let view = games.play(id, square)?;
+        let Some((bot, grid)) = games.bot_turn(id) else {
+            return Ok(view);
+        };
+        let thought = bot.think(&grid).wait(Duration::from_secs(60)).unwrap();
+        games.bot_played(id, &grid, thought, view.state.events)
+    }
+
+    fn propose(square: &str) -> Request {
+        Request::Message(Message::MoveProposed {
+            square: square.to_string(),
+        })
+    }
+
+    fn last_state(replies: &mut UnboundedReceiver<Reply>) -> Option<State> {
+        let mut last = None;
+        while let Ok(reply) = replies.try_recv() {
+            if let Reply::State(state) = reply {
+                last = Some(state);
+            }
+        }
+        last
+    }
+
+    #[test]
+    fn against_the_bot() {
+        let mut hub = Hub::default();
+        let (mut client, mut replies) = connect(&mut hub, "alice");
+        let new = Request::New {
+            opponent: Opponent::Bot,
+            difficulty: None,
+            clock: None,
+        };
+        hub.handle(&mut client, new);
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(Reply::Joined {
+                game: 1,
+                player: Player::X,
+                ..
+            })
+        ));
+
+        hub.handle(&mut client, propose("b2"));
+        let state = last_state(&mut replies).unwrap();
+        assert_eq!(state.turn, Some(Player::O));
+        // The engine moves once asked to, unless the game has moved on
+        let (bot, grid) = hub.bot_turn(1).unwrap();
+        let thought = bot.think(&grid).wait(Duration::from_secs(60)).unwrap();
+        answer(&mut hub, 1);
+        let state = last_state(&mut replies).unwrap();
+        assert_eq!(state.turn, Some(Player::X));
+        assert_eq!(state.events.len(), 1);
+        assert!(hub.bot_turn(1).is_none());
+        hub.bot_played(1, &grid, thought);
+        assert_eq!(last_state(&mut replies), None);
+    }
+
+    #[test]
+    fn handshake() {
+        let mut hub = Hub::default();
+        let (sender, mut replies) = mpsc::unbounded_channel();
+        let mut client = Client::new(sender);
+
+        hub.handle(&mut client, propose("b2"));
+        let hello = Message::Hello {
+            version: 0,
+            name: "bob".to_string(),
+        };
+        hub.handle(&mut client, Request::Message(hello));
+        for _ in 0..2 {
+            assert!(matches!(
+                replies.try_recv(),
+                Ok(Reply::Message(Message::Rejected { .. }))
+            ));
+        }
+
+        let json = r#"{"type":"move_proposed","square":"b2"}"#;
+        assert_eq!(
+            serde_json::from_str::<Request>(json).unwrap(),
+            propose("b2")
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        // What `Spectator` sends and reads back
+        let requests = [
+            Request::Watch { game: 3 },
+            Request::New {
+                opponent: Opponent::Human,
+                difficulty: Some(Difficulty::Easy),
+                clock: Some(60),
+            },
+            Request::Message(Message::hello("bob")),
+        ];
+        for request in requests {
+            let json = serde_json::to_string(&request).unwrap();
+            assert_eq!(serde_json::from_str::<Request>(&json).unwrap(), request);
+        }
+
+        let mut session = GameSession::new();
+        session.resign(Player::X).unwrap();
+        let state = State::of(
+            &session,
+            vec![Event::GameOver {
+                winner: Some(Player::O),
+                termination: "resignation".to_string(),
+            }],
+        );
+        let replies = [
+            Reply::Watching { game: 3 },
+            Reply::State(state.clone()),
+            Reply::Message(Message::rejected("no such game")),
+        ];
+        for reply in replies {
+            let json = serde_json::to_string(&reply).unwrap();
+            assert_eq!(serde_json::from_str::<Reply>(&json).unwrap(), reply);
+        }
+
+        let watched = Watched::from(state);
+        assert_eq!(watched.result, Some(GameResult::Resigned(Player::X)));
+    }
+
+    #[test]
+    fn seeded_bots() {
+        // The answer of an easy bot to b2 in the first game of a server
+        let answer = |seed| {
+            let mut games = Games {
+                seed,
+                ..Games::default()
+            };
+            games.create(NewGame {
+                opponent: Opponent::Bot,
+                difficulty: Some(Difficulty::Easy),
+            });
+            play(&mut games, 1, "b2").unwrap().state
+        };
+
+        assert_eq!(answer(7), answer(7));
+        let answers: Vec<_> = (0..20).map(answer).collect();
+        assert!(answers.iter().any(|a| *a != answers[0]));
+    }
+
+    #[test]
+    fn rest_api() {
+        let mut games = Games::default();
+        let view = games.create(NewGame::default());
+        assert_eq!(view.id, 1);
+        assert_eq!(view.state.legal_moves.len(), 9);
+
+        assert_eq!(games.play(1, "b2").unwrap().state.turn, Some(Player::O));
+        // The engine plays O
+        let error = play(&mut games, 1, "c3").unwrap_err();
+        assert_eq!(error.status, StatusCode::CONFLICT);
+        let (bot, grid) = games.bot_turn(1).unwrap();
+        let thought = bot.think(&grid).wait(Duration::from_secs(60)).unwrap();
+        let view = games.bot_played(1, &grid, thought, Vec::new()).unwrap();
+        assert_eq!(view.state.events.len(), 1);
+        assert_eq!(view.state.turn, Some(Player::X));
+        assert_eq!(games.view(1).unwrap().state.board, view.state.board);
+        assert_eq!(games.best_move(1).unwrap().value, "draw");
+
+        assert_eq!(
+            games.play(1, "b2").unwrap_err().status,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            games.play(2, "a1").unwrap_err().status,
+            StatusCode::NOT_FOUND
+        );
+
+        // Only the games that are over are logged
+        assert_eq!(games.log(), "");
+        games.create(NewGame {
+            opponent: Opponent::Human,
+            difficulty: None,
+        });
+        for square in ["a1", "b1", "a2", "b2", "a3"] {
+            games.play(2, square).unwrap();
+        }
+        let records = record::parse_log(&games.log()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].result, GameResult::Win(Player::X));
+        assert_eq!(records[0].tag("Event"), Some("server"));
+    }
+
+    #[test]
+    fn between_players() {
+        let mut hub = Hub::default();
+        let (mut x, mut x_replies) = connect(&mut hub, "alice");
+        let (mut o, mut o_replies) = connect(&mut hub, "bob");
+        let new = Request::New {
+            opponent: Opponent::Human,
+            difficulty: None,
+            clock: None,
+        };
+        hub.handle(&mut x, new);
+
+        hub.handle(&mut x, propose("b2"));
+        assert!(matches!(x_replies.try_recv(), Ok(Reply::Joined { .. })));
+        last_state(&mut x_replies);
+
+        hub.handle(&mut o, Request::Join { game: 1 });
+        hub.handle(&mut x, propose("b2"));
+        hub.handle(&mut o, propose("b2"));
+        assert!(matches!(o_replies.try_recv(), Ok(Reply::Joined { .. })));
+        assert_eq!(
+            o_replies.try_recv().unwrap(),
+            Reply::Opponent {
+                name: "alice".to_string()
+            }
+        );
+        assert!(matches!(o_replies.try_recv(), Ok(Reply::State(_))));
+        assert!(matches!(o_replies.try_recv(), Ok(Reply::State(_))));
+        assert!(matches!(
+            o_replies.try_recv(),
+            Ok(Reply::Message(Message::Rejected { .. }))
+        ));
+
+        // Leaving loses the game
+        hub.leave(&mut o);
+        let state = last_state(&mut x_replies).unwrap();
+        assert_eq!(
+            state.events,
+            vec![Event::GameOver {
+                winner: Some(Player::X),
+                termination: "resignation".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn lobby() {
+        let mut hub = Hub::default();
+        let (mut alice, mut alice_replies) = connect(&mut hub, "alice");
+        let (mut bob, mut bob_replies) = connect(&mut hub, "bob");
+
+        let (sender, mut replies) = mpsc::unbounded_channel();
+        let mut impostor = Client::new(sender);
+        hub.handle(&mut impostor, Request::Message(Message::hello("alice")));
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(Reply::Message(Message::Rejected { .. }))
+        ));
+
+        // Nobody is waiting, so alice starts a game that bob is paired into
+        hub.handle(&mut alice, Request::QuickMatch);
+        hub.handle(&mut bob, Request::List);
+        assert_eq!(
+            bob_replies.try_recv().unwrap(),
+            Reply::OpenGames {
+                games: vec![OpenGame {
+                    game: 1,
+                    host: "alice".to_string()
+                }]
+            }
+        );
+        hub.handle(&mut bob, Request::QuickMatch);
+        assert!(matches!(
+            bob_replies.try_recv(),
+            Ok(Reply::Joined {
+                game: 1,
+                player: Player::O,
+                ..
+            })
+        ));
+        let opponent = Reply::Opponent {
+            name: "bob".to_string(),
+        };
+        assert!(std::iter::from_fn(|| alice_replies.try_recv().ok()).any(|r| r == opponent));
+
+        last_state(&mut bob_replies);
+        hub.handle(&mut bob, Request::List);
+        assert_eq!(
+            bob_replies.try_recv().unwrap(),
+            Reply::OpenGames { games: Vec::new() }
+        );
+
+        // The name is free again once its client has gone
+        hub.disconnect(&mut alice);
+        connect(&mut hub, "alice");
+    }
+
+    #[test]
+    fn spectators() {
+        let mut hub = Hub::default();
+        let (mut x, _x_replies) = connect(&mut hub, "alice");
+        let (mut o, _o_replies) = connect(&mut hub, "bob");
+        let (mut spectator, mut replies) = connect(&mut hub, "carol");
+        hub.handle(&mut x, Request::QuickMatch);
+        hub.handle(&mut o, Request::QuickMatch);
+        hub.handle(&mut x, propose("b2"));
+
+        hub.handle(&mut spectator, Request::Watch { game: 1 });
+        assert_eq!(replies.try_recv().unwrap(), Reply::Watching { game: 1 });
+        let Ok(Reply::State(state)) = replies.try_recv() else {
+            panic!("no state");
+        };
+        assert_eq!(state.events.len(), 1);
+
+        // Spectators cannot play
+        hub.handle(&mut spectator, propose("a1"));
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(Reply::Message(Message::Rejected { .. }))
+        ));
+
+        hub.handle(&mut o, propose("a1"));
+        let state = last_state(&mut replies).unwrap();
+        assert_eq!(state.turn, Some(Player::X));
+
+        hub.leave(&mut spectator);
+        hub.handle(&mut x, propose("c3"));
+        assert_eq!(last_state(&mut replies), None);
+    }
+
+    #[test]
+    fn resume() {
+        let mut hub = Hub::default();
+        let (mut x, mut x_replies) = connect(&mut hub, "alice");
+        let (mut o, _o_replies) = connect(&mut hub, "bob");
+        hub.handle(&mut x, Request::QuickMatch);
+        hub.handle(&mut o, Request::QuickMatch);
+        let Ok(Reply::Joined { token, .. }) = x_replies.try_recv() else {
+            panic!("not joined");
+        };
+        hub.handle(&mut x, propose("b2"));
+
+        // Dropping keeps the game going
+        hub.disconnect(&mut x);
+        hub.handle(&mut o, propose("a1"));
+
+        let (mut x, mut x_replies) = connect(&mut hub, "alice");
+        let resume = Request::Resume {
+            token: token.clone(),
+        };
+        hub.handle(&mut x, resume.clone());
+        assert!(matches!(
+            x_replies.try_recv(),
+            Ok(Reply::Joined {
+                game: 1,
+                player: Player::X,
+                ..
+            })
+        ));
+        let state = last_state(&mut x_replies).unwrap();
+        assert_eq!(state.turn, Some(Player::X));
+        assert_eq!(state.events.len(), 2);
+
+        // Once the game is left, the token is of no use
+        hub.leave(&mut x);
+        let (mut other, mut replies) = connect(&mut hub, "carol");
+        hub.handle(&mut other, resume);
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(Reply::Message(Message::Rejected { .. }))
+        ));
+    }
+
+    #[test]
+    fn clocks() {
+        let mut hub = Hub::default();
+        let (mut x, mut x_replies) = connect(&mut hub, "alice");
+        let (mut o, _o_replies) = connect(&mut hub, "bob");
+        let new = Request::New {
+            opponent: Opponent::Human,
+            difficulty: None,
+            clock: Some(60),
+        };
+        hub.handle(&mut x, new);
+        // Not running before the second player is there
+        assert_eq!(last_state(&mut x_replies).unwrap().clock, None);
+        hub.handle(&mut o, Request::Join { game: 1 });
+        let clock = last_state(&mut x_replies).unwrap().clock.unwrap();
+        assert_eq!(clock.running, Some(Player::X));
+        assert!(clock.x <= 60_000 && clock.o == 60_000);
+
+        // Kept when saved, with the time spent
+        let game = &mut hub.games.get_mut(&1).unwrap();
+        game.session.penalize(Player::X, Duration::from_secs(20));
+        let restored = Game::restore(game.save(), 0).unwrap();
+        let remaining = restored.session.clock().unwrap().remaining(Player::X);
+        assert!(remaining <= Duration::from_secs(40) && remaining > Duration::from_secs(39));
+        assert_eq!(restored.clock, Some(Duration::from_secs(60)));
+
+        // A flag fall is noticed on the next request
+        let new = Request::New {
+            opponent: Opponent::Bot,
+            difficulty: None,
+            clock: Some(0),
+        };
+        hub.handle(&mut x, new);
+        last_state(&mut x_replies);
+        hub.handle(&mut x, propose("b2"));
+        let state = last_state(&mut x_replies).unwrap();
+        assert_eq!(
+            state.events,
+            vec![Event::GameOver {
+                winner: Some(Player::O),
+                termination: "time forfeit".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn chat() {
+        let mut hub = Hub::default();
+        let (mut x, _x_replies) = connect(&mut hub, "alice");
+        let (mut o, mut o_replies) = connect(&mut hub, "bob");
+        let (mut spectator, mut replies) = connect(&mut hub, "carol");
+        hub.handle(&mut x, Request::QuickMatch);
+        hub.handle(&mut o, Request::QuickMatch);
+        hub.handle(&mut spectator, Request::Watch { game: 1 });
+        while o_replies.try_recv().is_ok() {}
+        while replies.try_recv().is_ok() {}
+
+        hub.handle(&mut x, Request::Message(Message::chat("good luck")));
+        let chat = Reply::Chat {
+            from: "alice".to_string(),
+            text: "good luck".to_string(),
+        };
+        assert_eq!(o_replies.try_recv().unwrap(), chat);
+        assert_eq!(replies.try_recv().unwrap(), chat);
+
+        hub.handle(&mut spectator, Request::Message(Message::chat("hi")));
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(Reply::Message(Message::Rejected { .. }))
+        ));
+    }
+
+    #[test]
+    fn draw_offers() {
+        let mut hub = Hub::default();
+        let (mut x, mut x_replies) = connect(&mut hub, "alice");
+        let (mut o, mut o_replies) = connect(&mut hub, "bob");
+        hub.handle(&mut x, Request::QuickMatch);
+        hub.handle(&mut o, Request::QuickMatch);
+        while o_replies.try_recv().is_ok() {}
+
+        // Moving declines the offer
+        hub.handle(&mut x, Request::Message(Message::DrawOffer));
+        assert_eq!(
+            o_replies.try_recv().unwrap(),
+            Reply::Message(Message::DrawOffer)
+        );
+        hub.handle(&mut x, propose("b2"));
+        hub.handle(&mut o, propose("a1"));
+        last_state(&mut x_replies);
+        hub.handle(&mut x, Request::Message(Message::DrawAccepted));
+        assert!(matches!(
+            x_replies.try_recv(),
+            Ok(Reply::Message(Message::Rejected { .. }))
+        ));
+
+        hub.handle(&mut o, Request::Message(Message::DrawOffer));
+        hub.handle(&mut x, Request::Message(Message::DrawAccepted));
+        let state = last_state(&mut x_replies).unwrap();
+        assert_eq!(
+            state.events,
+            vec![Event::GameOver {
+                winner: None,
+                termination: "agreement".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn authentication() {
+        let auth = Auth::parse("# name token\nalice  s3cret\n\nbob t0ken\n").unwrap();
+        assert_eq!(auth.identify("s3cret"), Some("alice"));
+        assert_eq!(auth.identify("alice"), None);
+        assert!(Auth::parse("alice").is_err());
+        assert!(Auth::parse("alice a\nbob a").is_err());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer t0ken".parse().unwrap());
+        assert_eq!(bearer(&headers), Some("t0ken"));
+
+        let mut hub = Hub::default();
+        let (sender, mut replies) = mpsc::unbounded_channel();
+        let mut anonymous = Client::authenticated(sender, None);
+        hub.handle(&mut anonymous, Request::Message(Message::hello("eve")));
+        hub.handle(&mut anonymous, Request::QuickMatch);
+        assert_eq!(
+            replies.try_recv().unwrap(),
+            Reply::Message(Message::hello(NAME))
+        );
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(Reply::Message(Message::Rejected { .. }))
+        ));
+
+        // The name of the token is used rather than the one of the hello
+        let (sender, _) = mpsc::unbounded_channel();
+        let mut alice = Client::authenticated(sender, Some("alice".to_string()));
+        hub.handle(&mut alice, Request::Message(Message::hello("mallory")));
+        hub.handle(&mut alice, Request::QuickMatch);
+        hub.handle(&mut anonymous, Request::List);
+        let Ok(Reply::OpenGames { games }) = replies.try_recv() else {
+            panic!("no games");
+        };
+        assert_eq!(games[0].host, "alice");
+    }
+
+    #[test]
+    fn storage() {
+        let dir = std::env::temp_dir().join(format!("tictactoe-storage-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut hub = Hub::open(Store::open(dir.join("ws")).unwrap(), 0).unwrap();
+        let (mut x, mut x_replies) = connect(&mut hub, "alice");
+        let (mut o, _o_replies) = connect(&mut hub, "bob");
+        hub.handle(&mut x, Request::QuickMatch);
+        hub.handle(&mut o, Request::QuickMatch);
+        let Ok(Reply::Joined { token, .. }) = x_replies.try_recv() else {
+            panic!("not joined");
+        };
+        hub.handle(&mut x, propose("b2"));
+        hub.handle(&mut o, Request::Message(Message::Resign));
+
+        let mut games = Games::open(Store::open(dir.join("api")).unwrap(), 0).unwrap();
+        games.create(NewGame::default());
+        play(&mut games, 1, "a1").unwrap();
+
+        // As if the server had restarted
+        let mut hub = Hub::open(Store::open(dir.join("ws")).unwrap(), 0).unwrap();
+        let (mut x, mut x_replies) = connect(&mut hub, "alice");
+        hub.handle(&mut x, Request::Resume { token });
+        let state = last_state(&mut x_replies).unwrap();
+        assert_eq!(state.events.len(), 2);
+        assert_eq!(
+            state.events[1],
+            Event::GameOver {
+                winner: Some(Player::X),
+                termination: "resignation".to_string()
+            }
+        );
+
+        let games = Games::open(Store::open(dir.join("api")).unwrap(), 0).unwrap();
+        assert_eq!(games.view(1).unwrap().state.events.len(), 2);
+
+        // Games that are over are kept once their players have left
+        hub.leave(&mut x);
+        hub.handle(&mut x, Request::List);
+        assert!(dir.join("ws").join("1.json").exists());
+        assert_eq!(hub.records().len(), 1);
+        assert_eq!(hub.view(1).unwrap().turn, None);
+
+        // Unlike those nobody had joined
+        hub.handle(&mut x, Request::QuickMatch);
+        assert!(dir.join("ws").join("2.json").exists());
+        hub.leave(&mut x);
+        assert!(hub.view(2).is_none());
+        assert!(!dir.join("ws").join("2.json").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn spectated() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let router = router(Settings::default()).unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        // Alice plays the bot from a WebSocket of her own
+        let (mut alice, _) = tokio_tungstenite::connect_async(format!("ws://{address}/ws"))
+            .await
+            .unwrap();
+        for request in [
+            r#"{"type":"hello","version":1,"name":"alice"}"#,
+            r#"{"type":"new","opponent":"bot"}"#,
+        ] {
+            alice
+                .send(tungstenite::Message::text(request))
+                .await
+                .unwrap();
+        }
+        while let Some(Ok(message)) = alice.next().await {
+            if message.to_text().unwrap().contains("\"joined\"") {
+                break;
+            }
+        }
+
+        let mut spectator = Spectator::connect(&address, "bob", 1).await.unwrap();
+        let watched = spectator.next().await.unwrap().unwrap();
+        assert_eq!(watched.grid.number_of_turns, 0);
+        assert_eq!(watched.result, None);
+
+        let play = r#"{"type":"move_proposed","square":"b2"}"#;
+        alice.send(tungstenite::Message::text(play)).await.unwrap();
+        let mut watched = spectator.next().await.unwrap().unwrap();
+        while watched.grid.number_of_turns < 2 {
+            watched = spectator.next().await.unwrap().unwrap();
+        }
+        assert_eq!(watched.grid.matrix()[1][1], Player::X);
+        assert_eq!(watched.grid.player_turn, Player::X);
+
+        alice
+            .send(tungstenite::Message::text(r#"{"type":"resign"}"#))
+            .await
+            .unwrap();
+        let watched = spectator.next().await.unwrap().unwrap();
+        assert_eq!(watched.result, Some(GameResult::Resigned(Player::X)));
+
+        // The reason of a rejection is the error
+        let taken = Spectator::connect(&address, "alice", 1).await;
+        assert!(taken.is_err_and(|e| e.contains("taken")));
+        let missing = Spectator::connect(&address, "carol", 9).await;
+        assert_eq!(missing.err().unwrap(), "there is no game 9");
+    }
+}
@@ -0,0 +1,835 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::code;
+use crate::engine::Bot;
+use crate::profile::data_dir;
+use crate::{coordinates, minimax, Grid, Player};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Move {
+    pub player: Player,
+    pub x: usize,
+    pub y: usize,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.player, coordinates(self.x, self.y))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum GameResult {
+    Win(Player),
+    Draw,
+    // The player has run out of time
+    Timeout(Player),
+    // The player has given up
+    Resigned(Player),
+    // Both players have agreed to a draw
+    DrawAgreed,
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            Self::Win(p) => Some(*p),
+            Self::Draw | Self::DrawAgreed => None,
+            Self::Timeout(p) | Self::Resigned(p) => Some(p.opponent()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PlayError {
+    // The square is already taken by this player
+    Occupied(Player),
+    GameOver,
+}
+
+// A chess clock, counting down the time left to each player
+#[derive(Debug, Clone)]
+pub struct Clock {
+    remaining: [Duration; 2],
+    running: Option<(Player, Instant)>,
+}
+
+impl Clock {
+    pub fn new(per_player: Duration) -> Clock {
+        Clock {
+            remaining: [per_player; 2],
+            running: None,
+        }
+    }
+
+    fn index(player: Player) -> usize {
+        if player == Player::X {
+            0
+        } else {
+            1
+        }
+    }
+
+    // Start counting down the time of `player`, stopping the other clock
+    pub fn start(&mut self, player: Player) {
+        self.stop();
+        self.running = Some((player, Instant::now()));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some((player, since)) = self.running.take() {
+            let i = Clock::index(player);
+            self.remaining[i] = self.remaining[i].saturating_sub(since.elapsed());
+        }
+    }
+
+    pub fn running(&self) -> Option<Player> {
+        self.running.map(|(player, _)| player)
+    }
+
+    pub fn remaining(&self, player: Player) -> Duration {
+        let remaining = self.remaining[Clock::index(player)];
+        match self.running {
+            Some((p, since)) if p == player => remaining.saturating_sub(since.elapsed()),
+            _ => remaining,
+        }
+    }
+
+    // Take `penalty` off the time left to `player`
+    pub fn penalize(&mut self, player: Player, penalty: Duration) {
+        let i = Clock::index(player);
+        self.remaining[i] = self.remaining[i].saturating_sub(penalty);
+    }
+
+    // The player whose time is up, if any
+    pub fn flagged(&self) -> Option<Player> {
+        [Player::X, Player::O]
+            .into_iter()
+            .find(|&p| self.remaining(p).is_zero())
+    }
+}
+
+// Running score over the games of a session
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Scoreboard {
+    pub x_wins: u32,
+    pub o_wins: u32,
+    pub draws: u32,
+}
+
+impl Scoreboard {
+    pub fn record(&mut self, result: GameResult) {
+        match result.winner() {
+            Some(Player::X) => self.x_wins += 1,
+            Some(Player::O) => self.o_wins += 1,
+            _ => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.x_wins + self.o_wins + self.draws
+    }
+}
+
+impl fmt::Display for Scoreboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "X {} - {} O ({} draws)",
+            self.x_wins, self.o_wins, self.draws
+        )
+    }
+}
+
+// A best-of-`length` series, won by whoever wins the most games. It ends early
+// once the leader cannot be caught anymore.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Match {
+    pub length: u32,
+    pub score: Scoreboard,
+}
+
+impl Match {
+    pub fn new(length: u32) -> Match {
+        Match {
+            length,
+            score: Scoreboard::default(),
+        }
+    }
+
+    pub fn record(&mut self, result: GameResult) {
+        self.score.record(result);
+    }
+
+    // The number of the next game, starting at 1
+    pub fn next_game(&self) -> u32 {
+        self.score.games() + 1
+    }
+
+    pub fn is_over(&self) -> bool {
+        let left = self.length.saturating_sub(self.score.games());
+        left == 0 || self.score.x_wins.abs_diff(self.score.o_wins) > left
+    }
+
+    // The winner of the match, `None` while it is going on or if it is tied
+    pub fn winner(&self) -> Option<Player> {
+        if !self.is_over() {
+            return None;
+        }
+
+        match self.score.x_wins.cmp(&self.score.o_wins) {
+            std::cmp::Ordering::Greater => Some(Player::X),
+            std::cmp::Ordering::Less => Some(Player::O),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+// A game in progress, keeping track of every move played so frontends can
+// show the history and replay earlier positions.
+#[derive(Debug, Clone)]
+pub struct GameSession {
+    initial: Grid,
+    grid: Grid,
+    history: Vec<Move>,
+    clock: Option<Clock>,
+    timeout: Option<Player>,
+    resigned: Option<Player>,
+    draw_agreed: bool,
+    // End the game in a draw as soon as nobody can make a line
+    early_draw: bool,
+    // Where the game is saved after every move
+    autosave: Option<PathBuf>,
+    // Why it could not be, until `take_autosave_error`
+    autosave_error: Option<String>,
+}
+
+impl Default for GameSession {
+    fn default() -> Self {
+        GameSession::new()
+    }
+}
+
+impl GameSession {
+    pub fn new() -> GameSession {
+        GameSession::from(Grid::new())
+    }
+
+    pub fn from(grid: Grid) -> GameSession {
+        GameSession {
+            initial: grid.clone(),
+            grid,
+            history: Vec::new(),
+            clock: None,
+            timeout: None,
+            resigned: None,
+            draw_agreed: false,
+            early_draw: false,
+            autosave: None,
+            autosave_error: None,
+        }
+    }
+
+    // Save the game to `path` after every move, and remove the file once the
+    // game is over, so that a game cut short by a crash can be resumed with
+    // `load_autosave`
+    pub fn with_autosave(mut self, path: PathBuf) -> GameSession {
+        self.autosave = Some(path);
+        self
+    }
+
+    // Play with a clock giving `per_player` to each player, starting now
+    pub fn with_clock(mut self, per_player: Duration) -> GameSession {
+        let mut clock = Clock::new(per_player);
+        clock.start(self.grid.player_turn);
+        self.clock = Some(clock);
+        self
+    }
+
+    // Call the game a draw as soon as `Grid::is_dead`, rather than when the
+    // board is full
+    pub fn with_early_draw(mut self) -> GameSession {
+        self.early_draw = true;
+        self
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    // Take time off the clock of `player`, if the game has one
+    pub fn penalize(&mut self, player: Player, penalty: Duration) {
+        if let Some(clock) = &mut self.clock {
+            clock.penalize(player, penalty);
+        }
+        self.check_clock();
+    }
+
+    // Look for a flag fall, returning whether the game has been lost on time
+    pub fn check_clock(&mut self) -> bool {
+        if self.timeout.is_none() && self.result().is_none() {
+            if let Some(clock) = &mut self.clock {
+                self.timeout = clock.flagged();
+                if self.timeout.is_some() {
+                    clock.stop();
+                    self.save();
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(result = ?self.result(), "game over");
+                }
+            }
+        }
+
+        self.timeout.is_some()
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn first_player(&self) -> Player {
+        self.initial.player_turn
+    }
+
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    pub fn resign(&mut self, player: Player) -> Result<(), PlayError> {
+        self.check_clock();
+        if self.is_over() {
+            return Err(PlayError::GameOver);
+        }
+
+        self.resigned = Some(player);
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+        self.save();
+        #[cfg(feature = "tracing")]
+        tracing::info!(result = ?self.result(), "game over");
+
+        Ok(())
+    }
+
+    // End the game with a loss on time of `player`, when the time is kept
+    // elsewhere than on the clock of the session, such as in a game code
+    pub fn time_out(&mut self, player: Player) -> Result<(), PlayError> {
+        self.check_clock();
+        if self.is_over() {
+            return Err(PlayError::GameOver);
+        }
+
+        self.timeout = Some(player);
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+        self.save();
+        #[cfg(feature = "tracing")]
+        tracing::info!(result = ?self.result(), "game over");
+
+        Ok(())
+    }
+
+    // End the game in a draw, once a player has accepted the offer of the other
+    pub fn agree_draw(&mut self) -> Result<(), PlayError> {
+        self.check_clock();
+        if self.is_over() {
+            return Err(PlayError::GameOver);
+        }
+
+        self.draw_agreed = true;
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+        self.save();
+        #[cfg(feature = "tracing")]
+        tracing::info!(result = ?self.result(), "game over");
+
+        Ok(())
+    }
+
+    pub fn play(&mut self, x: usize, y: usize) -> Result<Move, PlayError> {
+        self.check_clock();
+        if self.is_over() {
+            return Err(PlayError::GameOver);
+        }
+
+        let player = self.grid.player_turn;
+        self.grid.set(x, y).map_err(PlayError::Occupied)?;
+
+        let m = Move { player, x, y };
+        self.history.push(m);
+
+        let over = self.is_over();
+        if let Some(clock) = &mut self.clock {
+            if over {
+                clock.stop();
+            } else {
+                clock.start(self.grid.player_turn);
+            }
+        }
+        self.save();
+        #[cfg(feature = "tracing")]
+        {
+            tracing::debug!(%player, square = %coordinates(x, y), "move played");
+            if over {
+                tracing::info!(result = ?self.result(), "game over");
+            }
+        }
+
+        Ok(m)
+    }
+
+    // Take back the last move, even if it ended the game. Games with a clock,
+    // and those resigned, lost on time or drawn by agreement, cannot be taken
+    // back.
+    pub fn undo(&mut self) -> Option<Move> {
+        if self.clock.is_some()
+            || self.timeout.is_some()
+            || self.resigned.is_some()
+            || self.draw_agreed
+        {
+            return None;
+        }
+
+        let m = self.history.pop()?;
+        self.grid = self.position_at(self.history.len());
+        self.save();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(square = %coordinates(m.x, m.y), "move taken back");
+        Some(m)
+    }
+
+    pub fn play_bot(&mut self) -> Option<Move> {
+        let (x, y) = self.grid.best_play()?;
+        self.play(x, y).ok()
+    }
+
+    pub fn play_with(&mut self, bot: &mut Bot) -> Option<Move> {
+        let (x, y) = bot.choose(&self.grid)?;
+        self.play(x, y).ok()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result().is_some()
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        if let Some(player) = self.timeout {
+            return Some(GameResult::Timeout(player));
+        }
+
+        if let Some(player) = self.resigned {
+            return Some(GameResult::Resigned(player));
+        }
+
+        if self.draw_agreed {
+            return Some(GameResult::DrawAgreed);
+        }
+
+        if self.grid.has_winner() {
+            // The winner is the one who has just played
+            return Some(GameResult::Win(self.grid.player_turn.opponent()));
+        }
+
+        if self.grid.is_full() || self.early_draw && self.grid.is_dead() {
+            return Some(GameResult::Draw);
+        }
+
+        None
+    }
+
+    pub fn evaluation(&self) -> i32 {
+        minimax(self.grid.clone())
+    }
+
+    // Why the game could not be saved, once: the game goes on, without
+    // saving it any more
+    pub fn take_autosave_error(&mut self) -> Option<String> {
+        self.autosave_error.take()
+    }
+
+    fn save(&mut self) {
+        let Some(path) = &self.autosave else {
+            return;
+        };
+        if let Err(e) = self.write_autosave(path) {
+            self.autosave_error = Some(format!("{}: {e}", path.display()));
+            self.autosave = None;
+        }
+    }
+
+    // The code of the game, when it was saved and the time left to X and O in
+    // milliseconds if it has a clock, one per line. It is written through a
+    // temporary file, so that a crash cannot leave half of it.
+    fn write_autosave(&self, path: &Path) -> io::Result<()> {
+        if self.is_over() {
+            return match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+
+        let saved = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let mut content = format!("{}\n{saved}\n", code::encode(self));
+        if let Some(clock) = &self.clock {
+            let millis = |player| clock.remaining(player).as_millis();
+            content += &format!("{} {}\n", millis(Player::X), millis(Player::O));
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, content)?;
+        fs::rename(temporary, path)
+    }
+
+    // The position as it was after the first `ply` moves of the history.
+    pub fn position_at(&self, ply: usize) -> Grid {
+        let mut grid = self.initial.clone();
+        for m in self.history.iter().take(ply) {
+            let _ = grid.set(m.x, m.y);
+        }
+
+        grid
+    }
+}
+
+// A game left unfinished, as found in an autosave file
+#[derive(Debug, Clone)]
+pub struct Unfinished {
+    pub session: GameSession,
+    // When the last move was played, in seconds since 1970
+    pub saved: u64,
+}
+
+impl Unfinished {
+    // How long ago the game was left
+    pub fn age(&self) -> Duration {
+        let saved = UNIX_EPOCH + Duration::from_secs(self.saved);
+        SystemTime::now()
+            .duration_since(saved)
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+// The game saved at `path` by `GameSession::with_autosave`, unless there is
+// none or it cannot be read
+pub fn load_autosave(path: &Path) -> Option<Unfinished> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut lines = content.lines();
+    let mut session = code::decode(lines.next()?).ok()?;
+    let saved = lines.next()?.trim().parse().ok()?;
+    if let Some(clock) = lines.next() {
+        let mut millis = clock.split_whitespace().map(str::parse::<u64>);
+        let (Some(Ok(x)), Some(Ok(o))) = (millis.next(), millis.next()) else {
+            return None;
+        };
+        let mut clock = Clock {
+            remaining: [Duration::from_millis(x), Duration::from_millis(o)],
+            running: None,
+        };
+        clock.start(session.grid.player_turn);
+        session.clock = Some(clock);
+    }
+    if session.is_over() {
+        return None;
+    }
+
+    Some(Unfinished { session, saved })
+}
+
+// Where the game being played is saved
+pub fn autosave_file() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("autosave"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_history() {
+        let mut session = GameSession::new();
+        session.play(1, 1).unwrap();
+        session.play(0, 0).unwrap();
+
+        assert_eq!(
+            session.history(),
+            &[
+                Move {
+                    player: Player::X,
+                    x: 1,
+                    y: 1
+                },
+                Move {
+                    player: Player::O,
+                    x: 0,
+                    y: 0
+                },
+            ]
+        );
+        assert_eq!(session.play(1, 1), Err(PlayError::Occupied(Player::X)));
+        assert_eq!(session.history().len(), 2);
+    }
+
+    #[test]
+    fn position_at() {
+        let mut session = GameSession::new();
+        session.play(1, 1).unwrap();
+        session.play(0, 0).unwrap();
+
+        assert_eq!(session.position_at(0).matrix(), Grid::new().matrix());
+        assert_eq!(session.position_at(1).matrix()[1][1], Player::X);
+        assert_eq!(session.position_at(1).matrix()[0][0], Player::Empty);
+        assert_eq!(session.position_at(2).matrix(), session.grid().matrix());
+    }
+
+    #[test]
+    fn result() {
+        let mut session = GameSession::new();
+        for (x, y) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            session.play(x, y).unwrap();
+            assert_eq!(session.result(), None);
+        }
+        session.play(2, 0).unwrap();
+
+        assert_eq!(session.result(), Some(GameResult::Win(Player::X)));
+    }
+
+    #[test]
+    fn early_draw() {
+        let moves = [
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (1, 1),
+            (2, 2),
+            (1, 2),
+            (0, 2),
+        ];
+        let mut early = GameSession::new().with_early_draw();
+        let mut full = GameSession::new();
+        for (x, y) in moves {
+            early.play(x, y).unwrap();
+            full.play(x, y).unwrap();
+        }
+
+        // Every line through b3, the last square, holds both X and O
+        assert_eq!(early.result(), Some(GameResult::Draw));
+        assert_eq!(early.play(2, 1), Err(PlayError::GameOver));
+        assert_eq!(full.result(), None);
+    }
+
+    #[test]
+    fn clock() {
+        let mut clock = Clock::new(Duration::from_secs(30));
+        assert_eq!(clock.remaining(Player::X), Duration::from_secs(30));
+
+        clock.start(Player::X);
+        clock.start(Player::O);
+        assert!(clock.remaining(Player::X) < Duration::from_secs(30));
+        assert_eq!(clock.running(), Some(Player::O));
+        assert_eq!(clock.flagged(), None);
+    }
+
+    #[test]
+    fn flag_fall() {
+        let mut session = GameSession::new().with_clock(Duration::ZERO);
+
+        assert!(session.check_clock());
+        assert_eq!(session.result(), Some(GameResult::Timeout(Player::X)));
+        assert_eq!(session.play(1, 1), Err(PlayError::GameOver));
+        assert_eq!(session.result().and_then(|r| r.winner()), Some(Player::O));
+    }
+
+    #[test]
+    fn penalty() {
+        let mut session = GameSession::new().with_clock(Duration::from_secs(30));
+        session.penalize(Player::O, Duration::from_secs(10));
+        let clock = session.clock().unwrap();
+        assert!(clock.remaining(Player::O) == Duration::from_secs(20));
+
+        session.penalize(Player::X, Duration::from_secs(60));
+        assert_eq!(session.result(), Some(GameResult::Timeout(Player::X)));
+    }
+
+    #[test]
+    fn autosave() {
+        let path = std::env::temp_dir().join(format!("tictactoe-autosave-{}", std::process::id()));
+        let mut session = GameSession::new().with_autosave(path.clone());
+        session.play(1, 1).unwrap();
+        session.play(0, 0).unwrap();
+
+        let unfinished = load_autosave(&path).unwrap();
+        assert_eq!(unfinished.session.history(), session.history());
+        assert!(unfinished.age() < Duration::from_secs(60));
+
+        // Nothing is left to resume once the game is over
+        session.resign(Player::X).unwrap();
+        assert!(!path.exists());
+        assert!(load_autosave(&path).is_none());
+        assert_eq!(session.take_autosave_error(), None);
+
+        // The time left is saved with the game
+        let mut session = GameSession::new()
+            .with_clock(Duration::from_secs(30))
+            .with_autosave(path.clone());
+        session.penalize(Player::X, Duration::from_secs(10));
+        session.play(1, 1).unwrap();
+        let resumed = load_autosave(&path).unwrap().session;
+        let clock = resumed.clock().unwrap();
+        assert!(clock.remaining(Player::X) <= Duration::from_secs(20));
+        assert!(clock.remaining(Player::X) > Duration::from_secs(19));
+        assert_eq!(clock.running(), Some(Player::O));
+        let _ = fs::remove_file(&path);
+
+        // A file that cannot be written is reported once
+        fs::write(&path, "").unwrap();
+        let mut session = GameSession::new().with_autosave(path.join("autosave"));
+        session.play(1, 1).unwrap();
+        assert!(session.take_autosave_error().is_some());
+        session.play(0, 0).unwrap();
+        assert_eq!(session.take_autosave_error(), None);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn traced() {
+        use std::io::{self, Write};
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let mut session = GameSession::new();
+            session.play(1, 1).unwrap();
+            session.play_with(&mut Bot::new(crate::engine::Difficulty::Hard, 0));
+            session.resign(Player::X).unwrap();
+        });
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        assert!(
+            output.contains("move played player=X square=b2"),
+            "{output}"
+        );
+        assert!(output.contains("search{depth=8}"), "{output}");
+        assert!(
+            output.contains("game over result=Some(Resigned(X))"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn resign() {
+        let mut session = GameSession::new();
+        session.play(1, 1).unwrap();
+        session.resign(Player::O).unwrap();
+
+        assert_eq!(session.result(), Some(GameResult::Resigned(Player::O)));
+        assert_eq!(session.result().unwrap().winner(), Some(Player::X));
+        assert_eq!(session.play(0, 0), Err(PlayError::GameOver));
+        assert_eq!(session.resign(Player::X), Err(PlayError::GameOver));
+    }
+
+    #[test]
+    fn agree_draw() {
+        let mut session = GameSession::new();
+        session.play(1, 1).unwrap();
+        session.agree_draw().unwrap();
+
+        assert_eq!(session.result(), Some(GameResult::DrawAgreed));
+        assert_eq!(session.result().unwrap().winner(), None);
+        assert_eq!(session.agree_draw(), Err(PlayError::GameOver));
+    }
+
+    #[test]
+    fn undo() {
+        let mut session = GameSession::new();
+        assert_eq!(session.undo(), None);
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            session.play(x, y).unwrap();
+        }
+        assert_eq!(session.result(), Some(GameResult::Win(Player::X)));
+
+        let m = session.undo().unwrap();
+        assert_eq!((m.player, m.x, m.y), (Player::X, 0, 2));
+        assert_eq!(session.result(), None);
+        assert_eq!(session.grid().player_turn, Player::X);
+        assert_eq!(session.history().len(), 4);
+
+        session.resign(Player::X).unwrap();
+        assert_eq!(session.undo(), None);
+    }
+
+    #[test]
+    fn scoreboard() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.record(GameResult::Win(Player::O));
+        scoreboard.record(GameResult::Draw);
+        scoreboard.record(GameResult::Win(Player::O));
+
+        assert_eq!(scoreboard.games(), 3);
+        assert_eq!(scoreboard.to_string(), "X 0 - 2 O (1 draws)");
+    }
+
+    #[test]
+    fn best_of_five() {
+        let mut m = Match::new(5);
+        m.record(GameResult::Win(Player::X));
+        m.record(GameResult::Draw);
+        m.record(GameResult::Win(Player::X));
+        assert_eq!(m.next_game(), 4);
+        assert!(!m.is_over());
+        assert_eq!(m.winner(), None);
+
+        // 3-0 with one game left
+        m.record(GameResult::Win(Player::X));
+        assert!(m.is_over());
+        assert_eq!(m.winner(), Some(Player::X));
+    }
+
+    #[test]
+    fn tied_match() {
+        let mut m = Match::new(3);
+        m.record(GameResult::Win(Player::O));
+        m.record(GameResult::Resigned(Player::O));
+        m.record(GameResult::Draw);
+
+        assert!(m.is_over());
+        assert_eq!(m.winner(), None);
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::session::{GameSession, Move, PlayError};
+
+// A game that several threads or tasks play and watch at once, such as the
+// tasks of a server or the engine thread of a GUI. Clones are handles to the
+// same game. Every update gets a new version number, so that those watching
+// can wait for the next one rather than poll:
+//
+//     let (session, version) = game.snapshot();
+//     draw(&session);
+//     game.wait_for_change(version, Duration::from_secs(1));
+//
+// A panic while changing the game does not lock the others out: they see the
+// game as the panic left it.
+#[derive(Debug, Clone, Default)]
+pub struct SharedGame {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    session: RwLock<GameSession>,
+    // Always taken after `session`, never before
+    version: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl SharedGame {
+    pub fn new(session: GameSession) -> SharedGame {
+        SharedGame {
+            inner: Arc::new(Inner {
+                session: RwLock::new(session),
+                ..Inner::default()
+            }),
+        }
+    }
+
+    // Look at the game without changing it; others may look at the same time
+    pub fn read<T>(&self, f: impl FnOnce(&GameSession) -> T) -> T {
+        let session = self
+            .inner
+            .session
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        f(&session)
+    }
+
+    // Change the game, which nobody else sees until `f` returns, and wake up
+    // those waiting for a change
+    pub fn update<T>(&self, f: impl FnOnce(&mut GameSession) -> T) -> T {
+        let mut session = self
+            .inner
+            .session
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let value = f(&mut session);
+
+        *self.version_lock() += 1;
+        self.inner.changed.notify_all();
+        value
+    }
+
+    // A copy of the game with its version, taken at once
+    pub fn snapshot(&self) -> (GameSession, u64) {
+        let session = self
+            .inner
+            .session
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        (session.clone(), *self.version_lock())
+    }
+
+    pub fn version(&self) -> u64 {
+        *self.version_lock()
+    }
+
+    pub fn play(&self, x: usize, y: usize) -> Result<Move, PlayError> {
+        self.update(|session| session.play(x, y))
+    }
+
+    // Wait until the version is past `version`, giving the new one, or `None`
+    // if it has not changed within `timeout`
+    pub fn wait_for_change(&self, version: u64, timeout: Duration) -> Option<u64> {
+        let deadline = Instant::now() + timeout;
+        let mut current = self.version_lock();
+        while *current <= version {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return None;
+            }
+            current = self
+                .inner
+                .changed
+                .wait_timeout(current, left)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+
+        Some(*current)
+    }
+
+    fn version_lock(&self) -> MutexGuard<'_, u64> {
+        self.inner
+            .version
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::Player;
+
+    #[test]
+    fn shared_between_threads() {
+        let game = SharedGame::default();
+        let (_, version) = game.snapshot();
+
+        // Each thread takes a square of its own
+        let players: Vec<_> = (0..4)
+            .map(|x| {
+                let game = game.clone();
+                thread::spawn(move || game.play(x % 3, x / 3))
+            })
+            .collect();
+        for player in players {
+            assert!(player.join().unwrap().is_ok());
+        }
+
+        assert_eq!(game.read(|session| session.history().len()), 4);
+        assert_eq!(game.wait_for_change(version, Duration::ZERO), Some(4));
+        assert_eq!(game.wait_for_change(4, Duration::from_millis(10)), None);
+    }
+
+    #[test]
+    fn wakes_up_watchers() {
+        let game = SharedGame::new(GameSession::new());
+        let watcher = {
+            let game = game.clone();
+            thread::spawn(move || game.wait_for_change(0, Duration::from_secs(60)))
+        };
+        game.update(|session| session.resign(Player::X)).unwrap();
+
+        assert_eq!(watcher.join().unwrap(), Some(1));
+        assert!(game.snapshot().0.is_over());
+        assert_eq!(game.play(0, 0), Err(PlayError::GameOver));
+    }
+}
@@ -0,0 +1,377 @@
+use std::ops::Add;
+
+use crate::analysis::{Annotations, Verdict};
+use crate::engine::Difficulty;
+use crate::record::GameRecord;
+use crate::session::{GameResult, Scoreboard};
+use crate::{coordinates, Player};
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Tally {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl Tally {
+    // Count a game where the player played `side`
+    pub fn record(&mut self, result: GameResult, side: Player) {
+        match result.winner() {
+            Some(p) if p == side => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    // A point for a win and half a point for a draw, as in chess
+    pub fn score(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+
+    // Percentage of the games won
+    pub fn win_rate(&self) -> f64 {
+        match self.games() {
+            0 => 0.0,
+            games => 100.0 * self.wins as f64 / games as f64,
+        }
+    }
+}
+
+impl Add for Tally {
+    type Output = Tally;
+
+    fn add(self, other: Tally) -> Tally {
+        Tally {
+            wins: self.wins + other.wins,
+            losses: self.losses + other.losses,
+            draws: self.draws + other.draws,
+        }
+    }
+}
+
+// A summary of the logged games of one player
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Stats {
+    pub overall: Tally,
+    // Against the bot, from the easiest difficulty to the hardest
+    pub by_difficulty: Vec<(Difficulty, Tally)>,
+    pub as_x: Tally,
+    pub as_o: Tally,
+    // By the first move of the game, the most played first
+    pub by_opening: Vec<(String, Tally)>,
+    moves: usize,
+    // The games annotated by `tictactoe analyze --archive`, and the flaws
+    // found in the moves of the player
+    pub analyzed: u32,
+    pub inaccuracies: u32,
+    pub mistakes: u32,
+    analyzed_moves: usize,
+}
+
+impl Stats {
+    // The stats of `player`, ignoring the games they did not play
+    pub fn of(player: &str, records: &[GameRecord]) -> Stats {
+        let mut stats = Stats::default();
+
+        for record in records {
+            let side = if record.x == player {
+                Player::X
+            } else if record.o == player {
+                Player::O
+            } else {
+                continue;
+            };
+
+            stats.overall.record(record.result, side);
+            match side {
+                Player::X => stats.as_x.record(record.result, side),
+                _ => stats.as_o.record(record.result, side),
+            }
+            if let Some(difficulty) = record.tag("Difficulty").and_then(|d| d.parse().ok()) {
+                stats
+                    .tally_difficulty(difficulty)
+                    .record(record.result, side);
+            }
+            stats.moves += record.moves.len();
+            tally_by(&mut stats.by_opening, opening(record, 1)).record(record.result, side);
+            if let Some(annotations) = Annotations::read(record) {
+                stats.analyzed += 1;
+                stats.inaccuracies += annotations.count(side, Verdict::Inaccuracy) as u32;
+                stats.mistakes += annotations.count(side, Verdict::Mistake) as u32;
+                stats.analyzed_moves += annotations.moves(side);
+            }
+        }
+        stats
+            .by_opening
+            .sort_by_key(|(_, tally)| std::cmp::Reverse(tally.games()));
+
+        stats
+    }
+
+    fn tally_difficulty(&mut self, difficulty: Difficulty) -> &mut Tally {
+        let i = match self
+            .by_difficulty
+            .iter()
+            .position(|&(d, _)| d == difficulty)
+        {
+            Some(i) => i,
+            None => {
+                self.by_difficulty.push((difficulty, Tally::default()));
+                self.by_difficulty.sort_by_key(|&(d, _)| d);
+                self.by_difficulty
+                    .iter()
+                    .position(|&(d, _)| d == difficulty)
+                    .unwrap()
+            }
+        };
+
+        &mut self.by_difficulty[i].1
+    }
+
+    // The share of the moves of the analyzed games that were among the best,
+    // in percent
+    pub fn accuracy(&self) -> Option<f64> {
+        let flaws = (self.inaccuracies + self.mistakes) as usize;
+        let moves = self.analyzed_moves;
+        (moves > 0).then(|| 100.0 * moves.saturating_sub(flaws) as f64 / moves as f64)
+    }
+
+    // Average number of moves in a game
+    pub fn average_length(&self) -> f64 {
+        match self.overall.games() {
+            0 => 0.0,
+            games => self.moves as f64 / games as f64,
+        }
+    }
+}
+
+// A summary of any set of games, such as those of a tournament
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Summary {
+    pub results: Scoreboard,
+    // By their first two moves, the most played first
+    pub by_opening: Vec<(String, Scoreboard)>,
+    moves: usize,
+}
+
+impl Summary {
+    pub fn of(records: &[GameRecord]) -> Summary {
+        let mut summary = Summary::default();
+        for record in records {
+            summary.results.record(record.result);
+            summary.moves += record.moves.len();
+            tally_by(&mut summary.by_opening, opening(record, 2)).record(record.result);
+        }
+        summary
+            .by_opening
+            .sort_by_key(|(_, score)| std::cmp::Reverse(score.games()));
+
+        summary
+    }
+
+    // Average number of moves in a game
+    pub fn average_length(&self) -> f64 {
+        match self.results.games() {
+            0 => 0.0,
+            games => self.moves as f64 / games as f64,
+        }
+    }
+}
+
+// The first `plies` moves of a game, such as `b2 a1`
+pub fn opening(record: &GameRecord, plies: usize) -> String {
+    let moves: Vec<String> = record
+        .moves
+        .iter()
+        .take(plies)
+        .map(|&(x, y)| coordinates(x, y))
+        .collect();
+    moves.join(" ")
+}
+
+// The entry for `key`, added at the end if it is not there yet
+fn tally_by<T: Default>(tallies: &mut Vec<(String, T)>, key: String) -> &mut T {
+    let i = match tallies.iter().position(|(k, _)| *k == key) {
+        Some(i) => i,
+        None => {
+            tallies.push((key, T::default()));
+            tallies.len() - 1
+        }
+    };
+
+    &mut tallies[i].1
+}
+
+// The results of each player against each other, such as the entrants of a
+// tournament
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Crosstable {
+    pub names: Vec<String>,
+    // `tallies[i][j]` is how the `i`th player did against the `j`th
+    pub tallies: Vec<Vec<Tally>>,
+}
+
+impl Crosstable {
+    // The games of other players are ignored
+    pub fn new(names: &[String], records: &[GameRecord]) -> Crosstable {
+        let mut tallies = vec![vec![Tally::default(); names.len()]; names.len()];
+        let index = |name: &str| names.iter().position(|n| n == name);
+
+        for record in records {
+            let (Some(x), Some(o)) = (index(&record.x), index(&record.o)) else {
+                continue;
+            };
+            tallies[x][o].record(record.result, Player::X);
+            tallies[o][x].record(record.result, Player::O);
+        }
+
+        Crosstable {
+            names: names.to_vec(),
+            tallies,
+        }
+    }
+
+    pub fn total(&self, i: usize) -> Tally {
+        self.tallies[i]
+            .iter()
+            .fold(Tally::default(), |total, &t| total + t)
+    }
+
+    // The players from the best score to the worst
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.names.len()).collect();
+        ranking.sort_by(|&a, &b| self.total(b).score().total_cmp(&self.total(a).score()));
+        ranking
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::test_game;
+
+    fn record(x: &str, o: &str, difficulty: &str, moves: usize, result: GameResult) -> GameRecord {
+        let played = [
+            (1, 1),
+            (0, 0),
+            (2, 2),
+            (2, 0),
+            (0, 2),
+            (1, 0),
+            (1, 2),
+            (0, 1),
+            (2, 1),
+        ];
+        let mut record = test_game(x, o, &played[..moves], result);
+        record.set_tag("Difficulty", difficulty);
+        record
+    }
+
+    #[test]
+    fn stats() {
+        let records = [
+            record("alice", "Bot", "hard", 9, GameResult::Draw),
+            record("alice", "Bot", "easy", 5, GameResult::Win(Player::X)),
+            record("Bot", "alice", "hard", 6, GameResult::Win(Player::X)),
+            record("bob", "Bot", "easy", 7, GameResult::Win(Player::O)),
+            record("alice", "Bot", "easy", 2, GameResult::Resigned(Player::X)),
+        ];
+        let stats = Stats::of("alice", &records);
+
+        let tally = |wins, losses, draws| Tally {
+            wins,
+            losses,
+            draws,
+        };
+        assert_eq!(stats.overall, tally(1, 2, 1));
+        assert_eq!(stats.as_x, tally(1, 1, 1));
+        assert_eq!(stats.as_o, tally(0, 1, 0));
+        assert_eq!(
+            stats.by_difficulty,
+            vec![
+                (Difficulty::Easy, tally(1, 1, 0)),
+                (Difficulty::Hard, tally(0, 1, 1)),
+            ]
+        );
+        assert_eq!(stats.average_length(), 5.5);
+        assert_eq!(stats.overall.win_rate(), 25.0);
+        assert_eq!(stats.by_opening, vec![("b2".to_string(), tally(1, 2, 1))]);
+        assert_eq!(stats.accuracy(), None);
+
+        // Only the moves of alice count, in the games that were analyzed
+        let mut records = records;
+        let annotations = Annotations::of(&records[2]);
+        annotations.write(&mut records[2]);
+        let stats = Stats::of("alice", &records);
+        assert_eq!(stats.analyzed, 1);
+        assert_eq!(stats.accuracy(), annotations.accuracy(Player::O));
+    }
+
+    #[test]
+    fn summary() {
+        let mut records = vec![
+            record("alice", "Bot", "hard", 9, GameResult::Draw),
+            record("Bot", "alice", "hard", 6, GameResult::Win(Player::X)),
+            record("bob", "Bot", "easy", 1, GameResult::Resigned(Player::O)),
+        ];
+        records[1].moves[1] = (2, 2);
+        let summary = Summary::of(&records);
+
+        assert_eq!(summary.results.games(), 3);
+        assert_eq!(summary.results.x_wins, 2);
+        assert_eq!(summary.average_length(), 16.0 / 3.0);
+        let openings: Vec<&str> = summary.by_opening.iter().map(|(o, _)| o.as_str()).collect();
+        assert_eq!(openings, ["b2 a1", "b2 c3", "b2"]);
+    }
+
+    #[test]
+    fn crosstable() {
+        let records = [
+            record("alice", "Bot", "hard", 9, GameResult::Draw),
+            record("Bot", "alice", "hard", 6, GameResult::Win(Player::X)),
+            record("bob", "Bot", "easy", 7, GameResult::Win(Player::O)),
+            record("carol", "dave", "easy", 7, GameResult::Win(Player::O)),
+        ];
+        let names = ["alice", "bob", "Bot"].map(String::from);
+        let table = Crosstable::new(&names, &records);
+
+        assert_eq!(
+            table.tallies[2][0],
+            Tally {
+                wins: 1,
+                losses: 0,
+                draws: 1
+            }
+        );
+        assert_eq!(
+            table.tallies[0][2],
+            Tally {
+                wins: 0,
+                losses: 1,
+                draws: 1
+            }
+        );
+        assert_eq!(
+            table.total(2),
+            Tally {
+                wins: 2,
+                losses: 0,
+                draws: 1
+            }
+        );
+        assert_eq!(table.total(2).score(), 2.5);
+        assert_eq!(table.ranking(), [2, 0, 1]);
+    }
+
+    #[test]
+    fn no_games() {
+        let stats = Stats::of("alice", &[]);
+        assert_eq!(stats.overall.games(), 0);
+        assert_eq!(stats.average_length(), 0.0);
+        assert_eq!(stats.overall.win_rate(), 0.0);
+    }
+}
@@ -0,0 +1,100 @@
+// Strategies for property tests with proptest, generating only positions that
+// can come up in a game rather than any nine squares:
+//
+//     proptest! {
+//         #[test]
+//         fn best_play_is_legal(grid in strategy::mid_game()) {
+//             prop_assert!(grid.best_play().is_some());
+//         }
+//     }
+//
+// They shrink towards the empty board and the first squares, row by row.
+use proptest::prelude::*;
+
+use crate::{Grid, Player};
+
+// A legal sequence of moves from the empty board, whoever starts: distinct
+// squares, cut short where a player gets three in a row
+pub fn moves() -> impl Strategy<Value = Vec<(usize, usize)>> {
+    let squares: Vec<usize> = (0..9).collect();
+    (Just(squares).prop_shuffle(), 0..=9usize).prop_map(|(squares, length)| {
+        let mut grid = Grid::new();
+        let mut moves = Vec::new();
+        for &i in &squares[..length] {
+            if grid.has_winner() {
+                break;
+            }
+            let _ = grid.set(i % 3, i / 3);
+            moves.push((i % 3, i / 3));
+        }
+        moves
+    })
+}
+
+// Any position of a game where X or O played first, finished ones included
+pub fn grids() -> impl Strategy<Value = Grid> {
+    (prop_oneof![Just(Player::X), Just(Player::O)], moves()).prop_map(|(first, moves)| {
+        let mut grid = Grid::starting_with(first);
+        for (x, y) in moves {
+            let _ = grid.set(x, y);
+        }
+        grid
+    })
+}
+
+// A position where the game goes on
+pub fn mid_game() -> impl Strategy<Value = Grid> {
+    grids().prop_filter("the game is over", |g| !g.has_winner() && !g.is_full())
+}
+
+impl Arbitrary for Grid {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Grid>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        grids().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::{parse_position, position, PositionError};
+
+    proptest! {
+        // The best move is searched from the empty board in some cases
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn moves_are_legal(moves in moves()) {
+            let mut grid = Grid::new();
+            for &(x, y) in &moves {
+                prop_assert!(!grid.has_winner());
+                prop_assert!(grid.set(x, y).is_ok());
+            }
+        }
+
+        #[test]
+        fn grids_are_reachable(grid: Grid) {
+            let (x, o) = (grid.count(Player::X), grid.count(Player::O));
+            prop_assert!(x.abs_diff(o) <= 1);
+            prop_assert_eq!(grid.number_of_turns as usize, x + o);
+            prop_assert_eq!(grid.to_features().iter().sum::<f32>(), 9.0);
+            // Read back with X to move when both have played as many moves,
+            // which a line of X made after O started contradicts
+            match parse_position(&position(&grid)) {
+                Ok(parsed) => prop_assert_eq!(*parsed.matrix(), *grid.matrix()),
+                Err(e) => {
+                    prop_assert_eq!(e, PositionError::Unreachable);
+                    prop_assert!(x == o && grid.has_winner());
+                }
+            }
+        }
+
+        #[test]
+        fn best_play_is_legal(grid in mid_game()) {
+            let (x, y) = grid.best_play().unwrap();
+            prop_assert_eq!(grid.matrix()[y][x], Player::Empty);
+        }
+    }
+}
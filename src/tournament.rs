@@ -0,0 +1,586 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::engine::{Bot, Difficulty};
+use crate::profile::is_valid_name;
+use crate::record::GameRecord;
+use crate::rng::{RandomSource, Rng};
+use crate::session::{GameResult, GameSession, Match};
+use crate::stats::Crosstable;
+use crate::Player;
+
+// An engine taking part in a tournament, named after its difficulty, or a
+// human playing at the keyboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entrant {
+    pub name: String,
+    // `None` for a human
+    pub difficulty: Option<Difficulty>,
+}
+
+impl Entrant {
+    pub fn new(difficulty: Difficulty) -> Entrant {
+        Entrant {
+            name: difficulty.to_string(),
+            difficulty: Some(difficulty),
+        }
+    }
+
+    pub fn human(name: &str) -> Entrant {
+        Entrant {
+            name: name.to_string(),
+            difficulty: None,
+        }
+    }
+
+    // The bot playing for this entrant, `None` for a human
+    pub fn bot(&self, seed: u64) -> Option<Bot> {
+        self.difficulty.map(|d| Bot::new(d, seed))
+    }
+}
+
+// What is wrong with a list of entrants
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum EntrantError {
+    // Neither a difficulty nor a profile name
+    Invalid(String),
+    Twice(String),
+    TooFew,
+}
+
+impl fmt::Display for EntrantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(name) => write!(f, "Invalid entrant: {name:?}"),
+            Self::Twice(name) => write!(f, "{name} is entered twice"),
+            Self::TooFew => write!(f, "A tournament needs at least two entrants"),
+        }
+    }
+}
+
+// A difficulty for an engine, any other name for a human
+impl FromStr for Entrant {
+    type Err = EntrantError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(difficulty) => Ok(Entrant::new(difficulty)),
+            Err(_) if is_valid_name(s) && !s.contains(',') => Ok(Entrant::human(s)),
+            Err(_) => Err(EntrantError::Invalid(s.to_string())),
+        }
+    }
+}
+
+// A list of entrants such as `easy,hard` or `alice,bob,hard`, each at most once
+pub fn parse_entrants(list: &str) -> Result<Vec<Entrant>, EntrantError> {
+    let mut entrants: Vec<Entrant> = Vec::new();
+    for name in list.split(',') {
+        let entrant: Entrant = name.trim().parse()?;
+        if entrants.iter().any(|e| e.name == entrant.name) {
+            return Err(EntrantError::Twice(entrant.name));
+        }
+        entrants.push(entrant);
+    }
+    if entrants.len() < 2 {
+        return Err(EntrantError::TooFew);
+    }
+
+    Ok(entrants)
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    // Everyone against everyone
+    #[default]
+    RoundRobin,
+    // A number of rounds where entrants with similar scores meet
+    Swiss(u32),
+    // Knockout rounds of best-of-N matches
+    Knockout(u32),
+}
+
+// A game between two bots, X starting
+pub fn play_game(x: &mut Bot, o: &mut Bot) -> GameSession {
+    let mut session = GameSession::new();
+    while !session.is_over() {
+        let bot = match session.grid().player_turn {
+            Player::X => &mut *x,
+            _ => &mut *o,
+        };
+        if session.play_with(bot).is_none() {
+            break;
+        }
+    }
+
+    session
+}
+
+// The games of a tournament are played by a function given the entrant playing
+// X, the one playing O and a seed for the bots. It returns the finished game,
+// or `None` to abandon the tournament, which then stops with the games played
+// so far. This one only knows how to play the engines.
+pub fn bot_game(x: &Entrant, o: &Entrant, seed: u64) -> Option<GameSession> {
+    Some(play_game(&mut x.bot(seed)?, &mut o.bot(seed ^ 1)?))
+}
+
+// Games between two entrants until `done` says that their pairing is over.
+// They go by two with the same seed, each side playing X in one of them, so
+// that neither side is favoured by the colors or by luck.
+fn play_pairing(
+    (a, b): (&Entrant, &Entrant),
+    event: &str,
+    rng: &mut impl RandomSource,
+    play: &mut impl FnMut(&Entrant, &Entrant, u64) -> Option<GameSession>,
+    mut done: impl FnMut(&[GameRecord]) -> bool,
+) -> Option<Vec<GameRecord>> {
+    let mut records = Vec::new();
+    let mut game_seed = 0;
+    for game in 0.. {
+        if done(&records) {
+            break;
+        }
+        if game % 2 == 0 {
+            game_seed = rng.next_u64();
+        }
+        let (x, o) = if game % 2 == 0 { (a, b) } else { (b, a) };
+        let session = play(x, o, game_seed)?;
+        let mut record = GameRecord::from_session(&session, &x.name, &o.name)?;
+        record.set_tag("Event", event);
+        record.set_tag("Seed", &game_seed.to_string());
+        records.push(record);
+    }
+
+    Some(records)
+}
+
+// Every entrant plays `games` games against every other
+pub fn round_robin(
+    entrants: &[Entrant],
+    games: u32,
+    seed: u64,
+    mut play: impl FnMut(&Entrant, &Entrant, u64) -> Option<GameSession>,
+) -> Vec<GameRecord> {
+    let mut rng = Rng::new(seed);
+    let mut records = Vec::new();
+
+    for (i, a) in entrants.iter().enumerate() {
+        for b in &entrants[i + 1..] {
+            let done = |played: &[GameRecord]| played.len() == games as usize;
+            match play_pairing((a, b), "round robin", &mut rng, &mut play, done) {
+                Some(played) => records.extend(played),
+                None => return records,
+            }
+        }
+    }
+
+    records
+}
+
+// A Swiss tournament of `rounds` rounds, with `games` games per pairing
+pub fn swiss(
+    entrants: &[Entrant],
+    rounds: u32,
+    games: u32,
+    seed: u64,
+    mut play: impl FnMut(&Entrant, &Entrant, u64) -> Option<GameSession>,
+) -> Swiss {
+    let mut rng = Rng::new(seed);
+    let names: Vec<String> = entrants.iter().map(|e| e.name.clone()).collect();
+    let mut swiss = Swiss::new(&names, games);
+
+    for round in 1..=rounds {
+        let event = format!("swiss, round {round}");
+        for pairing in swiss.pair() {
+            match pairing {
+                Pairing::Game(a, b) => {
+                    let pair = (&entrants[a], &entrants[b]);
+                    let done = |played: &[GameRecord]| played.len() == games as usize;
+                    match play_pairing(pair, &event, &mut rng, &mut play, done) {
+                        Some(played) => swiss.records.extend(played),
+                        None => return swiss,
+                    }
+                }
+                Pairing::Bye(i) => swiss.byes.push(i),
+            }
+        }
+    }
+
+    swiss
+}
+
+// A knockout tournament between the entrants, given from the first seed to the
+// last, with best-of-`best_of` matches. `None` if it was abandoned.
+pub fn knockout(
+    entrants: &[Entrant],
+    best_of: u32,
+    seed: u64,
+    mut play: impl FnMut(&Entrant, &Entrant, u64) -> Option<GameSession>,
+) -> Option<Bracket> {
+    let mut rng = Rng::new(seed);
+    let names: Vec<String> = entrants.iter().map(|e| e.name.clone()).collect();
+    let mut bracket = Bracket::new(&names, best_of);
+
+    while bracket.champion().is_none() {
+        let event = format!("knockout, round {}", bracket.rounds.len() + 1);
+        let mut heats = bracket.next_round();
+        for heat in heats.iter_mut() {
+            let Some(b) = heat.b else {
+                continue;
+            };
+            let (a, b) = (&entrants[heat.a], &entrants[b]);
+            let done = |played: &[GameRecord]| {
+                heat.score = Match::new(best_of);
+                for record in played {
+                    heat.score.record(result_for(record, &a.name));
+                }
+                heat.score.is_over()
+            };
+            bracket
+                .records
+                .extend(play_pairing((a, b), &event, &mut rng, &mut play, done)?);
+        }
+        bracket.rounds.push(heats);
+    }
+
+    Some(bracket)
+}
+
+// The result of a game seen from `name`, as if they had played X
+fn result_for(record: &GameRecord, name: &str) -> GameResult {
+    let side = if record.x == name {
+        Player::X
+    } else {
+        Player::O
+    };
+    match record.result.winner() {
+        Some(p) if p == side => GameResult::Win(Player::X),
+        Some(_) => GameResult::Win(Player::O),
+        None => GameResult::Draw,
+    }
+}
+
+// A match of a knockout round, between `a`, the higher seed, and `b`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Heat {
+    pub a: usize,
+    // `None` for a bye
+    pub b: Option<usize>,
+    // X for `a` and O for `b`, whatever they played in each game
+    pub score: Match,
+}
+
+impl Heat {
+    // Whoever won the most games, the higher seed if the match is tied
+    pub fn winner(&self) -> usize {
+        match (self.b, self.score.winner()) {
+            (Some(b), Some(Player::O)) => b,
+            _ => self.a,
+        }
+    }
+}
+
+// The rounds of a knockout tournament. The first round pairs the best seeds
+// with the worst, so that they can only meet in the last rounds, and gives byes
+// to the best seeds when the entrants are not a power of two.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bracket {
+    // From the first seed to the last
+    pub names: Vec<String>,
+    pub best_of: u32,
+    pub rounds: Vec<Vec<Heat>>,
+    pub records: Vec<GameRecord>,
+}
+
+impl Bracket {
+    pub fn new(names: &[String], best_of: u32) -> Bracket {
+        Bracket {
+            names: names.to_vec(),
+            best_of,
+            rounds: Vec::new(),
+            records: Vec::new(),
+        }
+    }
+
+    // The heats of the next round, yet to be played
+    pub fn next_round(&self) -> Vec<Heat> {
+        let heat = |a: usize, b: Option<usize>| Heat {
+            a: b.map_or(a, |b| a.min(b)),
+            b: b.map(|b| a.max(b)),
+            score: Match::new(self.best_of),
+        };
+
+        match self.rounds.last() {
+            Some(last) => last
+                .chunks(2)
+                .map(|pair| heat(pair[0].winner(), pair.get(1).map(Heat::winner)))
+                .collect(),
+            None => {
+                let order = seed_order(self.names.len().next_power_of_two());
+                let entrant = |seed: usize| (seed < self.names.len()).then_some(seed);
+                order
+                    .chunks(2)
+                    .map(|pair| heat(pair[0], pair.get(1).and_then(|&seed| entrant(seed))))
+                    .collect()
+            }
+        }
+    }
+
+    // The winner of the final, once it has been played
+    pub fn champion(&self) -> Option<usize> {
+        match self.rounds.last()?.as_slice() {
+            [final_heat] => Some(final_heat.winner()),
+            _ => None,
+        }
+    }
+}
+
+// The seeds of a bracket of `size` entrants, from top to bottom: 0 meets
+// `size - 1`, and the winner meets the one from the pairing of 1 and
+// `size - 2` only in the final
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let len = 2 * order.len();
+        order = order
+            .iter()
+            .flat_map(|&seed| [seed, len - 1 - seed])
+            .collect();
+    }
+
+    order
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Pairing {
+    Game(usize, usize),
+    // Left over when the number of entrants is odd
+    Bye(usize),
+}
+
+// A Swiss tournament, where each round pairs entrants with similar scores who
+// have not met yet, so that many entrants can be ranked in a few rounds. Ties
+// are broken by the Buchholz score, the sum of the scores of the opponents.
+// It works with any names, be they engines or profiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swiss {
+    pub names: Vec<String>,
+    // The number of games of a pairing, which is also what a bye is worth
+    pub games: u32,
+    // The entrants who had a bye, one per round at most
+    pub byes: Vec<usize>,
+    pub records: Vec<GameRecord>,
+}
+
+impl Swiss {
+    pub fn new(names: &[String], games: u32) -> Swiss {
+        Swiss {
+            names: names.to_vec(),
+            games,
+            byes: Vec::new(),
+            records: Vec::new(),
+        }
+    }
+
+    pub fn score(&self, i: usize) -> f64 {
+        let byes = self.byes.iter().filter(|&&b| b == i).count();
+        self.crosstable().total(i).score() + byes as f64 * self.games as f64
+    }
+
+    pub fn buchholz(&self, i: usize) -> f64 {
+        self.opponents(i).into_iter().map(|j| self.score(j)).sum()
+    }
+
+    // The entrants from the best to the worst, by score and then by Buchholz
+    pub fn standings(&self) -> Vec<usize> {
+        let mut standings: Vec<usize> = (0..self.names.len()).collect();
+        standings.sort_by(|&a, &b| {
+            let key = |i| (self.score(i), self.buchholz(i));
+            let ((score_a, buchholz_a), (score_b, buchholz_b)) = (key(a), key(b));
+            score_b
+                .total_cmp(&score_a)
+                .then(buchholz_b.total_cmp(&buchholz_a))
+        });
+        standings
+    }
+
+    // The pairings of the next round. From the top of the standings, each
+    // entrant meets the next one they have not met yet (or just the next one
+    // if they have met them all), and the lowest one without a bye sits out
+    // when their number is odd.
+    pub fn pair(&self) -> Vec<Pairing> {
+        let mut left = self.standings();
+        let mut bye = None;
+        if left.len() % 2 == 1 {
+            let i = left
+                .iter()
+                .rposition(|i| !self.byes.contains(i))
+                .unwrap_or(left.len() - 1);
+            bye = Some(left.remove(i));
+        }
+
+        let mut pairings = Vec::new();
+        while !left.is_empty() {
+            let a = left.remove(0);
+            let opponents = self.opponents(a);
+            let j = left
+                .iter()
+                .position(|b| !opponents.contains(b))
+                .unwrap_or(0);
+            pairings.push(Pairing::Game(a, left.remove(j)));
+        }
+        pairings.extend(bye.map(Pairing::Bye));
+
+        pairings
+    }
+
+    pub fn crosstable(&self) -> Crosstable {
+        Crosstable::new(&self.names, &self.records)
+    }
+
+    fn opponents(&self, i: usize) -> Vec<usize> {
+        let table = self.crosstable();
+        (0..self.names.len())
+            .filter(|&j| table.tallies[i][j].games() > 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::test_game;
+    use crate::session::GameResult;
+
+    #[test]
+    fn entrants() {
+        let entrants = parse_entrants("easy, Hard").unwrap();
+        assert_eq!(entrants[1], Entrant::new(Difficulty::Hard));
+        assert_eq!(entrants[1].name, "hard");
+        let twice = EntrantError::Twice("easy".to_string());
+        assert_eq!(parse_entrants("easy,easy"), Err(twice));
+        assert_eq!(parse_entrants("hard"), Err(EntrantError::TooFew));
+        assert_eq!(
+            parse_entrants("hard,"),
+            Err(EntrantError::Invalid(String::new()))
+        );
+
+        let entrants = parse_entrants("alice,hard").unwrap();
+        assert_eq!(entrants[0], Entrant::human("alice"));
+        assert!(entrants[0].bot(0).is_none());
+        // The engines only play each other
+        assert!(round_robin(&entrants, 2, 0, bot_game).is_empty());
+    }
+
+    #[test]
+    fn bracket() {
+        assert_eq!(seed_order(8), [0, 7, 3, 4, 1, 6, 2, 5]);
+
+        let names: Vec<String> = ["a", "b", "c", "d", "e"].map(String::from).to_vec();
+        let mut bracket = Bracket::new(&names, 3);
+        let round = bracket.next_round();
+        let pairs: Vec<_> = round.iter().map(|h| (h.a, h.b)).collect();
+        assert_eq!(pairs, [(0, None), (3, Some(4)), (1, None), (2, None)]);
+
+        // e wins its match, a and b go through on their byes
+        let mut round = round;
+        round[1].score.record(GameResult::Win(Player::O));
+        round[1].score.record(GameResult::Win(Player::O));
+        assert_eq!(round[1].winner(), 4);
+        bracket.rounds.push(round);
+        let pairs: Vec<_> = bracket.next_round().iter().map(|h| (h.a, h.b)).collect();
+        assert_eq!(pairs, [(0, Some(4)), (1, Some(2))]);
+        assert_eq!(bracket.champion(), None);
+    }
+
+    #[test]
+    fn knockout_tournament() {
+        let entrants = parse_entrants("hard,easy,medium").unwrap();
+        let bracket = knockout(&entrants, 3, 5, bot_game).unwrap();
+        assert_eq!(bracket.rounds.len(), 2);
+        // The first seed has a bye, then never loses
+        assert_eq!(bracket.rounds[0][0].b, None);
+        assert_eq!(bracket.champion(), Some(0));
+        let final_score = bracket.rounds[1][0].score;
+        assert!(final_score.is_over() && final_score.winner() != Some(Player::O));
+        assert_eq!(
+            bracket.records.last().unwrap().tag("Event"),
+            Some("knockout, round 2")
+        );
+
+        let entrants = parse_entrants("hard,alice").unwrap();
+        assert_eq!(knockout(&entrants, 3, 5, bot_game), None);
+    }
+
+    #[test]
+    fn swiss_pairings() {
+        let names: Vec<String> = ["a", "b", "c", "d", "e"].map(String::from).to_vec();
+        let mut swiss = Swiss::new(&names, 1);
+        let game = |x: &str, o: &str, result| test_game(x, o, &[], result);
+
+        // Nothing to tell them apart yet: in order, the last one sitting out
+        let pairings = swiss.pair();
+        assert_eq!(
+            pairings,
+            [Pairing::Game(0, 1), Pairing::Game(2, 3), Pairing::Bye(4)]
+        );
+
+        swiss
+            .records
+            .push(game("a", "b", GameResult::Win(Player::X)));
+        swiss.records.push(game("c", "d", GameResult::Draw));
+        swiss.byes.push(4);
+        assert_eq!(swiss.score(0), 1.0);
+        assert_eq!(swiss.score(4), 1.0);
+        assert_eq!(swiss.buchholz(2), 0.5);
+        assert_eq!(swiss.buchholz(4), 0.0);
+        assert_eq!(swiss.buchholz(1), 1.0);
+        assert_eq!(swiss.standings(), [0, 4, 2, 3, 1]);
+
+        // b sits out instead of e, and c meets d again for lack of anyone else
+        assert_eq!(
+            swiss.pair(),
+            [Pairing::Game(0, 4), Pairing::Game(2, 3), Pairing::Bye(1)]
+        );
+    }
+
+    #[test]
+    fn swiss_tournament() {
+        let entrants = parse_entrants("easy,medium,hard").unwrap();
+        let swiss = swiss(&entrants, 3, 2, 7, bot_game);
+        assert_eq!(swiss.byes.len(), 3);
+        assert_eq!(swiss.records.len(), 3 * 2);
+        assert_eq!(swiss.standings()[0], 2);
+        assert_eq!(swiss.records[0].tag("Event"), Some("swiss, round 1"));
+    }
+
+    #[test]
+    fn tournament() {
+        let entrants = parse_entrants("easy,medium,hard").unwrap();
+        let records = round_robin(&entrants, 4, 42, bot_game);
+        assert_eq!(records.len(), 3 * 4);
+        let moves = |records: &[GameRecord]| -> Vec<_> {
+            records.iter().map(|r| r.moves.clone()).collect()
+        };
+        assert_eq!(
+            moves(&records),
+            moves(&round_robin(&entrants, 4, 42, bot_game))
+        );
+
+        // Colors alternate within a pairing, with the same seed by two games
+        assert_eq!(
+            (records[0].x.as_str(), records[1].x.as_str()),
+            ("easy", "medium")
+        );
+        assert_eq!(records[0].tag("Seed"), records[1].tag("Seed"));
+        assert_ne!(records[1].tag("Seed"), records[2].tag("Seed"));
+
+        let names: Vec<String> = entrants.iter().map(|e| e.name.clone()).collect();
+        let table = Crosstable::new(&names, &records);
+        // The hard engine never loses
+        assert_eq!(table.total(2).losses, 0);
+        assert_eq!(table.ranking()[0], 2);
+        assert_eq!(table.tallies[0][2].wins, table.tallies[2][0].losses);
+        assert_eq!(table.total(0).games(), 8);
+        assert_eq!(table.tallies[1][1].games(), 0);
+    }
+}
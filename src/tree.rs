@@ -0,0 +1,234 @@
+// The game tree an alpha-beta search goes through from a position, to be drawn
+// with Graphviz when teaching minimax or looking for a pruning bug:
+//
+//     tictactoe tree X.O/.X./... --output tree.dot
+//     dot -Tsvg tree.dot -o tree.svg
+//
+// The search is `game::AlphaBeta` itself, told what it goes through as a
+// `Visitor`, so the moves are tried in its order and the cutoffs drawn are the
+// ones the engine makes.
+use std::fmt::Write;
+
+use crate::game::{AlphaBeta, Game, Visitor, INFINITY};
+use crate::notation::position;
+use crate::{coordinates, Grid, Player};
+
+// What the search found out about the value of a position, for X: the value
+// itself, or only a bound on it where the search was cut off below
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Bound {
+    Exact(i32),
+    AtLeast(i32),
+    AtMost(i32),
+}
+
+impl Bound {
+    // From the value of the search for the player to move, given the window
+    // it was searched with
+    fn new(value: i32, (alpha, beta): (i32, i32), player: Player) -> Bound {
+        let bound = if value <= alpha {
+            Bound::AtMost(value)
+        } else if value >= beta {
+            Bound::AtLeast(value)
+        } else {
+            Bound::Exact(value)
+        };
+
+        match (player, bound) {
+            (Player::X, bound) => bound,
+            (_, Bound::Exact(v)) => Bound::Exact(-v),
+            (_, Bound::AtLeast(v)) => Bound::AtMost(-v),
+            (_, Bound::AtMost(v)) => Bound::AtLeast(-v),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub grid: Grid,
+    // The move from the parent position, none for the root
+    pub last_move: Option<(usize, usize)>,
+    // The moves from the root
+    pub depth: u32,
+    // None for the moves a cutoff left unsearched
+    pub value: Option<Bound>,
+    // Indices in `SearchTree::nodes`
+    pub children: Vec<usize>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SearchTree {
+    // The root first, each node before its children
+    pub nodes: Vec<Node>,
+    // The nodes the search is in, from the root
+    path: Vec<usize>,
+}
+
+impl SearchTree {
+    pub fn explore(grid: &Grid) -> SearchTree {
+        let mut tree = SearchTree::default();
+        // Deep enough to reach the end of the game, so nothing is evaluated
+        let depth = grid.moves().count() as u32;
+        AlphaBeta::new().visit(grid, depth, &|_: &Grid| 0, &mut tree);
+
+        tree
+    }
+
+    // The positions the search went through, the root included
+    pub fn searched(&self) -> usize {
+        self.nodes.iter().filter(|n| n.value.is_some()).count()
+    }
+
+    // The moves left unsearched by the cutoffs
+    pub fn pruned(&self) -> usize {
+        self.nodes.len() - self.searched()
+    }
+
+    // A child of the node the search is in, or the root
+    fn add(&mut self, grid: Grid, last_move: Option<(usize, usize)>) -> usize {
+        let index = self.nodes.len();
+        if let Some(&parent) = self.path.last() {
+            self.nodes[parent].children.push(index);
+        }
+        self.nodes.push(Node {
+            grid,
+            last_move,
+            depth: self.path.len() as u32,
+            value: None,
+            children: Vec::new(),
+        });
+
+        index
+    }
+
+    // The tree in the DOT language of Graphviz, down to `max_depth` moves from
+    // the root if given. Each position is labeled with its rows and its value
+    // for X, the positions where X moves (and maximizes) as boxes and those
+    // where O moves as ellipses. The moves cut off are dashed and grey.
+    pub fn to_dot(&self, max_depth: Option<u32>) -> String {
+        let mut dot = String::from("digraph tree {\n");
+        dot += "    node [fontname=\"monospace\"];\n";
+        for (i, node) in self.nodes.iter().enumerate() {
+            if max_depth.is_some_and(|d| node.depth > d) {
+                continue;
+            }
+
+            let rows = position(&node.grid).replace('/', "\\n");
+            let value = match node.value {
+                Some(Bound::Exact(v)) => format!("{v}"),
+                Some(Bound::AtLeast(v)) => format!("≥ {v}"),
+                Some(Bound::AtMost(v)) => format!("≤ {v}"),
+                None => "pruned".to_string(),
+            };
+            let shape = if node.grid.player_turn == Player::X {
+                "box"
+            } else {
+                "ellipse"
+            };
+            let style = if node.value.is_none() {
+                ", style=dashed, color=grey, fontcolor=grey"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                dot,
+                "    n{i} [label=\"{rows}\\n{value}\", shape={shape}{style}];"
+            );
+
+            for &child in &node.children {
+                let next = &self.nodes[child];
+                if max_depth.is_some_and(|d| next.depth > d) {
+                    continue;
+                }
+                let (x, y) = next.last_move.unwrap_or_default();
+                let style = if next.value.is_none() {
+                    ", style=dashed, color=grey, fontcolor=grey"
+                } else {
+                    ""
+                };
+                let _ = writeln!(
+                    dot,
+                    "    n{i} -> n{child} [label=\"{}\"{style}];",
+                    coordinates(x, y)
+                );
+            }
+        }
+        dot += "}\n";
+
+        dot
+    }
+}
+
+impl Visitor<Grid> for SearchTree {
+    fn enter(&mut self, grid: &Grid, m: Option<(usize, usize)>) {
+        let index = self.add(grid.clone(), m);
+        self.path.push(index);
+    }
+
+    fn leave(&mut self, value: i32, window: (i32, i32)) {
+        let Some(index) = self.path.pop() else {
+            return;
+        };
+        let grid = &self.nodes[index].grid;
+        // The value of a finished game is exact, whatever the window
+        let window = match grid.value() {
+            Some(_) => (-INFINITY, INFINITY),
+            None => window,
+        };
+        self.nodes[index].value = Some(Bound::new(value, window, grid.player_turn));
+    }
+
+    // The moves cut off are still drawn, as what the cutoff saved
+    fn prune(&mut self, grid: &Grid, m: (usize, usize)) {
+        let mut next = grid.clone();
+        next.play(m);
+        self.add(next, Some(m));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::negamax;
+
+    #[test]
+    fn explore() {
+        let grid = Grid::try_from(&["XX.", "OO.", "..."]).unwrap();
+        let tree = SearchTree::explore(&grid);
+        assert_eq!(tree.nodes[0].value, Some(Bound::Exact(negamax(&grid))));
+        assert_eq!(tree.nodes[0].children.len(), 5);
+        assert_eq!(tree.nodes[1].last_move, Some((2, 0)));
+        // Once O has a reply that wins, their other replies are cut off
+        assert!(tree.pruned() > 0);
+        assert_eq!(tree.searched() + tree.pruned(), tree.nodes.len());
+        // The positions the engine's own search goes through
+        let mut search = AlphaBeta::new();
+        search.value(&grid, 5, (-INFINITY, INFINITY), &|_: &Grid| 0);
+        assert_eq!(tree.searched() as u64, search.nodes);
+
+        let dot = tree.to_dot(None);
+        assert!(dot.starts_with("digraph tree {\n"));
+        assert!(dot.contains("n0 -> n1 [label=\"a3\"];"));
+        assert!(dot.contains("n1 [label=\"XXX\\nOO.\\n...\\n5\", shape=ellipse];"));
+        assert!(dot.contains("\\npruned\", shape=box, style=dashed"));
+        assert!(dot.contains("[label=\"b3\", style=dashed"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn bounds() {
+        // The root is always searched with the full window
+        let grid = Grid::try_from(&["X..", ".O.", "..."]).unwrap();
+        let tree = SearchTree::explore(&grid);
+        assert_eq!(tree.nodes[0].value, Some(Bound::Exact(0)));
+        assert!(tree.pruned() > 0);
+        // Values for X, whoever moves
+        let ox = Bound::new(3, (-INFINITY, 2), Player::O);
+        assert_eq!(ox, Bound::AtMost(-3));
+        assert_eq!(Bound::new(3, (-INFINITY, 2), Player::X), Bound::AtLeast(3));
+
+        let shallow = tree.to_dot(Some(1));
+        assert!(!shallow.contains(&format!("n{} ", tree.nodes[1].children[0])));
+        assert!(shallow.len() < tree.to_dot(None).len());
+    }
+}
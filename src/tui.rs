@@ -0,0 +1,458 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+    MouseEvent, MouseEventKind,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Alignment, Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::engine::{Bot, OpponentModel, Search, WaitError};
+use crate::i18n::{Lang, Msg};
+use crate::render::Palette;
+use crate::session::{GameSession, PlayError, Scoreboard};
+use crate::{heat_label, minimax, Grid, Player};
+
+// Play against `bot` until the player quits
+pub fn run(lang: Lang, palette: Palette, bot: Bot) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let res = execute!(io::stdout(), EnableMouseCapture)
+        .and_then(|_| App::new(lang, palette, bot).run(&mut terminal));
+    let _ = execute!(io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+    res
+}
+
+struct App {
+    lang: Lang,
+    palette: Palette,
+    session: GameSession,
+    bot: Bot,
+    // The move of the bot while it is searching for it
+    thinking: Option<Search>,
+    scoreboard: Scoreboard,
+    first_player: Player,
+    cursor: (usize, usize),
+    // `None` while the position is being evaluated
+    evaluation: Option<Evaluation>,
+    // The evaluation of the live position while it is searched. Replacing it
+    // stops the search of the older position.
+    analysis: Option<Search<Evaluation>>,
+    message: Option<String>,
+    // Number of moves of the position being replayed, `None` for the live game
+    view: Option<usize>,
+    // Shade the empty squares by the score of playing them
+    heatmap: bool,
+    // Screen areas of the last frame, used to map mouse clicks
+    cells: [Rect; 9],
+    history_area: Rect,
+    quit: bool,
+}
+
+impl App {
+    fn new(lang: Lang, palette: Palette, bot: Bot) -> App {
+        let mut app = App {
+            lang,
+            palette,
+            session: GameSession::new(),
+            bot,
+            thinking: None,
+            scoreboard: Scoreboard::default(),
+            first_player: Player::X,
+            cursor: (1, 1),
+            evaluation: None,
+            analysis: None,
+            message: None,
+            view: None,
+            heatmap: false,
+            cells: [Rect::default(); 9],
+            history_area: Rect::default(),
+            quit: false,
+        };
+        app.evaluate();
+        app
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // Wait for the player, waking up now and then to show the move
+            // of the bot and the evaluation once they are found, and how long
+            // the bot has been thinking
+            while !event::poll(Duration::from_millis(50))? {
+                let moved = self.receive_move();
+                if self.receive_evaluation() || moved || self.thinking.is_some() {
+                    break;
+                }
+            }
+            if !event::poll(Duration::ZERO)? {
+                continue;
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key(key.code),
+                Event::Mouse(mouse) => self.on_mouse(mouse),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Whether the evaluation of the live position has just been found
+    fn receive_evaluation(&mut self) -> bool {
+        let Some(analysis) = &self.analysis else {
+            return false;
+        };
+        let evaluation = match analysis.wait(Duration::ZERO) {
+            Ok(evaluation) => Some(evaluation),
+            Err(WaitError::Timeout) => return false,
+            Err(WaitError::Died) => None,
+        };
+        self.analysis = None;
+        self.evaluation = evaluation;
+        evaluation.is_some()
+    }
+
+    // Evaluate the live position on another thread, so that the board can be
+    // played on meanwhile
+    fn evaluate(&mut self) {
+        self.evaluation = None;
+        let grid = self.session.grid().clone();
+        self.analysis = Some(Search::spawn(move |cancelled| {
+            Evaluation::search(&grid, cancelled)
+        }));
+    }
+
+    // Let the bot search for its move on another thread, unless the game is
+    // over
+    fn bot_turn(&mut self) {
+        if !self.session.is_over() {
+            self.thinking = Some(self.bot.think(self.session.grid()));
+        }
+    }
+
+    // Whether the bot has just played, or failed to
+    fn receive_move(&mut self) -> bool {
+        let Some(search) = &self.thinking else {
+            return false;
+        };
+        match search.wait(Duration::ZERO) {
+            Ok(thought) => {
+                self.bot = thought.bot;
+                if let Some((x, y)) = thought.choice {
+                    let _ = self.session.play(x, y);
+                }
+            }
+            Err(WaitError::Timeout) => return false,
+            Err(WaitError::Died) => self.message = Some(self.lang.text(Msg::SearchFailed)),
+        }
+        self.thinking = None;
+        self.evaluate();
+
+        if let Some(result) = self.session.result() {
+            self.scoreboard.record(result);
+        }
+        true
+    }
+
+    fn on_key(&mut self, code: KeyCode) {
+        let (x, y) = self.cursor;
+        match code {
+            KeyCode::Esc if self.view.is_some() => self.view = None,
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Left | KeyCode::Char('h') => self.cursor = (x.saturating_sub(1), y),
+            KeyCode::Right | KeyCode::Char('l') => self.cursor = ((x + 1).min(2), y),
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = (x, y.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (x, (y + 1).min(2)),
+            KeyCode::Enter | KeyCode::Char(' ') => self.play(),
+            KeyCode::Char('m') => self.heatmap = !self.heatmap,
+            KeyCode::Char('r') if self.session.is_over() => self.rematch(),
+            _ => (),
+        }
+    }
+
+    fn on_mouse(&mut self, mouse: MouseEvent) {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+            return;
+        }
+
+        let position = Position::new(mouse.column, mouse.row);
+        if let Some(i) = self.cells.iter().position(|cell| cell.contains(position)) {
+            self.cursor = (i % 3, i / 3);
+            self.play();
+        } else if self.history_area.contains(position) {
+            // The first entry is right below the top border of the block
+            let entry = (position.y - self.history_area.y) as usize;
+            if entry >= 1 && entry <= self.session.history().len() {
+                self.view = Some(entry);
+            }
+        }
+    }
+
+    fn play(&mut self) {
+        if self.view.is_some() {
+            // Playing is done on the live position only
+            self.view = None;
+            return;
+        }
+
+        if self.session.is_over() || self.thinking.is_some() {
+            return;
+        }
+
+        let (x, y) = self.cursor;
+        match self.session.play(x, y) {
+            Ok(_) => (),
+            Err(PlayError::Occupied(p)) => {
+                self.message = Some(self.lang.text(Msg::SquareTaken(p)));
+                return;
+            }
+            Err(PlayError::GameOver) => return,
+        }
+        self.message = None;
+        self.evaluate();
+        self.bot_turn();
+
+        if let Some(result) = self.session.result() {
+            self.scoreboard.record(result);
+        }
+    }
+
+    fn rematch(&mut self) {
+        // Take turns at starting the game
+        self.first_player = self.first_player.opponent();
+        self.session = GameSession::from(Grid::starting_with(self.first_player));
+        self.thinking = None;
+        if self.session.grid().player_turn == Player::O {
+            self.bot_turn();
+        }
+
+        self.evaluate();
+        self.view = None;
+        self.message = None;
+    }
+
+    fn displayed_grid(&self) -> Grid {
+        match self.view {
+            Some(ply) => self.session.position_at(ply),
+            None => self.session.grid().clone(),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [board, panel] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(30)]).areas(main);
+        let [evaluation, history] =
+            Layout::vertical([Constraint::Length(5), Constraint::Min(0)]).areas(panel);
+
+        self.draw_board(frame, board);
+        self.draw_evaluation(frame, evaluation);
+        self.draw_history(frame, history);
+        frame.render_widget(Paragraph::new(self.status()), status);
+    }
+
+    fn draw_board(&mut self, frame: &mut Frame, area: Rect) {
+        let grid = self.displayed_grid();
+        let heatmap = if self.heatmap {
+            grid.heatmap()
+        } else {
+            [[None; 3]; 3]
+        };
+        let rows = Layout::vertical([Constraint::Ratio(1, 3); 3]).split(area);
+
+        for (y, &row) in rows.iter().enumerate() {
+            let cells = Layout::horizontal([Constraint::Ratio(1, 3); 3]).split(row);
+
+            for (x, &cell) in cells.iter().enumerate() {
+                self.cells[y * 3 + x] = cell;
+
+                let mut block = Block::default().borders(Borders::ALL);
+                if (x, y) == self.cursor && self.view.is_none() && !self.session.is_over() {
+                    block = block.border_style(Style::default().fg(Color::Yellow));
+                }
+
+                let player = grid.matrix()[y][x];
+                let mut style = match (self.palette, player) {
+                    (_, Player::Empty) => Style::default(),
+                    (Palette::HighContrast, _) => Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                    (_, player) => Style::default().fg(self.color(player)),
+                };
+                let mut text = player.to_string();
+                if let Some(score) = heatmap[y][x] {
+                    let ((r, g, b), (fr, fg, fb)) = self.palette.heat_colors(score);
+                    block = block.style(Style::default().bg(Color::Rgb(r, g, b)));
+                    style = style.fg(Color::Rgb(fr, fg, fb));
+                    text = heat_label(score);
+                }
+
+                // Center the mark vertically inside the cell
+                let padding = cell.height.saturating_sub(3) / 2;
+                let mut lines = vec![Line::default(); padding as usize];
+                lines.push(Line::styled(text, style.add_modifier(Modifier::BOLD)));
+
+                let paragraph = Paragraph::new(lines)
+                    .alignment(Alignment::Center)
+                    .block(block);
+                frame.render_widget(paragraph, cell);
+            }
+        }
+    }
+
+    // The color of the marks of `player`
+    fn color(&self, player: Player) -> Color {
+        match (self.palette, player) {
+            (Palette::Standard, Player::X) => Color::Cyan,
+            (Palette::Standard, _) => Color::Magenta,
+            (Palette::Colorblind, Player::X) => Color::Rgb(0, 114, 178),
+            (Palette::Colorblind, _) => Color::Rgb(230, 159, 0),
+            (Palette::HighContrast, Player::X) => Color::White,
+            (Palette::HighContrast, _) => Color::DarkGray,
+        }
+    }
+
+    // The verdict, then a bar filled with the color of X as far as X stands
+    // better and with that of O for the rest, then the score of the match
+    fn draw_evaluation(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.lang.text(Msg::EvaluationTitle));
+        let [verdict, bar, score] =
+            Layout::vertical([Constraint::Length(1); 3]).areas(block.inner(area));
+        frame.render_widget(block, area);
+
+        let (text, ratio) = match self.evaluation {
+            Some(evaluation) => {
+                let turns = self.session.grid().number_of_turns;
+                let text = self.lang.text(describe_evaluation(evaluation.score, turns));
+                (text, evaluation.ratio())
+            }
+            None => (self.lang.text(Msg::Evaluating), 0.5),
+        };
+        frame.render_widget(Paragraph::new(text), verdict);
+        let gauge = Gauge::default()
+            .gauge_style(
+                Style::default()
+                    .fg(self.color(Player::X))
+                    .bg(self.color(Player::O)),
+            )
+            .ratio(ratio)
+            .label("");
+        frame.render_widget(gauge, bar);
+        frame.render_widget(
+            Paragraph::new(self.lang.text(Msg::Score(&self.scoreboard))),
+            score,
+        );
+    }
+
+    fn draw_history(&mut self, frame: &mut Frame, area: Rect) {
+        self.history_area = area;
+
+        let items: Vec<ListItem> = self
+            .session
+            .history()
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let item = ListItem::new(format!("{:>2}. {}", i + 1, m));
+                if self.view == Some(i + 1) {
+                    item.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    item
+                }
+            })
+            .collect();
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.lang.text(Msg::HistoryTitle));
+        let list = List::new(items).block(block);
+        frame.render_widget(list, area);
+    }
+
+    fn status(&self) -> String {
+        if let Some(message) = &self.message {
+            return message.clone();
+        }
+
+        if let Some(ply) = self.view {
+            return self.lang.text(Msg::Replaying(ply));
+        }
+
+        if let Some(search) = &self.thinking {
+            let player = self.session.grid().player_turn;
+            return self.lang.text(Msg::Thinking(player, search.elapsed()));
+        }
+
+        let Some(result) = self.session.result() else {
+            return self.lang.text(Msg::TuiHelp);
+        };
+        format!(
+            "{} {}",
+            self.lang.text(Msg::from(result)),
+            self.lang.text(Msg::TuiGameOverHelp)
+        )
+    }
+}
+
+// How the live position stands, as found by the background engine
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Evaluation {
+    // The minimax score, the sooner the win the larger
+    score: i32,
+    // The chance of X winning, less that of O, between players who play one
+    // of the best moves most of the time but blunder now and then
+    balance: f64,
+}
+
+// How often the players of `Evaluation::balance` blunder, as the `exploit`
+// engine expects of its opponents
+const BLUNDER_RATE: f64 = 0.3;
+
+impl Evaluation {
+    // `None` once `cancelled` is set
+    fn search(grid: &Grid, cancelled: &AtomicBool) -> Option<Evaluation> {
+        let score = minimax(grid.clone());
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let balance = if grid.has_winner() || grid.is_full() {
+            -f64::from(score.signum())
+        } else {
+            OpponentModel::new(BLUNDER_RATE).balance_unless(grid, cancelled)?
+        };
+
+        Some(Evaluation { score, balance })
+    }
+
+    // How much of the bar is filled for X: all or none of it once either
+    // player is sure to win, as far as the balance goes otherwise
+    fn ratio(self) -> f64 {
+        if self.score != 0 {
+            return if self.score < 0 { 1.0 } else { 0.0 };
+        }
+        ((self.balance + 1.0) / 2.0).clamp(0.0, 1.0)
+    }
+}
+
+// The minimax score is positive when O is winning, and the closer to 10 the
+// sooner the win (see `minimax`).
+fn describe_evaluation(score: i32, number_of_turns: i32) -> Msg<'static> {
+    if score > 0 {
+        Msg::WinsIn(Player::O, 10 - score - number_of_turns)
+    } else if score < 0 {
+        Msg::WinsIn(Player::X, 10 + score - number_of_turns)
+    } else {
+        Msg::DrawnPosition
+    }
+}
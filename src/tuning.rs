@@ -0,0 +1,186 @@
+// Tuning the `eval::Weights` with a genetic algorithm: a population of weight
+// sets plays matches against itself, the best half is kept and the other is
+// replaced by children of the kept ones, whose weights are taken from either
+// parent and sometimes nudged at random. After the last generation, the best
+// set is the one to save and load into `eval::search`:
+//
+//     let weights = tuning::evolve(&Tuning::default(), seed, |_| {});
+//     weights.save(Path::new("weights.txt"))?;
+use crate::engine::legal_moves;
+use crate::eval::{search, Weights};
+use crate::rng::{RandomSource, Rng};
+use crate::{Grid, Player};
+
+// The settings of the tuning, the larger the slower
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Tuning {
+    // The weight sets of each generation, at least two
+    pub population: usize,
+    pub generations: u32,
+    // How far the players of the matches look ahead
+    pub depth: u32,
+    // The games each pair of weight sets plays, half with each color
+    pub games: u32,
+    // How much a weight of a child can move away from that of its parent
+    pub mutation: f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Tuning {
+        Tuning {
+            population: 12,
+            generations: 20,
+            depth: 2,
+            games: 4,
+            mutation: 1.0,
+        }
+    }
+}
+
+// How a generation went, to show the progress
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Generation {
+    // Counted from 1
+    pub number: u32,
+    pub best: Weights,
+    // The share of the points the best set took in its matches
+    pub score: f64,
+}
+
+// The best weight set after `tuning.generations` generations, the first one
+// being the hand-picked weights and random ones. `on_generation` is told how
+// each generation went. The same seed gives the same weights.
+pub fn evolve(tuning: &Tuning, seed: u64, mut on_generation: impl FnMut(&Generation)) -> Weights {
+    let mut rng = Rng::new(seed);
+    let size = tuning.population.max(2);
+    let mut population = vec![Weights::default()];
+    while population.len() < size {
+        let weights = [(); 4].map(|_| rng.next_f64() as f32 * 4.0);
+        population.push(Weights::from_array(weights));
+    }
+
+    let mut best = Weights::default();
+    for number in 1..=tuning.generations {
+        let scores = play_matches(&population, tuning, &mut rng);
+        let mut ranked: Vec<(Weights, f64)> = population.into_iter().zip(scores).collect();
+        // The sort is stable, so ties keep the older sets first
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        best = ranked[0].0;
+        on_generation(&Generation {
+            number,
+            best,
+            score: ranked[0].1,
+        });
+
+        let parents: Vec<Weights> = ranked[..size.div_ceil(2)].iter().map(|&(w, _)| w).collect();
+        population = parents.clone();
+        while population.len() < size {
+            let a = *rng.choose(&parents).unwrap_or(&best);
+            let b = *rng.choose(&parents).unwrap_or(&best);
+            population.push(child(a, b, tuning.mutation, &mut rng));
+        }
+    }
+
+    best
+}
+
+// Each weight from either parent, a third of them nudged
+fn child(a: Weights, b: Weights, mutation: f32, rng: &mut Rng) -> Weights {
+    let (a, b) = (a.to_array(), b.to_array());
+    let weights = std::array::from_fn(|i| {
+        let weight = if rng.chance(0.5) { a[i] } else { b[i] };
+        if rng.chance(1.0 / 3.0) {
+            weight + (rng.next_f64() as f32 * 2.0 - 1.0) * mutation
+        } else {
+            weight
+        }
+    });
+
+    Weights::from_array(weights)
+}
+
+// The share of the points each set takes against all the others: 1 for a
+// win and 1/2 for a draw. The search always plays the same moves, so each
+// pair of games starts from a random first move instead of the empty board.
+fn play_matches(population: &[Weights], tuning: &Tuning, rng: &mut Rng) -> Vec<f64> {
+    let mut points = vec![0.0; population.len()];
+    let mut played = vec![0.0; population.len()];
+    let openings = legal_moves(&Grid::new());
+    for i in 0..population.len() {
+        for j in i + 1..population.len() {
+            let mut opening = (1, 1);
+            for game in 0..tuning.games {
+                if game % 2 == 0 {
+                    opening = *rng.choose(&openings).unwrap_or(&opening);
+                }
+                let (x, o) = if game % 2 == 0 { (i, j) } else { (j, i) };
+                let x_points = self_play(&population[x], &population[o], opening, tuning.depth);
+                points[x] += x_points;
+                points[o] += 1.0 - x_points;
+                played[x] += 1.0;
+                played[o] += 1.0;
+            }
+        }
+    }
+
+    points
+        .iter()
+        .zip(played)
+        .map(|(&p, n)| if n > 0.0 { p / n } else { 0.0 })
+        .collect()
+}
+
+// The points of X in a game between two weight sets where X starts on
+// `opening`
+fn self_play(x: &Weights, o: &Weights, (ox, oy): (usize, usize), depth: u32) -> f64 {
+    let mut grid = Grid::new();
+    let _ = grid.set(ox, oy);
+    loop {
+        let weights = if grid.player_turn == Player::X { x } else { o };
+        let Some(((mx, my), _)) = search(&grid, depth, weights) else {
+            // The player who just moved has won
+            return if grid.player_turn == Player::X {
+                0.0
+            } else {
+                1.0
+            };
+        };
+        let _ = grid.set(mx, my);
+        if grid.is_full() && !grid.has_winner() {
+            return 0.5;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evolves() {
+        let tuning = Tuning {
+            population: 4,
+            generations: 3,
+            games: 2,
+            ..Tuning::default()
+        };
+        let mut generations = Vec::new();
+        let best = evolve(&tuning, 5, |g| generations.push(*g));
+        assert_eq!(generations.len(), 3);
+        assert_eq!(generations[2].best, best);
+        assert!(generations.iter().all(|g| (0.0..=1.0).contains(&g.score)));
+
+        // The same seed, the same weights
+        assert_eq!(evolve(&tuning, 5, |_| {}), best);
+    }
+
+    #[test]
+    fn self_play_scores() {
+        let weights = Weights::default();
+        // Looking to the end of the game, both sides play perfectly
+        assert_eq!(self_play(&weights, &weights, (1, 1), 8), 0.5);
+        // Weights that go for the other player's lines lose
+        let reckless = Weights::from_array([-1.0, -3.0, -1.0, -2.0]);
+        assert_eq!(self_play(&weights, &reckless, (0, 0), 1), 1.0);
+    }
+}
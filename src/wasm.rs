@@ -0,0 +1,129 @@
+use wasm_bindgen::prelude::*;
+
+use crate::engine::legal_moves;
+use crate::notation::{parse_coordinates, parse_position, position};
+use crate::{coordinates, Grid, Player};
+
+// The grid for JavaScript, where squares are strings such as `b2` and players
+// `"X"`, `"O"` or `""`:
+//
+//     const grid = new Grid();
+//     grid.play("b2");
+//     grid.play(grid.bestPlay());
+#[wasm_bindgen(js_name = Grid)]
+pub struct WasmGrid {
+    grid: Grid,
+}
+
+impl Default for WasmGrid {
+    fn default() -> Self {
+        WasmGrid::new()
+    }
+}
+
+#[wasm_bindgen(js_class = Grid)]
+impl WasmGrid {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmGrid {
+        WasmGrid { grid: Grid::new() }
+    }
+
+    // A position written as by `position`, such as `X.O/.X./...`
+    #[wasm_bindgen(js_name = fromPosition)]
+    pub fn from_position(text: &str) -> Result<WasmGrid, String> {
+        let grid = parse_position(text).map_err(|e| e.to_string())?;
+        Ok(WasmGrid { grid })
+    }
+
+    pub fn position(&self) -> String {
+        position(&self.grid)
+    }
+
+    // The 9 squares, row by row
+    pub fn board(&self) -> Vec<String> {
+        self.grid
+            .matrix()
+            .iter()
+            .flatten()
+            .map(|&p| name(p))
+            .collect()
+    }
+
+    // The player to move, even once the game is over
+    pub fn turn(&self) -> String {
+        name(self.grid.player_turn)
+    }
+
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<String> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        legal_moves(&self.grid)
+            .into_iter()
+            .map(|(x, y)| coordinates(x, y))
+            .collect()
+    }
+
+    // Play `square` for the player to move
+    pub fn play(&mut self, square: &str) -> Result<(), String> {
+        if self.is_over() {
+            return Err("the game is over".to_string());
+        }
+        let (x, y) = parse_coordinates(square).map_err(|e| format!("{square}: {e}"))?;
+        self.grid
+            .set(x, y)
+            .map_err(|p| format!("{square} already has an {p}"))
+    }
+
+    // The move of the engine, or `undefined` once the game is over
+    #[wasm_bindgen(js_name = bestPlay)]
+    pub fn best_play(&self) -> Option<String> {
+        if self.is_over() {
+            return None;
+        }
+        self.grid.best_play().map(|(x, y)| coordinates(x, y))
+    }
+
+    // `"X"` or `"O"`, or `undefined` if no one has won (yet)
+    pub fn winner(&self) -> Option<String> {
+        self.grid
+            .has_winner()
+            .then(|| name(self.grid.player_turn.opponent()))
+    }
+
+    #[wasm_bindgen(js_name = isOver)]
+    pub fn is_over(&self) -> bool {
+        self.grid.has_winner() || self.grid.is_full()
+    }
+}
+
+fn name(player: Player) -> String {
+    match player {
+        Player::Empty => String::new(),
+        p => p.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play() {
+        let mut grid = WasmGrid::new();
+        assert_eq!(grid.legal_moves().len(), 9);
+        grid.play("b2").unwrap();
+        assert_eq!(grid.board()[4], "X");
+        assert_eq!(grid.turn(), "O");
+        assert!(grid.play("b2").is_err());
+
+        let grid = WasmGrid::from_position("XX./OO./...").unwrap();
+        assert_eq!(grid.best_play().as_deref(), Some("a3"));
+
+        let grid = WasmGrid::from_position("XXX/OO./...").unwrap();
+        assert!(grid.is_over());
+        assert_eq!(grid.winner().as_deref(), Some("X"));
+        assert_eq!(grid.legal_moves(), Vec::<String>::new());
+    }
+}